@@ -199,6 +199,7 @@ impl MavProfile {
         let mav_message_all_messages = self.emit_mav_message_all_messages();
         let mav_message_parse = self.emit_mav_message_parse(&enum_names, &struct_names);
         let mav_message_crc = self.emit_mav_message_crc(&id_width, &struct_names);
+        let mav_message_encoded_len = self.emit_mav_message_encoded_len(&id_width, &struct_names);
         let mav_message_name = self.emit_mav_message_name(&enum_names, &struct_names);
         let mav_message_id = self.emit_mav_message_id(&enum_names, &struct_names);
         let mav_message_id_from_name = self.emit_mav_message_id_from_name(&struct_names);
@@ -256,6 +257,7 @@ impl MavProfile {
                 #mav_message_random_from_id
                 #mav_message_serialize
                 #mav_message_crc
+                #mav_message_encoded_len
                 #mav_message_target_system_id
                 #mav_message_target_component_id
             }
@@ -370,6 +372,22 @@ impl MavProfile {
         }
     }
 
+    #[inline(always)]
+    fn emit_mav_message_encoded_len(
+        &self,
+        id_width: &Ident,
+        structs: &[TokenStream],
+    ) -> TokenStream {
+        quote! {
+            fn encoded_len(id: #id_width) -> Option<usize> {
+                match id {
+                    #(#structs::ID => Some(#structs::ENCODED_LEN),)*
+                    _ => None,
+                }
+            }
+        }
+    }
+
     #[inline(always)]
     fn emit_mav_message_name(&self, enums: &[TokenStream], structs: &[TokenStream]) -> TokenStream {
         quote! {
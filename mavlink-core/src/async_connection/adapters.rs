@@ -0,0 +1,105 @@
+//! [`Stream`] and [`Sink`] adapters for the halves of an async connection
+
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::{sink, stream, Sink, Stream};
+
+use super::split::{AsyncMavReceiver, AsyncMavSender, SharedConnection};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::{MavHeader, Message};
+
+type BoxedStream<M> = Pin<Box<dyn Stream<Item = Result<(MavHeader, M), MessageReadError>> + Send>>;
+type BoxedSink<M> = Pin<Box<dyn Sink<(MavHeader, M), Error = MessageWriteError> + Send>>;
+
+/// [`Stream`] of the messages received by an async connection
+///
+/// Created by [`AsyncMavReceiver::into_stream`]. Messages that could not be parsed are yielded as
+/// errors and the stream continues, an I/O error ends the stream as the connection can not be
+/// read from anymore.
+///
+/// Every message is received with [`AsyncMavConnection::recv`], so like any call of the trait
+/// method it allocates a future per message.
+///
+/// [`AsyncMavConnection::recv`]: super::AsyncMavConnection::recv
+pub struct AsyncMavStream<M: Message + Sync + Send> {
+    inner: BoxedStream<M>,
+}
+
+impl<M: Message + Sync + Send> Stream for AsyncMavStream<M> {
+    type Item = Result<(MavHeader, M), MessageReadError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// [`Sink`] sending messages over an async connection
+///
+/// Created by [`AsyncMavSender::into_sink`]. Every message is sent with the sequence number of the
+/// connection, like [`AsyncMavSender::send`] does. Sending a message calls
+/// [`AsyncMavConnection::send`], which allocates a future per message.
+///
+/// [`AsyncMavConnection::send`]: super::AsyncMavConnection::send
+pub struct AsyncMavSink<M: Message + Sync + Send> {
+    inner: BoxedSink<M>,
+}
+
+impl<M: Message + Sync + Send> Sink<(MavHeader, M)> for AsyncMavSink<M> {
+    type Error = MessageWriteError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // only a single message is buffered, so the sink is ready once it is sent
+        self.inner.as_mut().poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: (MavHeader, M)) -> Result<(), Self::Error> {
+        self.inner.as_mut().start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.as_mut().poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.as_mut().poll_close(cx)
+    }
+}
+
+impl<M: Message + Sync + Send + 'static> AsyncMavReceiver<M> {
+    /// Turn the receiver into a [`Stream`] of received messages
+    pub fn into_stream(self) -> AsyncMavStream<M> {
+        // the connection is dropped from the state after an I/O error, which ends the stream
+        let inner = stream::unfold(
+            Some(self.connection),
+            |connection: Option<SharedConnection<M>>| async move {
+                let connection = connection?;
+                let result = connection.recv().await;
+                let connection = match result {
+                    Err(MessageReadError::Io(_)) => None,
+                    _ => Some(connection),
+                };
+                Some((result, connection))
+            },
+        );
+        AsyncMavStream {
+            inner: Box::pin(inner),
+        }
+    }
+}
+
+impl<M: Message + Sync + Send + 'static> AsyncMavSender<M> {
+    /// Turn the sender into a [`Sink`] of messages to send
+    pub fn into_sink(self) -> AsyncMavSink<M> {
+        let inner = sink::unfold(
+            self.connection,
+            |connection: SharedConnection<M>, (header, msg): (MavHeader, M)| async move {
+                connection.send(&header, &msg).await?;
+                Ok::<_, MessageWriteError>(connection)
+            },
+        );
+        AsyncMavSink {
+            inner: Box::pin(inner),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Async Serial MAVLink connection
 
+use crate::link_stats::{LinkStats, SharedLinkStats};
 use core::ops::DerefMut;
 use core::sync::atomic::{self, AtomicU8};
 use std::io;
@@ -33,6 +34,7 @@ pub struct AsyncSerialConnection {
     sequence: AtomicU8,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
+    stats: SharedLinkStats,
     #[cfg(feature = "signing")]
     signing_data: Option<SigningData>,
 }
@@ -52,12 +54,11 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncSerialConnection {
                 self.signing_data.as_ref(),
             )
             .await;
+            self.stats.record(port.read_stats(), &result);
             match result {
                 Ok(message) => return Ok(message),
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
@@ -77,12 +78,11 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncSerialConnection {
                 self.signing_data.as_ref(),
             )
             .await;
+            self.stats.record(port.read_stats(), &result);
             match result {
                 Ok(message) => return Ok(message),
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
@@ -100,6 +100,7 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncSerialConnection {
         let result =
             read_versioned_msg_async_signed(port.deref_mut(), version, self.signing_data.as_ref())
                 .await;
+        self.stats.record(port.read_stats(), &result);
 
         result
     }
@@ -164,6 +165,10 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncSerialConnection {
         self.recv_any_version
     }
 
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
         self.signing_data = signing_data.map(SigningData::from_config);
@@ -192,6 +197,7 @@ impl AsyncConnectable for SerialConfig {
             sequence: AtomicU8::new(0),
             protocol_version: MavlinkVersion::V2,
             recv_any_version: false,
+            stats: SharedLinkStats::default(),
             #[cfg(feature = "signing")]
             signing_data: None,
         }))
@@ -1,52 +1,174 @@
 //! Async File MAVLINK connection
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use core::future::Future;
 use core::ops::DerefMut;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use core::time::Duration;
 use std::io;
 use std::path::PathBuf;
 
 use super::{AsyncConnectable, AsyncMavConnection};
-use crate::connection::file::config::FileConfig;
+use crate::connection::file::config::{FileConfig, FileMode};
 use crate::error::{MessageReadError, MessageWriteError};
+use crate::tlog::{timestamp_now, tlog_record};
 use crate::{
     async_peek_reader::AsyncPeekReader, MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message,
     ReadVersion,
 };
 
 use async_trait::async_trait;
-use futures::lock::Mutex;
+use futures::lock::{Mutex, MutexGuard};
 use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncWriteExt, ReadBuf};
+use tokio::time::Sleep;
 
 #[cfg(not(feature = "signing"))]
-use crate::{read_versioned_msg_async, read_versioned_raw_message_async};
+use crate::{read_versioned_msg_async, read_versioned_raw_message_async, write_versioned_msg};
 
 #[cfg(feature = "signing")]
 use crate::{
-    read_versioned_msg_async_signed, read_versioned_raw_message_async_signed, SigningConfig,
-    SigningData,
+    read_versioned_msg_async_signed, read_versioned_raw_message_async_signed,
+    write_versioned_msg_signed, SigningConfig, SigningData,
 };
 
 pub async fn open(file_path: &PathBuf) -> io::Result<AsyncFileConnection> {
     let file = File::open(file_path).await?;
-    Ok(AsyncFileConnection {
-        file: Mutex::new(AsyncPeekReader::new(file)),
-        protocol_version: MavlinkVersion::V2,
-        recv_any_version: false,
-        #[cfg(feature = "signing")]
-        signing_data: None,
-    })
+    Ok(AsyncFileConnection::new(
+        Some(AsyncPeekReader::new(AsyncFileRead::new(file, None))),
+        None,
+    ))
+}
+
+/// Open a file that is still being written, waiting for new data at its end
+pub async fn follow(
+    file_path: &PathBuf,
+    poll_interval: Duration,
+) -> io::Result<AsyncFileConnection> {
+    let file = File::open(file_path).await?;
+    Ok(AsyncFileConnection::new(
+        Some(AsyncPeekReader::new(AsyncFileRead::new(
+            file,
+            Some(poll_interval),
+        ))),
+        None,
+    ))
+}
+
+/// Create a telemetry log, truncating an existing file
+pub async fn create(file_path: &PathBuf) -> io::Result<AsyncFileConnection> {
+    let file = File::create(file_path).await?;
+    let writer = AsyncFileWrite { file, sequence: 0 };
+    Ok(AsyncFileConnection::new(None, Some(writer)))
+}
+
+struct AsyncFileRead {
+    file: File,
+    /// Interval to poll for new data when following the file
+    follow: Option<Duration>,
+    delay: Option<Pin<Box<Sleep>>>,
+    nonblocking: bool,
+}
+
+impl AsyncFileRead {
+    fn new(file: File, follow: Option<Duration>) -> Self {
+        Self {
+            file,
+            follow,
+            delay: None,
+            nonblocking: false,
+        }
+    }
+}
+
+impl AsyncRead for AsyncFileRead {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            if let Some(delay) = &mut self.delay {
+                ready!(delay.as_mut().poll(cx));
+                self.delay = None;
+            }
+
+            let filled = buf.filled().len();
+            ready!(Pin::new(&mut self.file).poll_read(cx, buf))?;
+            match self.follow {
+                // at the end of the file, wait for the writer to append more data
+                Some(interval) if buf.filled().len() == filled && buf.remaining() > 0 => {
+                    if self.nonblocking {
+                        return Poll::Ready(Err(io::ErrorKind::WouldBlock.into()));
+                    }
+                    self.delay = Some(Box::pin(tokio::time::sleep(interval)));
+                }
+                _ => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+/// Reader that does not wait for data to be appended until it is dropped, which also resets it if
+/// the receiving future is dropped before completing
+struct NonblockingReader<'a>(MutexGuard<'a, AsyncPeekReader<AsyncFileRead>>);
+
+impl<'a> NonblockingReader<'a> {
+    fn new(mut reader: MutexGuard<'a, AsyncPeekReader<AsyncFileRead>>) -> Self {
+        reader.reader_mut().nonblocking = true;
+        Self(reader)
+    }
+}
+
+impl Drop for NonblockingReader<'_> {
+    fn drop(&mut self) {
+        self.0.reader_mut().nonblocking = false;
+    }
+}
+
+struct AsyncFileWrite {
+    file: File,
+    sequence: u8,
 }
 
 pub struct AsyncFileConnection {
-    file: Mutex<AsyncPeekReader<File>>,
+    reader: Option<Mutex<AsyncPeekReader<AsyncFileRead>>>,
+    writer: Option<Mutex<AsyncFileWrite>>,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
+    stats: SharedLinkStats,
     #[cfg(feature = "signing")]
     signing_data: Option<SigningData>,
 }
 
+impl AsyncFileConnection {
+    fn new(reader: Option<AsyncPeekReader<AsyncFileRead>>, writer: Option<AsyncFileWrite>) -> Self {
+        Self {
+            reader: reader.map(Mutex::new),
+            writer: writer.map(Mutex::new),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            stats: SharedLinkStats::default(),
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+
+    async fn reader(&self) -> io::Result<MutexGuard<'_, AsyncPeekReader<AsyncFileRead>>> {
+        match &self.reader {
+            Some(reader) => Ok(reader.lock().await),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "file connection was opened for writing",
+            )),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncFileConnection {
     async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
-        let mut file = self.file.lock().await;
+        let mut file = self.reader().await?;
         let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
         loop {
             #[cfg(not(feature = "signing"))]
@@ -58,14 +180,13 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncFileConnection {
                 self.signing_data.as_ref(),
             )
             .await;
+            self.stats.record(file.read_stats(), &result);
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
@@ -73,7 +194,7 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncFileConnection {
     }
 
     async fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut file = self.file.lock().await;
+        let mut file = self.reader().await?;
         let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
         loop {
             #[cfg(not(feature = "signing"))]
@@ -85,14 +206,13 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncFileConnection {
                 self.signing_data.as_ref(),
             )
             .await;
+            self.stats.record(file.read_stats(), &result);
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
@@ -100,22 +220,56 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncFileConnection {
     }
 
     async fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut file = self.file.lock().await;
+        let mut file = NonblockingReader::new(self.reader().await?);
         let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
 
         #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg_async(file.deref_mut(), version).await;
+        let result = read_versioned_msg_async(file.0.deref_mut(), version).await;
 
         #[cfg(feature = "signing")]
-        let result =
-            read_versioned_msg_async_signed(file.deref_mut(), version, self.signing_data.as_ref())
-                .await;
-
+        let result = read_versioned_msg_async_signed(
+            file.0.deref_mut(),
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        self.stats.record(file.0.read_stats(), &result);
         result
     }
 
-    async fn send(&self, _header: &MavHeader, _data: &M) -> Result<usize, MessageWriteError> {
-        Ok(0)
+    async fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let Some(writer) = &self.writer else {
+            return Ok(0);
+        };
+        let mut guard = writer.lock().await;
+        let state = &mut *guard;
+
+        let header = MavHeader {
+            sequence: state.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        state.sequence = state.sequence.wrapping_add(1);
+
+        let mut buf = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut buf,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        state
+            .file
+            .write_all(&tlog_record(timestamp_now(), &buf))
+            .await?;
+        // tokio only completes file writes in the background, flushing waits for it
+        state.file.flush().await?;
+        Ok(buf.len())
     }
 
     fn set_protocol_version(&mut self, version: MavlinkVersion) {
@@ -134,6 +288,10 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncFileConnection {
         self.recv_any_version
     }
 
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
         self.signing_data = signing_data.map(SigningData::from_config);
@@ -146,6 +304,11 @@ impl AsyncConnectable for FileConfig {
     where
         M: Message + Sync + Send,
     {
-        Ok(Box::new(open(&self.address).await?))
+        let conn = match self.mode {
+            FileMode::Read => open(&self.address).await?,
+            FileMode::Write => create(&self.address).await?,
+            FileMode::Follow => follow(&self.address, self.poll_interval).await?,
+        };
+        Ok(Box::new(conn))
     }
 }
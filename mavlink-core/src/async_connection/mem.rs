@@ -0,0 +1,248 @@
+//! Async in-memory MAVLink connection
+
+use super::{AsyncConnectable, AsyncMavConnection};
+use crate::connection::mem::config::MemConfig;
+use crate::connection::mem::{
+    peer_closed, peer_disconnected, read_packet, Impairment, Packet, Registry,
+};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, ReadStats, ReceivedFrame, SharedLinkStats};
+use crate::peek_reader::PeekReader;
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+use async_trait::async_trait;
+use futures::lock::Mutex;
+use std::io;
+use std::sync::Arc;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::Instant;
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_versioned_msg, read_versioned_raw_message, write_versioned_msg};
+#[cfg(feature = "signing")]
+use crate::{
+    read_versioned_msg_signed, read_versioned_raw_message_signed, write_versioned_msg_signed,
+    SigningConfig, SigningData,
+};
+
+type Ends = (UnboundedSender<Packet>, UnboundedReceiver<Packet>);
+
+static REGISTRY: Registry<Ends> = Registry::new();
+
+fn channel() -> (Ends, Ends) {
+    let (a_tx, b_rx) = mpsc::unbounded_channel();
+    let (b_tx, a_rx) = mpsc::unbounded_channel();
+    ((a_tx, a_rx), (b_tx, b_rx))
+}
+
+struct AsyncMemRead {
+    receiver: UnboundedReceiver<Packet>,
+    // next packet, received by `try_recv` before it was due
+    pending: Option<Packet>,
+    stats: ReadStats,
+}
+
+impl AsyncMemRead {
+    async fn next_packet(&mut self, blocking: bool) -> io::Result<Packet> {
+        let packet = match self.pending.take() {
+            Some(packet) => packet,
+            None if blocking => self.receiver.recv().await.ok_or_else(peer_disconnected)?,
+            None => match self.receiver.try_recv() {
+                Ok(packet) => packet,
+                Err(TryRecvError::Empty) => return Err(io::ErrorKind::WouldBlock.into()),
+                Err(TryRecvError::Disconnected) => return Err(peer_disconnected()),
+            },
+        };
+        let deliver_at = Instant::from_std(packet.deliver_at);
+        if deliver_at > Instant::now() {
+            if !blocking {
+                self.pending = Some(packet);
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            tokio::time::sleep_until(deliver_at).await;
+        }
+        Ok(packet)
+    }
+}
+
+struct AsyncMemWrite {
+    sender: UnboundedSender<Packet>,
+    impairment: Impairment,
+    sequence: u8,
+}
+
+/// Async in-memory MAVLink connection to a peer in the same process
+///
+/// Async connections made to a `mem:` name are only connected to other async connections.
+pub struct AsyncMemConnection {
+    reader: Mutex<AsyncMemRead>,
+    writer: Mutex<AsyncMemWrite>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    stats: SharedLinkStats,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+    _alive: Arc<()>,
+}
+
+impl AsyncMemConnection {
+    fn new((sender, receiver): Ends, config: &MemConfig, alive: Arc<()>) -> Self {
+        Self {
+            reader: Mutex::new(AsyncMemRead {
+                receiver,
+                pending: None,
+                stats: ReadStats::default(),
+            }),
+            writer: Mutex::new(AsyncMemWrite {
+                sender,
+                impairment: Impairment::new(config),
+                sequence: 0,
+            }),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            stats: SharedLinkStats::default(),
+            #[cfg(feature = "signing")]
+            signing_data: None,
+            _alive: alive,
+        }
+    }
+
+    /// Create two connections connected to each other by a perfect link
+    pub fn pair() -> (Self, Self) {
+        let config = MemConfig::new(String::new());
+        let (a, b) = channel();
+        let alive = Arc::new(());
+        (
+            Self::new(a, &config, alive.clone()),
+            Self::new(b, &config, alive),
+        )
+    }
+
+    async fn read<T: ReceivedFrame>(
+        &self,
+        blocking: bool,
+        read: impl Fn(&mut PeekReader<&[u8]>) -> Result<T, MessageReadError>,
+    ) -> Result<T, MessageReadError> {
+        let mut reader = self.reader.lock().await;
+        let result = loop {
+            let packet = match reader.next_packet(blocking).await {
+                Ok(packet) => packet,
+                Err(e) => break Err(e.into()),
+            };
+            if let Some(result) = read_packet(&packet, &mut reader.stats, &read) {
+                break result;
+            }
+        };
+        self.stats.record(reader.stats, &result);
+        result
+    }
+
+    async fn recv_msg<M: Message + Sync + Send>(
+        &self,
+        blocking: bool,
+    ) -> Result<(MavHeader, M), MessageReadError> {
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        self.read(blocking, |frame| {
+            #[cfg(not(feature = "signing"))]
+            let result = read_versioned_msg(frame, version);
+            #[cfg(feature = "signing")]
+            let result = read_versioned_msg_signed(frame, version, self.signing_data.as_ref());
+            result
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncMemConnection {
+    async fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_msg(true).await
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        self.read(true, |frame| {
+            #[cfg(not(feature = "signing"))]
+            let result = read_versioned_raw_message::<M, _>(frame, version);
+            #[cfg(feature = "signing")]
+            let result = read_versioned_raw_message_signed::<M, _>(
+                frame,
+                version,
+                self.signing_data.as_ref(),
+            );
+            result
+        })
+        .await
+    }
+
+    async fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_msg(false).await
+    }
+
+    async fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let mut guard = self.writer.lock().await;
+        let state = &mut *guard;
+
+        let header = MavHeader {
+            sequence: state.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        state.sequence = state.sequence.wrapping_add(1);
+
+        let mut buf = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut buf,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        let len = buf.len();
+        if let Some(packet) = state.impairment.apply(buf) {
+            state.sender.send(packet).map_err(|_| peer_closed())?;
+        }
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
+
+#[async_trait]
+impl AsyncConnectable for MemConfig {
+    async fn connect_async<M>(&self) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>>
+    where
+        M: Message + Sync + Send,
+    {
+        let (ends, alive) = REGISTRY.connect(&self.name, channel);
+        Ok(Box::new(AsyncMemConnection::new(ends, self, alive)))
+    }
+}
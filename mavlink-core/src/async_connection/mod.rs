@@ -1,20 +1,58 @@
+#[cfg(feature = "tokio-1")]
 use async_trait::async_trait;
+#[cfg(feature = "tokio-1")]
 use std::io;
 
-use crate::{
-    connectable::ConnectionAddress, MAVLinkMessageRaw, MavFrame, MavHeader, MavlinkVersion, Message,
-};
-#[cfg(feature = "tcp")]
+#[cfg(feature = "tokio-1")]
+use crate::connectable::ConnectionAddress;
+use crate::link_stats::LinkStats;
+use crate::{MAVLinkMessageRaw, MavFrame, MavHeader, MavlinkVersion, Message};
+#[cfg(all(feature = "tokio-1", feature = "tcp"))]
 mod tcp;
+#[cfg(all(feature = "tokio-1", feature = "tcp"))]
+mod tcp_server;
+#[cfg(all(feature = "tokio-1", feature = "tcp"))]
+pub use tcp_server::AsyncTcpServerConnection;
 
-#[cfg(feature = "udp")]
+#[cfg(all(feature = "tokio-1", feature = "udp"))]
 mod udp;
-
-#[cfg(feature = "direct-serial")]
+#[cfg(all(feature = "tokio-1", feature = "udp"))]
+mod udp_server;
+#[cfg(all(feature = "tokio-1", feature = "udp"))]
+pub use udp_server::AsyncUdpServerConnection;
+
+#[cfg(all(feature = "tokio-1", feature = "unix", unix))]
+mod unix;
+#[cfg(all(feature = "tokio-1", feature = "unix", unix))]
+mod unix_datagram;
+
+#[cfg(feature = "websocket")]
+pub(crate) mod websocket;
+#[cfg(feature = "websocket")]
+pub use websocket::config::{WebSocketConfig, WebSocketMode};
+#[cfg(feature = "websocket")]
+pub use websocket::server::AsyncWebSocketServerConnection;
+
+#[cfg(all(feature = "tokio-1", feature = "direct-serial"))]
 mod direct_serial;
 
+#[cfg(feature = "tokio-1")]
 mod file;
 
+#[cfg(feature = "tokio-1")]
+mod mem;
+#[cfg(feature = "tokio-1")]
+pub use mem::AsyncMemConnection;
+
+mod split;
+pub use split::{AsyncMavReceiver, AsyncMavSender};
+
+mod adapters;
+pub use adapters::{AsyncMavSink, AsyncMavStream};
+
+mod stream;
+pub use stream::AsyncStreamConnection;
+
 #[cfg(feature = "signing")]
 use crate::SigningConfig;
 
@@ -61,6 +99,16 @@ pub trait AsyncMavConnection<M: Message + Sync + Send> {
     /// Wether messages of any MAVLink version may be received.
     fn allow_recv_any_version(&self) -> bool;
 
+    /// Snapshot of the receive statistics of this connection.
+    ///
+    /// The statistics are updated whenever a receive call returns, data discarded while
+    /// a call is still waiting for a valid message is accounted for once it returns.
+    ///
+    /// Connections that do not track statistics return empty statistics.
+    fn link_stats(&self) -> LinkStats {
+        LinkStats::default()
+    }
+
     /// Write whole frame.
     async fn send_frame(
         &self,
@@ -97,21 +145,38 @@ pub trait AsyncMavConnection<M: Message + Sync + Send> {
 ///
 ///  * `tcpin:<addr>:<port>` to create a TCP server, listening for an incoming connection
 ///  * `tcpout:<addr>:<port>` to create a TCP client
+///  * `tcpserver:<addr>:<port>` to create a TCP server, accepting any number of clients
 ///  * `udpin:<addr>:<port>` to create a UDP server, listening for incoming packets
 ///  * `udpout:<addr>:<port>` to create a UDP client
+///  * `udpserver:<addr>:<port>` to create a UDP server, replying to all clients
 ///  * `udpbcast:<addr>:<port>` to create a UDP broadcast
+///  * `unixin:<path>` to create a Unix stream socket server, listening for an incoming connection
+///  * `unix:<path>` to connect to a Unix stream socket server
+///  * `unixgram:<path>` to create a Unix datagram socket, replying to the last sender
+///  * `ws://<host>:<port>/<path>` or `wss://...` to create a WebSocket client, only available as
+///    async connection
+///  * `wsserver:<addr>:<port>` to create a WebSocket server, accepting any number of clients, only
+///    available as async connection
 ///  * `serial:<port>:<baudrate>` to create a serial connection
 ///  * `file:<path>` to extract file data, writing to such a connection does nothing
+///  * `fileout:<path>` to record sent messages to a telemetry log, reading from such a connection fails
+///  * `filefollow:<path>` to read a file that is still being written, waiting for new data like `tail -f`
+///  * `mem:<name>` to create an in-process connection to the other connection made to the same name
 ///
 /// The type of the connection is determined at runtime based on the address type, so the
 /// connection is returned as a trait object.
 ///
+/// All of these connections run on tokio, so this function requires the `tokio-1` feature. With
+/// only `futures-io`, streams opened by another runtime can be used with
+/// [`AsyncStreamConnection::from_futures`].
+///
 /// # Errors
 ///
 /// - [`AddrNotAvailable`] if the address string could not be parsed as a valid MAVLink address
 /// - When the connection could not be established a corresponding [`io::Error`] is returned
 ///
 /// [`AddrNotAvailable`]: io::ErrorKind::AddrNotAvailable
+#[cfg(feature = "tokio-1")]
 pub async fn connect_async<M: Message + Sync + Send>(
     address: &str,
 ) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>> {
@@ -121,7 +186,7 @@ pub async fn connect_async<M: Message + Sync + Send>(
 }
 
 /// Returns the socket address for the given address.
-#[cfg(any(feature = "tcp", feature = "udp"))]
+#[cfg(all(feature = "tokio-1", any(feature = "tcp", feature = "udp")))]
 pub(crate) fn get_socket_addr<T: std::net::ToSocketAddrs>(
     address: T,
 ) -> Result<std::net::SocketAddr, io::Error> {
@@ -137,6 +202,7 @@ pub(crate) fn get_socket_addr<T: std::net::ToSocketAddrs>(
 /// A MAVLink connection address that can be connected to, establishing an [`AsyncMavConnection`]
 ///
 /// This is the `async` version of `Connectable`.
+#[cfg(feature = "tokio-1")]
 #[async_trait]
 pub trait AsyncConnectable {
     /// Attempt to establish an asynchronous MAVLink connection
@@ -145,6 +211,7 @@ pub trait AsyncConnectable {
         M: Message + Sync + Send;
 }
 
+#[cfg(feature = "tokio-1")]
 #[async_trait]
 impl AsyncConnectable for ConnectionAddress {
     async fn connect_async<M>(&self) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>>
@@ -156,9 +223,14 @@ impl AsyncConnectable for ConnectionAddress {
             Self::Tcp(connectable) => connectable.connect_async::<M>().await,
             #[cfg(feature = "udp")]
             Self::Udp(connectable) => connectable.connect_async::<M>().await,
+            #[cfg(all(feature = "unix", unix))]
+            Self::Unix(connectable) => connectable.connect_async::<M>().await,
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(connectable) => connectable.connect_async::<M>().await,
             #[cfg(feature = "direct-serial")]
             Self::Serial(connectable) => connectable.connect_async::<M>().await,
             Self::File(connectable) => connectable.connect_async::<M>().await,
+            Self::Mem(connectable) => connectable.connect_async::<M>().await,
         }
     }
 }
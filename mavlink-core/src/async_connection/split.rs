@@ -0,0 +1,137 @@
+//! Receiving and sending halves of an async connection
+
+use std::sync::Arc;
+
+use super::AsyncMavConnection;
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::LinkStats;
+use crate::{MAVLinkMessageRaw, MavFrame, MavHeader, MavlinkVersion, Message};
+
+pub(super) type SharedConnection<M> = Arc<dyn AsyncMavConnection<M> + Sync + Send>;
+
+/// Receiving half of an async connection, created by splitting the connection
+///
+/// This is the `async` version of [`MavReceiver`](crate::MavReceiver).
+pub struct AsyncMavReceiver<M: Message + Sync + Send> {
+    pub(super) connection: SharedConnection<M>,
+}
+
+impl<M: Message + Sync + Send> AsyncMavReceiver<M> {
+    /// Receive a MAVLink message, see [`AsyncMavConnection::recv`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error that occured while receiving.
+    pub async fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.connection.recv().await
+    }
+
+    /// Receive a raw, unparsed MAVLink message, see [`AsyncMavConnection::recv_raw`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error that occured while receiving.
+    pub async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.connection.recv_raw().await
+    }
+
+    /// Try to receive a MAVLink message without waiting, see [`AsyncMavConnection::try_recv`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error encountered while receiving or deserializing a message.
+    pub async fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.connection.try_recv().await
+    }
+
+    /// Read whole frame, see [`AsyncMavConnection::recv_frame`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error encountered while receiving or deserializing a message.
+    pub async fn recv_frame(&self) -> Result<MavFrame<M>, MessageReadError> {
+        self.connection.recv_frame().await
+    }
+
+    /// Gets the currently used MAVLink version
+    pub fn protocol_version(&self) -> MavlinkVersion {
+        self.connection.protocol_version()
+    }
+
+    /// Wether messages of any MAVLink version may be received.
+    pub fn allow_recv_any_version(&self) -> bool {
+        self.connection.allow_recv_any_version()
+    }
+
+    /// Snapshot of the receive statistics of the connection
+    pub fn link_stats(&self) -> LinkStats {
+        self.connection.link_stats()
+    }
+}
+
+/// Sending half of an async connection, created by splitting the connection
+///
+/// This is the `async` version of [`MavSender`](crate::MavSender).
+pub struct AsyncMavSender<M: Message + Sync + Send> {
+    pub(super) connection: SharedConnection<M>,
+}
+
+impl<M: Message + Sync + Send> Clone for AsyncMavSender<M> {
+    fn clone(&self) -> Self {
+        Self {
+            connection: self.connection.clone(),
+        }
+    }
+}
+
+impl<M: Message + Sync + Send> AsyncMavSender<M> {
+    /// Send a MAVLink message, see [`AsyncMavConnection::send`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`MessageWriteError::Io`] error when sending fails.
+    pub async fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        self.connection.send(header, data).await
+    }
+
+    /// Write whole frame, see [`AsyncMavConnection::send_frame`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`MessageWriteError::Io`] error when sending fails.
+    pub async fn send_frame(&self, frame: &MavFrame<M>) -> Result<usize, MessageWriteError> {
+        self.connection.send_frame(frame).await
+    }
+
+    /// Send a message with default header, see [`AsyncMavConnection::send_default`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`MessageWriteError::Io`] error when sending fails.
+    pub async fn send_default(&self, data: &M) -> Result<usize, MessageWriteError> {
+        self.connection.send_default(data).await
+    }
+
+    /// Gets the currently used MAVLink version
+    pub fn protocol_version(&self) -> MavlinkVersion {
+        self.connection.protocol_version()
+    }
+}
+
+impl<M: Message + Sync + Send> dyn AsyncMavConnection<M> + Sync + Send {
+    /// Split the connection into a receiving and a sending half
+    ///
+    /// The async connections lock their reading and writing sides separately, so a task waiting
+    /// in [`AsyncMavReceiver::recv`] does not hold up tasks sending messages. Both halves keep
+    /// using the sequence number, protocol version and signing state of the connection, which
+    /// therefore has to be configured before splitting it.
+    pub fn split(self: Box<Self>) -> (AsyncMavReceiver<M>, AsyncMavSender<M>) {
+        let connection: SharedConnection<M> = self.into();
+        (
+            AsyncMavReceiver {
+                connection: connection.clone(),
+            },
+            AsyncMavSender { connection },
+        )
+    }
+}
@@ -0,0 +1,188 @@
+//! Async MAVLink connection over any byte stream
+
+use super::AsyncMavConnection;
+use crate::async_io::{self, AsyncByteRead, AsyncByteWrite};
+use crate::async_peek_reader::AsyncPeekReader;
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+use core::ops::DerefMut;
+use futures::{lock::Mutex, FutureExt};
+use std::io;
+
+#[cfg(not(feature = "signing"))]
+use crate::{
+    read_versioned_msg_async, read_versioned_raw_message_async, write_versioned_msg_async,
+};
+#[cfg(feature = "signing")]
+use crate::{
+    read_versioned_msg_async_signed, read_versioned_raw_message_async_signed,
+    write_versioned_msg_async_signed, SigningConfig, SigningData,
+};
+
+type BoxedReader = Box<dyn AsyncByteRead + Send + Unpin>;
+
+/// Async MAVLink connection over an arbitrary [`AsyncByteRead`] + [`AsyncByteWrite`] stream
+///
+/// This is the `async` version of [`StreamConnection`](crate::StreamConnection).
+///
+/// The connection does not depend on a specific runtime: tokio streams can be used directly, streams
+/// of other runtimes implementing the `futures` I/O traits can be used with
+/// [`from_futures`](Self::from_futures). This is the only async connection available without the
+/// `tokio-1` feature.
+pub struct AsyncStreamConnection {
+    reader: Mutex<AsyncPeekReader<BoxedReader>>,
+    writer: Mutex<StreamWrite>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    stats: SharedLinkStats,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+struct StreamWrite {
+    stream: Box<dyn AsyncByteWrite + Send + Unpin>,
+    sequence: u8,
+}
+
+impl AsyncStreamConnection {
+    /// Create a connection over a single tokio stream, which is split into reading and writing halves
+    #[cfg(feature = "tokio-1")]
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::split(stream);
+        Self::from_halves(reader, writer)
+    }
+
+    /// Create a connection over a single `futures` stream, which is split into reading and writing halves
+    ///
+    /// This allows using the streams of runtimes such as `smol` or `async-std`.
+    #[cfg(feature = "futures-io")]
+    pub fn from_futures<S>(stream: S) -> Self
+    where
+        S: futures::io::AsyncRead + futures::io::AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = futures::io::AsyncReadExt::split(stream);
+        Self::from_halves(
+            async_io::FuturesIo::new(reader),
+            async_io::FuturesIo::new(writer),
+        )
+    }
+
+    /// Create a connection from separate reading and writing halves of a stream
+    pub fn from_halves<R, W>(reader: R, writer: W) -> Self
+    where
+        R: AsyncByteRead + Send + Unpin + 'static,
+        W: AsyncByteWrite + Send + Unpin + 'static,
+    {
+        let reader: BoxedReader = Box::new(reader);
+        Self {
+            reader: Mutex::new(AsyncPeekReader::new(reader)),
+            writer: Mutex::new(StreamWrite {
+                stream: Box::new(writer),
+                sequence: 0,
+            }),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            stats: SharedLinkStats::default(),
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncStreamConnection {
+    async fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let mut reader = self.reader.lock().await;
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg_async(reader.deref_mut(), version).await;
+        #[cfg(feature = "signing")]
+        let result = read_versioned_msg_async_signed(
+            reader.deref_mut(),
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        self.stats.record(reader.read_stats(), &result);
+        result
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let mut reader = self.reader.lock().await;
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_raw_message_async::<M, _>(reader.deref_mut(), version).await;
+        #[cfg(feature = "signing")]
+        let result = read_versioned_raw_message_async_signed::<M, _>(
+            reader.deref_mut(),
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        self.stats.record(reader.read_stats(), &result);
+        result
+    }
+
+    async fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        match self.recv().now_or_never() {
+            Some(result) => result,
+            None => Err(MessageReadError::Io(io::ErrorKind::WouldBlock.into())),
+        }
+    }
+
+    async fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let mut lock = self.writer.lock().await;
+
+        let header = MavHeader {
+            sequence: lock.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        lock.sequence = lock.sequence.wrapping_add(1);
+        #[cfg(not(feature = "signing"))]
+        let len = write_versioned_msg_async(&mut lock.stream, self.protocol_version, header, data)
+            .await?;
+        #[cfg(feature = "signing")]
+        let len = write_versioned_msg_async_signed(
+            &mut lock.stream,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )
+        .await?;
+        async_io::flush(&mut lock.stream).await?;
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
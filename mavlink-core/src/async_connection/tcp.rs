@@ -1,7 +1,9 @@
 //! Async TCP MAVLink connection
 
+use crate::link_stats::{LinkStats, SharedLinkStats};
 use std::io;
 
+use super::tcp_server::AsyncTcpServerConnection;
 use super::{get_socket_addr, AsyncConnectable, AsyncMavConnection};
 use crate::async_peek_reader::AsyncPeekReader;
 use crate::connection::tcp::config::{TcpConfig, TcpMode};
@@ -38,6 +40,7 @@ pub async fn tcpout<T: std::net::ToSocketAddrs>(address: T) -> io::Result<AsyncT
         }),
         protocol_version: MavlinkVersion::V2,
         recv_any_version: false,
+        stats: SharedLinkStats::default(),
         #[cfg(feature = "signing")]
         signing_data: None,
     })
@@ -47,31 +50,21 @@ pub async fn tcpin<T: std::net::ToSocketAddrs>(address: T) -> io::Result<AsyncTc
     let addr = get_socket_addr(address)?;
     let listener = TcpListener::bind(addr).await?;
 
-    //For now we only accept one incoming stream: this yields until we get one
-    match listener.accept().await {
-        Ok((socket, _)) => {
-            let (reader, writer) = socket.into_split();
-            return Ok(AsyncTcpConnection {
-                reader: Mutex::new(AsyncPeekReader::new(reader)),
-                writer: Mutex::new(TcpWrite {
-                    socket: writer,
-                    sequence: 0,
-                }),
-                protocol_version: MavlinkVersion::V2,
-                recv_any_version: false,
-                #[cfg(feature = "signing")]
-                signing_data: None,
-            });
-        }
-        Err(e) => {
-            //TODO don't println in lib
-            println!("listener err: {e}");
-        }
-    }
-    Err(io::Error::new(
-        io::ErrorKind::NotConnected,
-        "No incoming connections!",
-    ))
+    // only a single incoming stream is accepted, use `tcpserver` for multiple clients
+    let (socket, _) = listener.accept().await?;
+    let (reader, writer) = socket.into_split();
+    Ok(AsyncTcpConnection {
+        reader: Mutex::new(AsyncPeekReader::new(reader)),
+        writer: Mutex::new(TcpWrite {
+            socket: writer,
+            sequence: 0,
+        }),
+        protocol_version: MavlinkVersion::V2,
+        recv_any_version: false,
+        stats: SharedLinkStats::default(),
+        #[cfg(feature = "signing")]
+        signing_data: None,
+    })
 }
 
 pub struct AsyncTcpConnection {
@@ -79,6 +72,7 @@ pub struct AsyncTcpConnection {
     writer: Mutex<TcpWrite>,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
+    stats: SharedLinkStats,
     #[cfg(feature = "signing")]
     signing_data: Option<SigningData>,
 }
@@ -102,6 +96,7 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncTcpConnection {
             self.signing_data.as_ref(),
         )
         .await;
+        self.stats.record(reader.read_stats(), &result);
         result
     }
 
@@ -117,6 +112,7 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncTcpConnection {
             self.signing_data.as_ref(),
         )
         .await;
+        self.stats.record(reader.read_stats(), &result);
         result
     }
 
@@ -174,6 +170,10 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncTcpConnection {
         self.recv_any_version
     }
 
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
         self.signing_data = signing_data.map(SigningData::from_config);
@@ -186,11 +186,12 @@ impl AsyncConnectable for TcpConfig {
     where
         M: Message + Sync + Send,
     {
-        let conn = match self.mode {
-            TcpMode::TcpIn => tcpin(&self.address).await,
-            TcpMode::TcpOut => tcpout(&self.address).await,
+        let conn: Box<dyn AsyncMavConnection<M> + Sync + Send> = match self.mode {
+            TcpMode::TcpIn => Box::new(tcpin(&self.address).await?),
+            TcpMode::TcpOut => Box::new(tcpout(&self.address).await?),
+            TcpMode::TcpServer => Box::new(AsyncTcpServerConnection::bind(&self.address).await?),
         };
 
-        Ok(Box::new(conn?))
+        Ok(conn)
     }
 }
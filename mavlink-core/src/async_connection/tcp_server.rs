@@ -0,0 +1,315 @@
+//! Async TCP MAVLink server accepting any number of clients
+
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::{lock::Mutex, FutureExt};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::{JoinHandle, JoinSet};
+
+use super::{get_socket_addr, AsyncMavConnection};
+use crate::connection::peer_inbox::{parse_raw, PeerEvent, PeerInbox};
+use crate::connection::{accept_retry_delay, CLIENT_READ_BUFFERS, CLIENT_WRITE_TIMEOUT};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::write_versioned_msg;
+#[cfg(feature = "signing")]
+use crate::{write_versioned_msg_signed, SigningConfig, SigningData};
+
+struct Clients {
+    writers: HashMap<SocketAddr, OwnedWriteHalf>,
+    sequence: u8,
+}
+
+/// Write a frame to a client, failing if the client does not take it within [`CLIENT_WRITE_TIMEOUT`]
+async fn write_frame(writer: &mut OwnedWriteHalf, frame: &[u8]) -> io::Result<()> {
+    tokio::time::timeout(CLIENT_WRITE_TIMEOUT, writer.write_all(frame))
+        .await
+        .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()))
+}
+
+impl Clients {
+    async fn broadcast(&mut self, frame: &[u8]) -> usize {
+        let mut failed = Vec::new();
+        for (address, writer) in &mut self.writers {
+            if write_frame(writer, frame).await.is_err() {
+                failed.push(*address);
+            }
+        }
+        for address in &failed {
+            self.writers.remove(address);
+        }
+        if self.writers.is_empty() {
+            0
+        } else {
+            frame.len()
+        }
+    }
+}
+
+/// Async TCP server connection that keeps accepting clients
+///
+/// This is the `async` version of `TcpServerConnection`, the clients are served by tasks spawned
+/// on the tokio runtime the server was created on. They are aborted when the connection is dropped.
+/// As with the blocking server, clients are not read from while the server has too much of their
+/// data buffered.
+pub struct AsyncTcpServerConnection {
+    clients: Arc<Mutex<Clients>>,
+    inbox: Mutex<(PeerInbox, Receiver<PeerEvent>)>,
+    stats: SharedLinkStats,
+    accept_task: JoinHandle<()>,
+    local_address: SocketAddr,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl AsyncTcpServerConnection {
+    /// Bind a TCP server to the given address and start accepting clients
+    ///
+    /// # Errors
+    ///
+    /// When the address could not be resolved or bound a corresponding [`io::Error`] is returned
+    pub async fn bind<T: std::net::ToSocketAddrs>(address: T) -> io::Result<Self> {
+        let listener = TcpListener::bind(get_socket_addr(address)?).await?;
+        let local_address = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Clients {
+            writers: HashMap::new(),
+            sequence: 0,
+        }));
+        let (sender, receiver) = mpsc::channel(CLIENT_READ_BUFFERS);
+        let accept_task = tokio::spawn(accept_clients(listener, clients.clone(), sender));
+
+        Ok(Self {
+            clients,
+            inbox: Mutex::new((PeerInbox::default(), receiver)),
+            stats: SharedLinkStats::default(),
+            accept_task,
+            local_address,
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_address
+    }
+
+    /// Addresses of the currently connected clients
+    pub async fn peers(&self) -> Vec<SocketAddr> {
+        self.clients.lock().await.writers.keys().copied().collect()
+    }
+
+    /// Receive a raw MAVLink message together with the address of the client that sent it
+    ///
+    /// Yields until a valid frame is received from any client.
+    ///
+    /// # Errors
+    ///
+    /// See [`AsyncMavConnection::recv_raw`]
+    pub async fn recv_raw_from<M: Message + Sync + Send>(
+        &self,
+    ) -> Result<(SocketAddr, MAVLinkMessageRaw), MessageReadError> {
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        let mut guard = self.inbox.lock().await;
+        let (inbox, events) = &mut *guard;
+        loop {
+            #[cfg(not(feature = "signing"))]
+            let frame = inbox.next_frame::<M>(version, &self.stats);
+            #[cfg(feature = "signing")]
+            let frame = inbox.next_frame::<M>(version, self.signing_data.as_ref(), &self.stats);
+            if let Some(frame) = frame {
+                return Ok(frame);
+            }
+            let event = events
+                .recv()
+                .await
+                .ok_or(io::Error::from(io::ErrorKind::NotConnected))?;
+            inbox.handle(event);
+        }
+    }
+
+    /// Receive a MAVLink message together with the address of the client that sent it
+    ///
+    /// Yields until a valid message is received from any client.
+    ///
+    /// # Errors
+    ///
+    /// See [`AsyncMavConnection::recv`]
+    pub async fn recv_from<M: Message + Sync + Send>(
+        &self,
+    ) -> Result<(SocketAddr, MavHeader, M), MessageReadError> {
+        let (address, raw) = self.recv_raw_from::<M>().await?;
+        let (header, msg) = parse_raw(&raw)?;
+        Ok((address, header, msg))
+    }
+
+    /// Send a MAVLink message to a single client
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MessageWriteError::Io`] with [`io::ErrorKind::NotConnected`] if no client with
+    /// the given address is connected, or the error that occurred while writing to it.
+    pub async fn send_to<M: Message>(
+        &self,
+        peer: SocketAddr,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, MessageWriteError> {
+        let mut clients = self.clients.lock().await;
+        let frame = self.serialize(&mut clients, header, data)?;
+        let writer = clients
+            .writers
+            .get_mut(&peer)
+            .ok_or(io::Error::from(io::ErrorKind::NotConnected))?;
+        if let Err(e) = write_frame(writer, &frame).await {
+            clients.writers.remove(&peer);
+            return Err(e.into());
+        }
+        Ok(frame.len())
+    }
+
+    fn serialize<M: Message>(
+        &self,
+        clients: &mut Clients,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<Vec<u8>, MessageWriteError> {
+        let header = MavHeader {
+            sequence: clients.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+        clients.sequence = clients.sequence.wrapping_add(1);
+
+        let mut frame = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut frame, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut frame,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        Ok(frame)
+    }
+}
+
+impl Drop for AsyncTcpServerConnection {
+    fn drop(&mut self) {
+        // the client tasks are owned by the accept task and aborted along with it
+        self.accept_task.abort();
+    }
+}
+
+async fn accept_clients(
+    listener: TcpListener,
+    clients: Arc<Mutex<Clients>>,
+    events: Sender<PeerEvent>,
+) {
+    let mut tasks = JoinSet::new();
+    loop {
+        let (stream, address) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                tokio::time::sleep(accept_retry_delay(&e)).await;
+                continue;
+            }
+        };
+        // clean up the tasks of disconnected clients
+        while let Some(Some(_)) = tasks.join_next().now_or_never() {}
+
+        let (reader, writer) = stream.into_split();
+        clients.lock().await.writers.insert(address, writer);
+        tasks.spawn(forward_client(
+            reader,
+            address,
+            clients.clone(),
+            events.clone(),
+        ));
+    }
+}
+
+async fn forward_client(
+    mut reader: OwnedReadHalf,
+    address: SocketAddr,
+    clients: Arc<Mutex<Clients>>,
+    events: Sender<PeerEvent>,
+) {
+    let mut buf = [0; 1024];
+    while let Ok(n @ 1..) = reader.read(&mut buf).await {
+        if events
+            .send(PeerEvent::Data(address, buf[..n].to_vec()))
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+    clients.lock().await.writers.remove(&address);
+    events.send(PeerEvent::Disconnected(address)).await.ok();
+}
+
+#[async_trait]
+impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncTcpServerConnection {
+    async fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_from().await.map(|(_, header, msg)| (header, msg))
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.recv_raw_from::<M>().await.map(|(_, raw)| raw)
+    }
+
+    async fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        match <Self as AsyncMavConnection<M>>::recv(self).now_or_never() {
+            Some(result) => result,
+            None => Err(MessageReadError::Io(io::ErrorKind::WouldBlock.into())),
+        }
+    }
+
+    async fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let mut clients = self.clients.lock().await;
+        let frame = self.serialize(&mut clients, header, data)?;
+        Ok(clients.broadcast(&frame).await)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
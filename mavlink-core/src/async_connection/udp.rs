@@ -1,5 +1,6 @@
 //! Async UDP MAVLink connection
 
+use crate::link_stats::{LinkStats, SharedLinkStats};
 use core::{ops::DerefMut, task::Poll};
 use std::io;
 use std::{collections::VecDeque, io::Read, sync::Arc};
@@ -15,6 +16,7 @@ use crate::connection::udp::config::{UdpConfig, UdpMode};
 use crate::MAVLinkMessageRaw;
 use crate::{async_peek_reader::AsyncPeekReader, MavHeader, MavlinkVersion, Message, ReadVersion};
 
+use super::udp_server::AsyncUdpServerConnection;
 use super::{get_socket_addr, AsyncConnectable, AsyncMavConnection};
 
 #[cfg(not(feature = "signing"))]
@@ -83,6 +85,7 @@ pub struct AsyncUdpConnection {
     writer: Mutex<UdpWrite>,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
+    stats: SharedLinkStats,
     server: bool,
     #[cfg(feature = "signing")]
     signing_data: Option<SigningData>,
@@ -109,6 +112,7 @@ impl AsyncUdpConnection {
             }),
             protocol_version: MavlinkVersion::V2,
             recv_any_version: false,
+            stats: SharedLinkStats::default(),
             #[cfg(feature = "signing")]
             signing_data: None,
         })
@@ -130,6 +134,7 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncUdpConnection {
                 self.signing_data.as_ref(),
             )
             .await;
+            self.stats.record(reader.read_stats(), &result);
             if self.server {
                 if let addr @ Some(_) = reader.reader_ref().last_recv_address {
                     self.writer.lock().await.dest = addr;
@@ -155,6 +160,7 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncUdpConnection {
                 self.signing_data.as_ref(),
             )
             .await;
+            self.stats.record(reader.read_stats(), &result);
             if self.server {
                 if let addr @ Some(_) = reader.reader_ref().last_recv_address {
                     self.writer.lock().await.dest = addr;
@@ -180,6 +186,7 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncUdpConnection {
             self.signing_data.as_ref(),
         )
         .await;
+        self.stats.record(reader.read_stats(), &result);
 
         if self.server {
             if let addr @ Some(_) = reader.reader_ref().last_recv_address {
@@ -251,6 +258,10 @@ impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncUdpConnection {
         self.recv_any_version
     }
 
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
         self.signing_data = signing_data.map(SigningData::from_config);
@@ -264,13 +275,20 @@ impl AsyncConnectable for UdpConfig {
         M: Message + Sync + Send,
     {
         let (addr, server, dest): (&str, _, _) = match self.mode {
-            UdpMode::Udpin => (&self.address, true, None),
+            UdpMode::Udpin | UdpMode::Udpserver => (&self.address, true, None),
             _ => ("0.0.0.0:0", false, Some(get_socket_addr(&self.address)?)),
         };
         let socket = UdpSocket::bind(addr).await?;
         if matches!(self.mode, UdpMode::Udpcast) {
             socket.set_broadcast(true)?;
         }
+        if matches!(self.mode, UdpMode::Udpserver) {
+            let mut conn = AsyncUdpServerConnection::new(socket);
+            if let Some(timeout) = self.peer_timeout {
+                conn.set_peer_timeout(timeout);
+            }
+            return Ok(Box::new(conn));
+        }
         Ok(Box::new(AsyncUdpConnection::new(socket, server, dest)?))
     }
 }
@@ -0,0 +1,250 @@
+//! Async UDP MAVLink server keeping track of all its peers
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::{lock::Mutex, FutureExt};
+use tokio::net::UdpSocket;
+
+use super::{get_socket_addr, AsyncMavConnection};
+use crate::connection::peer_inbox::{parse_raw, PeerEvent, PeerInbox};
+use crate::connection::udp::server::{is_refused, Peers, DEFAULT_PEER_TIMEOUT};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::write_versioned_msg;
+#[cfg(feature = "signing")]
+use crate::{write_versioned_msg_signed, SigningConfig, SigningData};
+
+const MTU_SIZE: usize = 1500;
+
+/// Async UDP server connection that keeps track of all its peers
+///
+/// This is the `async` version of `UdpServerConnection`.
+pub struct AsyncUdpServerConnection {
+    socket: UdpSocket,
+    inbox: Mutex<PeerInbox>,
+    peers: std::sync::Mutex<Peers>,
+    stats: SharedLinkStats,
+    peer_timeout: Duration,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl AsyncUdpServerConnection {
+    /// Bind a UDP server to the given address
+    ///
+    /// # Errors
+    ///
+    /// When the address could not be resolved or bound a corresponding [`io::Error`] is returned
+    pub async fn bind<T: std::net::ToSocketAddrs>(address: T) -> io::Result<Self> {
+        let socket = UdpSocket::bind(get_socket_addr(address)?).await?;
+        Ok(Self::new(socket))
+    }
+
+    pub(crate) fn new(socket: UdpSocket) -> Self {
+        Self {
+            socket,
+            inbox: Mutex::default(),
+            peers: std::sync::Mutex::new(Peers::new()),
+            stats: SharedLinkStats::default(),
+            peer_timeout: DEFAULT_PEER_TIMEOUT,
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+
+    /// Address the server is bound to
+    ///
+    /// # Errors
+    ///
+    /// See [`UdpSocket::local_addr`]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Set the time after which a peer that has not sent any data is forgotten, 10 seconds by default
+    pub fn set_peer_timeout(&mut self, timeout: Duration) {
+        self.peer_timeout = timeout;
+    }
+
+    /// Time after which a peer that has not sent any data is forgotten
+    pub fn peer_timeout(&self) -> Duration {
+        self.peer_timeout
+    }
+
+    /// Addresses of the peers that sent data within the peer timeout
+    pub fn peers(&self) -> Vec<SocketAddr> {
+        self.peers.lock().unwrap().alive(self.peer_timeout)
+    }
+
+    /// Receive a raw MAVLink message together with the address of the peer that sent it
+    ///
+    /// Yields until a valid frame is received from any peer.
+    ///
+    /// # Errors
+    ///
+    /// See [`AsyncMavConnection::recv_raw`]
+    pub async fn recv_raw_from<M: Message + Sync + Send>(
+        &self,
+    ) -> Result<(SocketAddr, MAVLinkMessageRaw), MessageReadError> {
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        let mut inbox = self.inbox.lock().await;
+        let mut buf = [0; MTU_SIZE];
+        loop {
+            #[cfg(not(feature = "signing"))]
+            let frame = inbox.next_frame::<M>(version, &self.stats);
+            #[cfg(feature = "signing")]
+            let frame = inbox.next_frame::<M>(version, self.signing_data.as_ref(), &self.stats);
+            if let Some(frame) = frame {
+                return Ok(frame);
+            }
+            let (n, address) = self.socket.recv_from(&mut buf).await?;
+
+            let expired = {
+                let mut peers = self.peers.lock().unwrap();
+                let expired = peers.expire(self.peer_timeout);
+                peers.seen(address);
+                expired
+            };
+            for expired in expired {
+                inbox.handle(PeerEvent::Disconnected(expired));
+            }
+            inbox.handle(PeerEvent::Data(address, buf[..n].to_vec()));
+        }
+    }
+
+    /// Receive a MAVLink message together with the address of the peer that sent it
+    ///
+    /// Yields until a valid message is received from any peer.
+    ///
+    /// # Errors
+    ///
+    /// See [`AsyncMavConnection::recv`]
+    pub async fn recv_from<M: Message + Sync + Send>(
+        &self,
+    ) -> Result<(SocketAddr, MavHeader, M), MessageReadError> {
+        let (address, raw) = self.recv_raw_from::<M>().await?;
+        let (header, msg) = parse_raw(&raw)?;
+        Ok((address, header, msg))
+    }
+
+    /// Send a MAVLink message to a single address
+    ///
+    /// The address does not need to be a known peer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that occurred while sending the message.
+    pub async fn send_to<M: Message>(
+        &self,
+        peer: SocketAddr,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, MessageWriteError> {
+        let frame = self.serialize(header, data)?;
+        Ok(self.socket.send_to(&frame, peer).await?)
+    }
+
+    fn serialize<M: Message>(
+        &self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<Vec<u8>, MessageWriteError> {
+        let mut peers = self.peers.lock().unwrap();
+        let header = MavHeader {
+            sequence: peers.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+        peers.sequence = peers.sequence.wrapping_add(1);
+
+        let mut frame = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut frame, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut frame,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        Ok(frame)
+    }
+
+    async fn broadcast(&self, frame: &[u8]) -> usize {
+        let mut sent = 0;
+        for address in self.peers() {
+            let mut result = self.socket.send_to(frame, address).await;
+            if is_refused(&result) {
+                // the error may be caused by an earlier datagram to another peer, in which case
+                // this datagram has not been sent yet
+                result = self.socket.send_to(frame, address).await;
+                if is_refused(&result) {
+                    self.peers.lock().unwrap().forget(address);
+                }
+            }
+            if let Ok(n) = result {
+                sent = n;
+            }
+        }
+        sent
+    }
+}
+
+#[async_trait]
+impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncUdpServerConnection {
+    async fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_from().await.map(|(_, header, msg)| (header, msg))
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.recv_raw_from::<M>().await.map(|(_, raw)| raw)
+    }
+
+    async fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        match <Self as AsyncMavConnection<M>>::recv(self).now_or_never() {
+            Some(result) => result,
+            None => Err(MessageReadError::Io(io::ErrorKind::WouldBlock.into())),
+        }
+    }
+
+    async fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let frame = self.serialize(header, data)?;
+        Ok(self.broadcast(&frame).await)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
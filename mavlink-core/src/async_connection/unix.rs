@@ -0,0 +1,191 @@
+//! Async Unix domain socket MAVLink connection
+
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use std::io;
+use std::path::Path;
+
+use super::unix_datagram::AsyncUnixDatagramConnection;
+use super::{AsyncConnectable, AsyncMavConnection};
+use crate::async_peek_reader::AsyncPeekReader;
+use crate::connection::unix::config::{UnixConfig, UnixMode};
+use crate::connection::unix::remove_stale_socket;
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+use async_trait::async_trait;
+use core::ops::DerefMut;
+use futures::{lock::Mutex, FutureExt};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+
+#[cfg(not(feature = "signing"))]
+use crate::{
+    read_versioned_msg_async, read_versioned_raw_message_async, write_versioned_msg_async,
+};
+#[cfg(feature = "signing")]
+use crate::{
+    read_versioned_msg_async_signed, read_versioned_raw_message_async_signed,
+    write_versioned_msg_async_signed, SigningConfig, SigningData,
+};
+
+async fn accept_stream(path: &Path) -> io::Result<UnixStream> {
+    remove_stale_socket(path, false)?;
+    let listener = UnixListener::bind(path)?;
+
+    // only a single client is accepted, just like `tcpin`
+    let (socket, _) = listener.accept().await?;
+    Ok(socket)
+}
+
+pub struct AsyncUnixConnection {
+    reader: Mutex<AsyncPeekReader<OwnedReadHalf>>,
+    writer: Mutex<UnixWrite>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    stats: SharedLinkStats,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+struct UnixWrite {
+    socket: OwnedWriteHalf,
+    sequence: u8,
+}
+
+impl AsyncUnixConnection {
+    fn new(socket: UnixStream) -> Self {
+        let (reader, writer) = socket.into_split();
+        Self {
+            reader: Mutex::new(AsyncPeekReader::new(reader)),
+            writer: Mutex::new(UnixWrite {
+                socket: writer,
+                sequence: 0,
+            }),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            stats: SharedLinkStats::default(),
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncUnixConnection {
+    async fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let mut reader = self.reader.lock().await;
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg_async(reader.deref_mut(), version).await;
+        #[cfg(feature = "signing")]
+        let result = read_versioned_msg_async_signed(
+            reader.deref_mut(),
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        self.stats.record(reader.read_stats(), &result);
+        result
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let mut reader = self.reader.lock().await;
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_raw_message_async::<M, _>(reader.deref_mut(), version).await;
+        #[cfg(feature = "signing")]
+        let result = read_versioned_raw_message_async_signed::<M, _>(
+            reader.deref_mut(),
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        self.stats.record(reader.read_stats(), &result);
+        result
+    }
+
+    async fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        match self.recv().now_or_never() {
+            Some(result) => result,
+            None => Err(crate::error::MessageReadError::Io(
+                io::ErrorKind::WouldBlock.into(),
+            )),
+        }
+    }
+
+    async fn send(
+        &self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let mut lock = self.writer.lock().await;
+
+        let header = MavHeader {
+            sequence: lock.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        lock.sequence = lock.sequence.wrapping_add(1);
+        #[cfg(not(feature = "signing"))]
+        let result =
+            write_versioned_msg_async(&mut lock.socket, self.protocol_version, header, data).await;
+        #[cfg(feature = "signing")]
+        let result = write_versioned_msg_async_signed(
+            &mut lock.socket,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        result
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
+
+#[async_trait]
+impl AsyncConnectable for UnixConfig {
+    async fn connect_async<M>(&self) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>>
+    where
+        M: Message + Sync + Send,
+    {
+        let conn: Box<dyn AsyncMavConnection<M> + Sync + Send> = match self.mode {
+            UnixMode::UnixIn => Box::new(AsyncUnixConnection::new(
+                accept_stream(&self.address).await?,
+            )),
+            UnixMode::UnixOut => Box::new(AsyncUnixConnection::new(
+                UnixStream::connect(&self.address).await?,
+            )),
+            UnixMode::UnixGram => Box::new(AsyncUnixDatagramConnection::bind(
+                &self.address,
+                self.peer.clone(),
+            )?),
+        };
+
+        Ok(conn)
+    }
+}
@@ -0,0 +1,224 @@
+//! Async Unix datagram socket MAVLink connection
+
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use core::{ops::DerefMut, task::Poll};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::{collections::VecDeque, sync::Arc};
+
+use futures::{lock::Mutex, FutureExt};
+use tokio::{
+    io::{AsyncRead, ReadBuf},
+    net::UnixDatagram,
+};
+
+use super::AsyncMavConnection;
+use crate::connection::unix::remove_stale_socket;
+use crate::MAVLinkMessageRaw;
+use crate::{async_peek_reader::AsyncPeekReader, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_versioned_msg_async, read_versioned_raw_message_async, write_versioned_msg};
+#[cfg(feature = "signing")]
+use crate::{
+    read_versioned_msg_async_signed, read_versioned_raw_message_async_signed,
+    write_versioned_msg_signed, SigningConfig, SigningData,
+};
+
+/// Largest datagram that is received as a whole, MAVLink frames are much smaller
+const DATAGRAM_SIZE: usize = 4096;
+
+struct UnixDatagramRead {
+    socket: Arc<UnixDatagram>,
+    buffer: VecDeque<u8>,
+    last_recv_address: Option<PathBuf>,
+}
+
+impl AsyncRead for UnixDatagramRead {
+    fn poll_read(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if self.buffer.is_empty() {
+            let mut read_buffer = [0u8; DATAGRAM_SIZE];
+            let mut read_buffer = ReadBuf::new(&mut read_buffer);
+
+            match self.socket.poll_recv_from(cx, &mut read_buffer) {
+                Poll::Ready(Ok(address)) => {
+                    let n_buffer = read_buffer.filled().len();
+
+                    let n = (&read_buffer.filled()[0..n_buffer]).read(buf.initialize_unfilled())?;
+                    buf.advance(n);
+
+                    self.buffer.extend(&read_buffer.filled()[n..n_buffer]);
+                    // unbound senders can not be replied to
+                    if let Some(path) = address.as_pathname() {
+                        self.last_recv_address = Some(path.to_owned());
+                    }
+                    Poll::Ready(Ok(()))
+                }
+                Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+                Poll::Pending => Poll::Pending,
+            }
+        } else {
+            let n = self.buffer.read(buf.initialize_unfilled())?;
+            buf.advance(n);
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+struct UnixDatagramWrite {
+    socket: Arc<UnixDatagram>,
+    dest: Option<PathBuf>,
+    sequence: u8,
+}
+
+pub struct AsyncUnixDatagramConnection {
+    reader: Mutex<AsyncPeekReader<UnixDatagramRead>>,
+    writer: Mutex<UnixDatagramWrite>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    stats: SharedLinkStats,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl AsyncUnixDatagramConnection {
+    pub(super) fn bind(path: &Path, dest: Option<PathBuf>) -> io::Result<Self> {
+        remove_stale_socket(path, true)?;
+        let socket = Arc::new(UnixDatagram::bind(path)?);
+        Ok(Self {
+            reader: Mutex::new(AsyncPeekReader::new(UnixDatagramRead {
+                socket: socket.clone(),
+                buffer: VecDeque::new(),
+                last_recv_address: None,
+            })),
+            writer: Mutex::new(UnixDatagramWrite {
+                socket,
+                dest,
+                sequence: 0,
+            }),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            stats: SharedLinkStats::default(),
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        })
+    }
+
+    /// Reply to the socket the last datagram was received from
+    async fn update_dest(&self, reader: &mut AsyncPeekReader<UnixDatagramRead>) {
+        if let Some(addr) = &reader.reader_ref().last_recv_address {
+            self.writer.lock().await.dest = Some(addr.clone());
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncUnixDatagramConnection {
+    async fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        let mut reader = self.reader.lock().await;
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg_async(reader.deref_mut(), version).await;
+        #[cfg(feature = "signing")]
+        let result = read_versioned_msg_async_signed(
+            reader.deref_mut(),
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        self.stats.record(reader.read_stats(), &result);
+        self.update_dest(&mut reader).await;
+        result
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
+        let mut reader = self.reader.lock().await;
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_raw_message_async::<M, _>(reader.deref_mut(), version).await;
+        #[cfg(feature = "signing")]
+        let result = read_versioned_raw_message_async_signed::<M, _>(
+            reader.deref_mut(),
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        self.stats.record(reader.read_stats(), &result);
+        self.update_dest(&mut reader).await;
+        result
+    }
+
+    async fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
+        match self.recv().now_or_never() {
+            Some(result) => result,
+            None => Err(crate::error::MessageReadError::Io(
+                io::ErrorKind::WouldBlock.into(),
+            )),
+        }
+    }
+
+    async fn send(
+        &self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, crate::error::MessageWriteError> {
+        let mut guard = self.writer.lock().await;
+        let state = &mut *guard;
+
+        let header = MavHeader {
+            sequence: state.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        state.sequence = state.sequence.wrapping_add(1);
+
+        let len = if let Some(addr) = &state.dest {
+            let mut buf = Vec::new();
+            #[cfg(not(feature = "signing"))]
+            write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
+            #[cfg(feature = "signing")]
+            write_versioned_msg_signed(
+                &mut buf,
+                self.protocol_version,
+                header,
+                data,
+                self.signing_data.as_ref(),
+            )?;
+            state.socket.send_to(&buf, addr).await?
+        } else {
+            0
+        };
+
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
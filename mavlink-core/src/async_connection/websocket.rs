@@ -0,0 +1,228 @@
+//! Async WebSocket MAVLink connection
+
+use core::ops::DerefMut;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+use async_trait::async_trait;
+use futures::stream::{SplitSink, SplitStream};
+use futures::{lock::Mutex, FutureExt, SinkExt, StreamExt};
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message as WsMessage};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use super::{AsyncConnectable, AsyncMavConnection};
+use crate::async_peek_reader::AsyncPeekReader;
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_versioned_msg_async, read_versioned_raw_message_async, write_versioned_msg};
+#[cfg(feature = "signing")]
+use crate::{
+    read_versioned_msg_async_signed, read_versioned_raw_message_async_signed,
+    write_versioned_msg_signed, SigningConfig, SigningData,
+};
+
+pub mod config;
+pub mod server;
+
+use config::{WebSocketConfig, WebSocketMode};
+use server::AsyncWebSocketServerConnection;
+
+type ClientStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Convert a WebSocket error into the corresponding I/O error
+pub(super) fn io_error(error: WsError) -> io::Error {
+    match error {
+        WsError::Io(e) => e,
+        WsError::Url(e) => io::Error::new(io::ErrorKind::AddrNotAvailable, e),
+        WsError::ConnectionClosed | WsError::AlreadyClosed => io::ErrorKind::BrokenPipe.into(),
+        e => io::Error::other(e),
+    }
+}
+
+struct WebSocketRead {
+    stream: SplitStream<ClientStream>,
+    buffer: VecDeque<u8>,
+}
+
+impl AsyncRead for WebSocketRead {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        while self.buffer.is_empty() {
+            match ready!(self.stream.poll_next_unpin(cx)) {
+                Some(Ok(WsMessage::Binary(data))) => self.buffer.extend(data.iter()),
+                // the end of the stream is signalled by not reading any data
+                Some(Ok(WsMessage::Close(_))) | None => return Poll::Ready(Ok(())),
+                // only binary messages carry MAVLink frames, pings are answered by the stream
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Poll::Ready(Err(io_error(e))),
+            }
+        }
+        let n = self.buffer.read(buf.initialize_unfilled())?;
+        buf.advance(n);
+        Poll::Ready(Ok(()))
+    }
+}
+
+struct WebSocketWrite {
+    sink: SplitSink<ClientStream, WsMessage>,
+    sequence: u8,
+}
+
+/// Async WebSocket client connection, every frame is sent in a binary message
+pub struct AsyncWebSocketConnection {
+    reader: Mutex<AsyncPeekReader<WebSocketRead>>,
+    writer: Mutex<WebSocketWrite>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    stats: SharedLinkStats,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl AsyncWebSocketConnection {
+    async fn connect(url: &str) -> io::Result<Self> {
+        let (stream, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .map_err(io_error)?;
+        let (sink, stream) = stream.split();
+        Ok(Self {
+            reader: Mutex::new(AsyncPeekReader::new(WebSocketRead {
+                stream,
+                buffer: VecDeque::new(),
+            })),
+            writer: Mutex::new(WebSocketWrite { sink, sequence: 0 }),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            stats: SharedLinkStats::default(),
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        })
+    }
+}
+
+#[async_trait]
+impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncWebSocketConnection {
+    async fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let mut reader = self.reader.lock().await;
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg_async(reader.deref_mut(), version).await;
+        #[cfg(feature = "signing")]
+        let result = read_versioned_msg_async_signed(
+            reader.deref_mut(),
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        self.stats.record(reader.read_stats(), &result);
+        result
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let mut reader = self.reader.lock().await;
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_raw_message_async::<M, _>(reader.deref_mut(), version).await;
+        #[cfg(feature = "signing")]
+        let result = read_versioned_raw_message_async_signed::<M, _>(
+            reader.deref_mut(),
+            version,
+            self.signing_data.as_ref(),
+        )
+        .await;
+        self.stats.record(reader.read_stats(), &result);
+        result
+    }
+
+    async fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        match <Self as AsyncMavConnection<M>>::recv(self).now_or_never() {
+            Some(result) => result,
+            None => Err(MessageReadError::Io(io::ErrorKind::WouldBlock.into())),
+        }
+    }
+
+    async fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let mut guard = self.writer.lock().await;
+        let state = &mut *guard;
+
+        let header = MavHeader {
+            sequence: state.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        state.sequence = state.sequence.wrapping_add(1);
+
+        let mut buf = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut buf,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        let len = buf.len();
+        state
+            .sink
+            .send(WsMessage::binary(buf))
+            .await
+            .map_err(io_error)?;
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
+
+#[async_trait]
+impl AsyncConnectable for WebSocketConfig {
+    async fn connect_async<M>(&self) -> io::Result<Box<dyn AsyncMavConnection<M> + Sync + Send>>
+    where
+        M: Message + Sync + Send,
+    {
+        let conn: Box<dyn AsyncMavConnection<M> + Sync + Send> = match self.mode {
+            WebSocketMode::WsClient => {
+                Box::new(AsyncWebSocketConnection::connect(&self.address).await?)
+            }
+            WebSocketMode::WsServer => {
+                Box::new(AsyncWebSocketServerConnection::bind(self.address.as_str()).await?)
+            }
+        };
+        Ok(conn)
+    }
+}
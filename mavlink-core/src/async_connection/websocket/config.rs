@@ -0,0 +1,52 @@
+use core::fmt::Display;
+
+/// Type of WebSocket connection
+#[derive(Debug, Clone, Copy)]
+pub enum WebSocketMode {
+    /// Connection will connect to the provided `ws://` or `wss://` URL
+    WsClient,
+    /// Connection will open a WebSocket server that binds to the provided address and keeps
+    /// accepting clients
+    WsServer,
+}
+
+/// MAVLink connection address for a WebSocket client or server
+///
+/// Every MAVLink frame is carried in a binary WebSocket message. WebSocket connections are only
+/// available as async connections. `wss://` URLs require the `websocket-tls` feature, servers
+/// only accept plain `ws://` clients and are meant to be put behind a TLS terminating proxy if
+/// needed.
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::{AsyncConnectable, WebSocketConfig, WebSocketMode};
+///
+/// let config = WebSocketConfig::new("0.0.0.0:5761".to_owned(), WebSocketMode::WsServer);
+/// config.connect_async::<mavlink::common::MavMessage>().await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebSocketConfig {
+    pub(crate) address: String,
+    pub(crate) mode: WebSocketMode,
+}
+
+impl WebSocketConfig {
+    /// Creates a WebSocket connection address.
+    ///
+    /// The address of a [`WebSocketMode::WsClient`] is the URL of the server, the address of a
+    /// [`WebSocketMode::WsServer`] is the socket address to bind to.
+    pub fn new(address: String, mode: WebSocketMode) -> Self {
+        Self { address, mode }
+    }
+}
+
+impl Display for WebSocketConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.mode {
+            // the URL already starts with its scheme
+            WebSocketMode::WsClient => write!(f, "{}", self.address),
+            WebSocketMode::WsServer => write!(f, "wsserver:{}", self.address),
+        }
+    }
+}
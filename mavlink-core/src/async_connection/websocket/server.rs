@@ -0,0 +1,296 @@
+//! Async WebSocket MAVLink server accepting any number of clients
+
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::stream::{SplitSink, SplitStream};
+use futures::{lock::Mutex, FutureExt, SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::async_connection::AsyncMavConnection;
+use crate::connection::peer_inbox::{parse_raw, PeerEvent, PeerInbox};
+use crate::connection::{accept_retry_delay, CLIENT_WRITE_TIMEOUT};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::write_versioned_msg;
+#[cfg(feature = "signing")]
+use crate::{write_versioned_msg_signed, SigningConfig, SigningData};
+
+type ServerStream = WebSocketStream<TcpStream>;
+
+struct Clients {
+    sinks: HashMap<SocketAddr, SplitSink<ServerStream, WsMessage>>,
+    sequence: u8,
+}
+
+/// Send a message to a client, failing if the client does not take it within
+/// [`CLIENT_WRITE_TIMEOUT`]
+async fn send_message(
+    sink: &mut SplitSink<ServerStream, WsMessage>,
+    message: WsMessage,
+) -> io::Result<()> {
+    match tokio::time::timeout(CLIENT_WRITE_TIMEOUT, sink.send(message)).await {
+        Ok(result) => result.map_err(io::Error::other),
+        Err(_) => Err(io::ErrorKind::TimedOut.into()),
+    }
+}
+
+impl Clients {
+    async fn broadcast(&mut self, frame: Vec<u8>) -> usize {
+        let len = frame.len();
+        // the payload is reference counted, so every client shares the same buffer
+        let message = WsMessage::binary(frame);
+        let mut failed = Vec::new();
+        for (address, sink) in &mut self.sinks {
+            if send_message(sink, message.clone()).await.is_err() {
+                failed.push(*address);
+            }
+        }
+        for address in &failed {
+            self.sinks.remove(address);
+        }
+        if self.sinks.is_empty() {
+            0
+        } else {
+            len
+        }
+    }
+}
+
+/// Async WebSocket server connection that keeps accepting clients
+///
+/// Messages are sent to all clients that completed the WebSocket handshake and received from any
+/// of them. Clients are dropped once a message to them fails, including messages that are not
+/// taken within half a second because the client stopped reading. The clients are served by tasks spawned on the tokio runtime the server was created
+/// on, they are aborted when the connection is dropped.
+pub struct AsyncWebSocketServerConnection {
+    clients: Arc<Mutex<Clients>>,
+    inbox: Mutex<(PeerInbox, UnboundedReceiver<PeerEvent>)>,
+    stats: SharedLinkStats,
+    accept_task: JoinHandle<()>,
+    local_address: SocketAddr,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl AsyncWebSocketServerConnection {
+    /// Bind a WebSocket server to the given address and start accepting clients
+    ///
+    /// # Errors
+    ///
+    /// When the address could not be resolved or bound a corresponding [`io::Error`] is returned
+    pub async fn bind<T: ToSocketAddrs>(address: T) -> io::Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        let local_address = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Clients {
+            sinks: HashMap::new(),
+            sequence: 0,
+        }));
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let accept_task = tokio::spawn(accept_clients(listener, clients.clone(), sender));
+
+        Ok(Self {
+            clients,
+            inbox: Mutex::new((PeerInbox::default(), receiver)),
+            stats: SharedLinkStats::default(),
+            accept_task,
+            local_address,
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_address
+    }
+
+    /// Addresses of the currently connected clients
+    pub async fn peers(&self) -> Vec<SocketAddr> {
+        self.clients.lock().await.sinks.keys().copied().collect()
+    }
+
+    async fn recv_raw_from<M: Message + Sync + Send>(
+        &self,
+    ) -> Result<(SocketAddr, MAVLinkMessageRaw), MessageReadError> {
+        let version = ReadVersion::from_async_conn_cfg::<_, M>(self);
+        let mut guard = self.inbox.lock().await;
+        let (inbox, events) = &mut *guard;
+        loop {
+            #[cfg(not(feature = "signing"))]
+            let frame = inbox.next_frame::<M>(version, &self.stats);
+            #[cfg(feature = "signing")]
+            let frame = inbox.next_frame::<M>(version, self.signing_data.as_ref(), &self.stats);
+            if let Some(frame) = frame {
+                return Ok(frame);
+            }
+            let event = events
+                .recv()
+                .await
+                .ok_or(io::Error::from(io::ErrorKind::NotConnected))?;
+            inbox.handle(event);
+        }
+    }
+
+    fn serialize<M: Message>(
+        &self,
+        clients: &mut Clients,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<Vec<u8>, MessageWriteError> {
+        let header = MavHeader {
+            sequence: clients.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+        clients.sequence = clients.sequence.wrapping_add(1);
+
+        let mut frame = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut frame, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut frame,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        Ok(frame)
+    }
+}
+
+impl Drop for AsyncWebSocketServerConnection {
+    fn drop(&mut self) {
+        // the client tasks are owned by the accept task and aborted along with it
+        self.accept_task.abort();
+    }
+}
+
+async fn accept_clients(
+    listener: TcpListener,
+    clients: Arc<Mutex<Clients>>,
+    events: UnboundedSender<PeerEvent>,
+) {
+    let mut tasks = JoinSet::new();
+    loop {
+        let (stream, address) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                tokio::time::sleep(accept_retry_delay(&e)).await;
+                continue;
+            }
+        };
+        // clean up the tasks of disconnected clients
+        while let Some(Some(_)) = tasks.join_next().now_or_never() {}
+
+        tasks.spawn(serve_client(
+            stream,
+            address,
+            clients.clone(),
+            events.clone(),
+        ));
+    }
+}
+
+async fn serve_client(
+    stream: TcpStream,
+    address: SocketAddr,
+    clients: Arc<Mutex<Clients>>,
+    events: UnboundedSender<PeerEvent>,
+) {
+    // the handshake is done by the client task, so a slow client does not hold up the others
+    let Ok(stream) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (sink, stream) = stream.split();
+    clients.lock().await.sinks.insert(address, sink);
+    forward_client(stream, address, &events).await;
+    clients.lock().await.sinks.remove(&address);
+    events.send(PeerEvent::Disconnected(address)).ok();
+}
+
+async fn forward_client(
+    mut stream: SplitStream<ServerStream>,
+    address: SocketAddr,
+    events: &UnboundedSender<PeerEvent>,
+) {
+    while let Some(Ok(message)) = stream.next().await {
+        match message {
+            WsMessage::Binary(data)
+                if events
+                    .send(PeerEvent::Data(address, data.to_vec()))
+                    .is_err() =>
+            {
+                break
+            }
+            WsMessage::Close(_) => break,
+            // pings are answered by the stream, text messages do not carry MAVLink frames
+            _ => {}
+        }
+    }
+}
+
+#[async_trait]
+impl<M: Message + Sync + Send> AsyncMavConnection<M> for AsyncWebSocketServerConnection {
+    async fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let (_, raw) = self.recv_raw_from::<M>().await?;
+        parse_raw(&raw)
+    }
+
+    async fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.recv_raw_from::<M>().await.map(|(_, raw)| raw)
+    }
+
+    async fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        match <Self as AsyncMavConnection<M>>::recv(self).now_or_never() {
+            Some(result) => result,
+            None => Err(MessageReadError::Io(io::ErrorKind::WouldBlock.into())),
+        }
+    }
+
+    async fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let mut clients = self.clients.lock().await;
+        let frame = self.serialize(&mut clients, header, data)?;
+        Ok(clients.broadcast(frame).await)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
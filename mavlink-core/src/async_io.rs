@@ -0,0 +1,224 @@
+//! This module abstracts over the async I/O traits of the different async runtimes.
+//!
+//! The `_async` read and write functions and the [`AsyncPeekReader`] are not tied to a single
+//! runtime, they work with any type implementing [`AsyncByteRead`] or [`AsyncByteWrite`]:
+//!
+//! - With the `tokio-1` feature these traits are implemented for every `tokio::io::AsyncRead`
+//!   and `tokio::io::AsyncWrite`, so tokio types can be used directly.
+//! - With the `futures-io` feature any [`futures::io::AsyncRead`] and [`futures::io::AsyncWrite`],
+//!   such as the sockets of `smol` or `async-std`, can be used by wrapping it in a [`FuturesIo`].
+//!
+//! ```ignore
+//! let stream = smol::net::TcpStream::connect("127.0.0.1:5760").await?;
+//! let mut reader = AsyncPeekReader::new(FuturesIo::new(stream));
+//! let (header, msg) = read_v2_msg_async::<MavMessage, _>(&mut reader).await?;
+//! ```
+//!
+//! Only these functions and [`AsyncStreamConnection`] are runtime-agnostic. The connections
+//! opened by address with `connect_async`, including TCP, UDP and the servers, are built on tokio
+//! and require the `tokio-1` feature. Without it, open a stream with the runtime in use and wrap it
+//! with [`AsyncStreamConnection::from_futures`]. There is no connection for datagram sockets
+//! without tokio, so UDP is not available with `futures-io` alone.
+//!
+//! [`AsyncPeekReader`]: crate::async_peek_reader::AsyncPeekReader
+//! [`AsyncStreamConnection`]: crate::AsyncStreamConnection
+//! [`AsyncStreamConnection::from_futures`]: crate::AsyncStreamConnection::from_futures
+
+use core::future::poll_fn;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::io;
+
+/// Source of bytes that can be read asynchronously
+///
+/// This is a minimal version of the `AsyncRead` traits of the async runtimes.
+pub trait AsyncByteRead {
+    /// Attempt to read bytes into `buf`, returning the number of bytes read
+    ///
+    /// Reading 0 bytes into a non-empty buffer signals EOF.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying reader.
+    fn poll_read_bytes(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>>;
+}
+
+/// Sink for bytes that can be written asynchronously
+///
+/// This is a minimal version of the `AsyncWrite` traits of the async runtimes.
+pub trait AsyncByteWrite {
+    /// Attempt to write bytes from `buf`, returning the number of bytes written
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying writer.
+    fn poll_write_bytes(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>>;
+
+    /// Attempt to flush all buffered data to its destination
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying writer.
+    fn poll_flush_bytes(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+impl AsyncByteRead for Box<dyn AsyncByteRead + Send + Unpin> {
+    fn poll_read_bytes(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut **self).poll_read_bytes(cx, buf)
+    }
+}
+
+impl AsyncByteWrite for Box<dyn AsyncByteWrite + Send + Unpin> {
+    fn poll_write_bytes(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut **self).poll_write_bytes(cx, buf)
+    }
+
+    fn poll_flush_bytes(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut **self).poll_flush_bytes(cx)
+    }
+}
+
+#[cfg(feature = "tokio-1")]
+mod tokio_io {
+    use super::{AsyncByteRead, AsyncByteWrite};
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+    use std::io;
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    impl<R: AsyncRead + ?Sized> AsyncByteRead for R {
+        fn poll_read_bytes(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let mut buf = ReadBuf::new(buf);
+            match self.poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(())) => Poll::Ready(Ok(buf.filled().len())),
+                Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+                Poll::Pending => Poll::Pending,
+            }
+        }
+    }
+
+    impl<W: AsyncWrite + ?Sized> AsyncByteWrite for W {
+        fn poll_write_bytes(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.poll_write(cx, buf)
+        }
+
+        fn poll_flush_bytes(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.poll_flush(cx)
+        }
+    }
+}
+
+/// Adapter using a [`futures::io::AsyncRead`] or [`futures::io::AsyncWrite`] as
+/// [`AsyncByteRead`] or [`AsyncByteWrite`]
+#[cfg(feature = "futures-io")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FuturesIo<T>(T);
+
+#[cfg(feature = "futures-io")]
+impl<T> FuturesIo<T> {
+    /// Wrap a `futures` reader and/or writer
+    pub const fn new(inner: T) -> Self {
+        Self(inner)
+    }
+
+    /// Returns a reference to the wrapped reader or writer
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns a mutable reference to the wrapped reader or writer
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    /// Unwrap the wrapped reader or writer
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<R: futures::io::AsyncRead + Unpin> AsyncByteRead for FuturesIo<R> {
+    fn poll_read_bytes(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<W: futures::io::AsyncWrite + Unpin> AsyncByteWrite for FuturesIo<W> {
+    fn poll_write_bytes(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush_bytes(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+}
+
+/// Read exactly `buf.len()` bytes, failing with [`io::ErrorKind::UnexpectedEof`] on EOF
+pub(crate) async fn read_exact<R: AsyncByteRead + Unpin + ?Sized>(
+    reader: &mut R,
+    mut buf: &mut [u8],
+) -> io::Result<()> {
+    while !buf.is_empty() {
+        let n = poll_fn(|cx| Pin::new(&mut *reader).poll_read_bytes(cx, buf)).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf = &mut core::mem::take(&mut buf)[n..];
+    }
+    Ok(())
+}
+
+/// Write all of `buf`, retrying on [`io::ErrorKind::Interrupted`]
+pub(crate) async fn write_all<W: AsyncByteWrite + Unpin + ?Sized>(
+    writer: &mut W,
+    mut buf: &[u8],
+) -> io::Result<()> {
+    while !buf.is_empty() {
+        match poll_fn(|cx| Pin::new(&mut *writer).poll_write_bytes(cx, buf)).await {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => buf = &buf[n..],
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Flush the writer
+pub(crate) async fn flush<W: AsyncByteWrite + Unpin + ?Sized>(writer: &mut W) -> io::Result<()> {
+    poll_fn(|cx| Pin::new(&mut *writer).poll_flush_bytes(cx)).await
+}
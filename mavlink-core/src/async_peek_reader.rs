@@ -3,26 +3,26 @@
 //! The purpose of the buffered/peekable reader is to allow for backtracking parsers.
 //!
 //! This is the async version of [`crate::peek_reader::PeekReader`].
-//! A reader implementing the tokio library's [`tokio::io::AsyncBufRead`]/[`tokio::io::AsyncBufReadExt`] traits seems like a good fit, but
+//! A reader implementing the `AsyncBufRead` traits of the async runtimes seems like a good fit, but
 //! it does not allow for peeking a specific number of bytes, so it provides no way to request
 //! more data from the underlying reader without consuming the existing data.
 //!
-//! This API still tries to adhere to the [`tokio::io::AsyncBufRead`]'s trait philosophy.
+//! This API still tries to adhere to the `AsyncBufRead` trait philosophy.
 //!
-//! The main type [`AsyncPeekReader`] does not implement [`tokio::io::AsyncBufReadExt`] itself, as there is no added benefit
+//! The main type [`AsyncPeekReader`] does not implement `AsyncBufRead` itself, as there is no added benefit
 //! in doing so.
 //!
 
 #[cfg(doc)]
 use std::io::ErrorKind;
 
-use tokio::io::AsyncReadExt;
-
+use crate::async_io::{self, AsyncByteRead};
 use crate::error::MessageReadError;
+use crate::link_stats::ReadStats;
 
 /// A buffered/peekable reader
 ///
-/// This reader wraps a type implementing [`tokio::io::AsyncRead`] and adds buffering via an internal buffer.
+/// This reader wraps a type implementing [`AsyncByteRead`] and adds buffering via an internal buffer.
 ///
 /// It allows the user to `peek` a specified number of bytes (without consuming them),
 /// to `read` bytes (consuming them), or to `consume` them after `peek`ing.
@@ -39,30 +39,33 @@ pub struct AsyncPeekReader<R, const BUFFER_SIZE: usize = 280> {
     top: usize,
     // The wrapped reader.
     reader: R,
+    // Statistics of the data read, updated by the MAVLink read functions.
+    pub(crate) stats: ReadStats,
 }
 
-impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFER_SIZE> {
-    /// Instantiates a new [`AsyncPeekReader`], wrapping the provided [`tokio::io::AsyncReadExt`] and using the default chunk size
+impl<R: AsyncByteRead + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFER_SIZE> {
+    /// Instantiates a new [`AsyncPeekReader`], wrapping the provided [`AsyncByteRead`] and using the default chunk size
     pub fn new(reader: R) -> Self {
         Self {
             buffer: [0; BUFFER_SIZE],
             cursor: 0,
             top: 0,
             reader,
+            stats: ReadStats::default(),
         }
     }
 
     /// Peeks an exact amount of bytes from the internal buffer
     ///
     /// If the internal buffer does not contain enough data, this function will read
-    /// from the underlying [`tokio::io::AsyncReadExt`] until it does, an error occurs or no more data can be read (EOF).
+    /// from the underlying [`AsyncByteRead`] until it does, an error occurs or no more data can be read (EOF).
     ///
     /// This function does not consume data from the buffer, so subsequent calls to `peek` or `read` functions
     /// will still return the peeked data.
     ///
     /// # Errors
     ///
-    /// - If any error occurs while reading from the underlying [`tokio::io::AsyncReadExt`] it is returned
+    /// - If any error occurs while reading from the underlying [`AsyncByteRead`] it is returned
     /// - If an EOF occurs and the specified amount could not be read, this function will return an [`ErrorKind::UnexpectedEof`].
     ///
     /// # Panics
@@ -75,13 +78,13 @@ impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFE
     /// Reads a specified amount of bytes from the internal buffer
     ///
     /// If the internal buffer does not contain enough data, this function will read
-    /// from the underlying [`tokio::io::AsyncReadExt`] until it does, an error occurs or no more data can be read (EOF).
+    /// from the underlying [`AsyncByteRead`] until it does, an error occurs or no more data can be read (EOF).
     ///
     /// This function consumes the data from the buffer, unless an error occurs, in which case no data is consumed.
     ///
     /// # Errors
     ///
-    /// - If any error occurs while reading from the underlying [`tokio::io::AsyncReadExt`] it is returned
+    /// - If any error occurs while reading from the underlying [`AsyncByteRead`] it is returned
     /// - If an EOF occurs and the specified amount could not be read, this function will return an [`ErrorKind::UnexpectedEof`].
     ///
     /// # Panics
@@ -94,13 +97,13 @@ impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFE
     /// Reads a byte from the internal buffer
     ///
     /// If the internal buffer does not contain enough data, this function will read
-    /// from the underlying [`tokio::io::AsyncReadExt`] until it does, an error occurs or no more data can be read (EOF).
+    /// from the underlying [`AsyncByteRead`] until it does, an error occurs or no more data can be read (EOF).
     ///
     /// This function consumes the data from the buffer, unless an error occurs, in which case no data is consumed.
     ///
     /// # Errors
     ///
-    /// - If any error occurs while reading from the underlying [`tokio::io::AsyncReadExt`] it is returned
+    /// - If any error occurs while reading from the underlying [`AsyncByteRead`] it is returned
     /// - If an EOF occurs before a byte could be read, this function will return an [`ErrorKind::UnexpectedEof`].
     ///
     /// # Panics
@@ -121,20 +124,25 @@ impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFE
         amount
     }
 
-    /// Returns an immutable reference to the underlying [`tokio::io::AsyncRead`]
+    /// Returns an immutable reference to the underlying [`AsyncByteRead`]
     ///
     /// Reading directly from the underlying reader will cause data loss
     pub fn reader_ref(&mut self) -> &R {
         &self.reader
     }
 
-    /// Returns a mutable reference to the underlying [`tokio::io::AsyncRead`]
+    /// Returns a mutable reference to the underlying [`AsyncByteRead`]
     ///
     /// Reading directly from the underlying reader will cause data loss
     pub fn reader_mut(&mut self) -> &mut R {
         &mut self.reader
     }
 
+    /// Returns the statistics of the data processed by the MAVLink `read_*` functions using this reader
+    pub fn read_stats(&self) -> ReadStats {
+        self.stats
+    }
+
     /// Internal function to fetch data from the internal buffer and/or reader
     async fn fetch(&mut self, amount: usize, consume: bool) -> Result<&[u8], MessageReadError> {
         assert!(BUFFER_SIZE >= amount);
@@ -155,9 +163,10 @@ impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFE
 
             // Read directly into the internal buffer.
             let dest = &mut self.buffer[self.top..self.top + bytes_needed];
-            self.reader.read_exact(dest).await?;
+            async_io::read_exact(&mut self.reader, dest).await?;
 
             self.top += bytes_needed;
+            self.stats.bytes_received += bytes_needed as u64;
         }
 
         let result = &self.buffer[self.cursor..self.cursor + amount];
@@ -168,7 +177,7 @@ impl<R: AsyncReadExt + Unpin, const BUFFER_SIZE: usize> AsyncPeekReader<R, BUFFE
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "tokio-1"))]
 mod tests {
     use super::*;
 
@@ -0,0 +1,119 @@
+//! This module implements a [`tokio_util::codec`] codec for MAVLink frames.
+//!
+//! [`MavCodec`] turns any `AsyncRead`/`AsyncWrite` into a [`Stream`](futures::Stream) and
+//! [`Sink`](futures::Sink) of [`MavFrame`]s with [`FramedRead`], [`FramedWrite`] or [`Framed`],
+//! so they can be combined with the `futures` combinators.
+//!
+//! ```ignore
+//! let socket = TcpStream::connect("127.0.0.1:5760").await?;
+//! let mut frames = Framed::new(socket, MavCodec::<MavMessage>::new(ReadVersion::Any));
+//! while let Some(frame) = frames.next().await {
+//!     println!("{:?}", frame?.msg);
+//! }
+//! ```
+//!
+//! Message signatures are neither added nor verified.
+//!
+//! [`FramedRead`]: tokio_util::codec::FramedRead
+//! [`FramedWrite`]: tokio_util::codec::FramedWrite
+//! [`Framed`]: tokio_util::codec::Framed
+
+use core::marker::PhantomData;
+
+use tokio_util::bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::slice_parser::parse_raw_message_ref;
+use crate::{write_versioned_msg, MavFrame, MavHeader, Message, ReadVersion};
+
+/// Codec decoding and encoding MAVLink frames
+///
+/// Data that is not part of a valid frame is skipped while decoding, just like the `read_*`
+/// functions do. Frames with a valid checksum that can not be parsed into a message of type `M`
+/// are skipped as well, as returning an error would end a [`FramedRead`] stream. A partial frame
+/// at the end of the stream is discarded.
+///
+/// Frames are encoded with the header and MAVLink version given in the [`MavFrame`].
+///
+/// [`FramedRead`]: tokio_util::codec::FramedRead
+pub struct MavCodec<M: Message> {
+    version: ReadVersion,
+    phantom: PhantomData<fn() -> M>,
+}
+
+impl<M: Message> Default for MavCodec<M> {
+    fn default() -> Self {
+        Self::new(ReadVersion::Any)
+    }
+}
+
+impl<M: Message> MavCodec<M> {
+    /// Create a codec decoding frames of the given version
+    pub const fn new(version: ReadVersion) -> Self {
+        Self {
+            version,
+            phantom: PhantomData,
+        }
+    }
+
+    /// MAVLink version(s) decoded by this codec
+    pub fn version(&self) -> ReadVersion {
+        self.version
+    }
+}
+
+impl<M: Message> Decoder for MavCodec<M> {
+    type Item = MavFrame<M>;
+    type Error = MessageReadError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            let (consumed, frame) = parse_raw_message_ref::<M>(src, self.version);
+            let frame = frame.map(|raw| {
+                let header = MavHeader {
+                    sequence: raw.sequence(),
+                    system_id: raw.system_id(),
+                    component_id: raw.component_id(),
+                };
+                let msg = M::parse(raw.version(), raw.message_id(), raw.payload());
+                (header, msg, raw.version())
+            });
+            src.advance(consumed);
+
+            match frame {
+                Some((header, Ok(msg), protocol_version)) => {
+                    return Ok(Some(MavFrame {
+                        header,
+                        msg,
+                        protocol_version,
+                    }))
+                }
+                Some((_, Err(_), _)) => {}
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let frame = self.decode(src)?;
+        if frame.is_none() {
+            src.clear();
+        }
+        Ok(frame)
+    }
+}
+
+impl<M: Message> Encoder<MavFrame<M>> for MavCodec<M> {
+    type Error = MessageWriteError;
+
+    fn encode(&mut self, frame: MavFrame<M>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        write_versioned_msg(
+            &mut dst.writer(),
+            frame.protocol_version,
+            frame.header,
+            &frame.msg,
+        )?;
+        Ok(())
+    }
+}
@@ -2,13 +2,18 @@ use core::fmt::Display;
 use std::io;
 use std::path::PathBuf;
 
+#[cfg(feature = "websocket")]
+use crate::async_connection::websocket::config::{WebSocketConfig, WebSocketMode};
 #[cfg(feature = "direct-serial")]
 use crate::connection::direct_serial::config::SerialConfig;
-use crate::connection::file::config::FileConfig;
+use crate::connection::file::config::{FileConfig, FileMode};
+use crate::connection::mem::config::MemConfig;
 #[cfg(feature = "tcp")]
 use crate::connection::tcp::config::{TcpConfig, TcpMode};
 #[cfg(feature = "udp")]
 use crate::connection::udp::config::{UdpConfig, UdpMode};
+#[cfg(all(feature = "unix", unix))]
+use crate::connection::unix::config::{UnixConfig, UnixMode};
 
 /// A parsed MAVLink connection address
 pub enum ConnectionAddress {
@@ -18,11 +23,19 @@ pub enum ConnectionAddress {
     /// UDP client, server or broadcast address
     #[cfg(feature = "udp")]
     Udp(UdpConfig),
+    /// Unix domain stream or datagram socket address
+    #[cfg(all(feature = "unix", unix))]
+    Unix(UnixConfig),
+    /// WebSocket client or server address
+    #[cfg(feature = "websocket")]
+    WebSocket(WebSocketConfig),
     /// Serial port address
     #[cfg(feature = "direct-serial")]
     Serial(SerialConfig),
-    /// File input address
+    /// File input or output address
     File(FileConfig),
+    /// In-memory connection address
+    Mem(MemConfig),
 }
 
 #[cfg(feature = "tcp")]
@@ -39,6 +52,20 @@ impl From<UdpConfig> for ConnectionAddress {
     }
 }
 
+#[cfg(all(feature = "unix", unix))]
+impl From<UnixConfig> for ConnectionAddress {
+    fn from(value: UnixConfig) -> Self {
+        Self::Unix(value)
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl From<WebSocketConfig> for ConnectionAddress {
+    fn from(value: WebSocketConfig) -> Self {
+        Self::WebSocket(value)
+    }
+}
+
 #[cfg(feature = "direct-serial")]
 impl From<SerialConfig> for ConnectionAddress {
     fn from(value: SerialConfig) -> Self {
@@ -52,6 +79,12 @@ impl From<FileConfig> for ConnectionAddress {
     }
 }
 
+impl From<MemConfig> for ConnectionAddress {
+    fn from(value: MemConfig) -> Self {
+        Self::Mem(value)
+    }
+}
+
 impl Display for ConnectionAddress {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
@@ -59,9 +92,14 @@ impl Display for ConnectionAddress {
             Self::Tcp(connectable) => write!(f, "{connectable}"),
             #[cfg(feature = "udp")]
             Self::Udp(connectable) => write!(f, "{connectable}"),
+            #[cfg(all(feature = "unix", unix))]
+            Self::Unix(connectable) => write!(f, "{connectable}"),
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(connectable) => write!(f, "{connectable}"),
             #[cfg(feature = "direct-serial")]
             Self::Serial(connectable) => write!(f, "{connectable}"),
             Self::File(connectable) => write!(f, "{connectable}"),
+            Self::Mem(connectable) => write!(f, "{connectable}"),
         }
     }
 }
@@ -73,11 +111,23 @@ impl ConnectionAddress {
     ///
     ///  * `tcpin:<addr>:<port>` to create a TCP server, listening for an incoming connection
     ///  * `tcpout:<addr>:<port>` to create a TCP client
+    ///  * `tcpserver:<addr>:<port>` to create a TCP server, accepting any number of clients
     ///  * `udpin:<addr>:<port>` to create a UDP server, listening for incoming packets
     ///  * `udpout:<addr>:<port>` to create a UDP client
+    ///  * `udpserver:<addr>:<port>` to create a UDP server, replying to all clients
     ///  * `udpbcast:<addr>:<port>` to create a UDP broadcast
+    ///  * `unixin:<path>` to create a Unix stream socket server, listening for an incoming connection
+    ///  * `unix:<path>` to connect to a Unix stream socket server
+    ///  * `unixgram:<path>` to create a Unix datagram socket, replying to the last sender
+    ///  * `ws://<host>:<port>/<path>` or `wss://...` to create a WebSocket client, only available as
+    ///    async connection
+    ///  * `wsserver:<addr>:<port>` to create a WebSocket server, accepting any number of clients, only
+    ///    available as async connection
     ///  * `serial:<port>:<baudrate>` to create a serial connection
     ///  * `file:<path>` to extract file data, writing to such a connection does nothing
+    ///  * `fileout:<path>` to record sent messages to a telemetry log, reading from such a connection fails
+    ///  * `filefollow:<path>` to read a file that is still being written, waiting for new data like `tail -f`
+    ///  * `mem:<name>` to create an in-process connection to the other connection made to the same name
     ///
     /// # Errors
     ///
@@ -104,25 +154,52 @@ impl ConnectionAddress {
                 ))
             }
             #[cfg(feature = "tcp")]
-            "tcpin" | "tcpout" => {
-                let mode = if protocol == "tcpout" {
-                    TcpMode::TcpOut
-                } else {
-                    TcpMode::TcpIn
+            "tcpin" | "tcpout" | "tcpserver" => {
+                let mode = match protocol {
+                    "tcpin" => TcpMode::TcpIn,
+                    "tcpout" => TcpMode::TcpOut,
+                    "tcpserver" => TcpMode::TcpServer,
+                    _ => unreachable!(),
                 };
                 Self::Tcp(TcpConfig::new(address.to_string(), mode))
             }
             #[cfg(feature = "udp")]
-            "udpin" | "udpout" | "udpcast" => Self::Udp(UdpConfig::new(
+            "udpin" | "udpout" | "udpcast" | "udpserver" => Self::Udp(UdpConfig::new(
                 address.to_string(),
                 match protocol {
                     "udpin" => UdpMode::Udpin,
                     "udpout" => UdpMode::Udpout,
                     "udpcast" => UdpMode::Udpcast,
+                    "udpserver" => UdpMode::Udpserver,
+                    _ => unreachable!(),
+                },
+            )),
+            #[cfg(all(feature = "unix", unix))]
+            "unixin" | "unix" | "unixgram" => Self::Unix(UnixConfig::new(
+                PathBuf::from(address),
+                match protocol {
+                    "unixin" => UnixMode::UnixIn,
+                    "unix" => UnixMode::UnixOut,
+                    "unixgram" => UnixMode::UnixGram,
                     _ => unreachable!(),
                 },
             )),
+            #[cfg(feature = "websocket")]
+            "ws" | "wss" => Self::WebSocket(WebSocketConfig::new(
+                format!("{protocol}:{address}"),
+                WebSocketMode::WsClient,
+            )),
+            #[cfg(feature = "websocket")]
+            "wsserver" => Self::WebSocket(WebSocketConfig::new(
+                address.to_string(),
+                WebSocketMode::WsServer,
+            )),
             "file" => Self::File(FileConfig::new(PathBuf::from(address))),
+            "fileout" => Self::File(FileConfig::new(PathBuf::from(address)).mode(FileMode::Write)),
+            "filefollow" => {
+                Self::File(FileConfig::new(PathBuf::from(address)).mode(FileMode::Follow))
+            }
+            "mem" => Self::Mem(MemConfig::new(address.to_string())),
             _ => {
                 return Err(io::Error::new(
                     io::ErrorKind::AddrNotAvailable,
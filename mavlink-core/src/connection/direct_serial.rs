@@ -1,14 +1,17 @@
 //! Serial MAVLINK connection
 
+use crate::connection::reconnect::{is_link_lost, Reconnect};
 use crate::connection::{Connection, MavConnection};
 use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
 use crate::peek_reader::PeekReader;
 use crate::Connectable;
 use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
 use core::ops::DerefMut;
 use core::sync::atomic::{self, AtomicU8};
-use std::io::{self, BufReader};
+use std::io::{self, BufReader, Write};
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use serialport::{DataBits, FlowControl, Parity, SerialPort, StopBits};
 
@@ -27,31 +30,97 @@ use config::SerialConfig;
 pub struct SerialConnection {
     // Separate ports for reading and writing as it's safe to use concurrently.
     // See the official ref: https://github.com/serialport/serialport-rs/blob/321f85e1886eaa1302aef8a600a631bc1c88703a/examples/duplex.rs
-    read_port: Mutex<PeekReader<BufReader<Box<dyn SerialPort>>>>,
+    read_port: Mutex<SerialReader>,
     write_port: Mutex<Box<dyn SerialPort>>,
     sequence: AtomicU8,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
+    stats: SharedLinkStats,
+    reconnect: Option<Reconnect<SerialConfig>>,
     #[cfg(feature = "signing")]
     signing_data: Option<SigningData>,
 }
 
+type SerialReader = PeekReader<BufReader<Box<dyn SerialPort>>>;
+
+fn open_port(config: &SerialConfig) -> io::Result<(SerialReader, Box<dyn SerialPort>)> {
+    let read_port = serialport::new(&config.port_name, config.baud_rate)
+        .data_bits(DataBits::Eight)
+        .parity(Parity::None)
+        .stop_bits(StopBits::One)
+        .flow_control(FlowControl::None)
+        .open()?;
+
+    let write_port = read_port.try_clone()?;
+
+    let read_buffer_capacity = config.buffer_capacity();
+    let buf_reader = BufReader::with_capacity(read_buffer_capacity, read_port);
+    Ok((PeekReader::new(buf_reader), write_port))
+}
+
+impl SerialConnection {
+    fn generation(&self) -> u32 {
+        self.reconnect.as_ref().map_or(0, Reconnect::generation)
+    }
+
+    /// Re-open the port if `error` shows that it was lost and a reconnect policy is set
+    ///
+    /// Returns whether the failed operation should be retried.
+    fn recover(&self, error: &io::Error, generation: u32) -> io::Result<bool> {
+        let Some(reconnect) = &self.reconnect else {
+            return Ok(false);
+        };
+        if !is_link_lost(error) {
+            return Ok(false);
+        }
+        self.reestablish(reconnect, generation)?;
+        Ok(true)
+    }
+
+    /// Re-open the port if a non-blocking call found it to be lost
+    fn recover_marked(&self) -> io::Result<()> {
+        match &self.reconnect {
+            Some(reconnect) if reconnect.is_marked_lost() => {
+                self.reestablish(reconnect, reconnect.generation())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn reestablish(&self, reconnect: &Reconnect<SerialConfig>, generation: u32) -> io::Result<()> {
+        reconnect.reconnect(generation, open_port, |(read_port, write_port)| {
+            *self.write_port.lock().unwrap() = write_port;
+            let mut port = self.read_port.lock().unwrap();
+            let stats = port.stats;
+            *port = read_port;
+            port.stats = stats;
+        })
+    }
+}
+
 impl<M: Message> MavConnection<M> for SerialConnection {
     fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
-        let mut port = self.read_port.lock().unwrap();
         let version = ReadVersion::from_conn_cfg::<_, M>(self);
 
         loop {
+            self.recover_marked()?;
+            let mut port = self.read_port.lock().unwrap();
+            let generation = self.generation();
             #[cfg(not(feature = "signing"))]
             let result = read_versioned_msg(port.deref_mut(), version);
             #[cfg(feature = "signing")]
             let result =
                 read_versioned_msg_signed(port.deref_mut(), version, self.signing_data.as_ref());
+            self.stats.record(port.read_stats(), &result);
+            drop(port);
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
                 Err(MessageReadError::Io(e)) => {
+                    if self.recover(&e, generation)? {
+                        continue;
+                    }
                     if e.kind() == io::ErrorKind::UnexpectedEof {
                         return Err(MessageReadError::Io(e));
                     }
@@ -62,10 +131,12 @@ impl<M: Message> MavConnection<M> for SerialConnection {
     }
 
     fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
-        let mut port = self.read_port.lock().unwrap();
         let version = ReadVersion::from_conn_cfg::<_, M>(self);
 
         loop {
+            self.recover_marked()?;
+            let mut port = self.read_port.lock().unwrap();
+            let generation = self.generation();
             #[cfg(not(feature = "signing"))]
             let result = read_versioned_raw_message::<M, _>(port.deref_mut(), version);
             #[cfg(feature = "signing")]
@@ -74,11 +145,16 @@ impl<M: Message> MavConnection<M> for SerialConnection {
                 version,
                 self.signing_data.as_ref(),
             );
+            self.stats.record(port.read_stats(), &result);
+            drop(port);
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
                 Err(MessageReadError::Io(e)) => {
+                    if self.recover(&e, generation)? {
+                        continue;
+                    }
                     if e.kind() == io::ErrorKind::UnexpectedEof {
                         return Err(MessageReadError::Io(e));
                     }
@@ -89,7 +165,15 @@ impl<M: Message> MavConnection<M> for SerialConnection {
     }
 
     fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        if self
+            .reconnect
+            .as_ref()
+            .is_some_and(Reconnect::is_marked_lost)
+        {
+            return Err(io::Error::from(io::ErrorKind::NotConnected).into());
+        }
         let mut port = self.read_port.lock().unwrap();
+        let generation = self.generation();
         let version = ReadVersion::from_conn_cfg::<_, M>(self);
 
         #[cfg(not(feature = "signing"))]
@@ -98,46 +182,108 @@ impl<M: Message> MavConnection<M> for SerialConnection {
         #[cfg(feature = "signing")]
         let result =
             read_versioned_msg_signed(port.deref_mut(), version, self.signing_data.as_ref());
+        self.stats.record(port.read_stats(), &result);
+        drop(port);
 
+        // a non-blocking call must not wait for the port, it is re-opened by the next blocking
+        // call
+        if let (Some(reconnect), Err(MessageReadError::Io(e))) = (&self.reconnect, &result) {
+            reconnect.mark_lost(e, generation);
+        }
         result
     }
 
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        let deadline = Instant::now() + timeout;
+        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+
+        loop {
+            self.recover_marked()?;
+            let mut port = self.read_port.lock().unwrap();
+            let generation = self.generation();
+            // only the reading side uses the port's timeout, restore it for `recv`
+            let serial = port.reader_mut().get_mut();
+            let previous = serial.timeout();
+            serial
+                .set_timeout(deadline.saturating_duration_since(Instant::now()))
+                .map_err(io::Error::from)?;
+            #[cfg(not(feature = "signing"))]
+            let result = read_versioned_msg(port.deref_mut(), version);
+            #[cfg(feature = "signing")]
+            let result =
+                read_versioned_msg_signed(port.deref_mut(), version, self.signing_data.as_ref());
+            port.reader_mut().get_mut().set_timeout(previous).ok();
+            self.stats.record(port.read_stats(), &result);
+            drop(port);
+
+            match &result {
+                Err(MessageReadError::Io(e)) if self.recover(e, generation)? => {}
+                _ => return result,
+            }
+        }
+    }
+
     fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
-        let mut port = self.write_port.lock().unwrap();
-
-        let sequence = self.sequence.fetch_add(
-            1,
-            // Safety:
-            //
-            // We are using `Ordering::Relaxed` here because:
-            // - We only need a unique sequence number per message
-            // - `Mutex` on `self.write_port` already makes sure the rest of the code is synchronized
-            // - No other thread reads or writes `self.sequence` without going through this `Mutex`
-            //
-            // Warning:
-            //
-            // If we later change this code to access `self.sequence` without locking `self.write_port` with the `Mutex`,
-            // then we should upgrade this ordering to `Ordering::SeqCst`.
-            atomic::Ordering::Relaxed,
-        );
-
-        let header = MavHeader {
-            sequence,
-            system_id: header.system_id,
-            component_id: header.component_id,
-        };
+        loop {
+            self.recover_marked()?;
+            let mut port = self.write_port.lock().unwrap();
+            let generation = self.generation();
 
-        #[cfg(not(feature = "signing"))]
-        let result = write_versioned_msg(port.deref_mut(), self.protocol_version, header, data);
-        #[cfg(feature = "signing")]
-        let result = write_versioned_msg_signed(
-            port.deref_mut(),
-            self.protocol_version,
-            header,
-            data,
-            self.signing_data.as_ref(),
-        );
-        result
+            let sequence = self.sequence.fetch_add(
+                1,
+                // Safety:
+                //
+                // We are using `Ordering::Relaxed` here because:
+                // - We only need a unique sequence number per message
+                // - `Mutex` on `self.write_port` already makes sure the rest of the code is synchronized
+                // - No other thread reads or writes `self.sequence` without going through this `Mutex`
+                //
+                // Warning:
+                //
+                // If we later change this code to access `self.sequence` without locking `self.write_port` with the `Mutex`,
+                // then we should upgrade this ordering to `Ordering::SeqCst`.
+                atomic::Ordering::Relaxed,
+            );
+
+            let header = MavHeader {
+                sequence,
+                system_id: header.system_id,
+                component_id: header.component_id,
+            };
+
+            #[cfg(not(feature = "signing"))]
+            let result = write_versioned_msg(port.deref_mut(), self.protocol_version, header, data);
+            #[cfg(feature = "signing")]
+            let result = write_versioned_msg_signed(
+                port.deref_mut(),
+                self.protocol_version,
+                header,
+                data,
+                self.signing_data.as_ref(),
+            );
+            drop(port);
+
+            match &result {
+                Err(MessageWriteError::Io(e)) if self.recover(e, generation)? => {}
+                _ => return result,
+            }
+        }
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        loop {
+            self.recover_marked()?;
+            let mut port = self.write_port.lock().unwrap();
+            let generation = self.generation();
+            let result = port.write_all(message.raw_bytes());
+            drop(port);
+
+            match result {
+                Ok(()) => return Ok(message.raw_bytes().len()),
+                Err(e) if self.recover(&e, generation)? => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
     }
 
     fn set_protocol_version(&mut self, version: MavlinkVersion) {
@@ -156,6 +302,10 @@ impl<M: Message> MavConnection<M> for SerialConnection {
         self.recv_any_version
     }
 
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
         self.signing_data = signing_data.map(SigningData::from_config);
@@ -164,26 +314,20 @@ impl<M: Message> MavConnection<M> for SerialConnection {
 
 impl Connectable for SerialConfig {
     fn connect<M: Message>(&self) -> io::Result<Connection<M>> {
-        let read_port = serialport::new(&self.port_name, self.baud_rate)
-            .data_bits(DataBits::Eight)
-            .parity(Parity::None)
-            .stop_bits(StopBits::One)
-            .flow_control(FlowControl::None)
-            .open()?;
-
-        let write_port = read_port.try_clone()?;
-
-        let read_buffer_capacity = self.buffer_capacity();
-        let buf_reader = BufReader::with_capacity(read_buffer_capacity, read_port);
+        let (read_port, write_port) = open_port(self)?;
 
         Ok(SerialConnection {
-            read_port: Mutex::new(PeekReader::new(buf_reader)),
+            read_port: Mutex::new(read_port),
             write_port: Mutex::new(write_port),
             sequence: AtomicU8::new(0),
             protocol_version: MavlinkVersion::V2,
             #[cfg(feature = "signing")]
             signing_data: None,
             recv_any_version: false,
+            stats: SharedLinkStats::default(),
+            reconnect: self
+                .reconnect
+                .map(|policy| Reconnect::new(self.clone(), policy)),
         }
         .into())
     }
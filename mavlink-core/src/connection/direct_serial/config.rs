@@ -1,5 +1,7 @@
 use core::fmt::Display;
 
+use crate::connection::reconnect::ReconnectPolicy;
+
 /// MAVLink address for a serial connection
 ///
 /// # Example
@@ -15,6 +17,7 @@ pub struct SerialConfig {
     pub(crate) port_name: String,
     pub(crate) baud_rate: u32,
     read_buffer_capacity: usize,
+    pub(crate) reconnect: Option<ReconnectPolicy>,
 }
 
 impl SerialConfig {
@@ -27,6 +30,7 @@ impl SerialConfig {
             port_name,
             baud_rate,
             read_buffer_capacity: default_capacity,
+            reconnect: None,
         }
     }
 
@@ -36,6 +40,12 @@ impl SerialConfig {
         self
     }
 
+    /// Sets the policy used to re-open the port after it was lost, e.g. by unplugging the adapter.
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
     /// Returns the configured read buffer capacity.
     pub fn buffer_capacity(&self) -> usize {
         self.read_buffer_capacity
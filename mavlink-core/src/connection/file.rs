@@ -2,49 +2,135 @@
 
 use crate::connection::{Connection, MavConnection};
 use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
 use crate::peek_reader::PeekReader;
+use crate::tlog::TlogWriter;
 use crate::{Connectable, MAVLinkMessageRaw};
 use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
 use core::ops::DerefMut;
+use core::time::Duration;
 use std::fs::File;
-use std::io;
+use std::io::{self, Read};
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Instant;
 
 #[cfg(not(feature = "signing"))]
-use crate::{read_versioned_msg, read_versioned_raw_message};
+use crate::{read_versioned_msg, read_versioned_raw_message, write_versioned_msg};
 #[cfg(feature = "signing")]
 use crate::{
-    read_versioned_msg_signed, read_versioned_raw_message_signed, SigningConfig, SigningData,
+    read_versioned_msg_signed, read_versioned_raw_message_signed, write_versioned_msg_signed,
+    SigningConfig, SigningData,
 };
 
 pub mod config;
 
-use config::FileConfig;
+use config::{FileConfig, FileMode};
 
 pub fn open(file_path: &PathBuf) -> io::Result<FileConnection> {
     let file = File::open(file_path)?;
+    let reader = FileRead {
+        file,
+        follow: None,
+        deadline: None,
+    };
+    Ok(FileConnection::new(Some(PeekReader::new(reader)), None))
+}
 
-    Ok(FileConnection {
-        file: Mutex::new(PeekReader::new(file)),
-        protocol_version: MavlinkVersion::V2,
-        #[cfg(feature = "signing")]
-        signing_data: None,
-        recv_any_version: false,
-    })
+/// Open a file that is still being written, waiting for new data at its end
+pub fn follow(file_path: &PathBuf, poll_interval: Duration) -> io::Result<FileConnection> {
+    let file = File::open(file_path)?;
+    let reader = FileRead {
+        file,
+        follow: Some(poll_interval),
+        deadline: None,
+    };
+    Ok(FileConnection::new(Some(PeekReader::new(reader)), None))
+}
+
+/// Create a telemetry log, truncating an existing file
+pub fn create(file_path: &PathBuf) -> io::Result<FileConnection> {
+    let file = File::create(file_path)?;
+    let writer = FileWrite {
+        log: TlogWriter::new(file),
+        sequence: 0,
+    };
+    Ok(FileConnection::new(None, Some(writer)))
+}
+
+struct FileRead {
+    file: File,
+    /// Interval to poll for new data when following the file
+    follow: Option<Duration>,
+    /// Time after which to stop waiting for new data when following the file
+    deadline: Option<Instant>,
+}
+
+impl Read for FileRead {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.file.read(buf)?;
+            match self.follow {
+                // at the end of the file, wait for the writer to append more data
+                Some(interval) if n == 0 && !buf.is_empty() => match self.deadline {
+                    Some(deadline) => {
+                        let remaining = deadline.saturating_duration_since(Instant::now());
+                        if remaining.is_zero() {
+                            return Err(io::ErrorKind::WouldBlock.into());
+                        }
+                        thread::sleep(interval.min(remaining));
+                    }
+                    None => thread::sleep(interval),
+                },
+                _ => return Ok(n),
+            }
+        }
+    }
+}
+
+struct FileWrite {
+    log: TlogWriter<File>,
+    sequence: u8,
 }
 
 pub struct FileConnection {
-    file: Mutex<PeekReader<File>>,
+    reader: Option<Mutex<PeekReader<FileRead>>>,
+    writer: Option<Mutex<FileWrite>>,
     protocol_version: MavlinkVersion,
     recv_any_version: bool,
+    stats: SharedLinkStats,
     #[cfg(feature = "signing")]
     signing_data: Option<SigningData>,
 }
 
+impl FileConnection {
+    fn new(reader: Option<PeekReader<FileRead>>, writer: Option<FileWrite>) -> Self {
+        Self {
+            reader: reader.map(Mutex::new),
+            writer: writer.map(Mutex::new),
+            protocol_version: MavlinkVersion::V2,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+            recv_any_version: false,
+            stats: SharedLinkStats::default(),
+        }
+    }
+
+    fn reader(&self) -> io::Result<MutexGuard<'_, PeekReader<FileRead>>> {
+        match &self.reader {
+            Some(reader) => Ok(reader.lock().unwrap()),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "file connection was opened for writing",
+            )),
+        }
+    }
+}
+
 impl<M: Message> MavConnection<M> for FileConnection {
     fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut file = self.file.lock().unwrap();
+        let mut file = self.reader()?;
         let version = ReadVersion::from_conn_cfg::<_, M>(self);
 
         loop {
@@ -53,14 +139,13 @@ impl<M: Message> MavConnection<M> for FileConnection {
             #[cfg(feature = "signing")]
             let result =
                 read_versioned_msg_signed(file.deref_mut(), version, self.signing_data.as_ref());
+            self.stats.record(file.read_stats(), &result);
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
@@ -68,7 +153,7 @@ impl<M: Message> MavConnection<M> for FileConnection {
     }
 
     fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
-        let mut file = self.file.lock().unwrap();
+        let mut file = self.reader()?;
         let version = ReadVersion::from_conn_cfg::<_, M>(self);
 
         loop {
@@ -80,14 +165,13 @@ impl<M: Message> MavConnection<M> for FileConnection {
                 version,
                 self.signing_data.as_ref(),
             );
+            self.stats.record(file.read_stats(), &result);
             match result {
                 ok @ Ok(..) => {
                     return ok;
                 }
-                Err(MessageReadError::Io(e)) => {
-                    if e.kind() == io::ErrorKind::UnexpectedEof {
-                        return Err(MessageReadError::Io(e));
-                    }
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(MessageReadError::Io(e));
                 }
                 _ => {}
             }
@@ -95,7 +179,8 @@ impl<M: Message> MavConnection<M> for FileConnection {
     }
 
     fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut file = self.file.lock().unwrap();
+        let mut file = self.reader()?;
+        file.reader_mut().deadline = Some(Instant::now());
         let version = ReadVersion::from_conn_cfg::<_, M>(self);
 
         #[cfg(not(feature = "signing"))]
@@ -103,12 +188,67 @@ impl<M: Message> MavConnection<M> for FileConnection {
         #[cfg(feature = "signing")]
         let result =
             read_versioned_msg_signed(file.deref_mut(), version, self.signing_data.as_ref());
+        self.stats.record(file.read_stats(), &result);
+
+        file.reader_mut().deadline = None;
+        result
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        let mut file = self.reader()?;
+        file.reader_mut().deadline = Some(Instant::now() + timeout);
+        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+
+        let result = loop {
+            #[cfg(not(feature = "signing"))]
+            let result = read_versioned_msg(file.deref_mut(), version);
+            #[cfg(feature = "signing")]
+            let result =
+                read_versioned_msg_signed(file.deref_mut(), version, self.signing_data.as_ref());
+            self.stats.record(file.read_stats(), &result);
+            if !matches!(result, Err(MessageReadError::Parse(_))) {
+                break result;
+            }
+        };
 
+        file.reader_mut().deadline = None;
         result
     }
 
-    fn send(&self, _header: &MavHeader, _data: &M) -> Result<usize, MessageWriteError> {
-        Ok(0)
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let Some(writer) = &self.writer else {
+            return Ok(0);
+        };
+        let mut guard = writer.lock().unwrap();
+        let state = &mut *guard;
+
+        let header = MavHeader {
+            sequence: state.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        state.sequence = state.sequence.wrapping_add(1);
+
+        let mut buf = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut buf,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        Ok(state.log.write_frame(&buf)?)
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        match &self.writer {
+            Some(writer) => Ok(writer.lock().unwrap().log.write_raw(message)?),
+            None => Ok(0),
+        }
     }
 
     fn set_protocol_version(&mut self, version: MavlinkVersion) {
@@ -127,6 +267,10 @@ impl<M: Message> MavConnection<M> for FileConnection {
         self.recv_any_version
     }
 
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
         self.signing_data = signing_data.map(SigningData::from_config);
@@ -135,6 +279,11 @@ impl<M: Message> MavConnection<M> for FileConnection {
 
 impl Connectable for FileConfig {
     fn connect<M: Message>(&self) -> io::Result<Connection<M>> {
-        Ok(open(&self.address)?.into())
+        let conn = match self.mode {
+            FileMode::Read => open(&self.address)?,
+            FileMode::Write => create(&self.address)?,
+            FileMode::Follow => follow(&self.address, self.poll_interval)?,
+        };
+        Ok(conn.into())
     }
 }
@@ -1,32 +1,85 @@
 use core::fmt::Display;
+use core::time::Duration;
 use std::path::PathBuf;
 
-/// MAVLink connection address for a file input
+/// Default interval in which a followed file is checked for new data
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Type of file connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileMode {
+    /// Read messages from an existing file, sending does nothing
+    #[default]
+    Read,
+    /// Record sent messages to a new telemetry log, receiving is not supported
+    ///
+    /// An existing file is truncated.
+    Write,
+    /// Read messages from a file that is still being written, sending does nothing
+    ///
+    /// Like `tail -f`, receiving waits for new data to be appended to the file instead of
+    /// reporting EOF once the end of the file is reached.
+    Follow,
+}
+
+/// MAVLink connection address for a file input or output
 ///
 /// # Example
 ///
 /// ```ignore
-/// use mavlink::{Connectable, FileConfig};
+/// use mavlink::{Connectable, FileConfig, FileMode};
 /// use std::path::PathBuf;
+/// use std::time::Duration;
 ///
 /// let config = FileConfig::new(PathBuf::from("/some/path"));
 /// config
 ///   .connect::<mavlink::ardupilotmega::MavMessage>()
 ///   .unwrap();
+///
+/// let config = FileConfig::new(PathBuf::from("/some/session.tlog")).mode(FileMode::Write);
+///
+/// let config = FileConfig::new(PathBuf::from("/some/live.tlog"))
+///     .mode(FileMode::Follow)
+///     .poll_interval(Duration::from_millis(20));
 /// ```
 #[derive(Debug, Clone)]
 pub struct FileConfig {
     pub(crate) address: PathBuf,
+    pub(crate) mode: FileMode,
+    pub(crate) poll_interval: Duration,
 }
 
 impl FileConfig {
     /// Creates a file input address from a file path string.
     pub fn new(address: PathBuf) -> Self {
-        Self { address }
+        Self {
+            address,
+            mode: FileMode::Read,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Sets whether the file is read or written.
+    pub fn mode(mut self, mode: FileMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the interval in which a followed file is checked for new data, defaults to 100 ms.
+    ///
+    /// This only has an effect in [`FileMode::Follow`].
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
     }
 }
 impl Display for FileConfig {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "file:{}", self.address.display())
+        let mode = match self.mode {
+            FileMode::Read => "file",
+            FileMode::Write => "fileout",
+            FileMode::Follow => "filefollow",
+        };
+        write!(f, "{mode}:{}", self.address.display())
     }
 }
@@ -0,0 +1,378 @@
+//! In-memory MAVLink connection
+
+use crate::connection::{Connection, MavConnection};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, ReadStats, ReceivedFrame, SharedLinkStats};
+use crate::peek_reader::PeekReader;
+use crate::{Connectable, MAVLinkMessageRaw};
+use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_versioned_msg, read_versioned_raw_message, write_versioned_msg};
+#[cfg(feature = "signing")]
+use crate::{
+    read_versioned_msg_signed, read_versioned_raw_message_signed, write_versioned_msg_signed,
+    SigningConfig, SigningData,
+};
+
+pub mod config;
+
+use config::MemConfig;
+
+/// Frame in flight between two in-memory connections
+pub(crate) struct Packet {
+    pub(crate) deliver_at: Instant,
+    pub(crate) frame: Vec<u8>,
+}
+
+/// Loss, corruption and latency applied to the frames sent by a connection
+pub(crate) struct Impairment {
+    latency: Duration,
+    loss: f64,
+    corruption: f64,
+    state: u64,
+}
+
+impl Impairment {
+    pub(crate) fn new(config: &MemConfig) -> Self {
+        Self {
+            latency: config.latency,
+            loss: config.loss,
+            corruption: config.corruption,
+            // xorshift gets stuck at zero
+            state: config.seed.max(1),
+        }
+    }
+
+    /// Packet to hand to the peer for a sent frame, `None` if the frame is lost
+    pub(crate) fn apply(&mut self, mut frame: Vec<u8>) -> Option<Packet> {
+        if self.chance(self.loss) {
+            return None;
+        }
+        if self.chance(self.corruption) && !frame.is_empty() {
+            let bit = self.next_u64() as usize % (frame.len() * 8);
+            frame[bit / 8] ^= 1 << (bit % 8);
+        }
+        Some(Packet {
+            deliver_at: Instant::now() + self.latency,
+            frame,
+        })
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        // uniform in [0, 1) from the upper 53 bits
+        let sample = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        probability > 0.0 && sample < probability
+    }
+
+    // xorshift64*
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+/// Connections waiting for a peer to connect to the same name
+pub(crate) struct Registry<E> {
+    waiting: Mutex<BTreeMap<String, (E, Weak<()>)>>,
+}
+
+impl<E> Registry<E> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            waiting: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Channel ends of a new connection with the given name
+    ///
+    /// Pairs the connection with the connection waiting for a peer under that name, if it is
+    /// still alive, otherwise the new connection waits for a peer. The returned token keeps a
+    /// waiting connection alive.
+    pub(crate) fn connect(&self, name: &str, channel: impl FnOnce() -> (E, E)) -> (E, Arc<()>) {
+        let mut waiting = self.waiting.lock().unwrap();
+        let alive = Arc::new(());
+        if let Some((ends, peer)) = waiting.remove(name) {
+            if peer.strong_count() > 0 {
+                return (ends, alive);
+            }
+        }
+        let (ends, peer_ends) = channel();
+        waiting.insert(name.to_owned(), (peer_ends, Arc::downgrade(&alive)));
+        (ends, alive)
+    }
+}
+
+type Ends = (Sender<Packet>, Receiver<Packet>);
+
+static REGISTRY: Registry<Ends> = Registry::new();
+
+fn channel() -> (Ends, Ends) {
+    let (a_tx, b_rx) = mpsc::channel();
+    let (b_tx, a_rx) = mpsc::channel();
+    ((a_tx, a_rx), (b_tx, b_rx))
+}
+
+pub(crate) fn peer_disconnected() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "peer disconnected")
+}
+
+pub(crate) fn peer_closed() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "peer disconnected")
+}
+
+/// Parse a received frame, `None` if it does not contain a message
+pub(crate) fn read_packet<T>(
+    packet: &Packet,
+    stats: &mut ReadStats,
+    read: impl Fn(&mut PeekReader<&[u8]>) -> Result<T, MessageReadError>,
+) -> Option<Result<T, MessageReadError>> {
+    let mut frame = PeekReader::new(packet.frame.as_slice());
+    let result = read(&mut frame);
+    *stats += frame.read_stats();
+    match result {
+        Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+        result => Some(result),
+    }
+}
+
+struct MemRead {
+    receiver: Receiver<Packet>,
+    // next packet, received by `try_recv` before it was due
+    pending: Option<Packet>,
+    stats: ReadStats,
+}
+
+impl MemRead {
+    /// Next packet that is due, waiting until `deadline` if it is set and forever otherwise
+    fn next_packet(&mut self, deadline: Option<Instant>) -> io::Result<Packet> {
+        let packet = match self.pending.take() {
+            Some(packet) => packet,
+            None => match deadline {
+                None => self.receiver.recv().map_err(|_| peer_disconnected())?,
+                Some(deadline) => {
+                    match self
+                        .receiver
+                        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    {
+                        Ok(packet) => packet,
+                        Err(RecvTimeoutError::Timeout) => {
+                            return Err(io::ErrorKind::WouldBlock.into())
+                        }
+                        Err(RecvTimeoutError::Disconnected) => return Err(peer_disconnected()),
+                    }
+                }
+            },
+        };
+        let now = Instant::now();
+        if packet.deliver_at > now {
+            if deadline.is_some_and(|deadline| packet.deliver_at > deadline) {
+                self.pending = Some(packet);
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            thread::sleep(packet.deliver_at - now);
+        }
+        Ok(packet)
+    }
+}
+
+struct MemWrite {
+    sender: Sender<Packet>,
+    impairment: Impairment,
+    sequence: u8,
+}
+
+impl MemWrite {
+    fn transmit(&mut self, frame: Vec<u8>) -> io::Result<()> {
+        if let Some(packet) = self.impairment.apply(frame) {
+            self.sender.send(packet).map_err(|_| peer_closed())?;
+        }
+        Ok(())
+    }
+}
+
+/// In-memory MAVLink connection to a peer in the same process
+///
+/// Each sent message is delivered to the peer as a whole, like a datagram.
+pub struct MemConnection {
+    reader: Mutex<MemRead>,
+    writer: Mutex<MemWrite>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    stats: SharedLinkStats,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+    _alive: Arc<()>,
+}
+
+impl MemConnection {
+    fn new((sender, receiver): Ends, config: &MemConfig, alive: Arc<()>) -> Self {
+        Self {
+            reader: Mutex::new(MemRead {
+                receiver,
+                pending: None,
+                stats: ReadStats::default(),
+            }),
+            writer: Mutex::new(MemWrite {
+                sender,
+                impairment: Impairment::new(config),
+                sequence: 0,
+            }),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            stats: SharedLinkStats::default(),
+            #[cfg(feature = "signing")]
+            signing_data: None,
+            _alive: alive,
+        }
+    }
+
+    /// Create two connections connected to each other by a perfect link
+    pub fn pair() -> (Self, Self) {
+        let config = MemConfig::new(String::new());
+        let (a, b) = channel();
+        let alive = Arc::new(());
+        (
+            Self::new(a, &config, alive.clone()),
+            Self::new(b, &config, alive),
+        )
+    }
+
+    /// Receive the next frame containing a valid message, waiting until `deadline` if it is set
+    fn read<T: ReceivedFrame>(
+        &self,
+        deadline: Option<Instant>,
+        read: impl Fn(&mut PeekReader<&[u8]>) -> Result<T, MessageReadError>,
+    ) -> Result<T, MessageReadError> {
+        let mut reader = self.reader.lock().unwrap();
+        let result = loop {
+            let packet = match reader.next_packet(deadline) {
+                Ok(packet) => packet,
+                Err(e) => break Err(e.into()),
+            };
+            if let Some(result) = read_packet(&packet, &mut reader.stats, &read) {
+                break result;
+            }
+        };
+        self.stats.record(reader.stats, &result);
+        result
+    }
+
+    fn recv_msg<M: Message>(
+        &self,
+        deadline: Option<Instant>,
+    ) -> Result<(MavHeader, M), MessageReadError> {
+        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+        self.read(deadline, |frame| {
+            #[cfg(not(feature = "signing"))]
+            let result = read_versioned_msg(frame, version);
+            #[cfg(feature = "signing")]
+            let result = read_versioned_msg_signed(frame, version, self.signing_data.as_ref());
+            result
+        })
+    }
+}
+
+impl<M: Message> MavConnection<M> for MemConnection {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_msg(None)
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+        self.read(None, |frame| {
+            #[cfg(not(feature = "signing"))]
+            let result = read_versioned_raw_message::<M, _>(frame, version);
+            #[cfg(feature = "signing")]
+            let result = read_versioned_raw_message_signed::<M, _>(
+                frame,
+                version,
+                self.signing_data.as_ref(),
+            );
+            result
+        })
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_msg(Some(Instant::now()))
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_msg(Some(Instant::now() + timeout))
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let mut guard = self.writer.lock().unwrap();
+        let state = &mut *guard;
+
+        let header = MavHeader {
+            sequence: state.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        state.sequence = state.sequence.wrapping_add(1);
+
+        let mut buf = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut buf,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        let len = buf.len();
+        state.transmit(buf)?;
+        Ok(len)
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let frame = message.raw_bytes().to_vec();
+        let len = frame.len();
+        self.writer.lock().unwrap().transmit(frame)?;
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
+
+impl Connectable for MemConfig {
+    fn connect<M: Message>(&self) -> io::Result<Connection<M>> {
+        let (ends, alive) = REGISTRY.connect(&self.name, channel);
+        Ok(MemConnection::new(ends, self, alive).into())
+    }
+}
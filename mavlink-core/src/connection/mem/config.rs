@@ -0,0 +1,72 @@
+use core::fmt::Display;
+use std::time::Duration;
+
+/// MAVLink address of an in-memory connection
+///
+/// The first two connections made to the same name are connected to each other. Link impairments
+/// apply to the messages sent by the connection made from this configuration, random decisions are
+/// made by a generator seeded with [`MemConfig::seed`] so test runs are reproducible.
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::{Connectable, MemConfig};
+/// use std::time::Duration;
+///
+/// let gcs = MemConfig::new("link".to_owned())
+///     .latency(Duration::from_millis(50))
+///     .loss(0.1)
+///     .connect::<mavlink::common::MavMessage>()?;
+/// let vehicle = mavlink::connect::<mavlink::common::MavMessage>("mem:link")?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemConfig {
+    pub(crate) name: String,
+    pub(crate) latency: Duration,
+    pub(crate) loss: f64,
+    pub(crate) corruption: f64,
+    pub(crate) seed: u64,
+}
+
+impl MemConfig {
+    /// Creates an in-memory connection address with a perfect link.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            latency: Duration::ZERO,
+            loss: 0.0,
+            corruption: 0.0,
+            seed: 0x853c_49e6_748f_ea9b,
+        }
+    }
+
+    /// Sets the time it takes for a sent message to arrive at the peer.
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Sets the probability of a sent message being dropped, between 0.0 and 1.0.
+    pub fn loss(mut self, probability: f64) -> Self {
+        self.loss = probability;
+        self
+    }
+
+    /// Sets the probability of a sent message getting a random bit flipped, between 0.0 and 1.0.
+    pub fn corruption(mut self, probability: f64) -> Self {
+        self.corruption = probability;
+        self
+    }
+
+    /// Sets the seed of the random decisions on loss and corruption.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+impl Display for MemConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "mem:{}", self.name)
+    }
+}
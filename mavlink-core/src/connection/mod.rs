@@ -4,35 +4,63 @@ pub mod tcp;
 #[cfg(feature = "udp")]
 pub mod udp;
 
+#[cfg(all(feature = "unix", unix))]
+pub mod unix;
+
 #[cfg(feature = "direct-serial")]
 pub mod direct_serial;
 
 pub mod file;
 
+pub mod mem;
+
+pub mod split;
+
+pub mod stream;
+
+#[cfg(any(feature = "tcp", feature = "udp", feature = "websocket"))]
+pub(crate) mod peer_inbox;
+
+#[cfg(any(feature = "tcp", feature = "direct-serial"))]
+pub(crate) mod reconnect;
+
 use core::fmt::Display;
 use core::marker::PhantomData;
 use std::io::{self};
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "tcp")]
-use self::tcp::TcpConnection;
+use self::tcp::{server::TcpServerConnection, TcpConnection};
 
 #[cfg(feature = "udp")]
-use self::udp::UdpConnection;
+use self::udp::{server::UdpServerConnection, UdpConnection};
+
+#[cfg(all(feature = "unix", unix))]
+use self::unix::{datagram::UnixDatagramConnection, UnixConnection};
 
 #[cfg(feature = "direct-serial")]
 use self::direct_serial::SerialConnection;
 
 use self::file::FileConnection;
 
+use self::mem::MemConnection;
+
+use self::stream::StreamConnection;
+
 #[cfg(feature = "signing")]
 use crate::SigningConfig;
 
 use crate::error::MessageReadError;
 use crate::error::MessageWriteError;
+use crate::link_stats::LinkStats;
 use crate::{
     connectable::ConnectionAddress, MAVLinkMessageRaw, MavFrame, MavHeader, MavlinkVersion, Message,
 };
 
+// time between two receive attempts of the default `recv_timeout` while no message is available
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 /// A MAVLink connection
 pub trait MavConnection<M: Message> {
     /// Receive a MAVLink message.
@@ -65,6 +93,32 @@ pub trait MavConnection<M: Message> {
     /// Returns any eror encounter while receiving or deserializing a message.
     fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError>;
 
+    /// Receive a MAVLink message, waiting at most `timeout` for data to arrive.
+    ///
+    /// The connections of this crate wait for data with a read timeout where the transport supports
+    /// one. Implementations that do not override this method poll `try_recv()` every few
+    /// milliseconds instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::WouldBlock`] or [`io::ErrorKind::TimedOut`] error if no
+    /// message arrived in time, or any other error encountered while receiving.
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.try_recv() {
+                Err(MessageReadError::Io(e))
+                    if e.kind() == io::ErrorKind::WouldBlock && Instant::now() < deadline =>
+                {
+                    thread::sleep(
+                        POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now())),
+                    );
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// Send a MAVLink message.
     ///
     /// # Errors
@@ -72,6 +126,20 @@ pub trait MavConnection<M: Message> {
     /// This function will return a [`MessageWriteError::Io`] error when sending fails.
     fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError>;
 
+    /// Send a raw MAVLink message as is.
+    ///
+    /// The message is neither re-serialized nor signed and keeps its original sequence number,
+    /// which makes this suitable for forwarding received messages.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`MessageWriteError::Io`] error when sending fails, or with
+    /// [`io::ErrorKind::Unsupported`] if the connection can not send raw messages.
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let _ = message;
+        Err(MessageWriteError::Io(io::ErrorKind::Unsupported.into()))
+    }
+
     /// Sets the MAVLink version to use for receiving (when `allow_recv_any_version()` is `false`) and sending messages.
     fn set_protocol_version(&mut self, version: MavlinkVersion);
     /// Gets the currently used MAVLink version
@@ -85,6 +153,16 @@ pub trait MavConnection<M: Message> {
     /// Wether messages of any MAVLink version may be received.
     fn allow_recv_any_version(&self) -> bool;
 
+    /// Snapshot of the receive statistics of this connection.
+    ///
+    /// The statistics are updated whenever a receive call returns, data discarded while
+    /// a call is still waiting for a valid message is accounted for once it returns.
+    ///
+    /// Connections that do not track statistics return empty statistics.
+    fn link_stats(&self) -> LinkStats {
+        LinkStats::default()
+    }
+
     /// Write whole frame.
     ///
     /// # Errors
@@ -133,11 +211,21 @@ pub struct Connection<M: Message> {
 enum ConnectionInner {
     #[cfg(feature = "tcp")]
     Tcp(TcpConnection),
+    #[cfg(feature = "tcp")]
+    TcpServer(TcpServerConnection),
     #[cfg(feature = "udp")]
     Udp(UdpConnection),
+    #[cfg(feature = "udp")]
+    UdpServer(UdpServerConnection),
+    #[cfg(all(feature = "unix", unix))]
+    Unix(UnixConnection),
+    #[cfg(all(feature = "unix", unix))]
+    UnixDatagram(UnixDatagramConnection),
     #[cfg(feature = "direct-serial")]
     Serial(SerialConnection),
     File(FileConnection),
+    Mem(MemConnection),
+    Stream(StreamConnection),
 }
 
 impl<M: Message> Connection<M> {
@@ -156,6 +244,13 @@ impl<M: Message> From<TcpConnection> for Connection<M> {
     }
 }
 
+#[cfg(feature = "tcp")]
+impl<M: Message> From<TcpServerConnection> for Connection<M> {
+    fn from(value: TcpServerConnection) -> Self {
+        Self::new(ConnectionInner::TcpServer(value))
+    }
+}
+
 #[cfg(feature = "udp")]
 impl<M: Message> From<UdpConnection> for Connection<M> {
     fn from(value: UdpConnection) -> Self {
@@ -163,6 +258,27 @@ impl<M: Message> From<UdpConnection> for Connection<M> {
     }
 }
 
+#[cfg(feature = "udp")]
+impl<M: Message> From<UdpServerConnection> for Connection<M> {
+    fn from(value: UdpServerConnection) -> Self {
+        Self::new(ConnectionInner::UdpServer(value))
+    }
+}
+
+#[cfg(all(feature = "unix", unix))]
+impl<M: Message> From<UnixConnection> for Connection<M> {
+    fn from(value: UnixConnection) -> Self {
+        Self::new(ConnectionInner::Unix(value))
+    }
+}
+
+#[cfg(all(feature = "unix", unix))]
+impl<M: Message> From<UnixDatagramConnection> for Connection<M> {
+    fn from(value: UnixDatagramConnection) -> Self {
+        Self::new(ConnectionInner::UnixDatagram(value))
+    }
+}
+
 #[cfg(feature = "direct-serial")]
 impl<M: Message> From<SerialConnection> for Connection<M> {
     fn from(value: SerialConnection) -> Self {
@@ -176,16 +292,44 @@ impl<M: Message> From<FileConnection> for Connection<M> {
     }
 }
 
+impl<M: Message> From<MemConnection> for Connection<M> {
+    fn from(value: MemConnection) -> Self {
+        Self::new(ConnectionInner::Mem(value))
+    }
+}
+
+impl<M: Message> From<StreamConnection> for Connection<M> {
+    fn from(value: StreamConnection) -> Self {
+        Self::new(ConnectionInner::Stream(value))
+    }
+}
+
 impl<M: Message> MavConnection<M> for Connection<M> {
     fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
         match &self.inner {
             #[cfg(feature = "tcp")]
             ConnectionInner::Tcp(conn) => <TcpConnection as MavConnection<M>>::recv(conn),
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::recv(conn)
+            }
             #[cfg(feature = "udp")]
             ConnectionInner::Udp(conn) => <UdpConnection as MavConnection<M>>::recv(conn),
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::recv(conn)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => <UnixConnection as MavConnection<M>>::recv(conn),
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::recv(conn)
+            }
             #[cfg(feature = "direct-serial")]
             ConnectionInner::Serial(conn) => <SerialConnection as MavConnection<M>>::recv(conn),
             ConnectionInner::File(conn) => <FileConnection as MavConnection<M>>::recv(conn),
+            ConnectionInner::Mem(conn) => <MemConnection as MavConnection<M>>::recv(conn),
+            ConnectionInner::Stream(conn) => <StreamConnection as MavConnection<M>>::recv(conn),
         }
     }
 
@@ -193,11 +337,27 @@ impl<M: Message> MavConnection<M> for Connection<M> {
         match &self.inner {
             #[cfg(feature = "tcp")]
             ConnectionInner::Tcp(conn) => <TcpConnection as MavConnection<M>>::recv_raw(conn),
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::recv_raw(conn)
+            }
             #[cfg(feature = "udp")]
             ConnectionInner::Udp(conn) => <UdpConnection as MavConnection<M>>::recv_raw(conn),
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::recv_raw(conn)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => <UnixConnection as MavConnection<M>>::recv_raw(conn),
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::recv_raw(conn)
+            }
             #[cfg(feature = "direct-serial")]
             ConnectionInner::Serial(conn) => <SerialConnection as MavConnection<M>>::recv_raw(conn),
             ConnectionInner::File(conn) => <FileConnection as MavConnection<M>>::recv_raw(conn),
+            ConnectionInner::Mem(conn) => <MemConnection as MavConnection<M>>::recv_raw(conn),
+            ConnectionInner::Stream(conn) => <StreamConnection as MavConnection<M>>::recv_raw(conn),
         }
     }
 
@@ -205,11 +365,69 @@ impl<M: Message> MavConnection<M> for Connection<M> {
         match &self.inner {
             #[cfg(feature = "tcp")]
             ConnectionInner::Tcp(conn) => <TcpConnection as MavConnection<M>>::try_recv(conn),
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::try_recv(conn)
+            }
             #[cfg(feature = "udp")]
             ConnectionInner::Udp(conn) => <UdpConnection as MavConnection<M>>::try_recv(conn),
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::try_recv(conn)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => <UnixConnection as MavConnection<M>>::try_recv(conn),
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::try_recv(conn)
+            }
             #[cfg(feature = "direct-serial")]
             ConnectionInner::Serial(conn) => <SerialConnection as MavConnection<M>>::try_recv(conn),
             ConnectionInner::File(conn) => <FileConnection as MavConnection<M>>::try_recv(conn),
+            ConnectionInner::Mem(conn) => <MemConnection as MavConnection<M>>::try_recv(conn),
+            ConnectionInner::Stream(conn) => <StreamConnection as MavConnection<M>>::try_recv(conn),
+        }
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        match &self.inner {
+            #[cfg(feature = "tcp")]
+            ConnectionInner::Tcp(conn) => {
+                <TcpConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
+            #[cfg(feature = "udp")]
+            ConnectionInner::Udp(conn) => {
+                <UdpConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => {
+                <UnixConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
+            #[cfg(feature = "direct-serial")]
+            ConnectionInner::Serial(conn) => {
+                <SerialConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
+            ConnectionInner::File(conn) => {
+                <FileConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
+            ConnectionInner::Mem(conn) => {
+                <MemConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
+            ConnectionInner::Stream(conn) => {
+                <StreamConnection as MavConnection<M>>::recv_timeout(conn, timeout)
+            }
         }
     }
 
@@ -219,10 +437,26 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::Tcp(conn) => {
                 <TcpConnection as MavConnection<M>>::send(conn, header, data)
             }
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::send(conn, header, data)
+            }
             #[cfg(feature = "udp")]
             ConnectionInner::Udp(conn) => {
                 <UdpConnection as MavConnection<M>>::send(conn, header, data)
             }
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::send(conn, header, data)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => {
+                <UnixConnection as MavConnection<M>>::send(conn, header, data)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::send(conn, header, data)
+            }
             #[cfg(feature = "direct-serial")]
             ConnectionInner::Serial(conn) => {
                 <SerialConnection as MavConnection<M>>::send(conn, header, data)
@@ -230,6 +464,54 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::File(conn) => {
                 <FileConnection as MavConnection<M>>::send(conn, header, data)
             }
+            ConnectionInner::Mem(conn) => {
+                <MemConnection as MavConnection<M>>::send(conn, header, data)
+            }
+            ConnectionInner::Stream(conn) => {
+                <StreamConnection as MavConnection<M>>::send(conn, header, data)
+            }
+        }
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        match &self.inner {
+            #[cfg(feature = "tcp")]
+            ConnectionInner::Tcp(conn) => {
+                <TcpConnection as MavConnection<M>>::send_raw(conn, message)
+            }
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::send_raw(conn, message)
+            }
+            #[cfg(feature = "udp")]
+            ConnectionInner::Udp(conn) => {
+                <UdpConnection as MavConnection<M>>::send_raw(conn, message)
+            }
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::send_raw(conn, message)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => {
+                <UnixConnection as MavConnection<M>>::send_raw(conn, message)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::send_raw(conn, message)
+            }
+            #[cfg(feature = "direct-serial")]
+            ConnectionInner::Serial(conn) => {
+                <SerialConnection as MavConnection<M>>::send_raw(conn, message)
+            }
+            ConnectionInner::File(conn) => {
+                <FileConnection as MavConnection<M>>::send_raw(conn, message)
+            }
+            ConnectionInner::Mem(conn) => {
+                <MemConnection as MavConnection<M>>::send_raw(conn, message)
+            }
+            ConnectionInner::Stream(conn) => {
+                <StreamConnection as MavConnection<M>>::send_raw(conn, message)
+            }
         }
     }
 
@@ -239,10 +521,26 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::Tcp(conn) => {
                 <TcpConnection as MavConnection<M>>::set_protocol_version(conn, version);
             }
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::set_protocol_version(conn, version);
+            }
             #[cfg(feature = "udp")]
             ConnectionInner::Udp(conn) => {
                 <UdpConnection as MavConnection<M>>::set_protocol_version(conn, version);
             }
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::set_protocol_version(conn, version);
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => {
+                <UnixConnection as MavConnection<M>>::set_protocol_version(conn, version);
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::set_protocol_version(conn, version);
+            }
             #[cfg(feature = "direct-serial")]
             ConnectionInner::Serial(conn) => {
                 <SerialConnection as MavConnection<M>>::set_protocol_version(conn, version);
@@ -250,6 +548,12 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::File(conn) => {
                 <FileConnection as MavConnection<M>>::set_protocol_version(conn, version);
             }
+            ConnectionInner::Mem(conn) => {
+                <MemConnection as MavConnection<M>>::set_protocol_version(conn, version);
+            }
+            ConnectionInner::Stream(conn) => {
+                <StreamConnection as MavConnection<M>>::set_protocol_version(conn, version);
+            }
         }
     }
 
@@ -259,10 +563,26 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::Tcp(conn) => {
                 <TcpConnection as MavConnection<M>>::protocol_version(conn)
             }
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::protocol_version(conn)
+            }
             #[cfg(feature = "udp")]
             ConnectionInner::Udp(conn) => {
                 <UdpConnection as MavConnection<M>>::protocol_version(conn)
             }
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::protocol_version(conn)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => {
+                <UnixConnection as MavConnection<M>>::protocol_version(conn)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::protocol_version(conn)
+            }
             #[cfg(feature = "direct-serial")]
             ConnectionInner::Serial(conn) => {
                 <SerialConnection as MavConnection<M>>::protocol_version(conn)
@@ -270,6 +590,12 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::File(conn) => {
                 <FileConnection as MavConnection<M>>::protocol_version(conn)
             }
+            ConnectionInner::Mem(conn) => {
+                <MemConnection as MavConnection<M>>::protocol_version(conn)
+            }
+            ConnectionInner::Stream(conn) => {
+                <StreamConnection as MavConnection<M>>::protocol_version(conn)
+            }
         }
     }
 
@@ -279,10 +605,28 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::Tcp(conn) => {
                 <TcpConnection as MavConnection<M>>::set_allow_recv_any_version(conn, allow);
             }
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::set_allow_recv_any_version(conn, allow);
+            }
             #[cfg(feature = "udp")]
             ConnectionInner::Udp(conn) => {
                 <UdpConnection as MavConnection<M>>::set_allow_recv_any_version(conn, allow);
             }
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::set_allow_recv_any_version(conn, allow);
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => {
+                <UnixConnection as MavConnection<M>>::set_allow_recv_any_version(conn, allow);
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::set_allow_recv_any_version(
+                    conn, allow,
+                );
+            }
             #[cfg(feature = "direct-serial")]
             ConnectionInner::Serial(conn) => {
                 <SerialConnection as MavConnection<M>>::set_allow_recv_any_version(conn, allow);
@@ -290,6 +634,12 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::File(conn) => {
                 <FileConnection as MavConnection<M>>::set_allow_recv_any_version(conn, allow);
             }
+            ConnectionInner::Mem(conn) => {
+                <MemConnection as MavConnection<M>>::set_allow_recv_any_version(conn, allow);
+            }
+            ConnectionInner::Stream(conn) => {
+                <StreamConnection as MavConnection<M>>::set_allow_recv_any_version(conn, allow);
+            }
         }
     }
 
@@ -299,10 +649,26 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::Tcp(conn) => {
                 <TcpConnection as MavConnection<M>>::allow_recv_any_version(conn)
             }
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::allow_recv_any_version(conn)
+            }
             #[cfg(feature = "udp")]
             ConnectionInner::Udp(conn) => {
                 <UdpConnection as MavConnection<M>>::allow_recv_any_version(conn)
             }
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::allow_recv_any_version(conn)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => {
+                <UnixConnection as MavConnection<M>>::allow_recv_any_version(conn)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::allow_recv_any_version(conn)
+            }
             #[cfg(feature = "direct-serial")]
             ConnectionInner::Serial(conn) => {
                 <SerialConnection as MavConnection<M>>::allow_recv_any_version(conn)
@@ -310,6 +676,44 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::File(conn) => {
                 <FileConnection as MavConnection<M>>::allow_recv_any_version(conn)
             }
+            ConnectionInner::Mem(conn) => {
+                <MemConnection as MavConnection<M>>::allow_recv_any_version(conn)
+            }
+            ConnectionInner::Stream(conn) => {
+                <StreamConnection as MavConnection<M>>::allow_recv_any_version(conn)
+            }
+        }
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        match &self.inner {
+            #[cfg(feature = "tcp")]
+            ConnectionInner::Tcp(conn) => <TcpConnection as MavConnection<M>>::link_stats(conn),
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::link_stats(conn)
+            }
+            #[cfg(feature = "udp")]
+            ConnectionInner::Udp(conn) => <UdpConnection as MavConnection<M>>::link_stats(conn),
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::link_stats(conn)
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => <UnixConnection as MavConnection<M>>::link_stats(conn),
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::link_stats(conn)
+            }
+            #[cfg(feature = "direct-serial")]
+            ConnectionInner::Serial(conn) => {
+                <SerialConnection as MavConnection<M>>::link_stats(conn)
+            }
+            ConnectionInner::File(conn) => <FileConnection as MavConnection<M>>::link_stats(conn),
+            ConnectionInner::Mem(conn) => <MemConnection as MavConnection<M>>::link_stats(conn),
+            ConnectionInner::Stream(conn) => {
+                <StreamConnection as MavConnection<M>>::link_stats(conn)
+            }
         }
     }
 
@@ -321,10 +725,29 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::Tcp(conn) => {
                 <TcpConnection as MavConnection<M>>::setup_signing(conn, signing_data.take());
             }
+            #[cfg(feature = "tcp")]
+            ConnectionInner::TcpServer(conn) => {
+                <TcpServerConnection as MavConnection<M>>::setup_signing(conn, signing_data.take());
+            }
             #[cfg(feature = "udp")]
             ConnectionInner::Udp(conn) => {
                 <UdpConnection as MavConnection<M>>::setup_signing(conn, signing_data.take());
             }
+            #[cfg(feature = "udp")]
+            ConnectionInner::UdpServer(conn) => {
+                <UdpServerConnection as MavConnection<M>>::setup_signing(conn, signing_data.take());
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => {
+                <UnixConnection as MavConnection<M>>::setup_signing(conn, signing_data.take());
+            }
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => {
+                <UnixDatagramConnection as MavConnection<M>>::setup_signing(
+                    conn,
+                    signing_data.take(),
+                );
+            }
             #[cfg(feature = "direct-serial")]
             ConnectionInner::Serial(conn) => {
                 <SerialConnection as MavConnection<M>>::setup_signing(conn, signing_data.take());
@@ -332,6 +755,12 @@ impl<M: Message> MavConnection<M> for Connection<M> {
             ConnectionInner::File(conn) => {
                 <FileConnection as MavConnection<M>>::setup_signing(conn, signing_data.take());
             }
+            ConnectionInner::Mem(conn) => {
+                <MemConnection as MavConnection<M>>::setup_signing(conn, signing_data.take());
+            }
+            ConnectionInner::Stream(conn) => {
+                <StreamConnection as MavConnection<M>>::setup_signing(conn, signing_data.take());
+            }
         }
     }
 }
@@ -342,11 +771,23 @@ impl<M: Message> MavConnection<M> for Connection<M> {
 ///
 ///  * `tcpin:<addr>:<port>` to create a TCP server, listening an incoming connection
 ///  * `tcpout:<addr>:<port>` to create a TCP client
+///  * `tcpserver:<addr>:<port>` to create a TCP server, accepting any number of clients
 ///  * `udpin:<addr>:<port>` to create a UDP server, listening for incoming packets
 ///  * `udpout:<addr>:<port>` to create a UDP client
+///  * `udpserver:<addr>:<port>` to create a UDP server, replying to all clients
 ///  * `udpbcast:<addr>:<port>` to create a UDP broadcast
+///  * `unixin:<path>` to create a Unix stream socket server, listening for an incoming connection
+///  * `unix:<path>` to connect to a Unix stream socket server
+///  * `unixgram:<path>` to create a Unix datagram socket, replying to the last sender
+///  * `ws://<host>:<port>/<path>` or `wss://...` to create a WebSocket client, only available as
+///    async connection
+///  * `wsserver:<addr>:<port>` to create a WebSocket server, accepting any number of clients, only
+///    available as async connection
 ///  * `serial:<port>:<baudrate>` to create a serial connection
 ///  * `file:<path>` to extract file data, writing to such a connection does nothing
+///  * `fileout:<path>` to record sent messages to a telemetry log, reading from such a connection fails
+///  * `filefollow:<path>` to read a file that is still being written, waiting for new data like `tail -f`
+///  * `mem:<name>` to create an in-process connection to the other connection made to the same name
 ///
 /// The type of the connection is determined at runtime based on the address type
 /// and the resulting [`Connection`] enum stores the concrete transport.
@@ -372,6 +813,205 @@ pub(crate) fn get_socket_addr<T: std::net::ToSocketAddrs>(
         .ok_or(io::Error::other("Host address lookup failed"))
 }
 
+/// Whether an I/O error of a receive call is transient, so that receiving can simply be retried
+///
+/// Besides timeouts and interruptions this includes `ConnectionRefused`, which UDP sockets report
+/// when an earlier datagram was answered with an ICMP port unreachable message.
+pub(crate) fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::ConnectionRefused
+    )
+}
+
+/// How long a write to a client may block before the client is considered stalled and dropped
+///
+/// Writes happen while all clients are locked, a client that stopped reading must not hold up the
+/// others for longer than that.
+#[cfg(any(feature = "tcp", feature = "tokio-1"))]
+pub(crate) const CLIENT_WRITE_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Number of reads from clients a server buffers before it stops reading from them
+///
+/// Once the buffer is full, clients are not read from until the server receives again, so TCP
+/// flow control slows them down instead of the buffer growing without bound.
+#[cfg(any(feature = "tcp", feature = "tokio-1"))]
+pub(crate) const CLIENT_READ_BUFFERS: usize = 64;
+
+/// Returns how long a server should wait before accepting again after accepting failed.
+///
+/// Errors that only concern the failed client, like a client that already disconnected again,
+/// are retried right away. Others, e.g. running out of file descriptors (`EMFILE`/`ENFILE`),
+/// persist until resources are released, retrying them immediately would spin.
+#[cfg(any(feature = "tcp", feature = "tokio-1"))]
+pub(crate) fn accept_retry_delay(error: &io::Error) -> std::time::Duration {
+    match error.kind() {
+        io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::Interrupted
+        | io::ErrorKind::WouldBlock => std::time::Duration::ZERO,
+        _ => std::time::Duration::from_millis(50),
+    }
+}
+
+/// A socket, or a reader of one, whose read timeout can be changed
+pub(crate) trait ReadTimeout {
+    fn read_timeout(&self) -> io::Result<Option<Duration>>;
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+#[cfg(any(feature = "tcp", feature = "udp", all(feature = "unix", unix)))]
+macro_rules! impl_read_timeout {
+    ($($socket:ty),*) => {$(
+        impl ReadTimeout for $socket {
+            fn read_timeout(&self) -> io::Result<Option<Duration>> {
+                <$socket>::read_timeout(self)
+            }
+
+            fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+                <$socket>::set_read_timeout(self, timeout)
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "tcp")]
+impl_read_timeout!(std::net::TcpStream);
+#[cfg(feature = "udp")]
+impl_read_timeout!(std::net::UdpSocket);
+#[cfg(all(feature = "unix", unix))]
+impl_read_timeout!(std::os::unix::net::UnixStream);
+
+#[cfg(feature = "udp")]
+impl<T: ReadTimeout + ?Sized> ReadTimeout for &T {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        (**self).read_timeout()
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        (**self).set_read_timeout(timeout)
+    }
+}
+
+/// Shortest read timeout, sockets reject a zero one
+const MIN_READ_TIMEOUT: Duration = Duration::from_micros(1);
+
+/// Reader whose reads wait at most a given time, the previous read timeout is restored when
+/// it is dropped
+///
+/// Only the reading side of a connection reads from its socket, so changing the timeout for the
+/// duration of a receive call does not affect any other call.
+#[cfg(feature = "udp")]
+pub(crate) struct TimeoutReader<'a, R: ReadTimeout> {
+    reader: &'a mut R,
+    previous: Option<Option<Duration>>,
+}
+
+#[cfg(feature = "udp")]
+impl<'a, R: ReadTimeout> TimeoutReader<'a, R> {
+    /// Limit the time each read waits to `timeout`, `None` leaves the read timeout as is
+    pub(crate) fn new(reader: &'a mut R, timeout: Option<Duration>) -> io::Result<Self> {
+        let previous = match timeout {
+            Some(timeout) => {
+                let previous = reader.read_timeout()?;
+                reader.set_read_timeout(Some(timeout.max(MIN_READ_TIMEOUT)))?;
+                Some(previous)
+            }
+            None => None,
+        };
+        Ok(Self { reader, previous })
+    }
+}
+
+#[cfg(feature = "udp")]
+impl<R: ReadTimeout> core::ops::Deref for TimeoutReader<'_, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "udp")]
+impl<R: ReadTimeout> core::ops::DerefMut for TimeoutReader<'_, R> {
+    fn deref_mut(&mut self) -> &mut R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "udp")]
+impl<R: ReadTimeout> Drop for TimeoutReader<'_, R> {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous {
+            self.reader.set_read_timeout(previous).ok();
+        }
+    }
+}
+
+/// Reader whose reads all end by a common deadline, if one is set
+///
+/// A single receive call may read many times, e.g. while skipping frames with a bad checksum, so
+/// the read timeout is set to the time left before each read. Once the deadline has passed, data
+/// that arrived already can still be read up to the size of a frame, so a frame that is complete
+/// is received, after which reads fail with [`io::ErrorKind::WouldBlock`].
+pub(crate) struct DeadlineReader<R> {
+    reader: R,
+    deadline: Option<Instant>,
+    /// Read timeout to restore once the deadline is cleared
+    previous: Option<Duration>,
+    /// Bytes that may still be read after the deadline passed
+    overdue: usize,
+}
+
+impl<R: ReadTimeout> DeadlineReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            deadline: None,
+            previous: None,
+            overdue: 0,
+        }
+    }
+
+    /// Let reads wait until `deadline` at most, `None` restores the previous read timeout
+    pub(crate) fn set_deadline(&mut self, deadline: Option<Instant>) -> io::Result<()> {
+        if self.deadline.is_none() && deadline.is_some() {
+            self.previous = self.reader.read_timeout()?;
+        }
+        let had_deadline = core::mem::replace(&mut self.deadline, deadline).is_some();
+        self.overdue = crate::MAX_FRAME_SIZE;
+        if had_deadline && deadline.is_none() {
+            self.reader.set_read_timeout(self.previous)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: io::Read + ReadTimeout> io::Read for DeadlineReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(deadline) = self.deadline else {
+            return self.reader.read(buf);
+        };
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !remaining.is_zero() {
+            self.reader.set_read_timeout(Some(remaining))?;
+            return self.reader.read(buf);
+        }
+        if self.overdue == 0 {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        self.reader.set_read_timeout(Some(MIN_READ_TIMEOUT))?;
+        let len = buf.len().min(self.overdue);
+        let n = self.reader.read(&mut buf[..len])?;
+        self.overdue -= n;
+        Ok(n)
+    }
+}
+
 /// A MAVLink connection address that can be connected to, establishing a [`MavConnection`]
 pub trait Connectable: Display {
     /// Attempt to establish a blocking MAVLink connection
@@ -393,9 +1033,17 @@ impl Connectable for ConnectionAddress {
             Self::Tcp(config) => config.connect::<M>(),
             #[cfg(feature = "udp")]
             Self::Udp(config) => config.connect::<M>(),
+            #[cfg(all(feature = "unix", unix))]
+            Self::Unix(config) => config.connect::<M>(),
+            #[cfg(feature = "websocket")]
+            Self::WebSocket(config) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{config} is only available as async connection"),
+            )),
             #[cfg(feature = "direct-serial")]
             Self::Serial(config) => config.connect::<M>(),
             Self::File(config) => config.connect::<M>(),
+            Self::Mem(config) => config.connect::<M>(),
         }
     }
 }
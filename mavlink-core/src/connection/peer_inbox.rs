@@ -0,0 +1,112 @@
+//! Framing of data received from multiple peers over a single connection
+
+use core::mem;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read};
+use std::net::SocketAddr;
+
+use crate::error::MessageReadError;
+use crate::link_stats::SharedLinkStats;
+use crate::peek_reader::PeekReader;
+use crate::{MAVLinkMessageRaw, MavHeader, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::read_versioned_raw_message;
+#[cfg(feature = "signing")]
+use crate::{read_versioned_raw_message_signed, SigningData};
+
+/// Data received from a peer
+pub(crate) enum PeerEvent {
+    Data(SocketAddr, Vec<u8>),
+    Disconnected(SocketAddr),
+}
+
+/// Peer data that does not contain a complete frame yet
+#[derive(Default)]
+struct PeerBuffer(VecDeque<u8>);
+
+impl Read for PeerBuffer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() {
+            // the reader resumes at the same position once more data has been received
+            Err(io::ErrorKind::WouldBlock.into())
+        } else {
+            self.0.read(buf)
+        }
+    }
+}
+
+/// Frames the data received from the peers of a server
+///
+/// The data of every peer is buffered separately, so frames of different peers are never
+/// mixed up. It is read with the regular `read_*` functions and accounted for in the statistics of
+/// the server.
+#[derive(Default)]
+pub(crate) struct PeerInbox {
+    peers: HashMap<SocketAddr, PeekReader<PeerBuffer>>,
+    // peers whose buffered data has not been searched for frames yet
+    pending: VecDeque<SocketAddr>,
+}
+
+impl PeerInbox {
+    pub(crate) fn handle(&mut self, event: PeerEvent) {
+        match event {
+            PeerEvent::Data(address, data) => {
+                self.peers
+                    .entry(address)
+                    .or_insert_with(|| PeekReader::new(PeerBuffer::default()))
+                    .reader_mut()
+                    .0
+                    .extend(data);
+                if !self.pending.contains(&address) {
+                    self.pending.push_back(address);
+                }
+            }
+            PeerEvent::Disconnected(address) => {
+                self.peers.remove(&address);
+                self.pending.retain(|pending| *pending != address);
+            }
+        }
+    }
+
+    /// Read the next complete frame of any peer
+    pub(crate) fn next_frame<M: Message>(
+        &mut self,
+        version: ReadVersion,
+        #[cfg(feature = "signing")] signing_data: Option<&SigningData>,
+        stats: &SharedLinkStats,
+    ) -> Option<(SocketAddr, MAVLinkMessageRaw)> {
+        while let Some(address) = self.pending.pop_front() {
+            let Some(reader) = self.peers.get_mut(&address) else {
+                continue;
+            };
+            #[cfg(not(feature = "signing"))]
+            let result = read_versioned_raw_message::<M, _>(reader, version);
+            #[cfg(feature = "signing")]
+            let result = read_versioned_raw_message_signed::<M, _>(reader, version, signing_data);
+
+            stats.record_delta(mem::take(&mut reader.stats), &result);
+
+            // the only possible error is running out of buffered data
+            if let Ok(frame) = result {
+                // more frames may be buffered, continue with the other peers first though
+                self.pending.push_back(address);
+                return Some((address, frame));
+            }
+        }
+        None
+    }
+}
+
+/// Parse a raw message into its header and message
+pub(crate) fn parse_raw<M: Message>(
+    raw: &MAVLinkMessageRaw,
+) -> Result<(MavHeader, M), MessageReadError> {
+    let msg = M::parse(raw.version(), raw.message_id(), raw.payload())?;
+    let header = MavHeader {
+        sequence: raw.sequence(),
+        system_id: raw.system_id(),
+        component_id: raw.component_id(),
+    };
+    Ok((header, msg))
+}
@@ -0,0 +1,181 @@
+//! Re-establishing lost TCP and serial links
+
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Policy for re-establishing a connection after its link was lost
+///
+/// Connections created from a config with a reconnect policy re-open their link when an I/O error
+/// shows that it is gone, e.g. because the TCP peer closed the connection or a USB serial adapter
+/// was unplugged. The failed operation is retried on the new link, so the connection keeps its
+/// protocol version, version acceptance and signing state. Non-blocking calls like `try_recv` do
+/// not wait for a new link, they return the error and leave re-establishing the link to the next
+/// blocking call.
+///
+/// Between failed attempts the policy waits for a backoff time that starts at
+/// [`initial_backoff`](Self::initial_backoff) and doubles up to [`max_backoff`](Self::max_backoff).
+///
+/// # Example
+///
+/// ```ignore
+/// use std::time::Duration;
+/// use mavlink::{Connectable, ReconnectPolicy, TcpConfig, TcpMode};
+///
+/// let config = TcpConfig::new("127.0.0.1:5760".to_owned(), TcpMode::TcpOut)
+///     .reconnect(ReconnectPolicy::new().max_attempts(10));
+/// config.connect::<mavlink::ardupilotmega::MavMessage>();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Creates a policy retrying forever, starting with a backoff of 100 ms up to 5 seconds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the time waited after the first failed attempt
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Sets the longest time waited between two attempts
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Sets the number of attempts after which the error of the last attempt is returned
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    fn retry<T>(&self, mut open: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut backoff = self.initial_backoff;
+        let mut attempts = 0;
+        loop {
+            let error = match open() {
+                Ok(link) => return Ok(link),
+                Err(e) => e,
+            };
+            attempts += 1;
+            if self.max_attempts.is_some_and(|max| attempts >= max) {
+                return Err(error);
+            }
+            thread::sleep(backoff);
+            backoff = (backoff * 2).min(self.max_backoff);
+        }
+    }
+}
+
+/// Whether an I/O error means that the link is gone, rather than e.g. just having no data available
+///
+/// Only errors known to be caused by a lost link are considered, others like an invalid
+/// configuration would most likely persist when the link is re-established.
+pub(crate) fn is_link_lost(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::NotConnected
+    ) || is_device_gone(error)
+}
+
+/// Whether an I/O error is the one reported by a serial device that has been unplugged
+fn is_device_gone(error: &io::Error) -> bool {
+    // EIO, ENXIO and ENODEV
+    #[cfg(unix)]
+    const DEVICE_GONE: [i32; 3] = [5, 6, 19];
+    // ERROR_BAD_COMMAND, ERROR_GEN_FAILURE and ERROR_DEVICE_NOT_CONNECTED
+    #[cfg(windows)]
+    const DEVICE_GONE: [i32; 3] = [22, 31, 1167];
+    #[cfg(not(any(unix, windows)))]
+    const DEVICE_GONE: [i32; 0] = [];
+
+    error
+        .raw_os_error()
+        .is_some_and(|code| DEVICE_GONE.contains(&code))
+}
+
+/// Reconnection state of a connection created from the config `C`
+pub(crate) struct Reconnect<C> {
+    pub(crate) config: C,
+    policy: ReconnectPolicy,
+    generation: AtomicU32,
+    lock: Mutex<()>,
+    // generation of the link that a non-blocking call found to be lost
+    lost: Mutex<Option<u32>>,
+}
+
+impl<C> Reconnect<C> {
+    pub(crate) fn new(config: C, policy: ReconnectPolicy) -> Self {
+        Self {
+            config,
+            policy,
+            generation: AtomicU32::new(0),
+            lock: Mutex::new(()),
+            lost: Mutex::new(None),
+        }
+    }
+
+    /// Number of times the link was re-established
+    pub(crate) fn generation(&self) -> u32 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Remember that the link of `generation` is lost if `error` shows so
+    ///
+    /// Used by non-blocking calls, which must not wait for the link to be re-established. That is
+    /// left to the next blocking call, see [`is_marked_lost`](Self::is_marked_lost).
+    pub(crate) fn mark_lost(&self, error: &io::Error, generation: u32) {
+        if is_link_lost(error) {
+            *self.lost.lock().unwrap() = Some(generation);
+        }
+    }
+
+    /// Whether the current link was marked as lost and still has to be re-established
+    pub(crate) fn is_marked_lost(&self) -> bool {
+        *self.lost.lock().unwrap() == Some(self.generation())
+    }
+
+    /// Re-establish the link with `open` and hand it to `replace`
+    ///
+    /// Nothing is done if the link was already re-established since `generation` was observed,
+    /// so concurrent readers and writers noticing the same lost link only reconnect once.
+    pub(crate) fn reconnect<L>(
+        &self,
+        generation: u32,
+        mut open: impl FnMut(&C) -> io::Result<L>,
+        replace: impl FnOnce(L),
+    ) -> io::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        if self.generation() != generation {
+            return Ok(());
+        }
+        let link = self.policy.retry(|| open(&self.config))?;
+        replace(link);
+        self.generation.fetch_add(1, Ordering::Release);
+        Ok(())
+    }
+}
@@ -0,0 +1,513 @@
+//! Receiving and sending halves of a connection
+
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{Connection, ConnectionInner, DeadlineReader, MavConnection, ReadTimeout};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use crate::peek_reader::PeekReader;
+use crate::{MAVLinkMessageRaw, MavFrame, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::{read_versioned_msg, read_versioned_raw_message, write_versioned_msg};
+#[cfg(feature = "signing")]
+use crate::{
+    read_versioned_msg_signed, read_versioned_raw_message_signed, write_versioned_msg_signed,
+    SigningConfig, SigningData,
+};
+
+/// Reading side of a connection, owned by a [`MavReceiver`] once the connection is split
+pub(crate) struct ReadHalf<R> {
+    reader: Mutex<PeekReader<DeadlineReader<R>>>,
+    stats: SharedLinkStats,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<R: Read + ReadTimeout> ReadHalf<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader: Mutex::new(PeekReader::new(DeadlineReader::new(reader))),
+            stats: SharedLinkStats::default(),
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+
+    fn version(&self) -> ReadVersion {
+        if self.recv_any_version {
+            ReadVersion::Any
+        } else {
+            self.protocol_version.into()
+        }
+    }
+
+    fn read_msg<M: Message>(
+        &self,
+        reader: &mut PeekReader<DeadlineReader<R>>,
+    ) -> Result<(MavHeader, M), MessageReadError> {
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_msg(reader, self.version());
+        #[cfg(feature = "signing")]
+        let result =
+            read_versioned_msg_signed(reader, self.version(), self.signing_data.as_deref());
+        self.stats.record(reader.read_stats(), &result);
+        result
+    }
+
+    pub(crate) fn recv<M: Message>(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.read_msg(&mut self.reader.lock().unwrap())
+    }
+
+    pub(crate) fn recv_raw<M: Message>(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let mut reader = self.reader.lock().unwrap();
+        #[cfg(not(feature = "signing"))]
+        let result = read_versioned_raw_message::<M, _>(&mut reader, self.version());
+        #[cfg(feature = "signing")]
+        let result = read_versioned_raw_message_signed::<M, _>(
+            &mut reader,
+            self.version(),
+            self.signing_data.as_deref(),
+        );
+        self.stats.record(reader.read_stats(), &result);
+        result
+    }
+
+    pub(crate) fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    /// Read from a new reader, e.g. after the link was re-established, keeping the read counters
+    #[cfg(feature = "tcp")]
+    pub(crate) fn replace(&self, reader: R) {
+        let mut current = self.reader.lock().unwrap();
+        let stats = current.stats;
+        *current = PeekReader::new(DeadlineReader::new(reader));
+        current.stats = stats;
+    }
+
+    pub(crate) fn recv_timeout<M: Message>(
+        &self,
+        timeout: Duration,
+    ) -> Result<(MavHeader, M), MessageReadError> {
+        let mut reader = self.reader.lock().unwrap();
+        // a timeout too long to be represented waits indefinitely
+        reader
+            .reader_mut()
+            .set_deadline(Instant::now().checked_add(timeout))?;
+        let result = self.read_msg(&mut reader);
+        reader.reader_mut().set_deadline(None)?;
+        result
+    }
+
+    /// Receive a message if one has arrived already
+    ///
+    /// Instead of switching the socket to nonblocking mode, which would also apply to the clone
+    /// of it the writing half uses, the reads wait for the shortest possible read timeout.
+    pub(crate) fn try_recv<M: Message>(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_timeout(Duration::ZERO)
+    }
+}
+
+/// Destination of serialized frames
+pub(crate) trait FrameWrite {
+    /// Write a whole frame, returning the number of bytes sent
+    fn write_frame(&mut self, frame: &[u8]) -> io::Result<usize>;
+}
+
+impl<W: Write> FrameWrite for W {
+    fn write_frame(&mut self, frame: &[u8]) -> io::Result<usize> {
+        self.write_all(frame)?;
+        self.flush()?;
+        Ok(frame.len())
+    }
+}
+
+struct SequencedWrite<W> {
+    writer: W,
+    sequence: u8,
+}
+
+/// Writing side of a connection, owned by a [`MavSender`] once the connection is split
+pub(crate) struct WriteHalf<W> {
+    writer: Mutex<SequencedWrite<W>>,
+    protocol_version: MavlinkVersion,
+    #[cfg(feature = "signing")]
+    signing_data: Option<Arc<SigningData>>,
+}
+
+impl<W: FrameWrite> WriteHalf<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(SequencedWrite {
+                writer,
+                sequence: 0,
+            }),
+            protocol_version: MavlinkVersion::V2,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+
+    pub(crate) fn send<M: Message>(
+        &self,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, MessageWriteError> {
+        let mut lock = self.writer.lock().unwrap();
+
+        let header = MavHeader {
+            sequence: lock.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+
+        lock.sequence = lock.sequence.wrapping_add(1);
+
+        let mut frame = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut frame, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut frame,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_deref(),
+        )?;
+        Ok(lock.writer.write_frame(&frame)?)
+    }
+
+    pub(crate) fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let mut lock = self.writer.lock().unwrap();
+        Ok(lock.writer.write_frame(message.raw_bytes())?)
+    }
+
+    /// Write to a new writer, e.g. after the link was re-established
+    #[cfg(feature = "tcp")]
+    pub(crate) fn replace(&self, writer: W) {
+        self.writer.lock().unwrap().writer = writer;
+    }
+}
+
+/// Connection over separate reading and writing halves, which [`Connection::split`] hands to
+/// the [`MavReceiver`] and the [`MavSender`]
+pub(crate) struct Duplex<R, W> {
+    pub(crate) read: ReadHalf<R>,
+    pub(crate) write: WriteHalf<W>,
+}
+
+impl<R: Read + ReadTimeout, W: FrameWrite> Duplex<R, W> {
+    pub(crate) fn new(reader: R, writer: W) -> Self {
+        Self {
+            read: ReadHalf::new(reader),
+            write: WriteHalf::new(writer),
+        }
+    }
+
+    pub(crate) fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.read.protocol_version = version;
+        self.write.protocol_version = version;
+    }
+
+    pub(crate) fn protocol_version(&self) -> MavlinkVersion {
+        self.write.protocol_version
+    }
+
+    pub(crate) fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.read.recv_any_version = allow;
+    }
+
+    pub(crate) fn allow_recv_any_version(&self) -> bool {
+        self.read.recv_any_version
+    }
+
+    #[cfg(feature = "signing")]
+    pub(crate) fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        // both halves keep using the same signing state, e.g. the timestamp
+        let signing_data = signing_data.map(|config| Arc::new(SigningData::from_config(config)));
+        self.read.signing_data.clone_from(&signing_data);
+        self.write.signing_data = signing_data;
+    }
+}
+
+/// What a [`MavReceiver`] receives from
+trait Receiving<M: Message>: Send + Sync {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError>;
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError>;
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError>;
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError>;
+    fn link_stats(&self) -> LinkStats;
+}
+
+/// What a [`MavSender`] sends to
+trait Sending<M: Message>: Send + Sync {
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError>;
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError>;
+}
+
+impl<M: Message, R: Read + ReadTimeout + Send> Receiving<M> for ReadHalf<R> {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        Self::recv(self)
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        Self::recv_raw::<M>(self)
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        Self::try_recv(self)
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        Self::recv_timeout(self, timeout)
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        Self::link_stats(self)
+    }
+}
+
+impl<M: Message, W: FrameWrite + Send> Sending<M> for WriteHalf<W> {
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        Self::send(self, header, data)
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        Self::send_raw(self, message)
+    }
+}
+
+/// Connections that can not be divided are shared by both halves
+impl<M: Message + Send + Sync> Receiving<M> for Connection<M> {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        MavConnection::recv(self)
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        MavConnection::recv_raw(self)
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        MavConnection::try_recv(self)
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        MavConnection::recv_timeout(self, timeout)
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        MavConnection::link_stats(self)
+    }
+}
+
+impl<M: Message + Send + Sync> Sending<M> for Connection<M> {
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        MavConnection::send(self, header, data)
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        MavConnection::send_raw(self, message)
+    }
+}
+
+/// Receiving half of a [`Connection`], created by [`Connection::split`]
+pub struct MavReceiver<M: Message> {
+    receiver: Arc<dyn Receiving<M>>,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+}
+
+impl<M: Message> MavReceiver<M> {
+    /// Receive a MAVLink message, see [`MavConnection::recv`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error that occured while receiving.
+    pub fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.receiver.recv()
+    }
+
+    /// Receive a raw, unparsed MAVLink message, see [`MavConnection::recv_raw`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error that occured while receiving.
+    pub fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.receiver.recv_raw()
+    }
+
+    /// Try to receive a MAVLink message without blocking, see [`MavConnection::try_recv`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error encountered while receiving or deserializing a message.
+    pub fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.receiver.try_recv()
+    }
+
+    /// Receive a MAVLink message waiting at most `timeout`, see [`MavConnection::recv_timeout`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error encountered while receiving or deserializing a message.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Read whole frame, see [`MavConnection::recv_frame`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error encountered while receiving or deserializing a message.
+    pub fn recv_frame(&self) -> Result<MavFrame<M>, MessageReadError> {
+        let (header, msg) = self.recv()?;
+        Ok(MavFrame {
+            header,
+            msg,
+            protocol_version: self.protocol_version,
+        })
+    }
+
+    /// Gets the currently used MAVLink version
+    pub fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    /// Wether messages of any MAVLink version may be received.
+    pub fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    /// Snapshot of the receive statistics of the connection
+    pub fn link_stats(&self) -> LinkStats {
+        self.receiver.link_stats()
+    }
+}
+
+/// Sending half of a [`Connection`], created by [`Connection::split`]
+///
+/// The sender can be cloned to send from several threads, all clones share the sequence number
+/// and signing state of the connection.
+pub struct MavSender<M: Message> {
+    sender: Arc<dyn Sending<M>>,
+    protocol_version: MavlinkVersion,
+}
+
+impl<M: Message> Clone for MavSender<M> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            protocol_version: self.protocol_version,
+        }
+    }
+}
+
+impl<M: Message> MavSender<M> {
+    /// Send a MAVLink message, see [`MavConnection::send`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`MessageWriteError::Io`] error when sending fails.
+    pub fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        self.sender.send(header, data)
+    }
+
+    /// Send a raw MAVLink message as is, see [`MavConnection::send_raw`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`MessageWriteError::Io`] error when sending fails.
+    pub fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.sender.send_raw(message)
+    }
+
+    /// Write whole frame, see [`MavConnection::send_frame`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`MessageWriteError::Io`] error when sending fails.
+    pub fn send_frame(&self, frame: &MavFrame<M>) -> Result<usize, MessageWriteError> {
+        self.send(&frame.header, &frame.msg)
+    }
+
+    /// Send a message with default header, see [`MavConnection::send_default`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`MessageWriteError::Io`] error when sending fails.
+    pub fn send_default(&self, data: &M) -> Result<usize, MessageWriteError> {
+        self.send(&MavHeader::default(), data)
+    }
+
+    /// Gets the currently used MAVLink version
+    pub fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+}
+
+type Halves<M> = (Arc<dyn Receiving<M>>, Arc<dyn Sending<M>>);
+
+fn divided<M, R, W>(duplex: Duplex<R, W>) -> Halves<M>
+where
+    M: Message,
+    ReadHalf<R>: Receiving<M> + 'static,
+    WriteHalf<W>: Sending<M> + 'static,
+{
+    (Arc::new(duplex.read), Arc::new(duplex.write))
+}
+
+fn shared<M: Message + Send + Sync + 'static>(inner: ConnectionInner) -> Halves<M> {
+    let connection = Arc::new(Connection::new(inner));
+    (connection.clone(), connection)
+}
+
+impl<M: Message + Send + Sync + 'static> Connection<M> {
+    /// Split the connection into a receiving and a sending half
+    ///
+    /// The halves can be moved to different threads. Connections over a single stream or
+    /// socket hand their reading side to the [`MavReceiver`] and their writing side to the
+    /// [`MavSender`], so a thread blocked in [`MavReceiver::recv`] does not hold up a thread
+    /// sending messages. Servers, serial ports, files, in-memory connections and connections
+    /// that re-establish their link are shared by both halves, which still receive and send
+    /// under separate locks.
+    ///
+    /// Both halves keep using the protocol version and signing state of the connection, which
+    /// therefore have to be configured before splitting it.
+    pub fn split(self) -> (MavReceiver<M>, MavSender<M>) {
+        let protocol_version = self.protocol_version();
+        let recv_any_version = self.allow_recv_any_version();
+        let (receiver, sender) = match self.inner {
+            #[cfg(feature = "tcp")]
+            ConnectionInner::Tcp(conn) => match conn.into_duplex() {
+                Ok(duplex) => divided(duplex),
+                Err(conn) => shared(ConnectionInner::Tcp(*conn)),
+            },
+            #[cfg(feature = "udp")]
+            ConnectionInner::Udp(conn) => divided(conn.into_duplex()),
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::Unix(conn) => divided(conn.into_duplex()),
+            #[cfg(all(feature = "unix", unix))]
+            ConnectionInner::UnixDatagram(conn) => divided(conn.into_duplex()),
+            ConnectionInner::Stream(conn) => divided(conn.into_duplex()),
+            inner => shared(inner),
+        };
+        (
+            MavReceiver {
+                receiver,
+                protocol_version,
+                recv_any_version,
+            },
+            MavSender {
+                sender,
+                protocol_version,
+            },
+        )
+    }
+}
@@ -0,0 +1,155 @@
+//! MAVLink connection over any byte stream
+
+use crate::connection::split::Duplex;
+use crate::connection::{MavConnection, ReadTimeout};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::LinkStats;
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message};
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "signing")]
+use crate::SigningConfig;
+
+/// Half of a stream used for both reading and writing
+struct SharedStream<S>(Arc<Mutex<S>>);
+
+impl<S: Read> Read for SharedStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().read(buf)
+    }
+}
+
+impl<S: Write> Write for SharedStream<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.lock().unwrap().flush()
+    }
+}
+
+/// MAVLink connection over an arbitrary [`Read`] + [`Write`] stream
+///
+/// This allows running MAVLink over transports the crate does not know about, like pseudo
+/// terminals, TLS streams or SSH channels. The connection can be turned into a [`Connection`] to
+/// be used wherever the built-in transports are.
+///
+/// A stream of unknown type can not be told to stop waiting for data, so
+/// [`MavConnection::try_recv`] and [`MavConnection::recv_timeout`] fail with
+/// [`io::ErrorKind::Unsupported`] instead of blocking.
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::{Connection, MavConnection, StreamConnection};
+///
+/// let (reader, writer) = open_channel()?;
+/// let connection: Connection<mavlink::common::MavMessage> =
+///     StreamConnection::from_halves(reader, writer).into();
+/// ```
+///
+/// [`Connection`]: crate::Connection
+pub struct StreamConnection {
+    duplex: Duplex<Box<dyn Read + Send>, Box<dyn Write + Send>>,
+}
+
+impl StreamConnection {
+    /// Create a connection over a single stream
+    ///
+    /// Reads and writes share the stream, so a read waiting for data delays every send until
+    /// data arrives. Prefer [`StreamConnection::from_halves`] for streams that can be split or
+    /// cloned, this is meant for those that can not, like TLS streams, SSH channels or the
+    /// master side of a pseudo terminal.
+    pub fn new<S: Read + Write + Send + 'static>(stream: S) -> Self {
+        let stream = Arc::new(Mutex::new(stream));
+        Self::from_halves(SharedStream(stream.clone()), SharedStream(stream))
+    }
+
+    /// Create a connection from separate reading and writing halves of a stream
+    ///
+    /// Sending must not wait for a read in progress, so the halves have to be usable
+    /// independently, e.g. by cloning the handle of the stream with `try_clone`.
+    pub fn from_halves<R, W>(reader: R, writer: W) -> Self
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        Self {
+            duplex: Duplex::new(Box::new(reader), Box::new(writer)),
+        }
+    }
+
+    pub(crate) fn into_duplex(self) -> Duplex<Box<dyn Read + Send>, Box<dyn Write + Send>> {
+        self.duplex
+    }
+}
+
+impl<M: Message> MavConnection<M> for StreamConnection {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.recv()
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.duplex.read.recv_raw::<M>()
+    }
+
+    /// Not supported, fails with [`io::ErrorKind::Unsupported`] unless a message is buffered
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.try_recv()
+    }
+
+    /// Not supported, fails with [`io::ErrorKind::Unsupported`] unless a message is buffered
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.recv_timeout(timeout)
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        self.duplex.write.send(header, data)
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.duplex.write.send_raw(message)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.duplex.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.duplex.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.duplex.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.duplex.allow_recv_any_version()
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.duplex.read.link_stats()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.duplex.setup_signing(signing_data);
+    }
+}
+
+/// A stream of unknown type can not be told to stop waiting for data
+impl ReadTimeout for Box<dyn Read + Send> {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        Ok(None)
+    }
+
+    fn set_read_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "stream connections can not stop waiting for data",
+        ))
+    }
+}
@@ -1,193 +1,229 @@
 //! TCP MAVLink connection
 
-use crate::connection::get_socket_addr;
+use crate::connection::reconnect::{is_link_lost, Reconnect};
+use crate::connection::split::{Duplex, ReadHalf, WriteHalf};
+use crate::connection::{accept_retry_delay, get_socket_addr};
 use crate::connection::{Connection, MavConnection};
-use crate::peek_reader::PeekReader;
-#[cfg(not(feature = "signing"))]
-use crate::read_versioned_raw_message;
-#[cfg(feature = "signing")]
-use crate::read_versioned_raw_message_signed;
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::LinkStats;
 use crate::Connectable;
 use crate::MAVLinkMessageRaw;
-use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
-use core::ops::DerefMut;
+use crate::{MavHeader, MavlinkVersion, Message};
 use std::io;
 use std::net::ToSocketAddrs;
 use std::net::{TcpListener, TcpStream};
-use std::sync::Mutex;
+use std::thread;
 use std::time::Duration;
 
-#[cfg(not(feature = "signing"))]
-use crate::{read_versioned_msg, write_versioned_msg};
-
 #[cfg(feature = "signing")]
-use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningConfig, SigningData};
+use crate::SigningConfig;
 
 pub mod config;
+pub mod server;
 
 use config::{TcpConfig, TcpMode};
+use server::TcpServerConnection;
 
-pub fn tcpout<T: ToSocketAddrs>(address: T) -> io::Result<TcpConnection> {
+fn connect_stream<T: ToSocketAddrs>(address: T) -> io::Result<TcpStream> {
     let addr = get_socket_addr(&address)?;
 
     let socket = TcpStream::connect(addr)?;
     socket.set_read_timeout(Some(Duration::from_millis(100)))?;
-
-    Ok(TcpConnection {
-        reader: Mutex::new(PeekReader::new(socket.try_clone()?)),
-        writer: Mutex::new(TcpWrite {
-            socket,
-            sequence: 0,
-        }),
-        protocol_version: MavlinkVersion::V2,
-        recv_any_version: false,
-        #[cfg(feature = "signing")]
-        signing_data: None,
-    })
+    Ok(socket)
 }
 
-pub fn tcpin<T: ToSocketAddrs>(address: T) -> io::Result<TcpConnection> {
+fn accept_stream<T: ToSocketAddrs>(address: T) -> io::Result<TcpStream> {
     let addr = get_socket_addr(&address)?;
     let listener = TcpListener::bind(addr)?;
 
-    //For now we only accept one incoming stream: this blocks until we get one
-    for incoming in listener.incoming() {
-        match incoming {
-            Ok(socket) => {
-                return Ok(TcpConnection {
-                    reader: Mutex::new(PeekReader::new(socket.try_clone()?)),
-                    writer: Mutex::new(TcpWrite {
-                        socket,
-                        sequence: 0,
-                    }),
-                    protocol_version: MavlinkVersion::V2,
-                    recv_any_version: false,
-                    #[cfg(feature = "signing")]
-                    signing_data: None,
-                })
-            }
-            Err(e) => {
-                //TODO don't println in lib
-                println!("listener err: {e}");
-            }
+    // only a single incoming stream is accepted, use `tcpserver` for multiple clients
+    loop {
+        match listener.accept() {
+            Ok((socket, _)) => return Ok(socket),
+            Err(e) => thread::sleep(accept_retry_delay(&e)),
         }
     }
-    Err(io::Error::new(
-        io::ErrorKind::NotConnected,
-        "No incoming connections!",
-    ))
 }
 
 pub struct TcpConnection {
-    reader: Mutex<PeekReader<TcpStream>>,
-    writer: Mutex<TcpWrite>,
-    protocol_version: MavlinkVersion,
-    recv_any_version: bool,
-    #[cfg(feature = "signing")]
-    signing_data: Option<SigningData>,
+    duplex: Duplex<TcpStream, TcpStream>,
+    reconnect: Option<Reconnect<TcpConfig>>,
 }
 
-struct TcpWrite {
-    socket: TcpStream,
-    sequence: u8,
-}
+impl TcpConnection {
+    fn new(socket: TcpStream, reconnect: Option<Reconnect<TcpConfig>>) -> io::Result<Self> {
+        Ok(Self {
+            duplex: Duplex::new(socket.try_clone()?, socket),
+            reconnect,
+        })
+    }
 
-impl<M: Message> MavConnection<M> for TcpConnection {
-    fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
-        #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg(reader.deref_mut(), version);
-        #[cfg(feature = "signing")]
-        let result =
-            read_versioned_msg_signed(reader.deref_mut(), version, self.signing_data.as_ref());
-        result
+    /// The halves of the connection, unless they have to be replaced together on reconnect
+    pub(crate) fn into_duplex(self) -> Result<Duplex<TcpStream, TcpStream>, Box<Self>> {
+        match self.reconnect {
+            None => Ok(self.duplex),
+            Some(_) => Err(Box::new(self)),
+        }
     }
 
-    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
-        #[cfg(not(feature = "signing"))]
-        let result = read_versioned_raw_message::<M, _>(reader.deref_mut(), version);
-        #[cfg(feature = "signing")]
-        let result = read_versioned_raw_message_signed::<M, _>(
-            reader.deref_mut(),
-            version,
-            self.signing_data.as_ref(),
-        );
-        result
+    fn generation(&self) -> u32 {
+        self.reconnect.as_ref().map_or(0, Reconnect::generation)
     }
 
-    fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        reader.reader_mut().set_nonblocking(true)?;
+    /// Re-establish the link if `error` shows that it was lost and a reconnect policy is set
+    ///
+    /// Returns whether the failed operation should be retried.
+    fn recover(&self, error: &io::Error, generation: u32) -> io::Result<bool> {
+        let Some(reconnect) = &self.reconnect else {
+            return Ok(false);
+        };
+        if !is_link_lost(error) {
+            return Ok(false);
+        }
+        self.reestablish(reconnect, generation)?;
+        Ok(true)
+    }
 
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+    /// Re-establish the link if a non-blocking call found it to be lost
+    fn recover_marked(&self) -> io::Result<()> {
+        match &self.reconnect {
+            Some(reconnect) if reconnect.is_marked_lost() => {
+                self.reestablish(reconnect, reconnect.generation())
+            }
+            _ => Ok(()),
+        }
+    }
 
-        #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg(reader.deref_mut(), version);
+    fn reestablish(&self, reconnect: &Reconnect<TcpConfig>, generation: u32) -> io::Result<()> {
+        reconnect.reconnect(
+            generation,
+            |config| {
+                let socket = match config.mode {
+                    TcpMode::TcpIn => accept_stream(&config.address)?,
+                    _ => connect_stream(&config.address)?,
+                };
+                Ok((socket.try_clone()?, socket))
+            },
+            |(read_socket, write_socket)| {
+                self.duplex.write.replace(write_socket);
+                self.duplex.read.replace(read_socket);
+            },
+        )
+    }
 
-        #[cfg(feature = "signing")]
-        let result =
-            read_versioned_msg_signed(reader.deref_mut(), version, self.signing_data.as_ref());
+    /// Run a receive call, re-establishing the link and retrying when it was lost
+    fn recv_with<T>(
+        &self,
+        recv: impl Fn(&ReadHalf<TcpStream>) -> Result<T, MessageReadError>,
+    ) -> Result<T, MessageReadError> {
+        loop {
+            self.recover_marked()?;
+            let generation = self.generation();
+            let result = recv(&self.duplex.read);
+            match &result {
+                Err(MessageReadError::Io(e)) if self.recover(e, generation)? => {}
+                _ => return result,
+            }
+        }
+    }
 
-        reader.reader_mut().set_nonblocking(false)?;
+    /// Run a send call, re-establishing the link and retrying when it was lost
+    fn send_with(
+        &self,
+        send: impl Fn(&WriteHalf<TcpStream>) -> Result<usize, MessageWriteError>,
+    ) -> Result<usize, MessageWriteError> {
+        loop {
+            self.recover_marked()?;
+            let generation = self.generation();
+            let result = send(&self.duplex.write);
+            match &result {
+                Err(MessageWriteError::Io(e)) if self.recover(e, generation)? => {}
+                _ => return result,
+            }
+        }
+    }
+}
 
-        result
+impl<M: Message> MavConnection<M> for TcpConnection {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_with(ReadHalf::recv)
     }
 
-    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, crate::error::MessageWriteError> {
-        let mut lock = self.writer.lock().unwrap();
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.recv_with(ReadHalf::recv_raw::<M>)
+    }
 
-        let header = MavHeader {
-            sequence: lock.sequence,
-            system_id: header.system_id,
-            component_id: header.component_id,
-        };
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        if self
+            .reconnect
+            .as_ref()
+            .is_some_and(Reconnect::is_marked_lost)
+        {
+            return Err(io::Error::from(io::ErrorKind::NotConnected).into());
+        }
+        let generation = self.generation();
+        let result = self.duplex.read.try_recv();
 
-        lock.sequence = lock.sequence.wrapping_add(1);
-        #[cfg(not(feature = "signing"))]
-        let result = write_versioned_msg(&mut lock.socket, self.protocol_version, header, data);
-        #[cfg(feature = "signing")]
-        let result = write_versioned_msg_signed(
-            &mut lock.socket,
-            self.protocol_version,
-            header,
-            data,
-            self.signing_data.as_ref(),
-        );
+        // a non-blocking call must not wait for the link, it is re-established by the next
+        // blocking call
+        if let (Some(reconnect), Err(MessageReadError::Io(e))) = (&self.reconnect, &result) {
+            reconnect.mark_lost(e, generation);
+        }
         result
     }
 
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_with(|read| read.recv_timeout(timeout))
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        self.send_with(|write| write.send(header, data))
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.send_with(|write| write.send_raw(message))
+    }
+
     fn set_protocol_version(&mut self, version: MavlinkVersion) {
-        self.protocol_version = version;
+        self.duplex.set_protocol_version(version);
     }
 
     fn protocol_version(&self) -> MavlinkVersion {
-        self.protocol_version
+        self.duplex.protocol_version()
     }
 
     fn set_allow_recv_any_version(&mut self, allow: bool) {
-        self.recv_any_version = allow;
+        self.duplex.set_allow_recv_any_version(allow);
     }
 
     fn allow_recv_any_version(&self) -> bool {
-        self.recv_any_version
+        self.duplex.allow_recv_any_version()
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.duplex.read.link_stats()
     }
 
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
-        self.signing_data = signing_data.map(SigningData::from_config);
+        self.duplex.setup_signing(signing_data);
     }
 }
 
 impl Connectable for TcpConfig {
     fn connect<M: Message>(&self) -> io::Result<Connection<M>> {
+        let reconnect = self
+            .reconnect
+            .map(|policy| Reconnect::new(self.clone(), policy));
         let conn = match self.mode {
-            TcpMode::TcpIn => tcpin(&self.address),
-            TcpMode::TcpOut => tcpout(&self.address),
+            TcpMode::TcpIn => TcpConnection::new(accept_stream(&self.address)?, reconnect)?.into(),
+            TcpMode::TcpOut => {
+                TcpConnection::new(connect_stream(&self.address)?, reconnect)?.into()
+            }
+            // clients of a server come and go anyway
+            TcpMode::TcpServer => TcpServerConnection::bind(&self.address)?.into(),
         };
 
-        Ok(conn?.into())
+        Ok(conn)
     }
 }
@@ -1,5 +1,7 @@
 use core::fmt::Display;
 
+use crate::connection::reconnect::ReconnectPolicy;
+
 /// Type of TCP connection
 #[derive(Debug, Clone, Copy)]
 pub enum TcpMode {
@@ -7,6 +9,9 @@ pub enum TcpMode {
     TcpIn,
     /// Connection will connect to the provided TCP server address
     TcpOut,
+    /// Connection will open a TCP server that binds to the provided address and keeps accepting
+    /// clients
+    TcpServer,
 }
 
 /// MAVLink connection address for a TCP server or client
@@ -23,12 +28,27 @@ pub enum TcpMode {
 pub struct TcpConfig {
     pub(crate) address: String,
     pub(crate) mode: TcpMode,
+    pub(crate) reconnect: Option<ReconnectPolicy>,
 }
 
 impl TcpConfig {
     /// Creates a TCP connection address.
     pub fn new(address: String, mode: TcpMode) -> Self {
-        Self { address, mode }
+        Self {
+            address,
+            mode,
+            reconnect: None,
+        }
+    }
+
+    /// Sets the policy used to reconnect after the TCP connection was lost.
+    ///
+    /// A [`TcpMode::TcpIn`] connection waits for the next client, a [`TcpMode::TcpOut`]
+    /// connection connects to the server again. [`TcpMode::TcpServer`] connections ignore the
+    /// policy as they keep accepting clients anyway. Async connections do not reconnect.
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
     }
 }
 impl Display for TcpConfig {
@@ -36,6 +56,7 @@ impl Display for TcpConfig {
         match self.mode {
             TcpMode::TcpIn => write!(f, "tcpin:{}", self.address),
             TcpMode::TcpOut => write!(f, "tcpout:{}", self.address),
+            TcpMode::TcpServer => write!(f, "tcpserver:{}", self.address),
         }
     }
 }
@@ -0,0 +1,398 @@
+//! TCP MAVLink server accepting any number of clients
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::connection::peer_inbox::{parse_raw, PeerEvent, PeerInbox};
+use crate::connection::MavConnection;
+use crate::connection::{
+    accept_retry_delay, get_socket_addr, CLIENT_READ_BUFFERS, CLIENT_WRITE_TIMEOUT,
+};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::write_versioned_msg;
+#[cfg(feature = "signing")]
+use crate::{write_versioned_msg_signed, SigningConfig, SigningData};
+
+struct Clients {
+    streams: HashMap<SocketAddr, TcpStream>,
+    sequence: u8,
+    closed: bool,
+}
+
+impl Clients {
+    fn write(&mut self, address: SocketAddr, frame: &[u8]) -> io::Result<()> {
+        let stream = self
+            .streams
+            .get_mut(&address)
+            .ok_or(io::Error::from(io::ErrorKind::NotConnected))?;
+        let result = stream.write_all(frame);
+        if result.is_err() {
+            self.disconnect(address);
+        }
+        result
+    }
+
+    fn broadcast(&mut self, frame: &[u8]) -> usize {
+        let mut sent = false;
+        self.streams.retain(|_, stream| {
+            if stream.write_all(frame).is_ok() {
+                sent = true;
+                true
+            } else {
+                stream.shutdown(Shutdown::Both).ok();
+                false
+            }
+        });
+        if sent {
+            frame.len()
+        } else {
+            0
+        }
+    }
+
+    fn disconnect(&mut self, address: SocketAddr) {
+        if let Some(stream) = self.streams.remove(&address) {
+            stream.shutdown(Shutdown::Both).ok();
+        }
+    }
+}
+
+/// TCP server connection that keeps accepting clients
+///
+/// Messages sent are written to all connected clients, while messages received from any client are
+/// returned by `recv`. Use [`recv_from`](Self::recv_from) and [`send_to`](Self::send_to) to tell
+/// the clients apart. Clients are dropped once they disconnect or a write to them fails, including
+/// writes that do not complete within half a second because the client stopped reading.
+///
+/// Every client is served by a thread forwarding its data to the connection, all threads are
+/// stopped when the connection is dropped. Clients are no longer read from while the data of
+/// 64 reads waits to be received, so a fast client is slowed down by TCP flow control.
+pub struct TcpServerConnection {
+    clients: Arc<Mutex<Clients>>,
+    inbox: Mutex<(PeerInbox, Receiver<PeerEvent>)>,
+    stats: SharedLinkStats,
+    local_address: SocketAddr,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl TcpServerConnection {
+    /// Bind a TCP server to the given address and start accepting clients
+    ///
+    /// # Errors
+    ///
+    /// When the address could not be resolved or bound a corresponding [`io::Error`] is returned
+    pub fn bind<T: ToSocketAddrs>(address: T) -> io::Result<Self> {
+        let listener = TcpListener::bind(get_socket_addr(&address)?)?;
+        let local_address = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Clients {
+            streams: HashMap::new(),
+            sequence: 0,
+            closed: false,
+        }));
+        let (sender, receiver) = mpsc::sync_channel(CLIENT_READ_BUFFERS);
+
+        thread::spawn({
+            let clients = clients.clone();
+            move || accept_clients(&listener, &clients, &sender)
+        });
+
+        Ok(Self {
+            clients,
+            inbox: Mutex::new((PeerInbox::default(), receiver)),
+            stats: SharedLinkStats::default(),
+            local_address,
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_address
+    }
+
+    /// Addresses of the currently connected clients
+    pub fn peers(&self) -> Vec<SocketAddr> {
+        self.clients
+            .lock()
+            .unwrap()
+            .streams
+            .keys()
+            .copied()
+            .collect()
+    }
+
+    /// Disconnect the client with the given address
+    pub fn disconnect(&self, peer: SocketAddr) {
+        self.clients.lock().unwrap().disconnect(peer);
+    }
+
+    /// Receive a raw MAVLink message together with the address of the client that sent it
+    ///
+    /// Blocks until a valid frame is received from any client.
+    ///
+    /// # Errors
+    ///
+    /// See [`MavConnection::recv_raw`]
+    pub fn recv_raw_from<M: Message>(
+        &self,
+    ) -> Result<(SocketAddr, MAVLinkMessageRaw), MessageReadError> {
+        self.next_raw_frame::<M>(None)
+    }
+
+    /// Receive the next valid frame from any client, waiting until `deadline` if it is set
+    fn next_raw_frame<M: Message>(
+        &self,
+        deadline: Option<Instant>,
+    ) -> Result<(SocketAddr, MAVLinkMessageRaw), MessageReadError> {
+        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+        let mut guard = self.inbox.lock().unwrap();
+        let (inbox, events) = &mut *guard;
+        loop {
+            #[cfg(not(feature = "signing"))]
+            let frame = inbox.next_frame::<M>(version, &self.stats);
+            #[cfg(feature = "signing")]
+            let frame = inbox.next_frame::<M>(version, self.signing_data.as_ref(), &self.stats);
+            if let Some(frame) = frame {
+                return Ok(frame);
+            }
+            let event = match deadline {
+                None => events.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                Some(deadline) => {
+                    events.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+            };
+            match event {
+                Ok(event) => inbox.handle(event),
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(io::Error::from(io::ErrorKind::TimedOut).into())
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::from(io::ErrorKind::NotConnected).into())
+                }
+            }
+        }
+    }
+
+    /// Receive a MAVLink message together with the address of the client that sent it
+    ///
+    /// Blocks until a valid message is received from any client.
+    ///
+    /// # Errors
+    ///
+    /// See [`MavConnection::recv`]
+    pub fn recv_from<M: Message>(&self) -> Result<(SocketAddr, MavHeader, M), MessageReadError> {
+        let (address, raw) = self.recv_raw_from::<M>()?;
+        let (header, msg) = parse_raw(&raw)?;
+        Ok((address, header, msg))
+    }
+
+    /// Send a MAVLink message to a single client
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MessageWriteError::Io`] with [`io::ErrorKind::NotConnected`] if no client with
+    /// the given address is connected, or the error that occurred while writing to it.
+    pub fn send_to<M: Message>(
+        &self,
+        peer: SocketAddr,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, MessageWriteError> {
+        let mut clients = self.clients.lock().unwrap();
+        let frame = self.serialize(&mut clients, header, data)?;
+        clients.write(peer, &frame)?;
+        Ok(frame.len())
+    }
+
+    fn serialize<M: Message>(
+        &self,
+        clients: &mut Clients,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<Vec<u8>, MessageWriteError> {
+        let header = MavHeader {
+            sequence: clients.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+        clients.sequence = clients.sequence.wrapping_add(1);
+
+        let mut frame = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut frame, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut frame,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        Ok(frame)
+    }
+}
+
+impl Drop for TcpServerConnection {
+    fn drop(&mut self) {
+        let mut clients = self.clients.lock().unwrap();
+        clients.closed = true;
+        for stream in clients.streams.values() {
+            stream.shutdown(Shutdown::Both).ok();
+        }
+        drop(clients);
+
+        // wake up the thread blocked in accept so it notices the server is closed
+        let mut address = self.local_address;
+        if address.ip().is_unspecified() {
+            address.set_ip(match address {
+                SocketAddr::V4(_) => std::net::Ipv4Addr::LOCALHOST.into(),
+                SocketAddr::V6(_) => std::net::Ipv6Addr::LOCALHOST.into(),
+            });
+        }
+        TcpStream::connect(address).ok();
+    }
+}
+
+fn accept_clients(
+    listener: &TcpListener,
+    clients: &Arc<Mutex<Clients>>,
+    events: &SyncSender<PeerEvent>,
+) {
+    for incoming in listener.incoming() {
+        let stream = match incoming {
+            Ok(stream) => stream,
+            Err(e) => {
+                thread::sleep(accept_retry_delay(&e));
+                continue;
+            }
+        };
+        let mut guard = clients.lock().unwrap();
+        if guard.closed {
+            return;
+        }
+        let (Ok(address), Ok(reader), Ok(())) = (
+            stream.peer_addr(),
+            stream.try_clone(),
+            stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT)),
+        ) else {
+            continue;
+        };
+        guard.streams.insert(address, stream);
+        drop(guard);
+
+        let clients = clients.clone();
+        let events = events.clone();
+        thread::spawn(move || {
+            forward_client(reader, address, &events);
+            clients.lock().unwrap().disconnect(address);
+        });
+    }
+}
+
+fn forward_client(mut stream: TcpStream, address: SocketAddr, events: &SyncSender<PeerEvent>) {
+    let mut buf = [0; 1024];
+    loop {
+        match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                if events
+                    .send(PeerEvent::Data(address, buf[..n].to_vec()))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+    events.send(PeerEvent::Disconnected(address)).ok();
+}
+
+impl<M: Message> MavConnection<M> for TcpServerConnection {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_from().map(|(_, header, msg)| (header, msg))
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.recv_raw_from::<M>().map(|(_, raw)| raw)
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+        let mut guard = self.inbox.lock().unwrap();
+        let (inbox, events) = &mut *guard;
+        loop {
+            match events.try_recv() {
+                Ok(event) => inbox.handle(event),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    return Err(io::Error::from(io::ErrorKind::NotConnected).into())
+                }
+            }
+        }
+        #[cfg(not(feature = "signing"))]
+        let frame = inbox.next_frame::<M>(version, &self.stats);
+        #[cfg(feature = "signing")]
+        let frame = inbox.next_frame::<M>(version, self.signing_data.as_ref(), &self.stats);
+        match frame {
+            Some((_, raw)) => parse_raw(&raw),
+            None => Err(io::Error::from(io::ErrorKind::WouldBlock).into()),
+        }
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        let (_, raw) = self.next_raw_frame::<M>(Some(Instant::now() + timeout))?;
+        parse_raw(&raw)
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let mut clients = self.clients.lock().unwrap();
+        let frame = self.serialize(&mut clients, header, data)?;
+        Ok(clients.broadcast(&frame))
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        Ok(self.clients.lock().unwrap().broadcast(message.raw_bytes()))
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
@@ -1,35 +1,32 @@
 //! UDP MAVLink connection
 
 use crate::connection::get_socket_addr;
-use crate::connection::{Connection, MavConnection};
-use crate::peek_reader::PeekReader;
-#[cfg(not(feature = "signing"))]
-use crate::read_versioned_raw_message;
-#[cfg(feature = "signing")]
-use crate::read_versioned_raw_message_signed;
+use crate::connection::split::{Duplex, FrameWrite};
+use crate::connection::{Connection, MavConnection, ReadTimeout};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::LinkStats;
 use crate::Connectable;
-use crate::MAVLinkMessageRaw;
-use crate::{MavHeader, MavlinkVersion, Message, ReadVersion};
-use core::ops::DerefMut;
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message};
 use std::collections::VecDeque;
 use std::io::{self, Read};
 use std::net::{SocketAddr, UdpSocket};
-use std::sync::Mutex;
-
-#[cfg(not(feature = "signing"))]
-use crate::{read_versioned_msg, write_versioned_msg};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[cfg(feature = "signing")]
-use crate::{read_versioned_msg_signed, write_versioned_msg_signed, SigningConfig, SigningData};
+use crate::SigningConfig;
 
 pub mod config;
+pub mod server;
 
 use config::{UdpConfig, UdpMode};
+use server::UdpServerConnection;
 
-struct UdpRead {
+pub(crate) struct UdpRead {
     socket: UdpSocket,
     buffer: VecDeque<u8>,
-    last_recv_address: Option<SocketAddr>,
+    /// Destination of the writing side, updated to the sender of each datagram in server mode
+    reply_to: Option<Arc<Mutex<Option<SocketAddr>>>>,
 }
 
 const MTU_SIZE: usize = 1500;
@@ -43,169 +40,116 @@ impl Read for UdpRead {
             let n = (&read_buffer[0..n_buffer]).read(buf)?;
             self.buffer.extend(&read_buffer[n..n_buffer]);
 
-            self.last_recv_address = Some(address);
+            if let Some(reply_to) = &self.reply_to {
+                *reply_to.lock().unwrap() = Some(address);
+            }
             Ok(n)
         }
     }
 }
 
-struct UdpWrite {
+impl ReadTimeout for UdpRead {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        self.socket.read_timeout()
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.socket.set_read_timeout(timeout)
+    }
+}
+
+pub(crate) struct UdpWrite {
     socket: UdpSocket,
-    dest: Option<SocketAddr>,
-    sequence: u8,
+    dest: Arc<Mutex<Option<SocketAddr>>>,
+}
+
+impl FrameWrite for UdpWrite {
+    fn write_frame(&mut self, frame: &[u8]) -> io::Result<usize> {
+        match *self.dest.lock().unwrap() {
+            Some(addr) => self.socket.send_to(frame, addr),
+            None => Ok(0),
+        }
+    }
 }
 
 pub struct UdpConnection {
-    reader: Mutex<PeekReader<UdpRead>>,
-    writer: Mutex<UdpWrite>,
-    protocol_version: MavlinkVersion,
-    recv_any_version: bool,
-    server: bool,
-    #[cfg(feature = "signing")]
-    signing_data: Option<SigningData>,
+    duplex: Duplex<UdpRead, UdpWrite>,
 }
 
 impl UdpConnection {
     fn new(socket: UdpSocket, server: bool, dest: Option<SocketAddr>) -> io::Result<Self> {
+        let dest = Arc::new(Mutex::new(dest));
+        let reader = UdpRead {
+            socket: socket.try_clone()?,
+            buffer: VecDeque::new(),
+            reply_to: server.then(|| dest.clone()),
+        };
+        let writer = UdpWrite { socket, dest };
         Ok(Self {
-            server,
-            reader: Mutex::new(PeekReader::new(UdpRead {
-                socket: socket.try_clone()?,
-                buffer: VecDeque::new(),
-                last_recv_address: None,
-            })),
-            writer: Mutex::new(UdpWrite {
-                socket,
-                dest,
-                sequence: 0,
-            }),
-            protocol_version: MavlinkVersion::V2,
-            recv_any_version: false,
-            #[cfg(feature = "signing")]
-            signing_data: None,
+            duplex: Duplex::new(reader, writer),
         })
     }
+
+    pub(crate) fn into_duplex(self) -> Duplex<UdpRead, UdpWrite> {
+        self.duplex
+    }
 }
 
 impl<M: Message> MavConnection<M> for UdpConnection {
-    fn recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
-
-        #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg(reader.deref_mut(), version);
-        #[cfg(feature = "signing")]
-        let result =
-            read_versioned_msg_signed(reader.deref_mut(), version, self.signing_data.as_ref());
-        if self.server {
-            if let addr @ Some(_) = reader.reader_ref().last_recv_address {
-                self.writer.lock().unwrap().dest = addr;
-            }
-        }
-        result
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.recv()
     }
 
-    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
-
-        #[cfg(not(feature = "signing"))]
-        let result = read_versioned_raw_message::<M, _>(reader.deref_mut(), version);
-        #[cfg(feature = "signing")]
-        let result = read_versioned_raw_message_signed::<M, _>(
-            reader.deref_mut(),
-            version,
-            self.signing_data.as_ref(),
-        );
-        if self.server {
-            if let addr @ Some(_) = reader.reader_ref().last_recv_address {
-                self.writer.lock().unwrap().dest = addr;
-            }
-        }
-        result
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.duplex.read.recv_raw::<M>()
     }
 
-    fn try_recv(&self) -> Result<(MavHeader, M), crate::error::MessageReadError> {
-        let mut reader = self.reader.lock().unwrap();
-        reader.reader_mut().socket.set_nonblocking(true)?;
-
-        let version = ReadVersion::from_conn_cfg::<_, M>(self);
-
-        #[cfg(not(feature = "signing"))]
-        let result = read_versioned_msg(reader.deref_mut(), version);
-        #[cfg(feature = "signing")]
-        let result =
-            read_versioned_msg_signed(reader.deref_mut(), version, self.signing_data.as_ref());
-
-        if self.server {
-            if let addr @ Some(_) = reader.reader_ref().last_recv_address {
-                self.writer.lock().unwrap().dest = addr;
-            }
-        }
-
-        reader.reader_mut().socket.set_nonblocking(false)?;
-
-        result
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.try_recv()
     }
 
-    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, crate::error::MessageWriteError> {
-        let mut guard = self.writer.lock().unwrap();
-        let state = &mut *guard;
-
-        let header = MavHeader {
-            sequence: state.sequence,
-            system_id: header.system_id,
-            component_id: header.component_id,
-        };
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.recv_timeout(timeout)
+    }
 
-        state.sequence = state.sequence.wrapping_add(1);
-
-        let len = if let Some(addr) = state.dest {
-            let mut buf = Vec::new();
-            #[cfg(not(feature = "signing"))]
-            write_versioned_msg(&mut buf, self.protocol_version, header, data)?;
-            #[cfg(feature = "signing")]
-            write_versioned_msg_signed(
-                &mut buf,
-                self.protocol_version,
-                header,
-                data,
-                self.signing_data.as_ref(),
-            )?;
-            state.socket.send_to(&buf, addr)?
-        } else {
-            0
-        };
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        self.duplex.write.send(header, data)
+    }
 
-        Ok(len)
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.duplex.write.send_raw(message)
     }
 
     fn set_protocol_version(&mut self, version: MavlinkVersion) {
-        self.protocol_version = version;
+        self.duplex.set_protocol_version(version);
     }
 
     fn protocol_version(&self) -> MavlinkVersion {
-        self.protocol_version
+        self.duplex.protocol_version()
     }
 
     fn set_allow_recv_any_version(&mut self, allow: bool) {
-        self.recv_any_version = allow;
+        self.duplex.set_allow_recv_any_version(allow);
     }
 
     fn allow_recv_any_version(&self) -> bool {
-        self.recv_any_version
+        self.duplex.allow_recv_any_version()
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.duplex.read.link_stats()
     }
 
     #[cfg(feature = "signing")]
     fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
-        self.signing_data = signing_data.map(SigningData::from_config);
+        self.duplex.setup_signing(signing_data);
     }
 }
 
 impl Connectable for UdpConfig {
     fn connect<M: Message>(&self) -> io::Result<Connection<M>> {
         let (addr, server, dest): (&str, _, _) = match self.mode {
-            UdpMode::Udpin => (&self.address, true, None),
+            UdpMode::Udpin | UdpMode::Udpserver => (&self.address, true, None),
             _ => ("0.0.0.0:0", false, Some(get_socket_addr(&self.address)?)),
         };
         let socket = UdpSocket::bind(addr)?;
@@ -215,6 +159,13 @@ impl Connectable for UdpConfig {
         if matches!(self.mode, UdpMode::Udpcast) {
             socket.set_broadcast(true)?;
         }
+        if matches!(self.mode, UdpMode::Udpserver) {
+            let mut conn = UdpServerConnection::new(socket);
+            if let Some(timeout) = self.peer_timeout {
+                conn.set_peer_timeout(timeout);
+            }
+            return Ok(conn.into());
+        }
         Ok(UdpConnection::new(socket, server, dest)?.into())
     }
 }
@@ -222,6 +173,8 @@ impl Connectable for UdpConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::connection::DeadlineReader;
+    use std::time::Instant;
 
     #[test]
     fn test_datagram_buffering() {
@@ -229,7 +182,7 @@ mod tests {
         let mut udp_reader = UdpRead {
             socket: receiver_socket.try_clone().unwrap(),
             buffer: VecDeque::new(),
-            last_recv_address: None,
+            reply_to: None,
         };
         let sender_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
         sender_socket.connect("127.0.0.1:5000").unwrap();
@@ -259,4 +212,38 @@ mod tests {
         assert_eq!(n_read, 20);
         assert_eq!(&buf[0..n_read], (30..50).collect::<Vec<_>>().as_slice());
     }
+
+    #[test]
+    fn test_deadline_reader() {
+        let receiver_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = receiver_socket.local_addr().unwrap();
+        let mut reader = DeadlineReader::new(UdpRead {
+            socket: receiver_socket,
+            buffer: VecDeque::new(),
+            reply_to: None,
+        });
+        let sender_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        for _ in 0..10 {
+            sender_socket.send_to(&[0; 100], address).unwrap();
+        }
+
+        // data that arrived already is read after the deadline, but at most a frame of it
+        reader.set_deadline(Some(Instant::now())).unwrap();
+        let mut buf = [0; 1000];
+        let mut n_read = 0;
+        let err = loop {
+            match reader.read(&mut buf) {
+                Ok(n) => n_read += n,
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(n_read, crate::MAX_FRAME_SIZE);
+
+        // a new deadline allows reading again
+        reader
+            .set_deadline(Some(Instant::now() + Duration::from_millis(100)))
+            .unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 20);
+    }
 }
@@ -21,6 +21,8 @@ pub enum UdpMode {
     Udpout,
     /// Client connection that is allowed to send to broadcast addresses
     Udpcast,
+    /// Server connection keeping track of all clients
+    Udpserver,
 }
 
 /// MAVLink address for a UDP server client or broadcast connection
@@ -29,6 +31,7 @@ pub struct UdpConfig {
     pub(crate) address: String,
     pub(crate) mode: UdpMode,
     pub(crate) read_timeout: Option<Duration>,
+    pub(crate) peer_timeout: Option<Duration>,
 }
 
 impl UdpConfig {
@@ -40,6 +43,7 @@ impl UdpConfig {
             address,
             mode,
             read_timeout: None,
+            peer_timeout: None,
         }
     }
 
@@ -52,6 +56,12 @@ impl UdpConfig {
         self.read_timeout = Some(timeout);
         self
     }
+
+    /// Sets the time after which a [`UdpMode::Udpserver`] forgets a peer that has not sent any data.
+    pub fn peer_timeout(mut self, timeout: Duration) -> Self {
+        self.peer_timeout = Some(timeout);
+        self
+    }
 }
 
 impl Display for UdpConfig {
@@ -60,6 +70,7 @@ impl Display for UdpConfig {
             UdpMode::Udpin => "udpin",
             UdpMode::Udpout => "udpout",
             UdpMode::Udpcast => "udpcast",
+            UdpMode::Udpserver => "udpserver",
         };
         write!(f, "{mode}:{}", self.address)
     }
@@ -0,0 +1,352 @@
+//! UDP MAVLink server keeping track of all its peers
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::MTU_SIZE;
+use crate::connection::get_socket_addr;
+use crate::connection::peer_inbox::{parse_raw, PeerEvent, PeerInbox};
+use crate::connection::{MavConnection, TimeoutReader};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, SharedLinkStats};
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion};
+
+#[cfg(not(feature = "signing"))]
+use crate::write_versioned_msg;
+#[cfg(feature = "signing")]
+use crate::{write_versioned_msg_signed, SigningConfig, SigningData};
+
+/// Time after which a peer that has not sent any data is forgotten by default
+pub(crate) const DEFAULT_PEER_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) struct Peers {
+    last_seen: HashMap<SocketAddr, Instant>,
+    /// Peers forgotten since the last call to `expire`
+    forgotten: Vec<SocketAddr>,
+    pub(crate) sequence: u8,
+}
+
+impl Peers {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_seen: HashMap::new(),
+            forgotten: Vec::new(),
+            sequence: 0,
+        }
+    }
+
+    pub(crate) fn seen(&mut self, address: SocketAddr) {
+        self.last_seen.insert(address, Instant::now());
+    }
+
+    pub(crate) fn forget(&mut self, address: SocketAddr) {
+        if self.last_seen.remove(&address).is_some() {
+            self.forgotten.push(address);
+        }
+    }
+
+    /// Remove the peers that timed out, returning their addresses and those of the peers
+    /// forgotten since the last call
+    ///
+    /// Only the receiving side calls this, so it can drop the data buffered for the returned
+    /// peers.
+    pub(crate) fn expire(&mut self, timeout: Duration) -> Vec<SocketAddr> {
+        let mut expired = core::mem::take(&mut self.forgotten);
+        self.last_seen.retain(|address, last_seen| {
+            let alive = last_seen.elapsed() < timeout;
+            if !alive {
+                expired.push(*address);
+            }
+            alive
+        });
+        expired
+    }
+
+    /// Addresses of the peers that sent data within `timeout`
+    pub(crate) fn alive(&self, timeout: Duration) -> Vec<SocketAddr> {
+        self.last_seen
+            .iter()
+            .filter(|(_, last_seen)| last_seen.elapsed() < timeout)
+            .map(|(address, _)| *address)
+            .collect()
+    }
+}
+
+/// UDP server connection that keeps track of all its peers
+///
+/// Every address a datagram is received from is remembered as a peer until it did not send
+/// anything for the peer timeout. Messages sent are sent to all peers, while messages received
+/// from any peer are returned by `recv`. Use [`recv_from`](Self::recv_from) and
+/// [`send_to`](Self::send_to) to tell the peers apart.
+pub struct UdpServerConnection {
+    socket: UdpSocket,
+    inbox: Mutex<PeerInbox>,
+    peers: Mutex<Peers>,
+    stats: SharedLinkStats,
+    peer_timeout: Duration,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl UdpServerConnection {
+    /// Bind a UDP server to the given address
+    ///
+    /// # Errors
+    ///
+    /// When the address could not be resolved or bound a corresponding [`io::Error`] is returned
+    pub fn bind<T: ToSocketAddrs>(address: T) -> io::Result<Self> {
+        let socket = UdpSocket::bind(get_socket_addr(&address)?)?;
+        Ok(Self::new(socket))
+    }
+
+    pub(crate) fn new(socket: UdpSocket) -> Self {
+        Self {
+            socket,
+            inbox: Mutex::default(),
+            peers: Mutex::new(Peers::new()),
+            stats: SharedLinkStats::default(),
+            peer_timeout: DEFAULT_PEER_TIMEOUT,
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+
+    /// Address the server is bound to
+    ///
+    /// # Errors
+    ///
+    /// See [`UdpSocket::local_addr`]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Set the time after which a peer that has not sent any data is forgotten, 10 seconds by default
+    pub fn set_peer_timeout(&mut self, timeout: Duration) {
+        self.peer_timeout = timeout;
+    }
+
+    /// Time after which a peer that has not sent any data is forgotten
+    pub fn peer_timeout(&self) -> Duration {
+        self.peer_timeout
+    }
+
+    /// Addresses of the peers that sent data within the peer timeout
+    pub fn peers(&self) -> Vec<SocketAddr> {
+        self.peers.lock().unwrap().alive(self.peer_timeout)
+    }
+
+    /// Receive a raw MAVLink message together with the address of the peer that sent it
+    ///
+    /// Blocks until a valid frame is received from any peer.
+    ///
+    /// # Errors
+    ///
+    /// See [`MavConnection::recv_raw`]
+    pub fn recv_raw_from<M: Message>(
+        &self,
+    ) -> Result<(SocketAddr, MAVLinkMessageRaw), MessageReadError> {
+        self.next_raw_frame::<M>(None)
+    }
+
+    /// Receive the next valid frame from any peer, waiting until `deadline` if it is set
+    fn next_raw_frame<M: Message>(
+        &self,
+        deadline: Option<Instant>,
+    ) -> Result<(SocketAddr, MAVLinkMessageRaw), MessageReadError> {
+        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+        let mut inbox = self.inbox.lock().unwrap();
+        let mut buf = [0; MTU_SIZE];
+        let mut received = false;
+        loop {
+            #[cfg(not(feature = "signing"))]
+            let frame = inbox.next_frame::<M>(version, &self.stats);
+            #[cfg(feature = "signing")]
+            let frame = inbox.next_frame::<M>(version, self.signing_data.as_ref(), &self.stats);
+            if let Some(frame) = frame {
+                return Ok(frame);
+            }
+            let (n, address) = match deadline {
+                None => self.socket.recv_from(&mut buf)?,
+                Some(deadline) => self.recv_from_until(&mut buf, deadline, received)?,
+            };
+            received = true;
+
+            // a peer that timed out is expired before it is seen again, so data it sent before
+            // does not get mixed up with the new datagram
+            let mut peers = self.peers.lock().unwrap();
+            for expired in peers.expire(self.peer_timeout) {
+                inbox.handle(PeerEvent::Disconnected(expired));
+            }
+            peers.seen(address);
+            drop(peers);
+            inbox.handle(PeerEvent::Data(address, buf[..n].to_vec()));
+        }
+    }
+
+    /// Receive a datagram, waiting until `deadline` at most
+    ///
+    /// A datagram that arrived already is received by the first read even if the deadline has
+    /// passed, later reads fail right away so a steady stream of datagrams can not hold up the
+    /// call.
+    fn recv_from_until(
+        &self,
+        buf: &mut [u8],
+        deadline: Instant,
+        received: bool,
+    ) -> io::Result<(usize, SocketAddr)> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if timeout.is_zero() && received {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        // the socket waits for the shortest possible read timeout instead of a zero one
+        let mut socket = &self.socket;
+        let reader = TimeoutReader::new(&mut socket, Some(timeout))?;
+        reader.recv_from(buf)
+    }
+
+    /// Receive a MAVLink message together with the address of the peer that sent it
+    ///
+    /// Blocks until a valid message is received from any peer.
+    ///
+    /// # Errors
+    ///
+    /// See [`MavConnection::recv`]
+    pub fn recv_from<M: Message>(&self) -> Result<(SocketAddr, MavHeader, M), MessageReadError> {
+        let (address, raw) = self.recv_raw_from::<M>()?;
+        let (header, msg) = parse_raw(&raw)?;
+        Ok((address, header, msg))
+    }
+
+    /// Send a MAVLink message to a single address
+    ///
+    /// The address does not need to be a known peer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that occurred while sending the message.
+    pub fn send_to<M: Message>(
+        &self,
+        peer: SocketAddr,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<usize, MessageWriteError> {
+        let frame = self.serialize(&mut self.peers.lock().unwrap(), header, data)?;
+        Ok(self.socket.send_to(&frame, peer)?)
+    }
+
+    fn serialize<M: Message>(
+        &self,
+        peers: &mut Peers,
+        header: &MavHeader,
+        data: &M,
+    ) -> Result<Vec<u8>, MessageWriteError> {
+        let header = MavHeader {
+            sequence: peers.sequence,
+            system_id: header.system_id,
+            component_id: header.component_id,
+        };
+        peers.sequence = peers.sequence.wrapping_add(1);
+
+        let mut frame = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut frame, self.protocol_version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut frame,
+            self.protocol_version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+        Ok(frame)
+    }
+
+    fn broadcast(&self, peers: &mut Peers, frame: &[u8]) -> usize {
+        let mut sent = 0;
+        for address in peers.alive(self.peer_timeout) {
+            let mut result = self.socket.send_to(frame, address);
+            if is_refused(&result) {
+                // the error may be caused by an earlier datagram to another peer, in which case
+                // this datagram has not been sent yet
+                result = self.socket.send_to(frame, address);
+                if is_refused(&result) {
+                    peers.forget(address);
+                }
+            }
+            if let Ok(n) = result {
+                sent = n;
+            }
+        }
+        sent
+    }
+}
+
+/// Whether sending failed because a peer is unreachable
+///
+/// Unconnected sockets report an ICMP port unreachable error on the next call, so the refused
+/// datagram is not necessarily the one the failed call tried to send.
+pub(crate) fn is_refused(result: &io::Result<usize>) -> bool {
+    matches!(result, Err(e) if e.kind() == io::ErrorKind::ConnectionRefused)
+}
+
+impl<M: Message> MavConnection<M> for UdpServerConnection {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.recv_from().map(|(_, header, msg)| (header, msg))
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.recv_raw_from::<M>().map(|(_, raw)| raw)
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let (_, raw) = self.next_raw_frame::<M>(Some(Instant::now()))?;
+        parse_raw(&raw)
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        let (_, raw) = self.next_raw_frame::<M>(Some(Instant::now() + timeout))?;
+        parse_raw(&raw)
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let mut peers = self.peers.lock().unwrap();
+        let frame = self.serialize(&mut peers, header, data)?;
+        Ok(self.broadcast(&mut peers, &frame))
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        Ok(self.broadcast(&mut self.peers.lock().unwrap(), message.raw_bytes()))
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
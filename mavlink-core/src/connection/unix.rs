@@ -0,0 +1,146 @@
+//! Unix domain socket MAVLink connection
+
+use crate::connection::split::Duplex;
+use crate::connection::{Connection, MavConnection};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::LinkStats;
+use crate::Connectable;
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message};
+use std::fs;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixDatagram, UnixListener, UnixStream};
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(feature = "signing")]
+use crate::SigningConfig;
+
+pub mod config;
+pub mod datagram;
+
+use config::{UnixConfig, UnixMode};
+use datagram::UnixDatagramConnection;
+
+/// Remove a socket file left behind by a previous process, so the path can be bound again
+///
+/// The file is only removed if connecting to it is refused, i.e. nobody is bound to it anymore. A
+/// socket that is still in use fails with [`io::ErrorKind::AddrInUse`] instead of being taken over.
+/// A process still listening on a stream socket sees the probe as a client that disconnects
+/// right away.
+pub(crate) fn remove_stale_socket(path: &Path, datagram: bool) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {}
+        _ => return Ok(()),
+    }
+    let probe = if datagram {
+        UnixDatagram::unbound().and_then(|socket| socket.connect(path))
+    } else {
+        UnixStream::connect(path).map(drop)
+    };
+    match probe {
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("socket {} is in use", path.display()),
+        )),
+    }
+}
+
+fn accept_stream(path: &Path) -> io::Result<UnixStream> {
+    remove_stale_socket(path, false)?;
+    let listener = UnixListener::bind(path)?;
+
+    // only a single client is accepted, just like `tcpin`
+    let (socket, _) = listener.accept()?;
+    Ok(socket)
+}
+
+pub struct UnixConnection {
+    duplex: Duplex<UnixStream, UnixStream>,
+}
+
+impl UnixConnection {
+    fn new(socket: UnixStream) -> io::Result<Self> {
+        Ok(Self {
+            duplex: Duplex::new(socket.try_clone()?, socket),
+        })
+    }
+
+    pub(crate) fn into_duplex(self) -> Duplex<UnixStream, UnixStream> {
+        self.duplex
+    }
+}
+
+impl<M: Message> MavConnection<M> for UnixConnection {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.recv()
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.duplex.read.recv_raw::<M>()
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.try_recv()
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.recv_timeout(timeout)
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        self.duplex.write.send(header, data)
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.duplex.write.send_raw(message)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.duplex.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.duplex.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.duplex.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.duplex.allow_recv_any_version()
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.duplex.read.link_stats()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.duplex.setup_signing(signing_data);
+    }
+}
+
+impl Connectable for UnixConfig {
+    fn connect<M: Message>(&self) -> io::Result<Connection<M>> {
+        let socket = match self.mode {
+            UnixMode::UnixIn => accept_stream(&self.address)?,
+            UnixMode::UnixOut => UnixStream::connect(&self.address)?,
+            UnixMode::UnixGram => {
+                let conn = UnixDatagramConnection::bind(
+                    &self.address,
+                    self.peer.clone(),
+                    self.read_timeout,
+                )?;
+                return Ok(conn.into());
+            }
+        };
+        if let Some(timeout) = self.read_timeout {
+            socket.set_read_timeout(Some(timeout))?;
+        }
+        Ok(UnixConnection::new(socket)?.into())
+    }
+}
@@ -0,0 +1,75 @@
+use core::fmt::Display;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Type of Unix domain socket connection
+#[derive(Debug, Clone, Copy)]
+pub enum UnixMode {
+    /// Connection will listen on a stream socket at the provided path and accept a single client
+    UnixIn,
+    /// Connection will connect to the stream socket listening at the provided path
+    UnixOut,
+    /// Connection will bind a datagram socket to the provided path and reply to the last sender
+    UnixGram,
+}
+
+/// MAVLink connection address for a Unix domain socket
+///
+/// A stale socket file left at the path of a listening or datagram socket is removed before
+/// binding.
+///
+/// # Example
+///
+/// ```ignore
+/// use mavlink::{Connectable, UnixConfig, UnixMode};
+/// use std::path::PathBuf;
+///
+/// let config = UnixConfig::new(PathBuf::from("/run/mavlink/gcs.sock"), UnixMode::UnixGram)
+///     .peer(PathBuf::from("/run/mavlink/autopilot.sock"));
+/// config.connect::<mavlink::common::MavMessage>()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnixConfig {
+    pub(crate) address: PathBuf,
+    pub(crate) mode: UnixMode,
+    pub(crate) peer: Option<PathBuf>,
+    pub(crate) read_timeout: Option<Duration>,
+}
+
+impl UnixConfig {
+    /// Creates a Unix domain socket connection address.
+    pub fn new(address: PathBuf, mode: UnixMode) -> Self {
+        Self {
+            address,
+            mode,
+            peer: None,
+            read_timeout: None,
+        }
+    }
+
+    /// Sets the socket a [`UnixMode::UnixGram`] connection sends to until it receives a message.
+    pub fn peer(mut self, peer: PathBuf) -> Self {
+        self.peer = Some(peer);
+        self
+    }
+
+    /// Sets the read timeout on the socket.
+    ///
+    /// When set, `recv()` and `recv_raw()` will return an error after the specified duration
+    /// instead of blocking indefinitely. Async connections ignore the timeout.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+}
+
+impl Display for UnixConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mode = match self.mode {
+            UnixMode::UnixIn => "unixin",
+            UnixMode::UnixOut => "unix",
+            UnixMode::UnixGram => "unixgram",
+        };
+        write!(f, "{mode}:{}", self.address.display())
+    }
+}
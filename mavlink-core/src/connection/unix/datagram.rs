@@ -0,0 +1,153 @@
+//! Unix datagram socket MAVLink connection
+
+use crate::connection::split::{Duplex, FrameWrite};
+use crate::connection::{MavConnection, ReadTimeout};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::LinkStats;
+use crate::{MAVLinkMessageRaw, MavHeader, MavlinkVersion, Message};
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "signing")]
+use crate::SigningConfig;
+
+use super::remove_stale_socket;
+
+/// Largest datagram that is received as a whole, MAVLink frames are much smaller
+const DATAGRAM_SIZE: usize = 4096;
+
+pub(crate) struct UnixDatagramRead {
+    socket: UnixDatagram,
+    buffer: VecDeque<u8>,
+    /// Destination of the writing side, the socket the last datagram was received from
+    reply_to: Arc<Mutex<Option<PathBuf>>>,
+}
+
+impl Read for UnixDatagramRead {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.buffer.is_empty() {
+            self.buffer.read(buf)
+        } else {
+            let mut read_buffer = [0u8; DATAGRAM_SIZE];
+            let (n_buffer, address) = self.socket.recv_from(&mut read_buffer)?;
+            let n = (&read_buffer[0..n_buffer]).read(buf)?;
+            self.buffer.extend(&read_buffer[n..n_buffer]);
+
+            // unbound senders can not be replied to
+            if let Some(path) = address.as_pathname() {
+                *self.reply_to.lock().unwrap() = Some(path.to_owned());
+            }
+            Ok(n)
+        }
+    }
+}
+
+impl ReadTimeout for UnixDatagramRead {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        self.socket.read_timeout()
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.socket.set_read_timeout(timeout)
+    }
+}
+
+pub(crate) struct UnixDatagramWrite {
+    socket: UnixDatagram,
+    dest: Arc<Mutex<Option<PathBuf>>>,
+}
+
+impl FrameWrite for UnixDatagramWrite {
+    fn write_frame(&mut self, frame: &[u8]) -> io::Result<usize> {
+        match &*self.dest.lock().unwrap() {
+            Some(addr) => self.socket.send_to(frame, addr),
+            None => Ok(0),
+        }
+    }
+}
+
+/// Connection over a Unix datagram socket, replying to the socket it last received from
+pub struct UnixDatagramConnection {
+    duplex: Duplex<UnixDatagramRead, UnixDatagramWrite>,
+}
+
+impl UnixDatagramConnection {
+    pub(crate) fn bind(
+        path: &Path,
+        dest: Option<PathBuf>,
+        read_timeout: Option<Duration>,
+    ) -> io::Result<Self> {
+        remove_stale_socket(path, true)?;
+        let socket = UnixDatagram::bind(path)?;
+        socket.set_read_timeout(read_timeout)?;
+        let dest = Arc::new(Mutex::new(dest));
+        let reader = UnixDatagramRead {
+            socket: socket.try_clone()?,
+            buffer: VecDeque::new(),
+            reply_to: dest.clone(),
+        };
+        let writer = UnixDatagramWrite { socket, dest };
+        Ok(Self {
+            duplex: Duplex::new(reader, writer),
+        })
+    }
+
+    pub(crate) fn into_duplex(self) -> Duplex<UnixDatagramRead, UnixDatagramWrite> {
+        self.duplex
+    }
+}
+
+impl<M: Message> MavConnection<M> for UnixDatagramConnection {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.recv()
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        self.duplex.read.recv_raw::<M>()
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.try_recv()
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        self.duplex.read.recv_timeout(timeout)
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        self.duplex.write.send(header, data)
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        self.duplex.write.send_raw(message)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.duplex.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.duplex.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.duplex.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.duplex.allow_recv_any_version()
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.duplex.read.link_stats()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.duplex.setup_signing(signing_data);
+    }
+}
@@ -0,0 +1,277 @@
+//! This module implements fan-out of received MAVLink messages to many subscribers.
+//!
+//! A [`Dispatcher`] owns a [`Connection`] and runs its receive loop. Any number of subscribers
+//! register a [`Filter`] and get a [`Subscription`] that receives the matching messages over a
+//! bounded channel.
+//!
+//! The receive loop never waits for a subscriber: when the queue of a subscriber is full the
+//! message is dropped for that subscriber and reported as [`RecvError::Lagged`] by its next
+//! receive call. Subscriptions that are dropped are removed from the dispatcher.
+//!
+//! ```ignore
+//! let dispatcher = Arc::new(Dispatcher::new(mavlink::connect::<MavMessage>("udpin:0.0.0.0:14550")?));
+//! let heartbeats = dispatcher.subscribe(Filter::all().message::<HEARTBEAT_DATA>(), 16);
+//! let vehicle = dispatcher.subscribe(Filter::all().system_id(1), 256);
+//! thread::spawn({
+//!     let dispatcher = dispatcher.clone();
+//!     move || dispatcher.run()
+//! });
+//! while let Ok((header, msg)) = heartbeats.recv() {
+//!     println!("{header:?} {msg:?}");
+//! }
+//! ```
+
+use core::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::connection::{is_transient, Connection, MavConnection};
+use crate::error::MessageReadError;
+use crate::{MavHeader, Message, MessageData};
+
+/// Selects the messages a [`Subscription`] receives
+///
+/// A message matches when it matches every criterion that is set, a filter without any
+/// criteria matches all messages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    message_ids: Vec<u32>,
+    system_id: Option<u8>,
+    component_id: Option<u8>,
+}
+
+impl Filter {
+    /// Create a filter matching all messages
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Only match messages with the given id
+    ///
+    /// Can be called multiple times to match any of several message ids.
+    pub fn message_id(mut self, message_id: u32) -> Self {
+        self.message_ids.push(message_id);
+        self
+    }
+
+    /// Only match messages of the given type, e.g. `HEARTBEAT_DATA`
+    ///
+    /// Can be combined with other message filters to match any of several message types.
+    pub fn message<D: MessageData>(self) -> Self {
+        self.message_id(D::ID)
+    }
+
+    /// Only match messages sent by the given system
+    pub fn system_id(mut self, system_id: u8) -> Self {
+        self.system_id = Some(system_id);
+        self
+    }
+
+    /// Only match messages sent by the given component
+    pub fn component_id(mut self, component_id: u8) -> Self {
+        self.component_id = Some(component_id);
+        self
+    }
+
+    /// Whether a message with the given header and message id matches this filter
+    pub fn matches(&self, header: &MavHeader, message_id: u32) -> bool {
+        (self.message_ids.is_empty() || self.message_ids.contains(&message_id))
+            && self.system_id.map_or(true, |id| id == header.system_id)
+            && self
+                .component_id
+                .map_or(true, |id| id == header.component_id)
+    }
+}
+
+/// Error receiving from a [`Subscription`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// The subscriber did not keep up, the given number of messages were dropped since the
+    /// previous receive call
+    ///
+    /// Receiving again returns the next message that was queued.
+    Lagged(u64),
+    /// No message is queued, only returned by [`Subscription::try_recv`] and
+    /// [`Subscription::recv_timeout`]
+    Empty,
+    /// The dispatcher stopped receiving, no more messages will arrive
+    Closed,
+}
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Lagged(count) => write!(f, "subscriber lagged behind by {count} messages"),
+            Self::Empty => write!(f, "no message queued"),
+            Self::Closed => write!(f, "dispatcher stopped"),
+        }
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Receiving end of a subscription to a [`Dispatcher`]
+///
+/// Dropping the subscription unsubscribes.
+pub struct Subscription<M: Message> {
+    receiver: Receiver<(MavHeader, M)>,
+    lagged: Arc<AtomicU64>,
+}
+
+impl<M: Message> Subscription<M> {
+    /// Wait for the next matching message
+    ///
+    /// # Errors
+    ///
+    /// - [`RecvError::Lagged`] if messages were dropped because the queue was full
+    /// - [`RecvError::Closed`] once the dispatcher stopped and all queued messages were received
+    pub fn recv(&self) -> Result<(MavHeader, M), RecvError> {
+        self.check_lagged()?;
+        self.receiver.recv().map_err(|_| RecvError::Closed)
+    }
+
+    /// Receive the next matching message without waiting
+    ///
+    /// # Errors
+    ///
+    /// Same as [`recv`](Self::recv), or [`RecvError::Empty`] if no message is queued.
+    pub fn try_recv(&self) -> Result<(MavHeader, M), RecvError> {
+        self.check_lagged()?;
+        self.receiver.try_recv().map_err(|err| match err {
+            mpsc::TryRecvError::Empty => RecvError::Empty,
+            mpsc::TryRecvError::Disconnected => RecvError::Closed,
+        })
+    }
+
+    /// Wait for the next matching message for at most `timeout`
+    ///
+    /// # Errors
+    ///
+    /// Same as [`recv`](Self::recv), or [`RecvError::Empty`] if no message arrived in time.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), RecvError> {
+        self.check_lagged()?;
+        self.receiver
+            .recv_timeout(timeout)
+            .map_err(|err| match err {
+                mpsc::RecvTimeoutError::Timeout => RecvError::Empty,
+                mpsc::RecvTimeoutError::Disconnected => RecvError::Closed,
+            })
+    }
+
+    fn check_lagged(&self) -> Result<(), RecvError> {
+        match self.lagged.swap(0, Ordering::Relaxed) {
+            0 => Ok(()),
+            count => Err(RecvError::Lagged(count)),
+        }
+    }
+}
+
+struct Subscriber<M: Message> {
+    filter: Filter,
+    sender: SyncSender<(MavHeader, M)>,
+    lagged: Arc<AtomicU64>,
+}
+
+/// Receives messages from a connection and distributes them to subscribers
+pub struct Dispatcher<M: Message> {
+    connection: Connection<M>,
+    subscribers: Mutex<Vec<Subscriber<M>>>,
+    closed: AtomicBool,
+}
+
+impl<M: Message + Clone> Dispatcher<M> {
+    /// Create a dispatcher for the messages received on `connection`
+    pub fn new(connection: Connection<M>) -> Self {
+        Self {
+            connection,
+            subscribers: Mutex::default(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// The connection messages are received on, which can also be used to send messages
+    pub fn connection(&self) -> &Connection<M> {
+        &self.connection
+    }
+
+    /// Subscribe to the messages matching `filter`
+    ///
+    /// Up to `capacity` messages are queued for the subscription. Subscribing after the
+    /// dispatcher stopped returns a subscription that is already closed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `capacity` is 0.
+    pub fn subscribe(&self, filter: Filter, capacity: usize) -> Subscription<M> {
+        assert!(capacity > 0, "subscription capacity must not be 0");
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let lagged = Arc::new(AtomicU64::new(0));
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if !self.closed.load(Ordering::Relaxed) {
+            subscribers.push(Subscriber {
+                filter,
+                sender,
+                lagged: Arc::clone(&lagged),
+            });
+        }
+        Subscription { receiver, lagged }
+    }
+
+    /// Number of subscriptions that have not been dropped yet
+    ///
+    /// Dropped subscriptions are only noticed when a message matching their filter is dispatched.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+
+    /// Distribute a message to all subscribers with a matching filter
+    ///
+    /// Returns the number of subscribers the message was queued for.
+    pub fn dispatch(&self, header: MavHeader, message: &M) -> usize {
+        let message_id = message.message_id();
+        let mut delivered = 0;
+        self.subscribers.lock().unwrap().retain(|subscriber| {
+            if !subscriber.filter.matches(&header, message_id) {
+                return true;
+            }
+            match subscriber.sender.try_send((header, message.clone())) {
+                Ok(()) => {
+                    delivered += 1;
+                    true
+                }
+                Err(TrySendError::Full(_)) => {
+                    subscriber.lagged.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+        delivered
+    }
+
+    /// Receive messages on the connection and dispatch them to the subscribers
+    ///
+    /// Returns once receiving fails with an I/O error other than a timeout or interruption,
+    /// e.g. when a file has been read completely or a TCP peer disconnected. All subscriptions
+    /// are closed afterwards.
+    pub fn run(&self) {
+        loop {
+            match self.connection.recv() {
+                Ok((header, message)) => {
+                    self.dispatch(header, &message);
+                }
+                Err(MessageReadError::Io(e)) if is_transient(&e) => {}
+                Err(MessageReadError::Io(_)) => break,
+                // messages of other dialects are not dispatched
+                Err(MessageReadError::Parse(_)) => {}
+            }
+        }
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        subscribers.clear();
+    }
+}
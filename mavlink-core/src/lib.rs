@@ -39,7 +39,7 @@
 //! - `versioned` functions read messages of the version specified in an aditional `version` parameter
 //! - `raw_message` functions return an unparsed message as [`MAVLinkV1MessageRaw`], [`MAVLinkV2MessageRaw`] or [`MAVLinkMessageRaw`]
 //! - `msg` functions return a parsed message as a tupel of [`MavHeader`] and the `Message` of the specified dialect
-//! - `_async` functions, which are only enabled with the `tokio-1` feature, are [async](https://doc.rust-lang.org/std/keyword.async.html) and read from an [`AsyncPeekReader`] instead.
+//! - `_async` functions, which are only enabled with the `tokio-1` or `futures-io` feature, are [async](https://doc.rust-lang.org/std/keyword.async.html) and read from an [`AsyncPeekReader`] instead.
 //! - `_signed` functions, which are only enabled with the `signing` feature, have an `Option<&SigningData>` parameter that allows the use of MAVLink 2 message signing.
 //!   MAVLink 1 exclusive functions do not have a `_signed` variant and functions that allow both MAVLink 1 and 2 messages treat MAVLink 1 messages as unsigned.
 //!   When an invalidly signed message is received it is ignored.
@@ -61,8 +61,8 @@
 //! - `v1` functions write messages using MAVLink 1 serialisation
 //! - `v2` functions write messages using MAVLink 2 serialisation
 //! - `versioned` functions write messages using the version specified in an aditional `version` parameter
-//! - `_async` functions, which are only enabled with the `tokio-1` feature, are
-//!   [async](https://doc.rust-lang.org/std/keyword.async.html) and write from an [`tokio::io::AsyncWrite`]r instead.
+//! - `_async` functions, which are only enabled with the `tokio-1` or `futures-io` feature, are
+//!   [async](https://doc.rust-lang.org/std/keyword.async.html) and write to an [`AsyncByteWrite`]r instead.
 //! - `_signed` functions, which are only enabled with the `signing` feature, have an `Option<&SigningData>` parameter that allows the use of MAVLink 2 message signing.
 //!
 //! ## Write errors
@@ -76,8 +76,9 @@
 //! [`PeekReader`]: peek_reader::PeekReader
 //! [`AsyncPeekReader`]: async_peek_reader::AsyncPeekReader
 //! [`UnexpectedEof`]: std::io::ErrorKind::UnexpectedEof
-//! [`AsyncRead`]: tokio::io::AsyncRead
-//! [`AsyncWrite`]: tokio::io::AsyncWrite
+//! [`AsyncRead`]: async_io::AsyncByteRead
+//! [`AsyncWrite`]: async_io::AsyncByteWrite
+//! [`AsyncByteWrite`]: async_io::AsyncByteWrite
 //! [`Interrupted`]: std::io::ErrorKind::Interrupted
 #![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -99,6 +100,13 @@ use serde::{Deserialize, Serialize};
 pub mod peek_reader;
 use peek_reader::PeekReader;
 
+pub mod link_stats;
+use link_stats::ReadStats;
+#[cfg(feature = "tokio-codec")]
+pub mod codec;
+pub mod parser;
+pub mod slice_parser;
+
 use crate::{
     bytes::Bytes,
     error::{MessageReadError, MessageWriteError, ParserError},
@@ -116,18 +124,37 @@ pub mod error;
 pub mod types;
 #[cfg(feature = "std")]
 pub use self::connection::{connect, Connectable, Connection, MavConnection};
+#[cfg(feature = "std")]
+pub mod dispatcher;
+#[cfg(feature = "std")]
+pub mod router;
+#[cfg(feature = "std")]
+pub mod tlog;
 
-#[cfg(feature = "tokio-1")]
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
 mod async_connection;
+#[cfg(all(feature = "tokio-1", feature = "tcp"))]
+pub use self::async_connection::AsyncTcpServerConnection;
+#[cfg(all(feature = "tokio-1", feature = "udp"))]
+pub use self::async_connection::AsyncUdpServerConnection;
 #[cfg(feature = "tokio-1")]
-pub use self::async_connection::{connect_async, AsyncConnectable, AsyncMavConnection};
-
-#[cfg(feature = "tokio-1")]
+pub use self::async_connection::{connect_async, AsyncConnectable, AsyncMemConnection};
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub use self::async_connection::{
+    AsyncMavConnection, AsyncMavReceiver, AsyncMavSender, AsyncMavSink, AsyncMavStream,
+    AsyncStreamConnection,
+};
+#[cfg(feature = "websocket")]
+pub use self::async_connection::{AsyncWebSocketServerConnection, WebSocketConfig, WebSocketMode};
+
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub mod async_io;
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+use async_io::{AsyncByteRead, AsyncByteWrite};
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
 pub mod async_peek_reader;
-#[cfg(feature = "tokio-1")]
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
 use async_peek_reader::AsyncPeekReader;
-#[cfg(feature = "tokio-1")]
-use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 #[cfg(any(feature = "embedded", feature = "embedded-hal-02"))]
 pub mod embedded;
@@ -155,14 +182,35 @@ pub use connectable::ConnectionAddress;
 #[cfg(feature = "direct-serial")]
 pub use connection::direct_serial::config::SerialConfig;
 
+#[cfg(any(feature = "tcp", feature = "direct-serial"))]
+pub use connection::reconnect::ReconnectPolicy;
+
 #[cfg(feature = "tcp")]
 pub use connection::tcp::config::{TcpConfig, TcpMode};
 
+#[cfg(feature = "tcp")]
+pub use connection::tcp::server::TcpServerConnection;
+
 #[cfg(feature = "udp")]
 pub use connection::udp::config::{UdpConfig, UdpMode};
 
+#[cfg(feature = "udp")]
+pub use connection::udp::server::UdpServerConnection;
+
+#[cfg(all(feature = "unix", unix))]
+pub use connection::unix::config::{UnixConfig, UnixMode};
+
+#[cfg(feature = "std")]
+pub use connection::file::config::{FileConfig, FileMode};
+
+#[cfg(feature = "std")]
+pub use connection::mem::{config::MemConfig, MemConnection};
+
+#[cfg(feature = "std")]
+pub use connection::split::{MavReceiver, MavSender};
+
 #[cfg(feature = "std")]
-pub use connection::file::config::FileConfig;
+pub use connection::stream::StreamConnection;
 
 /// Maximum size of any MAVLink frame in bytes.
 ///
@@ -215,6 +263,13 @@ where
     fn random_message_from_id<R: rand::RngCore>(id: u32, rng: &mut R) -> Option<Self>;
     /// Return a message types [CRC_EXTRA byte](https://mavlink.io/en/guide/serialization.html#crc_extra)
     fn extra_crc(id: u32) -> u8;
+    /// Return the full payload length of the specified message id, including extension fields
+    ///
+    /// `None` if the message is not part of the dialect or the dialect does not provide the length.
+    fn encoded_len(id: u32) -> Option<usize> {
+        let _ = id;
+        None
+    }
 }
 
 pub trait MessageData: Sized {
@@ -420,7 +475,7 @@ impl ReadVersion {
             conn.protocol_version().into()
         }
     }
-    #[cfg(feature = "tokio-1")]
+    #[cfg(any(feature = "tokio-1", feature = "futures-io"))]
     fn from_async_conn_cfg<C: AsyncMavConnection<M>, M: Message + Sync + Send>(conn: &C) -> Self {
         if conn.allow_recv_any_version() {
             Self::Any
@@ -477,8 +532,8 @@ pub fn read_versioned_raw_message<M: Message, R: Read>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(feature = "tokio-1")]
-pub async fn read_versioned_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn read_versioned_msg_async<M: Message, R: AsyncByteRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
 ) -> Result<(MavHeader, M), MessageReadError> {
@@ -494,8 +549,8 @@ pub async fn read_versioned_msg_async<M: Message, R: tokio::io::AsyncRead + Unpi
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(feature = "tokio-1")]
-pub async fn read_versioned_raw_message_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn read_versioned_raw_message_async<M: Message, R: AsyncByteRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
 ) -> Result<MAVLinkMessageRaw, MessageReadError> {
@@ -564,11 +619,8 @@ pub fn read_versioned_msg_signed<M: Message, R: Read>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_versioned_raw_message_async_signed<
-    M: Message,
-    R: tokio::io::AsyncRead + Unpin,
->(
+#[cfg(all(any(feature = "tokio-1", feature = "futures-io"), feature = "signing"))]
+pub async fn read_versioned_raw_message_async_signed<M: Message, R: AsyncByteRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
     signing_data: Option<&SigningData>,
@@ -592,8 +644,8 @@ pub async fn read_versioned_raw_message_async_signed<
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_versioned_msg_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(all(any(feature = "tokio-1", feature = "futures-io"), feature = "signing"))]
+pub async fn read_versioned_msg_async_signed<M: Message, R: AsyncByteRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
     signing_data: Option<&SigningData>,
@@ -792,6 +844,24 @@ impl MAVLinkV1MessageRaw {
     }
 }
 
+/// Records a frame candidate with a checksum mismatch
+///
+/// The candidate only counts as a CRC error if its header is plausible, i.e. the message is part of
+/// the dialect `M` and the payload fits it. Otherwise the STX marker was most likely part of other
+/// data, which is only counted if the message ID is unknown, as these can not be told apart from
+/// messages of other dialects.
+///
+/// Without the payload length of the message, any candidate of a message of the dialect counts as
+/// a CRC error.
+fn record_invalid_crc<M: Message>(stats: &mut ReadStats, message_id: u32, payload_length: u8) {
+    match M::encoded_len(message_id) {
+        Some(len) if usize::from(payload_length) <= len => stats.crc_errors += 1,
+        Some(_) => {}
+        None if M::default_message_from_id(message_id).is_some() => stats.crc_errors += 1,
+        None => stats.unknown_messages += 1,
+    }
+}
+
 fn try_decode_v1<M: Message, R: Read>(
     reader: &mut PeekReader<R>,
 ) -> Result<Option<MAVLinkV1MessageRaw>, MessageReadError> {
@@ -811,15 +881,21 @@ fn try_decode_v1<M: Message, R: Read>(
     // (an STX byte may appear in the middle of a message)
     if message.has_valid_crc::<M>() {
         reader.consume(message.raw_bytes().len());
+        reader.stats.frames_received += 1;
         Ok(Some(message))
     } else {
+        record_invalid_crc::<M>(
+            &mut reader.stats,
+            message.message_id().into(),
+            message.payload_length(),
+        );
         Ok(None)
     }
 }
 
-#[cfg(feature = "tokio-1")]
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
 // other then the blocking version the STX is read not peeked, this changed some sizes
-async fn try_decode_v1_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+async fn try_decode_v1_async<M: Message, R: AsyncByteRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
 ) -> Result<Option<MAVLinkV1MessageRaw>, MessageReadError> {
     let mut message = MAVLinkV1MessageRaw::new();
@@ -839,8 +915,14 @@ async fn try_decode_v1_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
     // (an STX byte may appear in the middle of a message)
     if message.has_valid_crc::<M>() {
         reader.consume(message.raw_bytes().len() - 1);
+        reader.stats.frames_received += 1;
         Ok(Some(message))
     } else {
+        record_invalid_crc::<M>(
+            &mut reader.stats,
+            message.message_id().into(),
+            message.payload_length(),
+        );
         Ok(None)
     }
 }
@@ -872,8 +954,8 @@ pub fn read_v1_raw_message<M: Message, R: Read>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(feature = "tokio-1")]
-pub async fn read_v1_raw_message_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn read_v1_raw_message_async<M: Message, R: AsyncByteRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
 ) -> Result<MAVLinkV1MessageRaw, MessageReadError> {
     loop {
@@ -962,8 +1044,8 @@ pub fn read_v1_msg<M: Message, R: Read>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(feature = "tokio-1")]
-pub async fn read_v1_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn read_v1_msg_async<M: Message, R: AsyncByteRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
 ) -> Result<(MavHeader, M), MessageReadError> {
     let message = read_v1_raw_message_async::<M, _>(r).await?;
@@ -1363,6 +1445,7 @@ fn try_decode_v2<M: Message, R: Read>(
     if message.incompatibility_flags() & !MAVLINK_SUPPORTED_IFLAGS > 0 {
         // if there are incompatibility flags set that we do not know discard the message
         reader.consume(1);
+        reader.stats.incompatible_frames += 1;
         return Ok(None);
     }
 
@@ -1376,14 +1459,21 @@ fn try_decode_v2<M: Message, R: Read>(
     if message.has_valid_crc::<M>() {
         // even if the signature turn out to be invalid the valid crc shows that the received data presents a valid message as opposed to random bytes
         reader.consume(message.raw_bytes().len());
+        reader.stats.frames_received += 1;
     } else {
         reader.consume(1);
+        record_invalid_crc::<M>(
+            &mut reader.stats,
+            message.message_id(),
+            message.payload_length(),
+        );
         return Ok(None);
     }
 
     #[cfg(feature = "signing")]
     if let Some(signing_data) = signing_data {
         if !signing_data.verify_signature(&message) {
+            reader.stats.bad_signatures += 1;
             return Ok(None);
         }
     }
@@ -1391,10 +1481,10 @@ fn try_decode_v2<M: Message, R: Read>(
     Ok(Some(message))
 }
 
-#[cfg(feature = "tokio-1")]
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
 #[allow(unused_variables)]
 // other then the blocking version the STX is read not peeked, this changed some sizes
-async fn try_decode_v2_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+async fn try_decode_v2_async<M: Message, R: AsyncByteRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<Option<MAVLinkV2MessageRaw>, MessageReadError> {
@@ -1407,6 +1497,7 @@ async fn try_decode_v2_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
 
     if message.incompatibility_flags() & !MAVLINK_SUPPORTED_IFLAGS > 0 {
         // if there are incompatibility flags set that we do not know discard the message
+        reader.stats.incompatible_frames += 1;
         return Ok(None);
     }
 
@@ -1420,13 +1511,20 @@ async fn try_decode_v2_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
     if message.has_valid_crc::<M>() {
         // even if the signature turn out to be invalid the valid crc shows that the received data presents a valid message as opposed to random bytes
         reader.consume(message.raw_bytes().len() - 1);
+        reader.stats.frames_received += 1;
     } else {
+        record_invalid_crc::<M>(
+            &mut reader.stats,
+            message.message_id(),
+            message.payload_length(),
+        );
         return Ok(None);
     }
 
     #[cfg(feature = "signing")]
     if let Some(signing_data) = signing_data {
         if !signing_data.verify_signature(&message) {
+            reader.stats.bad_signatures += 1;
             return Ok(None);
         }
     }
@@ -1482,16 +1580,16 @@ fn read_v2_raw_message_inner<M: Message, R: Read>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(feature = "tokio-1")]
-pub async fn read_v2_raw_message_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn read_v2_raw_message_async<M: Message, R: AsyncByteRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
 ) -> Result<MAVLinkV2MessageRaw, MessageReadError> {
     read_v2_raw_message_async_inner::<M, R>(reader, None).await
 }
 
-#[cfg(feature = "tokio-1")]
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
 #[allow(unused_variables)]
-async fn read_v2_raw_message_async_inner<M: Message, R: tokio::io::AsyncRead + Unpin>(
+async fn read_v2_raw_message_async_inner<M: Message, R: AsyncByteRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<MAVLinkV2MessageRaw, MessageReadError> {
@@ -1514,8 +1612,8 @@ async fn read_v2_raw_message_async_inner<M: Message, R: tokio::io::AsyncRead + U
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_v2_raw_message_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(all(any(feature = "tokio-1", feature = "futures-io"), feature = "signing"))]
+pub async fn read_v2_raw_message_async_signed<M: Message, R: AsyncByteRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<MAVLinkV2MessageRaw, MessageReadError> {
@@ -1617,8 +1715,8 @@ fn read_v2_msg_inner<M: Message, R: Read>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(feature = "tokio-1")]
-pub async fn read_v2_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn read_v2_msg_async<M: Message, R: AsyncByteRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
 ) -> Result<(MavHeader, M), MessageReadError> {
     read_v2_msg_async_inner(read, None).await
@@ -1629,16 +1727,16 @@ pub async fn read_v2_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_v2_msg_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(all(any(feature = "tokio-1", feature = "futures-io"), feature = "signing"))]
+pub async fn read_v2_msg_async_signed<M: Message, R: AsyncByteRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<(MavHeader, M), MessageReadError> {
     read_v2_msg_async_inner(read, signing_data).await
 }
 
-#[cfg(feature = "tokio-1")]
-async fn read_v2_msg_async_inner<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+async fn read_v2_msg_async_inner<M: Message, R: AsyncByteRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<(MavHeader, M), MessageReadError> {
@@ -1678,6 +1776,7 @@ pub async fn read_v2_msg_async<M: Message, R: embedded_io_async::Read>(
     ))
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 /// Raw byte representation of a MAVLink message of either version
 pub enum MAVLinkMessageRaw {
     V1(MAVLinkV1MessageRaw),
@@ -1685,6 +1784,12 @@ pub enum MAVLinkMessageRaw {
 }
 
 impl MAVLinkMessageRaw {
+    pub fn raw_bytes(&self) -> &[u8] {
+        match self {
+            Self::V1(msg) => msg.raw_bytes(),
+            Self::V2(msg) => msg.raw_bytes(),
+        }
+    }
     pub fn payload(&self) -> &[u8] {
         match self {
             Self::V1(msg) => msg.payload(),
@@ -1775,6 +1880,7 @@ fn read_any_raw_message_inner<M: Message, R: Read>(
                         if signing.config.allow_unsigned {
                             return Ok(MAVLinkMessageRaw::V1(message));
                         }
+                        reader.stats.bad_signatures += 1;
                     } else {
                         return Ok(MAVLinkMessageRaw::V1(message));
                     }
@@ -1798,8 +1904,8 @@ fn read_any_raw_message_inner<M: Message, R: Read>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(feature = "tokio-1")]
-pub async fn read_any_raw_message_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn read_any_raw_message_async<M: Message, R: AsyncByteRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
 ) -> Result<MAVLinkMessageRaw, MessageReadError> {
     read_any_raw_message_async_inner::<M, R>(reader, None).await
@@ -1812,17 +1918,17 @@ pub async fn read_any_raw_message_async<M: Message, R: tokio::io::AsyncRead + Un
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn read_any_raw_message_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(all(any(feature = "tokio-1", feature = "futures-io"), feature = "signing"))]
+pub async fn read_any_raw_message_async_signed<M: Message, R: AsyncByteRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<MAVLinkMessageRaw, MessageReadError> {
     read_any_raw_message_async_inner::<M, R>(reader, signing_data).await
 }
 
-#[cfg(feature = "tokio-1")]
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
 #[allow(unused_variables)]
-async fn read_any_raw_message_async_inner<M: Message, R: tokio::io::AsyncRead + Unpin>(
+async fn read_any_raw_message_async_inner<M: Message, R: AsyncByteRead + Unpin>(
     reader: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<MAVLinkMessageRaw, MessageReadError> {
@@ -1847,6 +1953,7 @@ async fn read_any_raw_message_async_inner<M: Message, R: tokio::io::AsyncRead +
                         if signing.config.allow_unsigned {
                             return Ok(MAVLinkMessageRaw::V1(message));
                         }
+                        reader.stats.bad_signatures += 1;
                     } else {
                         return Ok(MAVLinkMessageRaw::V1(message));
                     }
@@ -1911,8 +2018,8 @@ fn read_any_msg_inner<M: Message, R: Read>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(feature = "tokio-1")]
-pub async fn read_any_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn read_any_msg_async<M: Message, R: AsyncByteRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
 ) -> Result<(MavHeader, M), MessageReadError> {
     read_any_msg_async_inner(read, None).await
@@ -1925,17 +2032,17 @@ pub async fn read_any_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
 /// # Errors
 ///
 /// See [`read_` function error documentation](crate#read-errors)
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
+#[cfg(all(any(feature = "tokio-1", feature = "futures-io"), feature = "signing"))]
 #[inline]
-pub async fn read_any_msg_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+pub async fn read_any_msg_async_signed<M: Message, R: AsyncByteRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<(MavHeader, M), MessageReadError> {
     read_any_msg_async_inner(read, signing_data).await
 }
 
-#[cfg(feature = "tokio-1")]
-async fn read_any_msg_async_inner<M: Message, R: tokio::io::AsyncRead + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+async fn read_any_msg_async_inner<M: Message, R: AsyncByteRead + Unpin>(
     read: &mut AsyncPeekReader<R>,
     signing_data: Option<&SigningData>,
 ) -> Result<(MavHeader, M), MessageReadError> {
@@ -1989,13 +2096,13 @@ pub fn write_versioned_msg_signed<M: Message, W: Write>(
     }
 }
 
-/// Asynchronously write a MAVLink message using the given MAVLink version to a [`AsyncWrite`]r.
+/// Asynchronously write a MAVLink message using the given MAVLink version to a [`AsyncByteWrite`]r.
 ///
 /// # Errors
 ///
 /// See [`write_` function error documentation](crate#write-errors).
-#[cfg(feature = "tokio-1")]
-pub async fn write_versioned_msg_async<M: Message, W: AsyncWrite + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn write_versioned_msg_async<M: Message, W: AsyncByteWrite + Unpin>(
     w: &mut W,
     version: MavlinkVersion,
     header: MavHeader,
@@ -2007,15 +2114,15 @@ pub async fn write_versioned_msg_async<M: Message, W: AsyncWrite + Unpin>(
     }
 }
 
-/// Asynchronously write a MAVLink message using the given MAVLink version to a [`AsyncWrite`]r with signing support.
+/// Asynchronously write a MAVLink message using the given MAVLink version to a [`AsyncByteWrite`]r with signing support.
 ///
 /// When using [`MavlinkVersion::V1`] signing is ignored.
 ///
 /// # Errors
 ///
 /// See [`write_` function error documentation](crate#write-errors).
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
-pub async fn write_versioned_msg_async_signed<M: Message, W: AsyncWrite + Unpin>(
+#[cfg(all(any(feature = "tokio-1", feature = "futures-io"), feature = "signing"))]
+pub async fn write_versioned_msg_async_signed<M: Message, W: AsyncByteWrite + Unpin>(
     w: &mut W,
     version: MavlinkVersion,
     header: MavHeader,
@@ -2102,13 +2209,13 @@ pub fn write_v2_msg_signed<M: Message, W: Write>(
     Ok(len)
 }
 
-/// Asynchronously write a MAVLink 2 message to a [`AsyncWrite`]r.
+/// Asynchronously write a MAVLink 2 message to a [`AsyncByteWrite`]r.
 ///
 /// # Errors
 ///
 /// See [`write_` function error documentation](crate#write-errors).
-#[cfg(feature = "tokio-1")]
-pub async fn write_v2_msg_async<M: Message, W: AsyncWrite + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn write_v2_msg_async<M: Message, W: AsyncByteWrite + Unpin>(
     w: &mut W,
     header: MavHeader,
     data: &M,
@@ -2119,19 +2226,19 @@ pub async fn write_v2_msg_async<M: Message, W: AsyncWrite + Unpin>(
     let payload_length: usize = message_raw.payload_length().into();
     let len = 1 + MAVLinkV2MessageRaw::HEADER_SIZE + payload_length + 2;
 
-    w.write_all(&message_raw.0[..len]).await?;
+    async_io::write_all(w, &message_raw.0[..len]).await?;
 
     Ok(len)
 }
 
-/// Write a MAVLink 2 message to a [`AsyncWrite`]r with signing support.
+/// Write a MAVLink 2 message to a [`AsyncByteWrite`]r with signing support.
 ///
 /// # Errors
 ///
 /// See [`write_` function error documentation](crate#write-errors).
 #[cfg(feature = "signing")]
-#[cfg(feature = "tokio-1")]
-pub async fn write_v2_msg_async_signed<M: Message, W: AsyncWrite + Unpin>(
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn write_v2_msg_async_signed<M: Message, W: AsyncByteWrite + Unpin>(
     w: &mut W,
     header: MavHeader,
     data: &M,
@@ -2156,7 +2263,7 @@ pub async fn write_v2_msg_async_signed<M: Message, W: AsyncWrite + Unpin>(
     let payload_length: usize = message_raw.payload_length().into();
     let len = 1 + MAVLinkV2MessageRaw::HEADER_SIZE + payload_length + 2 + signature_len;
 
-    w.write_all(&message_raw.0[..len]).await?;
+    async_io::write_all(w, &message_raw.0[..len]).await?;
 
     Ok(len)
 }
@@ -2212,13 +2319,13 @@ pub fn write_v1_msg<M: Message, W: Write>(
     Ok(len)
 }
 
-/// Asynchronously write a MAVLink 1 message to a [`AsyncWrite`]r.
+/// Asynchronously write a MAVLink 1 message to a [`AsyncByteWrite`]r.
 ///
 /// # Errors
 ///
-/// Returns the first error that occurs when writing to the [`AsyncWrite`]r.
-#[cfg(feature = "tokio-1")]
-pub async fn write_v1_msg_async<M: Message, W: AsyncWrite + Unpin>(
+/// Returns the first error that occurs when writing to the [`AsyncByteWrite`]r.
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
+pub async fn write_v1_msg_async<M: Message, W: AsyncByteWrite + Unpin>(
     w: &mut W,
     header: MavHeader,
     data: &M,
@@ -2232,7 +2339,7 @@ pub async fn write_v1_msg_async<M: Message, W: AsyncWrite + Unpin>(
     let payload_length: usize = message_raw.payload_length().into();
     let len = 1 + MAVLinkV1MessageRaw::HEADER_SIZE + payload_length + 2;
 
-    w.write_all(&message_raw.0[..len]).await?;
+    async_io::write_all(w, &message_raw.0[..len]).await?;
 
     Ok(len)
 }
@@ -2271,9 +2378,9 @@ pub fn read_raw_versioned_msg<M: Message, R: Read>(
     read_versioned_raw_message::<M, _>(r, version)
 }
 
-#[cfg(feature = "tokio-1")]
+#[cfg(any(feature = "tokio-1", feature = "futures-io"))]
 #[deprecated = "use read_versioned_raw_message_async instead"]
-pub async fn read_raw_versioned_msg_async<M: Message, R: tokio::io::AsyncRead + Unpin>(
+pub async fn read_raw_versioned_msg_async<M: Message, R: AsyncByteRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
 ) -> Result<MAVLinkMessageRaw, MessageReadError> {
@@ -2290,9 +2397,9 @@ pub fn read_raw_versioned_msg_signed<M: Message, R: Read>(
     read_versioned_raw_message_signed::<M, _>(r, version, signing_data)
 }
 
-#[cfg(all(feature = "tokio-1", feature = "signing"))]
+#[cfg(all(any(feature = "tokio-1", feature = "futures-io"), feature = "signing"))]
 #[deprecated = "use read_versioned_raw_message_async_signed instead"]
-pub async fn read_raw_versioned_msg_async_signed<M: Message, R: tokio::io::AsyncRead + Unpin>(
+pub async fn read_raw_versioned_msg_async_signed<M: Message, R: AsyncByteRead + Unpin>(
     r: &mut AsyncPeekReader<R>,
     version: ReadVersion,
     signing_data: Option<&SigningData>,
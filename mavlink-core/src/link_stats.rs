@@ -0,0 +1,205 @@
+//! Receive statistics of MAVLink links.
+//!
+//! The [`PeekReader`](crate::peek_reader::PeekReader) used by all `read_*` functions keeps a
+//! [`ReadStats`] record of the data it processed, including the frames that were silently discarded
+//! while searching for the next valid message. Connections combine these with per sender
+//! sequence tracking into a [`LinkStats`] snapshot that can be used to judge the quality of a link.
+
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+#[cfg(feature = "std")]
+use crate::{MAVLinkMessageRaw, MavHeader};
+
+/// Counters of the data processed while reading MAVLink frames
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReadStats {
+    /// Number of bytes read from the underlying reader
+    pub bytes_received: u64,
+    /// Number of frames with a valid checksum
+    pub frames_received: u64,
+    /// Number of frames discarded because of a checksum mismatch
+    ///
+    /// Only frames whose header is plausible, with a message ID of the dialect and a payload length
+    /// that fits the message, are counted. STX markers found inside other data are skipped silently.
+    pub crc_errors: u64,
+    /// Number of frame candidates discarded because their message ID is not part of the dialect
+    ///
+    /// As the CRC_EXTRA of an unknown message is not known, these frames can not be told apart
+    /// from corrupted frames and are not counted as CRC errors. This includes STX markers found
+    /// inside other data that are followed by an unknown message ID.
+    pub unknown_messages: u64,
+    /// Number of valid frames discarded because of a missing or invalid signature
+    pub bad_signatures: u64,
+    /// Number of frames discarded because of unsupported incompatibility flags
+    pub incompatible_frames: u64,
+}
+
+impl core::ops::AddAssign for ReadStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.bytes_received += rhs.bytes_received;
+        self.frames_received += rhs.frames_received;
+        self.crc_errors += rhs.crc_errors;
+        self.unknown_messages += rhs.unknown_messages;
+        self.bad_signatures += rhs.bad_signatures;
+        self.incompatible_frames += rhs.incompatible_frames;
+    }
+}
+
+/// Packet loss estimate for a single sender derived from the packet sequence numbers
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceStats {
+    /// Number of messages received from this sender
+    pub received: u64,
+    /// Number of messages missing according to the gaps in the sequence numbers
+    pub lost: u64,
+    /// Number of messages received twice or out of order
+    ///
+    /// A sequence number that lies less than half the sequence range before the expected one is
+    /// considered to have gone backwards instead of skipping ahead, and is not counted as loss.
+    pub out_of_order: u64,
+    /// Sequence number of the last message received from this sender
+    pub last_sequence: u8,
+}
+
+#[cfg(feature = "std")]
+impl SequenceStats {
+    /// Fraction of messages lost, between 0.0 and 1.0
+    pub fn loss_ratio(&self) -> f64 {
+        let total = self.received + self.lost;
+        if total == 0 {
+            0.0
+        } else {
+            self.lost as f64 / total as f64
+        }
+    }
+
+    fn record(&mut self, sequence: u8) {
+        self.received += 1;
+        if self.received > 1 {
+            let expected = self.last_sequence.wrapping_add(1);
+            let gap = sequence.wrapping_sub(expected);
+            if gap > u8::MAX / 2 {
+                // the sequence number went backwards, keep tracking gaps from the newest message
+                self.out_of_order += 1;
+                return;
+            }
+            self.lost += u64::from(gap);
+        }
+        self.last_sequence = sequence;
+    }
+}
+
+/// Snapshot of the receive statistics of a connection
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LinkStats {
+    /// Counters of the data received on the connection
+    pub read: ReadStats,
+    /// Sequence statistics per sender, keyed by `(system_id, component_id)`
+    pub sources: HashMap<(u8, u8), SequenceStats>,
+}
+
+#[cfg(feature = "std")]
+impl LinkStats {
+    /// Number of messages missing according to the sequence numbers of all senders
+    pub fn lost(&self) -> u64 {
+        self.sources.values().map(|source| source.lost).sum()
+    }
+
+    /// Fraction of messages lost over all senders, between 0.0 and 1.0
+    pub fn loss_ratio(&self) -> f64 {
+        let received: u64 = self.sources.values().map(|source| source.received).sum();
+        let lost = self.lost();
+        if received + lost == 0 {
+            0.0
+        } else {
+            lost as f64 / (received + lost) as f64
+        }
+    }
+
+    /// Update the statistics after a receive attempt
+    pub(crate) fn update<F: ReceivedFrame>(&mut self, read: ReadStats, frame: Option<&F>) {
+        self.read = read;
+        if let Some(frame) = frame {
+            let (system_id, component_id, sequence) = frame.source();
+            self.sources
+                .entry((system_id, component_id))
+                .or_default()
+                .record(sequence);
+        }
+    }
+}
+
+/// Statistics of a connection, updated by its receive calls
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub(crate) struct SharedLinkStats(Mutex<LinkStats>);
+
+#[cfg(feature = "std")]
+impl SharedLinkStats {
+    /// Update the statistics with the read counters and the outcome of a receive call
+    pub(crate) fn record<F: ReceivedFrame, E>(&self, read: ReadStats, result: &Result<F, E>) {
+        self.0.lock().unwrap().update(read, result.as_ref().ok());
+    }
+
+    /// Add the read counters of a receive call to the statistics, for connections that read
+    /// from several readers
+    #[cfg(any(feature = "tcp", feature = "udp", feature = "websocket"))]
+    pub(crate) fn record_delta<F: ReceivedFrame, E>(
+        &self,
+        delta: ReadStats,
+        result: &Result<F, E>,
+    ) {
+        let mut stats = self.0.lock().unwrap();
+        let mut read = stats.read;
+        read += delta;
+        stats.update(read, result.as_ref().ok());
+    }
+
+    pub(crate) fn snapshot(&self) -> LinkStats {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Received messages from which the sender and sequence number can be determined
+#[cfg(feature = "std")]
+pub(crate) trait ReceivedFrame {
+    /// `(system_id, component_id, sequence)` of the message
+    fn source(&self) -> (u8, u8, u8);
+}
+
+#[cfg(feature = "std")]
+impl<M> ReceivedFrame for (MavHeader, M) {
+    fn source(&self) -> (u8, u8, u8) {
+        (self.0.system_id, self.0.component_id, self.0.sequence)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ReceivedFrame for MAVLinkMessageRaw {
+    fn source(&self) -> (u8, u8, u8) {
+        (self.system_id(), self.component_id(), self.sequence())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_gaps() {
+        let mut stats = SequenceStats::default();
+        for sequence in [250, 251, 253, 254, 255, 0, 4] {
+            stats.record(sequence);
+        }
+        assert_eq!(stats.received, 7);
+        // 252, 1, 2 and 3 are missing
+        assert_eq!(stats.lost, 4);
+        assert_eq!(stats.last_sequence, 4);
+        assert!((stats.loss_ratio() - 4.0 / 11.0).abs() < f64::EPSILON);
+    }
+}
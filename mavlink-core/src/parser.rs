@@ -0,0 +1,169 @@
+//! This module implements a push-based MAVLink parser.
+//!
+//! Unlike the `read_*` functions, which pull data from a [`PeekReader`](crate::peek_reader::PeekReader)
+//! and block until a whole message has been read, [`MavParser`] is fed data as it becomes available,
+//! either one byte at a time (like the C library's `mavlink_parse_char`) or in slices of arbitrary size.
+//! It does not need a `Read` implementation, never blocks and does not allocate, so it can be used from
+//! interrupt handlers, event loops or any `poll`-style code.
+//!
+//! ```ignore
+//! let mut parser = MavParser::<MavMessage>::new(ReadVersion::Any);
+//! loop {
+//!     let n = uart.read(&mut buf)?;
+//!     for raw in parser.parse(&buf[..n]) {
+//!         handle(raw);
+//!     }
+//! }
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::{
+    slice_parser::parse_raw_message_ref, MAVLinkMessageRaw, MAVLinkV1MessageRaw,
+    MAVLinkV2MessageRaw, MavlinkVersion, Message, ReadVersion, MAVLINK_IFLAG_SIGNED,
+    MAVLINK_SUPPORTED_IFLAGS, MAV_STX, MAV_STX_V2, MAX_FRAME_SIZE,
+};
+
+/// Incremental parser for MAVLink messages of the dialect `M`
+///
+/// Data without STX marker, with an invalid CRC checksum or with unknown incompatibility flags is
+/// discarded. When a potential frame turns out to be invalid, parsing resumes at the byte following
+/// its STX marker, so no valid message is lost.
+///
+/// Message signatures are not verified, with the `signing` feature `SigningData::verify_signature`
+/// can be used on the emitted messages to do so.
+pub struct MavParser<M: Message> {
+    // Bytes of the current frame candidate, always starting with a STX marker
+    buffer: [u8; MAX_FRAME_SIZE],
+    // Number of valid bytes in the buffer
+    len: usize,
+    version: ReadVersion,
+    phantom: PhantomData<fn() -> M>,
+}
+
+impl<M: Message> Default for MavParser<M> {
+    fn default() -> Self {
+        Self::new(ReadVersion::Any)
+    }
+}
+
+impl<M: Message> MavParser<M> {
+    /// Create a new parser accepting messages of the given version
+    pub const fn new(version: ReadVersion) -> Self {
+        Self {
+            buffer: [0; MAX_FRAME_SIZE],
+            len: 0,
+            version,
+            phantom: PhantomData,
+        }
+    }
+
+    /// MAVLink version(s) accepted by this parser
+    pub fn version(&self) -> ReadVersion {
+        self.version
+    }
+
+    /// Discard any partially received message
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+
+    /// Parse a single byte
+    ///
+    /// Returns a message once its last byte has been received.
+    pub fn parse_char(&mut self, byte: u8) -> Option<MAVLinkMessageRaw> {
+        // a discarded frame candidate may have contained another complete message,
+        // emit it first so the buffer is guaranteed to have room for the new byte
+        let pending = self.next_message();
+        self.push(byte);
+        pending.or_else(|| self.next_message())
+    }
+
+    /// Parse a slice of bytes
+    ///
+    /// The returned iterator yields all messages completed by the given bytes. Bytes not consumed
+    /// when the iterator is dropped are discarded, while an incomplete message at the end of the
+    /// slice is kept and completed by the next call.
+    pub fn parse<'a>(&'a mut self, bytes: &'a [u8]) -> MavParserIter<'a, M> {
+        MavParserIter {
+            parser: self,
+            bytes,
+        }
+    }
+
+    fn is_stx(&self, byte: u8) -> bool {
+        match self.version {
+            ReadVersion::Single(MavlinkVersion::V1) => byte == MAV_STX,
+            ReadVersion::Single(MavlinkVersion::V2) => byte == MAV_STX_V2,
+            ReadVersion::Any => byte == MAV_STX || byte == MAV_STX_V2,
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.len == 0 && !self.is_stx(byte) {
+            return;
+        }
+        self.buffer[self.len] = byte;
+        self.len += 1;
+    }
+
+    /// Number of buffered bytes required to decide whether the buffered frame candidate is valid
+    fn required_len(&self) -> usize {
+        match self.buffer[..self.len] {
+            [] => 1,
+            [MAV_STX] | [MAV_STX_V2] | [MAV_STX_V2, _] => self.len + 1,
+            [MAV_STX, payload_length, ..] => {
+                1 + MAVLinkV1MessageRaw::HEADER_SIZE + payload_length as usize + 2
+            }
+            [_, payload_length, incompat_flags, ..] => {
+                if incompat_flags & !MAVLINK_SUPPORTED_IFLAGS != 0 {
+                    // unknown flags, this candidate is discarded right away
+                    return self.len;
+                }
+                let signature_size = if incompat_flags & MAVLINK_IFLAG_SIGNED == 0 {
+                    0
+                } else {
+                    MAVLinkV2MessageRaw::SIGNATURE_SIZE
+                };
+                1 + MAVLinkV2MessageRaw::HEADER_SIZE + payload_length as usize + 2 + signature_size
+            }
+            // the buffer only ever starts with a STX marker
+            [_, ..] => unreachable!(),
+        }
+    }
+
+    fn next_message(&mut self) -> Option<MAVLinkMessageRaw> {
+        while self.len > 0 && self.len >= self.required_len() {
+            let (consumed, message) =
+                parse_raw_message_ref::<M>(&self.buffer[..self.len], self.version);
+            let message = message.map(MAVLinkMessageRaw::from);
+            self.buffer.copy_within(consumed..self.len, 0);
+            self.len -= consumed;
+            if message.is_some() {
+                return message;
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the messages parsed from a slice, returned by [`MavParser::parse`]
+pub struct MavParserIter<'a, M: Message> {
+    parser: &'a mut MavParser<M>,
+    bytes: &'a [u8],
+}
+
+impl<M: Message> Iterator for MavParserIter<'_, M> {
+    type Item = MAVLinkMessageRaw;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(message) = self.parser.next_message() {
+                return Some(message);
+            }
+            let (&byte, rest) = self.bytes.split_first()?;
+            self.bytes = rest;
+            self.parser.push(byte);
+        }
+    }
+}
@@ -21,6 +21,7 @@ use std::io::Read;
 use std::io::ErrorKind;
 
 use crate::error::MessageReadError;
+use crate::link_stats::ReadStats;
 
 /// A buffered/peekable reader
 ///
@@ -41,6 +42,8 @@ pub struct PeekReader<R, const BUFFER_SIZE: usize = 280> {
     top: usize,
     // The wrapped reader.
     reader: R,
+    // Statistics of the data read, updated by the MAVLink read functions.
+    pub(crate) stats: ReadStats,
 }
 
 impl<R: Read, const BUFFER_SIZE: usize> PeekReader<R, BUFFER_SIZE> {
@@ -51,6 +54,7 @@ impl<R: Read, const BUFFER_SIZE: usize> PeekReader<R, BUFFER_SIZE> {
             cursor: 0,
             top: 0,
             reader,
+            stats: ReadStats::default(),
         }
     }
 
@@ -138,6 +142,11 @@ impl<R: Read, const BUFFER_SIZE: usize> PeekReader<R, BUFFER_SIZE> {
         &mut self.reader
     }
 
+    /// Returns the statistics of the data processed by the MAVLink `read_*` functions using this reader
+    pub fn read_stats(&self) -> ReadStats {
+        self.stats
+    }
+
     /// Internal function to fetch data from the internal buffer and/or reader
     fn fetch(&mut self, amount: usize, consume: bool) -> Result<&[u8], MessageReadError> {
         assert!(BUFFER_SIZE >= amount);
@@ -171,6 +180,7 @@ impl<R: Read, const BUFFER_SIZE: usize> PeekReader<R, BUFFER_SIZE> {
             }
 
             self.top += bytes_read;
+            self.stats.bytes_received += bytes_read as u64;
         }
 
         let result = &self.buffer[self.cursor..self.cursor + amount];
@@ -0,0 +1,164 @@
+//! This module implements routing of MAVLink messages between several connections.
+//!
+//! A [`Router`] owns a set of endpoints, each a [`Connection`] of any kind. It learns which
+//! `(system_id, component_id)` pairs are reachable through which endpoint from the messages it
+//! receives and forwards every message according to the
+//! [MAVLink routing rules](https://mavlink.io/en/guide/routing.html):
+//!
+//! - messages without a target or targeted at system `0` are broadcast to all other endpoints
+//! - messages targeted at a system are forwarded to the endpoints that system has been seen on,
+//!   narrowed down to the endpoints of the target component unless the component is `0`
+//! - messages are never sent back to the endpoint they were received on
+//!
+//! Messages are forwarded as received, without being re-serialized or re-signed.
+//!
+//! ```ignore
+//! let mut router = Router::<MavMessage>::new();
+//! router.add_endpoint(&ConnectionAddress::parse_address("serial:/dev/ttyUSB0:57600")?)?;
+//! router.add_endpoint(&ConnectionAddress::parse_address("udpin:0.0.0.0:14550")?)?;
+//! router.run();
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::Mutex;
+use std::thread;
+
+use crate::connection::{is_transient, Connectable, Connection, MavConnection};
+use crate::error::MessageReadError;
+use crate::{MAVLinkMessageRaw, Message};
+
+/// Forwards MAVLink messages between multiple connections
+pub struct Router<M: Message> {
+    endpoints: Vec<Connection<M>>,
+    // indices of the endpoints each (system_id, component_id) has been seen on
+    routes: Mutex<HashMap<(u8, u8), HashSet<usize>>>,
+}
+
+impl<M: Message> Default for Router<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Message> Router<M> {
+    /// Create a router without any endpoints
+    pub fn new() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            routes: Mutex::default(),
+        }
+    }
+
+    /// Connect to the given address and add the connection as an endpoint
+    ///
+    /// Returns the index of the new endpoint.
+    ///
+    /// # Errors
+    ///
+    /// When the connection could not be established a corresponding [`io::Error`] is returned
+    pub fn add_endpoint<C: Connectable>(&mut self, address: &C) -> io::Result<usize> {
+        let connection = address.connect::<M>()?;
+        Ok(self.add_connection(connection))
+    }
+
+    /// Add an already established connection as an endpoint
+    ///
+    /// Returns the index of the new endpoint.
+    pub fn add_connection(&mut self, connection: Connection<M>) -> usize {
+        self.endpoints.push(connection);
+        self.endpoints.len() - 1
+    }
+
+    /// Endpoints of this router, in the order they were added
+    pub fn endpoints(&self) -> &[Connection<M>] {
+        &self.endpoints
+    }
+
+    /// Snapshot of the learned routes, the endpoint indices each `(system_id, component_id)`
+    /// has been seen on
+    pub fn route_table(&self) -> HashMap<(u8, u8), HashSet<usize>> {
+        self.routes.lock().unwrap().clone()
+    }
+
+    /// Route a message received on the endpoint `from`
+    ///
+    /// Records the sender of the message and forwards it to the matching endpoints.
+    /// Failures to send to an endpoint are ignored.
+    /// Returns the number of endpoints the message was forwarded to.
+    pub fn route(&self, from: usize, message: &MAVLinkMessageRaw) -> usize {
+        let targets = self.learn_and_resolve(from, message);
+        targets
+            .into_iter()
+            .filter_map(|index| self.endpoints.get(index))
+            .filter(|endpoint| endpoint.send_raw(message).is_ok())
+            .count()
+    }
+
+    /// Receive and route messages on all endpoints
+    ///
+    /// Every endpoint is served by its own thread. An endpoint stops being served when receiving
+    /// from it fails with an I/O error other than a timeout or interruption, e.g. when a file has
+    /// been read completely or a TCP peer disconnected. This function returns once no endpoint
+    /// is served anymore.
+    pub fn run(&self)
+    where
+        M: Sync + Send,
+    {
+        thread::scope(|scope| {
+            for index in 0..self.endpoints.len() {
+                scope.spawn(move || self.serve(index));
+            }
+        });
+    }
+
+    fn serve(&self, index: usize) {
+        let endpoint = &self.endpoints[index];
+        loop {
+            match endpoint.recv_raw() {
+                Ok(message) => {
+                    self.route(index, &message);
+                }
+                Err(MessageReadError::Io(e)) if is_transient(&e) => {}
+                Err(MessageReadError::Io(_)) => return,
+                // invalid messages are discarded by the reader
+                Err(MessageReadError::Parse(_)) => {}
+            }
+        }
+    }
+
+    fn learn_and_resolve(&self, from: usize, message: &MAVLinkMessageRaw) -> Vec<usize> {
+        let mut routes = self.routes.lock().unwrap();
+        routes
+            .entry((message.system_id(), message.component_id()))
+            .or_default()
+            .insert(from);
+
+        let (target_system, target_component) =
+            match M::parse(message.version(), message.message_id(), message.payload()) {
+                Ok(msg) => (
+                    msg.target_system_id().unwrap_or(0),
+                    msg.target_component_id().unwrap_or(0),
+                ),
+                Err(_) => (0, 0),
+            };
+
+        let mut targets: HashSet<usize> = if target_system == 0 {
+            (0..self.endpoints.len()).collect()
+        } else {
+            routes
+                .iter()
+                .filter(|((system_id, component_id), _)| {
+                    *system_id == target_system
+                        && (target_component == 0 || *component_id == target_component)
+                })
+                .flat_map(|(_, endpoints)| endpoints.iter().copied())
+                .collect()
+        };
+        targets.remove(&from);
+
+        let mut targets: Vec<usize> = targets.into_iter().collect();
+        targets.sort_unstable();
+        targets
+    }
+}
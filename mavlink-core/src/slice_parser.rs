@@ -0,0 +1,452 @@
+//! This module implements a zero-copy parser for MAVLink frames stored in byte slices.
+//!
+//! Unlike the `read_*` functions, which copy every frame into a fixed size [`MAVLinkV1MessageRaw`]
+//! or [`MAVLinkV2MessageRaw`] buffer and require a [`PeekReader`](crate::peek_reader::PeekReader),
+//! the functions in this module operate directly on `&[u8]` and return views borrowing from it.
+//! They neither allocate nor copy and are available without the `std` feature, which makes them
+//! suitable for inspecting and forwarding frames straight out of e.g. a DMA ring buffer.
+//!
+//! Every parse reports how many bytes of the input were consumed. When no complete frame is
+//! found, the unconsumed remainder is the beginning of a possible frame and should be presented
+//! again once more data is available.
+//!
+//! ```ignore
+//! let mut parser = SliceParser::<MavMessage>::new(&dma_buffer[..filled], ReadVersion::Any);
+//! for frame in &mut parser {
+//!     forward(frame.raw_bytes());
+//! }
+//! ring.release(parser.consumed());
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::{
+    calculate_crc, MAVLinkV1MessageRaw, MAVLinkV2MessageRaw, MavlinkVersion, Message, ReadVersion,
+    MAVLINK_IFLAG_SIGNED, MAVLINK_SUPPORTED_IFLAGS, MAV_STX, MAV_STX_V2,
+};
+
+const V1_HEADER_SIZE: usize = MAVLinkV1MessageRaw::HEADER_SIZE;
+const V2_HEADER_SIZE: usize = MAVLinkV2MessageRaw::HEADER_SIZE;
+const V2_SIGNATURE_SIZE: usize = MAVLinkV2MessageRaw::SIGNATURE_SIZE;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Borrowed view of a complete MAVLink 1 frame beginning with the STX marker.
+pub struct MAVLinkV1MessageRef<'a>(&'a [u8]);
+
+impl<'a> MAVLinkV1MessageRef<'a> {
+    /// Raw byte slice of the message
+    #[inline]
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Reference to the 5 byte header slice of the message
+    #[inline]
+    pub fn header(&self) -> &'a [u8] {
+        &self.0[1..=V1_HEADER_SIZE]
+    }
+
+    /// Size of the payload of the message
+    #[inline]
+    pub fn payload_length(&self) -> u8 {
+        self.0[1]
+    }
+
+    /// Packet sequence number
+    #[inline]
+    pub fn sequence(&self) -> u8 {
+        self.0[2]
+    }
+
+    /// Message sender System ID
+    #[inline]
+    pub fn system_id(&self) -> u8 {
+        self.0[3]
+    }
+
+    /// Message sender Component ID
+    #[inline]
+    pub fn component_id(&self) -> u8 {
+        self.0[4]
+    }
+
+    /// Message ID
+    #[inline]
+    pub fn message_id(&self) -> u8 {
+        self.0[5]
+    }
+
+    /// Reference to the payload byte slice of the message
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        let payload_length: usize = self.payload_length().into();
+        &self.0[(1 + V1_HEADER_SIZE)..(1 + V1_HEADER_SIZE + payload_length)]
+    }
+
+    /// [CRC-16 checksum](https://mavlink.io/en/guide/serialization.html#checksum) field of the message
+    #[inline]
+    pub fn checksum(&self) -> u16 {
+        let payload_length: usize = self.payload_length().into();
+        u16::from_le_bytes([
+            self.0[1 + V1_HEADER_SIZE + payload_length],
+            self.0[1 + V1_HEADER_SIZE + payload_length + 1],
+        ])
+    }
+
+    /// Checks wether the message's [CRC-16 checksum](https://mavlink.io/en/guide/serialization.html#checksum) calculation matches its checksum field.
+    #[inline]
+    pub fn has_valid_crc<M: Message>(&self) -> bool {
+        let payload_length: usize = self.payload_length().into();
+        self.checksum()
+            == calculate_crc(
+                &self.0[1..(1 + V1_HEADER_SIZE + payload_length)],
+                M::extra_crc(self.message_id().into()),
+            )
+    }
+}
+
+impl From<MAVLinkV1MessageRef<'_>> for MAVLinkV1MessageRaw {
+    fn from(value: MAVLinkV1MessageRef<'_>) -> Self {
+        let mut raw = Self::new();
+        raw.0[..value.0.len()].copy_from_slice(value.0);
+        raw
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Borrowed view of a complete MAVLink 2 frame beginning with the STX marker, including its signature if present.
+pub struct MAVLinkV2MessageRef<'a>(&'a [u8]);
+
+impl<'a> MAVLinkV2MessageRef<'a> {
+    /// Raw byte slice of the message
+    #[inline]
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Reference to the 9 byte header slice of the message
+    #[inline]
+    pub fn header(&self) -> &'a [u8] {
+        &self.0[1..=V2_HEADER_SIZE]
+    }
+
+    /// Size of the payload of the message
+    #[inline]
+    pub fn payload_length(&self) -> u8 {
+        self.0[1]
+    }
+
+    /// [Incompatiblity flags](https://mavlink.io/en/guide/serialization.html#incompat_flags) of the message
+    #[inline]
+    pub fn incompatibility_flags(&self) -> u8 {
+        self.0[2]
+    }
+
+    /// [Compatibility Flags](https://mavlink.io/en/guide/serialization.html#compat_flags) of the message
+    #[inline]
+    pub fn compatibility_flags(&self) -> u8 {
+        self.0[3]
+    }
+
+    /// Packet sequence number
+    #[inline]
+    pub fn sequence(&self) -> u8 {
+        self.0[4]
+    }
+
+    /// Message sender System ID
+    #[inline]
+    pub fn system_id(&self) -> u8 {
+        self.0[5]
+    }
+
+    /// Message sender Component ID
+    #[inline]
+    pub fn component_id(&self) -> u8 {
+        self.0[6]
+    }
+
+    /// Message ID
+    #[inline]
+    pub fn message_id(&self) -> u32 {
+        u32::from_le_bytes([self.0[7], self.0[8], self.0[9], 0])
+    }
+
+    /// Reference to the payload byte slice of the message
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        let payload_length: usize = self.payload_length().into();
+        &self.0[(1 + V2_HEADER_SIZE)..(1 + V2_HEADER_SIZE + payload_length)]
+    }
+
+    /// [CRC-16 checksum](https://mavlink.io/en/guide/serialization.html#checksum) field of the message
+    #[inline]
+    pub fn checksum(&self) -> u16 {
+        let payload_length: usize = self.payload_length().into();
+        u16::from_le_bytes([
+            self.0[1 + V2_HEADER_SIZE + payload_length],
+            self.0[1 + V2_HEADER_SIZE + payload_length + 1],
+        ])
+    }
+
+    /// Whether the `MAVLINK_IFLAG_SIGNED` incompatibility flag is set and the frame carries a signature
+    #[inline]
+    pub fn is_signed(&self) -> bool {
+        self.incompatibility_flags() & MAVLINK_IFLAG_SIGNED != 0
+    }
+
+    /// Reference to the 13 byte [signature](https://mavlink.io/en/guide/message_signing.html#frame_format) of the message
+    ///
+    /// Returns `None` if the message is not signed.
+    #[inline]
+    pub fn signature(&self) -> Option<&'a [u8]> {
+        if self.is_signed() {
+            let payload_length: usize = self.payload_length().into();
+            let signature_start = 1 + V2_HEADER_SIZE + payload_length + 2;
+            Some(&self.0[signature_start..(signature_start + V2_SIGNATURE_SIZE)])
+        } else {
+            None
+        }
+    }
+
+    /// Signature [Link ID](https://mavlink.io/en/guide/message_signing.html#link_ids)
+    #[inline]
+    pub fn signature_link_id(&self) -> Option<u8> {
+        self.signature().map(|signature| signature[0])
+    }
+
+    /// Message [signature timestamp](https://mavlink.io/en/guide/message_signing.html#timestamp)
+    /// in units of 10 microseconds since 1st January 2015 GMT
+    #[inline]
+    pub fn signature_timestamp(&self) -> Option<u64> {
+        self.signature().map(|signature| {
+            let mut timestamp_bytes = [0u8; 8];
+            timestamp_bytes[0..6].copy_from_slice(&signature[1..7]);
+            u64::from_le_bytes(timestamp_bytes)
+        })
+    }
+
+    /// Reference to the 48 bit [message signature](https://mavlink.io/en/guide/message_signing.html#signature) byte slice
+    #[inline]
+    pub fn signature_value(&self) -> Option<&'a [u8]> {
+        self.signature().map(|signature| &signature[7..])
+    }
+
+    /// Checks wether the message's [CRC-16 checksum](https://mavlink.io/en/guide/serialization.html#checksum) calculation matches its checksum field.
+    #[inline]
+    pub fn has_valid_crc<M: Message>(&self) -> bool {
+        let payload_length: usize = self.payload_length().into();
+        self.checksum()
+            == calculate_crc(
+                &self.0[1..(1 + V2_HEADER_SIZE + payload_length)],
+                M::extra_crc(self.message_id()),
+            )
+    }
+}
+
+impl From<MAVLinkV2MessageRef<'_>> for MAVLinkV2MessageRaw {
+    fn from(value: MAVLinkV2MessageRef<'_>) -> Self {
+        let mut raw = Self::new();
+        raw.0[..value.0.len()].copy_from_slice(value.0);
+        raw
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Borrowed view of a MAVLink frame of either version
+pub enum MAVLinkMessageRef<'a> {
+    /// MAVLink 1 frame
+    V1(MAVLinkV1MessageRef<'a>),
+    /// MAVLink 2 frame
+    V2(MAVLinkV2MessageRef<'a>),
+}
+
+impl<'a> MAVLinkMessageRef<'a> {
+    /// Raw byte slice of the message
+    #[inline]
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        match self {
+            Self::V1(msg) => msg.raw_bytes(),
+            Self::V2(msg) => msg.raw_bytes(),
+        }
+    }
+
+    /// Reference to the payload byte slice of the message
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        match self {
+            Self::V1(msg) => msg.payload(),
+            Self::V2(msg) => msg.payload(),
+        }
+    }
+
+    /// Packet sequence number
+    #[inline]
+    pub fn sequence(&self) -> u8 {
+        match self {
+            Self::V1(msg) => msg.sequence(),
+            Self::V2(msg) => msg.sequence(),
+        }
+    }
+
+    /// Message sender System ID
+    #[inline]
+    pub fn system_id(&self) -> u8 {
+        match self {
+            Self::V1(msg) => msg.system_id(),
+            Self::V2(msg) => msg.system_id(),
+        }
+    }
+
+    /// Message sender Component ID
+    #[inline]
+    pub fn component_id(&self) -> u8 {
+        match self {
+            Self::V1(msg) => msg.component_id(),
+            Self::V2(msg) => msg.component_id(),
+        }
+    }
+
+    /// Message ID
+    #[inline]
+    pub fn message_id(&self) -> u32 {
+        match self {
+            Self::V1(msg) => u32::from(msg.message_id()),
+            Self::V2(msg) => msg.message_id(),
+        }
+    }
+
+    /// MAVLink version of the frame
+    #[inline]
+    pub fn version(&self) -> MavlinkVersion {
+        match self {
+            Self::V1(_) => MavlinkVersion::V1,
+            Self::V2(_) => MavlinkVersion::V2,
+        }
+    }
+}
+
+impl From<MAVLinkMessageRef<'_>> for crate::MAVLinkMessageRaw {
+    fn from(value: MAVLinkMessageRef<'_>) -> Self {
+        match value {
+            MAVLinkMessageRef::V1(msg) => Self::V1(msg.into()),
+            MAVLinkMessageRef::V2(msg) => Self::V2(msg.into()),
+        }
+    }
+}
+
+fn is_stx(byte: u8, version: ReadVersion) -> bool {
+    match version {
+        ReadVersion::Single(MavlinkVersion::V1) => byte == MAV_STX,
+        ReadVersion::Single(MavlinkVersion::V2) => byte == MAV_STX_V2,
+        ReadVersion::Any => byte == MAV_STX || byte == MAV_STX_V2,
+    }
+}
+
+/// Parse the first MAVLink frame of the specified version from a byte slice without copying it.
+///
+/// Returns the number of bytes consumed from the start of `buf` together with the parsed frame, if any.
+/// Data without STX marker, with an invalid CRC checksum or with unknown incompatibility flags is skipped
+/// and counted as consumed.
+///
+/// If `None` is returned, `buf` does not contain a complete frame. The consumed bytes can be discarded while
+/// the remaining bytes are the start of a potential frame and should be parsed again once more data has been appended.
+///
+/// Message signatures are not verified.
+pub fn parse_raw_message_ref<M: Message>(
+    buf: &[u8],
+    version: ReadVersion,
+) -> (usize, Option<MAVLinkMessageRef<'_>>) {
+    let mut offset = 0;
+    loop {
+        // search for the magic framing value indicating start of a MAVLink message
+        let Some(stx_position) = buf[offset..].iter().position(|&b| is_stx(b, version)) else {
+            return (buf.len(), None);
+        };
+        offset += stx_position;
+        let candidate = &buf[offset..];
+
+        if candidate[0] == MAV_STX {
+            let Some(&payload_length) = candidate.get(1) else {
+                return (offset, None);
+            };
+            let packet_length = 1 + V1_HEADER_SIZE + payload_length as usize + 2;
+            let Some(packet) = candidate.get(..packet_length) else {
+                return (offset, None);
+            };
+            let message = MAVLinkV1MessageRef(packet);
+            if message.has_valid_crc::<M>() {
+                return (offset + packet_length, Some(MAVLinkMessageRef::V1(message)));
+            }
+        } else {
+            let (Some(&payload_length), Some(&incompat_flags)) =
+                (candidate.get(1), candidate.get(2))
+            else {
+                return (offset, None);
+            };
+            // if there are incompatibility flags set that we do not know the frame is discarded
+            if incompat_flags & !MAVLINK_SUPPORTED_IFLAGS == 0 {
+                let signature_size = if incompat_flags & MAVLINK_IFLAG_SIGNED == 0 {
+                    0
+                } else {
+                    V2_SIGNATURE_SIZE
+                };
+                let packet_length =
+                    1 + V2_HEADER_SIZE + payload_length as usize + 2 + signature_size;
+                let Some(packet) = candidate.get(..packet_length) else {
+                    return (offset, None);
+                };
+                let message = MAVLinkV2MessageRef(packet);
+                if message.has_valid_crc::<M>() {
+                    return (offset + packet_length, Some(MAVLinkMessageRef::V2(message)));
+                }
+            }
+        }
+
+        offset += 1;
+    }
+}
+
+/// Iterator over all MAVLink frames contained in a byte slice.
+///
+/// Yields borrowed frames in the order they appear in the slice. Once the iterator is exhausted,
+/// [`consumed`](Self::consumed) reports how many bytes from the start of the slice can be discarded
+/// and [`remaining`](Self::remaining) holds the start of an incomplete frame, if any.
+pub struct SliceParser<'a, M: Message> {
+    buf: &'a [u8],
+    consumed: usize,
+    version: ReadVersion,
+    phantom: PhantomData<fn() -> M>,
+}
+
+impl<'a, M: Message> SliceParser<'a, M> {
+    /// Create a new parser over `buf` accepting frames of the given version
+    pub fn new(buf: &'a [u8], version: ReadVersion) -> Self {
+        Self {
+            buf,
+            consumed: 0,
+            version,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Number of bytes from the start of the slice that were consumed so far
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Bytes of the slice that were not consumed yet
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.buf[self.consumed..]
+    }
+}
+
+impl<'a, M: Message> Iterator for SliceParser<'a, M> {
+    type Item = MAVLinkMessageRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (consumed, message) = parse_raw_message_ref::<M>(self.remaining(), self.version);
+        self.consumed += consumed;
+        message
+    }
+}
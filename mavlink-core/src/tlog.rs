@@ -0,0 +1,635 @@
+//! Recording and replay of MAVLink telemetry logs.
+//!
+//! Telemetry logs (`.tlog`) as written by QGroundControl and MAVProxy are a plain sequence of
+//! records, each consisting of the time the frame was seen as microseconds since the UNIX epoch,
+//! encoded as 8 byte big-endian integer, followed by the raw MAVLink frame.
+//!
+//! A [`TlogWriter`] appends such records to any [`Write`]r, the `fileout:<path>` connection
+//! address writes all sent messages to a new telemetry log and a [`TlogRecorder`] wraps an
+//! existing connection to record the traffic in both directions.
+//!
+//! A [`TlogReader`] reads the records back with their timestamps and a [`TlogReplay`] is a
+//! connection emitting the recorded messages at their original pace or a multiple of it.
+//!
+//! ```ignore
+//! let connection = mavlink::connect::<MavMessage>("udpin:0.0.0.0:14550")?;
+//! let recorder = TlogRecorder::create(connection, "flight.tlog")?;
+//! loop {
+//!     let (header, msg) = recorder.recv()?;
+//!     // ...
+//! }
+//!
+//! // later, replay the flight at twice the speed
+//! let replay = TlogReplay::open("flight.tlog")?.speed(2.0);
+//! let (header, msg) = MavConnection::<MavMessage>::recv(&replay)?;
+//! ```
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::connection::MavConnection;
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::link_stats::{LinkStats, ReadStats, SharedLinkStats};
+use crate::peek_reader::PeekReader;
+use crate::{
+    record_invalid_crc, write_versioned_msg, MAVLinkMessageRaw, MAVLinkV1MessageRaw,
+    MAVLinkV2MessageRaw, MavHeader, MavlinkVersion, Message, ReadVersion, MAVLINK_SUPPORTED_IFLAGS,
+    MAV_STX, MAV_STX_V2,
+};
+
+#[cfg(feature = "signing")]
+use crate::{write_versioned_msg_signed, SigningConfig, SigningData};
+
+pub mod index;
+
+/// Size of the timestamp preceding every frame in a telemetry log
+pub const TLOG_TIMESTAMP_SIZE: usize = 8;
+
+/// Size of the largest possible telemetry log record
+const TLOG_RECORD_MAX_SIZE: usize = TLOG_TIMESTAMP_SIZE + 280;
+
+/// Current time as microseconds since the UNIX epoch
+pub(crate) fn timestamp_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_micros() as u64)
+}
+
+/// Telemetry log record of a frame with the given timestamp
+pub(crate) fn tlog_record(timestamp_us: u64, frame: &[u8]) -> Vec<u8> {
+    let mut record = Vec::with_capacity(TLOG_TIMESTAMP_SIZE + frame.len());
+    record.extend_from_slice(&timestamp_us.to_be_bytes());
+    record.extend_from_slice(frame);
+    record
+}
+
+/// Writes MAVLink frames in telemetry log format
+pub struct TlogWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TlogWriter<W> {
+    /// Create a telemetry log writer appending to the given [`Write`]r
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write a frame stamped with the current time
+    ///
+    /// Returns the length of the frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying writer.
+    pub fn write_frame(&mut self, frame: &[u8]) -> io::Result<usize> {
+        self.write_frame_at(timestamp_now(), frame)
+    }
+
+    /// Write a frame with the given timestamp in microseconds since the UNIX epoch
+    ///
+    /// Returns the length of the frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying writer.
+    pub fn write_frame_at(&mut self, timestamp_us: u64, frame: &[u8]) -> io::Result<usize> {
+        // a single write keeps the record intact if the writer is shared with other handles
+        self.writer.write_all(&tlog_record(timestamp_us, frame))?;
+        Ok(frame.len())
+    }
+
+    /// Write a raw message stamped with the current time
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying writer.
+    pub fn write_raw(&mut self, message: &MAVLinkMessageRaw) -> io::Result<usize> {
+        self.write_frame(message.raw_bytes())
+    }
+
+    /// Flush the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Unwraps the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Connection wrapper recording all received and sent frames to a telemetry log
+///
+/// Messages are received with [`MavConnection::recv_raw`] of the wrapped connection, so frames
+/// are recorded exactly as they were received, including frames of unknown messages.
+/// [`MavConnection::try_recv`] has no raw counterpart, messages received that way are recorded
+/// serialized again from the parsed message.
+///
+/// Sent messages are serialized by the recorder using its own sequence numbers and signing
+/// configuration and passed to [`MavConnection::send_raw`] of the wrapped connection, so the
+/// recorded frame is the one that was sent.
+pub struct TlogRecorder<C, W: Write = File> {
+    connection: C,
+    log: Mutex<TlogWriter<W>>,
+    sequence: AtomicU8,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+impl<C> TlogRecorder<C> {
+    /// Record the traffic of a connection to a newly created telemetry log file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be created.
+    pub fn create<P: AsRef<Path>>(connection: C, path: P) -> io::Result<Self> {
+        Ok(Self::new(connection, File::create(path)?))
+    }
+}
+
+impl<C, W: Write> TlogRecorder<C, W> {
+    /// Record the traffic of a connection to the given [`Write`]r
+    pub fn new(connection: C, log: W) -> Self {
+        Self {
+            connection,
+            log: Mutex::new(TlogWriter::new(log)),
+            sequence: AtomicU8::new(0),
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+
+    /// Reference to the wrapped connection
+    pub fn get_ref(&self) -> &C {
+        &self.connection
+    }
+
+    /// Unwraps the recorder into the wrapped connection and the log writer
+    pub fn into_parts(self) -> (C, W) {
+        let log = self.log.into_inner().unwrap();
+        (self.connection, log.into_inner())
+    }
+
+    fn record(&self, frame: &[u8]) -> io::Result<usize> {
+        self.log.lock().unwrap().write_frame(frame)
+    }
+
+    /// Record a received message that is only available parsed
+    fn record_msg<M: Message>(
+        &self,
+        version: MavlinkVersion,
+        header: MavHeader,
+        msg: &M,
+    ) -> io::Result<()> {
+        let mut frame = Vec::new();
+        // messages that can not be represented in the configured version are not recorded
+        if write_versioned_msg(&mut frame, version, header, msg).is_ok() {
+            self.record(&frame)?;
+        }
+        Ok(())
+    }
+}
+
+/// Copy a serialized frame into a raw message buffer
+fn raw_message(version: MavlinkVersion, frame: &[u8]) -> MAVLinkMessageRaw {
+    match version {
+        MavlinkVersion::V1 => {
+            let mut message = MAVLinkV1MessageRaw::new();
+            message.as_mut_slice()[..frame.len()].copy_from_slice(frame);
+            MAVLinkMessageRaw::V1(message)
+        }
+        MavlinkVersion::V2 => {
+            let mut message = MAVLinkV2MessageRaw::new();
+            message.as_mut_slice()[..frame.len()].copy_from_slice(frame);
+            MAVLinkMessageRaw::V2(message)
+        }
+    }
+}
+
+/// Parse a raw frame into its header and message
+fn parse<M: Message>(message: &MAVLinkMessageRaw) -> Result<(MavHeader, M), MessageReadError> {
+    let header = MavHeader {
+        system_id: message.system_id(),
+        component_id: message.component_id(),
+        sequence: message.sequence(),
+    };
+    let msg = M::parse(message.version(), message.message_id(), message.payload())?;
+    Ok((header, msg))
+}
+
+impl<M: Message, C: MavConnection<M>, W: Write> MavConnection<M> for TlogRecorder<C, W> {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let message = <Self as MavConnection<M>>::recv_raw(self)?;
+        parse(&message)
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let message = self.connection.recv_raw()?;
+        self.record(message.raw_bytes())?;
+        Ok(message)
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let (header, msg) = self.connection.try_recv()?;
+        self.record_msg(self.connection.protocol_version(), header, &msg)?;
+        Ok((header, msg))
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Result<(MavHeader, M), MessageReadError> {
+        let (header, msg) = self.connection.recv_timeout(timeout)?;
+        self.record_msg(self.connection.protocol_version(), header, &msg)?;
+        Ok((header, msg))
+    }
+
+    fn send(&self, header: &MavHeader, data: &M) -> Result<usize, MessageWriteError> {
+        let header = MavHeader {
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed),
+            ..*header
+        };
+        let version = self.connection.protocol_version();
+
+        let mut frame = Vec::new();
+        #[cfg(not(feature = "signing"))]
+        write_versioned_msg(&mut frame, version, header, data)?;
+        #[cfg(feature = "signing")]
+        write_versioned_msg_signed(
+            &mut frame,
+            version,
+            header,
+            data,
+            self.signing_data.as_ref(),
+        )?;
+
+        <Self as MavConnection<M>>::send_raw(self, &raw_message(version, &frame))
+    }
+
+    fn send_raw(&self, message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        let len = self.connection.send_raw(message)?;
+        self.record(message.raw_bytes())?;
+        Ok(len)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.connection.set_protocol_version(version);
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.connection.protocol_version()
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.connection.set_allow_recv_any_version(allow);
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.connection.allow_recv_any_version()
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.connection.link_stats()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.clone().map(SigningData::from_config);
+        self.connection.setup_signing(signing_data);
+    }
+}
+
+/// Reads timestamped frames from a telemetry log
+///
+/// Frames are returned without verifying their checksum, as that requires knowing the dialect.
+/// Data that does not look like the start of a record, for example the remains of a truncated
+/// record, is skipped byte by byte until the next record is found.
+pub struct TlogReader<R> {
+    reader: PeekReader<R, TLOG_RECORD_MAX_SIZE>,
+    // offset of the next byte to be read from the log
+    position: u64,
+    skipped: u64,
+}
+
+impl TlogReader<File> {
+    /// Open a telemetry log file for reading
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be opened.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> TlogReader<R> {
+    /// Create a telemetry log reader reading from the given [`Read`]er
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: PeekReader::new(reader),
+            position: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Number of bytes skipped because they were not part of a record
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Offset in the log of the next byte to be read, right after the last record read
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Read the next record as timestamp in microseconds since the UNIX epoch and frame
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying reader, at the end of the log this is an
+    /// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) error.
+    pub fn read_record(&mut self) -> Result<(u64, MAVLinkMessageRaw), MessageReadError> {
+        loop {
+            let stx = self.reader.peek_exact(TLOG_TIMESTAMP_SIZE + 1)?[TLOG_TIMESTAMP_SIZE];
+            let message = match stx {
+                MAV_STX => {
+                    let mut message = MAVLinkV1MessageRaw::new();
+                    self.peek_frame(message.as_mut_slice(), MAVLinkV1MessageRaw::HEADER_SIZE)?;
+                    let len = message.raw_bytes().len();
+                    self.peek_frame(message.as_mut_slice(), len - 1)?;
+                    MAVLinkMessageRaw::V1(message)
+                }
+                MAV_STX_V2 => {
+                    let mut message = MAVLinkV2MessageRaw::new();
+                    self.peek_frame(message.as_mut_slice(), MAVLinkV2MessageRaw::HEADER_SIZE)?;
+                    if message.incompatibility_flags() & !MAVLINK_SUPPORTED_IFLAGS > 0 {
+                        // the length of frames with unknown flags can not be trusted
+                        self.skip();
+                        continue;
+                    }
+                    let len = message.raw_bytes().len();
+                    self.peek_frame(message.as_mut_slice(), len - 1)?;
+                    MAVLinkMessageRaw::V2(message)
+                }
+                _ => {
+                    self.skip();
+                    continue;
+                }
+            };
+
+            let timestamp = self.reader.read_exact(TLOG_TIMESTAMP_SIZE)?;
+            let timestamp = u64::from_be_bytes(timestamp.try_into().unwrap());
+            let len = message.raw_bytes().len();
+            self.reader.consume(len);
+            self.position += (TLOG_TIMESTAMP_SIZE + len) as u64;
+            return Ok((timestamp, message));
+        }
+    }
+
+    fn skip(&mut self) {
+        self.reader.consume(1);
+        self.position += 1;
+        self.skipped += 1;
+    }
+
+    /// Copy the STX and the following `len` bytes of the frame of the next record into `frame`
+    fn peek_frame(&mut self, frame: &mut [u8], len: usize) -> Result<(), MessageReadError> {
+        let record = self.reader.peek_exact(TLOG_TIMESTAMP_SIZE + 1 + len)?;
+        frame[..=len].copy_from_slice(&record[TLOG_TIMESTAMP_SIZE..]);
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> TlogReader<R> {
+    /// Continue reading at the given offset in the log, which should be the start of a record
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying reader.
+    pub fn seek(&mut self, offset: u64) -> io::Result<()> {
+        self.reader.reader_mut().seek(SeekFrom::Start(offset))?;
+        // drop everything buffered from the old position
+        self.reader.consume(usize::MAX);
+        self.position = offset;
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for TlogReader<R> {
+    type Item = Result<(u64, MAVLinkMessageRaw), MessageReadError>;
+
+    /// Read the next record, ending at the end of the log or at a truncated last record
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_record() {
+            Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            result => Some(result),
+        }
+    }
+}
+
+/// Connection replaying the messages of a telemetry log
+///
+/// Messages are emitted at the pace they were recorded at, relative to the first message
+/// received from the replay. The pace can be scaled with [`TlogReplay::speed`].
+/// [`MavConnection::try_recv`] returns a [`WouldBlock`](io::ErrorKind::WouldBlock) error
+/// while the next message is not due yet. Sending messages does nothing.
+///
+/// At the end of the log an [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) error is returned.
+pub struct TlogReplay<R = File> {
+    state: Mutex<ReplayState<R>>,
+    speed: f64,
+    protocol_version: MavlinkVersion,
+    recv_any_version: bool,
+    stats: SharedLinkStats,
+    #[cfg(feature = "signing")]
+    signing_data: Option<SigningData>,
+}
+
+struct ReplayState<R> {
+    log: TlogReader<R>,
+    // real time and log time the replay started at
+    start: Option<(Instant, u64)>,
+    // next frame, put back by `try_recv` as it was not due yet
+    pending: Option<(u64, MAVLinkMessageRaw)>,
+    stats: ReadStats,
+}
+
+impl<R> ReplayState<R> {
+    /// Time at which the frame with the given timestamp is due
+    fn due(&mut self, timestamp: u64, speed: f64) -> Instant {
+        let (start, first) = *self.start.get_or_insert((Instant::now(), timestamp));
+        let offset = timestamp.saturating_sub(first) as f64 / 1e6 / speed;
+        Duration::try_from_secs_f64(offset)
+            .ok()
+            .and_then(|offset| start.checked_add(offset))
+            .unwrap_or(start)
+    }
+}
+
+impl TlogReplay<File> {
+    /// Replay a telemetry log file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be opened.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> TlogReplay<R> {
+    /// Replay a telemetry log read from the given [`Read`]er at its original pace
+    pub fn new(reader: R) -> Self {
+        Self {
+            state: Mutex::new(ReplayState {
+                log: TlogReader::new(reader),
+                start: None,
+                pending: None,
+                stats: ReadStats::default(),
+            }),
+            speed: 1.0,
+            protocol_version: MavlinkVersion::V2,
+            recv_any_version: false,
+            stats: SharedLinkStats::default(),
+            #[cfg(feature = "signing")]
+            signing_data: None,
+        }
+    }
+
+    /// Sets the replay speed as a multiple of the original pace.
+    ///
+    /// [`f64::INFINITY`] replays all messages without any delay.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is not positive.
+    pub fn speed(mut self, speed: f64) -> Self {
+        assert!(speed > 0.0, "replay speed must be positive");
+        self.speed = speed;
+        self
+    }
+
+    /// Next frame of the log accepted by the configuration of this connection
+    fn next_frame<M: Message>(
+        &self,
+        state: &mut ReplayState<R>,
+    ) -> Result<(u64, MAVLinkMessageRaw), MessageReadError> {
+        if let Some(pending) = state.pending.take() {
+            return Ok(pending);
+        }
+        let version = ReadVersion::from_conn_cfg::<_, M>(self);
+        loop {
+            let (timestamp, message) = state.log.read_record()?;
+            state.stats.bytes_received += (TLOG_TIMESTAMP_SIZE + message.raw_bytes().len()) as u64;
+
+            let valid_crc = match &message {
+                MAVLinkMessageRaw::V1(message) => message.has_valid_crc::<M>(),
+                MAVLinkMessageRaw::V2(message) => message.has_valid_crc::<M>(),
+            };
+            if !valid_crc {
+                record_invalid_crc::<M>(
+                    &mut state.stats,
+                    message.message_id(),
+                    message.payload().len() as u8,
+                );
+                continue;
+            }
+            state.stats.frames_received += 1;
+
+            #[cfg(feature = "signing")]
+            if let (Some(signing_data), MAVLinkMessageRaw::V2(message)) =
+                (&self.signing_data, &message)
+            {
+                if !signing_data.verify_signature(message) {
+                    state.stats.bad_signatures += 1;
+                    continue;
+                }
+            }
+
+            if version == ReadVersion::Any || version == ReadVersion::Single(message.version()) {
+                return Ok((timestamp, message));
+            }
+        }
+    }
+}
+
+impl<M: Message, R: Read> MavConnection<M> for TlogReplay<R> {
+    fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        loop {
+            let message = <Self as MavConnection<M>>::recv_raw(self)?;
+            if let ok @ Ok(..) = parse(&message) {
+                return ok;
+            }
+        }
+    }
+
+    fn recv_raw(&self) -> Result<MAVLinkMessageRaw, MessageReadError> {
+        let mut state = self.state.lock().unwrap();
+        let result = self.next_frame::<M>(&mut state);
+        if let Ok((timestamp, _)) = &result {
+            let due = state.due(*timestamp, self.speed);
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+        }
+        let result = result.map(|(_, message)| message);
+        self.stats.record(state.stats, &result);
+        result
+    }
+
+    fn try_recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        let mut state = self.state.lock().unwrap();
+        let result = self.next_frame::<M>(&mut state);
+        let result = match result {
+            Ok((timestamp, message)) if state.due(timestamp, self.speed) > Instant::now() => {
+                state.pending = Some((timestamp, message));
+                Err(io::Error::from(io::ErrorKind::WouldBlock).into())
+            }
+            result => result.map(|(_, message)| message),
+        };
+        self.stats.record(state.stats, &result);
+        parse(&result?)
+    }
+
+    fn send(&self, _header: &MavHeader, _data: &M) -> Result<usize, MessageWriteError> {
+        Ok(0)
+    }
+
+    fn send_raw(&self, _message: &MAVLinkMessageRaw) -> Result<usize, MessageWriteError> {
+        Ok(0)
+    }
+
+    fn set_protocol_version(&mut self, version: MavlinkVersion) {
+        self.protocol_version = version;
+    }
+
+    fn protocol_version(&self) -> MavlinkVersion {
+        self.protocol_version
+    }
+
+    fn set_allow_recv_any_version(&mut self, allow: bool) {
+        self.recv_any_version = allow;
+    }
+
+    fn allow_recv_any_version(&self) -> bool {
+        self.recv_any_version
+    }
+
+    fn link_stats(&self) -> LinkStats {
+        self.stats.snapshot()
+    }
+
+    #[cfg(feature = "signing")]
+    fn setup_signing(&mut self, signing_data: Option<SigningConfig>) {
+        self.signing_data = signing_data.map(SigningData::from_config);
+    }
+}
@@ -0,0 +1,322 @@
+//! Random access to telemetry logs.
+//!
+//! A [`TlogIndex`] holds the offset, timestamp and message ID of every record of a telemetry log.
+//! It is built in a single pass over the log without parsing any payloads and can be saved next to
+//! the log, so large logs only have to be scanned once. An [`IndexedTlogReader`] uses the index to
+//! seek to a point in time and to read only the records of selected messages.
+//!
+//! ```ignore
+//! let mut reader = IndexedTlogReader::open_with_index("flight.tlog", "flight.tlog.idx")?;
+//! println!("{} positions", reader.index().count(GLOBAL_POSITION_INT_DATA::ID));
+//!
+//! let (start, _) = reader.index().time_range().unwrap();
+//! reader.seek_to_time(start + 60_000_000);
+//! reader.select([GLOBAL_POSITION_INT_DATA::ID]);
+//! for record in reader {
+//!     let (timestamp, frame) = record?;
+//!     // ...
+//! }
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use super::{TlogReader, TLOG_TIMESTAMP_SIZE};
+use crate::error::MessageReadError;
+use crate::MAVLinkMessageRaw;
+
+/// Magic bytes at the start of a saved index, followed by the format version
+const INDEX_MAGIC: &[u8; 7] = b"MAVTIDX";
+const INDEX_VERSION: u8 = 1;
+
+/// Size of a saved index entry
+const ENTRY_SIZE: usize = 8 + 8 + 4;
+
+/// Location of a record in a telemetry log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Offset of the record in the log
+    pub offset: u64,
+    /// Timestamp of the record in microseconds since the UNIX epoch
+    pub timestamp: u64,
+    /// Message ID of the recorded frame
+    pub message_id: u32,
+}
+
+/// Index of all records of a telemetry log
+///
+/// Lookups by time assume the timestamps of the log to be non-decreasing, as they are in logs
+/// recorded by a single writer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlogIndex {
+    entries: Vec<IndexEntry>,
+    log_size: u64,
+}
+
+impl TlogIndex {
+    /// Build the index of a telemetry log by reading it from the start
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying reader.
+    pub fn build<R: Read + Seek>(mut reader: R) -> Result<Self, MessageReadError> {
+        let log_size = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+
+        let mut log = TlogReader::new(reader);
+        let mut entries = Vec::new();
+        while let Some(record) = log.next() {
+            let (timestamp, message) = record?;
+            let record_len = TLOG_TIMESTAMP_SIZE + message.raw_bytes().len();
+            entries.push(IndexEntry {
+                offset: log.position() - record_len as u64,
+                timestamp,
+                message_id: message.message_id(),
+            });
+        }
+        Ok(Self { entries, log_size })
+    }
+
+    /// Load an index saved with [`TlogIndex::save`]
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying reader and an [`InvalidData`](io::ErrorKind::InvalidData)
+    /// error if the data is not a saved index.
+    pub fn load<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut header = [0; INDEX_MAGIC.len() + 1 + 8 + 8];
+        reader.read_exact(&mut header)?;
+        if header[..INDEX_MAGIC.len()] != INDEX_MAGIC[..]
+            || header[INDEX_MAGIC.len()] != INDEX_VERSION
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a telemetry log index",
+            ));
+        }
+        let log_size = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let len = u64::from_le_bytes(header[16..24].try_into().unwrap());
+
+        let mut entries = Vec::new();
+        let mut entry = [0; ENTRY_SIZE];
+        for _ in 0..len {
+            reader.read_exact(&mut entry)?;
+            entries.push(IndexEntry {
+                offset: u64::from_le_bytes(entry[0..8].try_into().unwrap()),
+                timestamp: u64::from_le_bytes(entry[8..16].try_into().unwrap()),
+                message_id: u32::from_le_bytes(entry[16..20].try_into().unwrap()),
+            });
+        }
+        Ok(Self { entries, log_size })
+    }
+
+    /// Save the index to the given [`Write`]r
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying writer.
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(INDEX_MAGIC)?;
+        writer.write_all(&[INDEX_VERSION])?;
+        writer.write_all(&self.log_size.to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        for entry in &self.entries {
+            writer.write_all(&entry.offset.to_le_bytes())?;
+            writer.write_all(&entry.timestamp.to_le_bytes())?;
+            writer.write_all(&entry.message_id.to_le_bytes())?;
+        }
+        writer.flush()
+    }
+
+    /// All records of the log in the order they were recorded
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    /// Number of records in the log
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the log does not contain any records
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Size of the log in bytes when the index was built
+    pub fn log_size(&self) -> u64 {
+        self.log_size
+    }
+
+    /// Timestamps of the first and the last record
+    pub fn time_range(&self) -> Option<(u64, u64)> {
+        Some((
+            self.entries.first()?.timestamp,
+            self.entries.last()?.timestamp,
+        ))
+    }
+
+    /// Number of records of the given message
+    pub fn count(&self, message_id: u32) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.message_id == message_id)
+            .count()
+    }
+
+    /// Number of records per message ID
+    pub fn counts(&self) -> BTreeMap<u32, usize> {
+        let mut counts = BTreeMap::new();
+        for entry in &self.entries {
+            *counts.entry(entry.message_id).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Position of the first record at or after the given timestamp
+    pub fn position_at(&self, timestamp: u64) -> usize {
+        self.entries
+            .partition_point(|entry| entry.timestamp < timestamp)
+    }
+}
+
+/// Telemetry log reader with random access through a [`TlogIndex`]
+pub struct IndexedTlogReader<R = File> {
+    log: TlogReader<R>,
+    index: TlogIndex,
+    // position in the index of the next record to read
+    next: usize,
+    // whether the log reader is at the offset it reports
+    positioned: bool,
+    selected: Option<HashSet<u32>>,
+}
+
+impl IndexedTlogReader<File> {
+    /// Open a telemetry log file, building its index
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be opened or read.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MessageReadError> {
+        Self::new(File::open(path)?)
+    }
+
+    /// Open a telemetry log file using the index saved at `index_path`
+    ///
+    /// If there is no saved index or it does not match the size of the log, the index is built
+    /// and saved to `index_path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log could not be opened or read or the index could not be saved.
+    pub fn open_with_index<P: AsRef<Path>, Q: AsRef<Path>>(
+        path: P,
+        index_path: Q,
+    ) -> Result<Self, MessageReadError> {
+        let mut file = File::open(path)?;
+        let log_size = file.metadata()?.len();
+        let saved =
+            File::open(&index_path).and_then(|index| TlogIndex::load(BufReader::new(index)));
+        let index = match saved {
+            Ok(index) if index.log_size() == log_size => index,
+            _ => {
+                let index = TlogIndex::build(&mut file)?;
+                index.save(BufWriter::new(File::create(index_path)?))?;
+                index
+            }
+        };
+        Ok(Self::with_index(file, index))
+    }
+}
+
+impl<R: Read + Seek> IndexedTlogReader<R> {
+    /// Create an indexed reader of the telemetry log read from the given [`Read`]er
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying reader while building the index.
+    pub fn new(mut reader: R) -> Result<Self, MessageReadError> {
+        let index = TlogIndex::build(&mut reader)?;
+        Ok(Self::with_index(reader, index))
+    }
+
+    /// Create an indexed reader of the telemetry log read from the given [`Read`]er with an
+    /// existing index of the log
+    pub fn with_index(reader: R, index: TlogIndex) -> Self {
+        Self {
+            log: TlogReader::new(reader),
+            index,
+            next: 0,
+            positioned: false,
+            selected: None,
+        }
+    }
+
+    /// Index of the log
+    pub fn index(&self) -> &TlogIndex {
+        &self.index
+    }
+
+    /// Continue reading at the first record at or after the given timestamp
+    pub fn seek_to_time(&mut self, timestamp: u64) {
+        self.next = self.index.position_at(timestamp);
+    }
+
+    /// Continue reading at the record with the given position in the index
+    pub fn seek_to_record(&mut self, position: usize) {
+        self.next = position.min(self.index.len());
+    }
+
+    /// Only read records of the given messages
+    pub fn select<I: IntoIterator<Item = u32>>(&mut self, message_ids: I) {
+        self.selected = Some(message_ids.into_iter().collect());
+    }
+
+    /// Read the records of all messages
+    pub fn select_all(&mut self) {
+        self.selected = None;
+    }
+
+    /// Read the next selected record as timestamp in microseconds since the UNIX epoch and frame
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of the underlying reader, after the last record this is an
+    /// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) error.
+    pub fn read_record(&mut self) -> Result<(u64, MAVLinkMessageRaw), MessageReadError> {
+        let selected = self.index.entries[self.next..]
+            .iter()
+            .position(|entry| {
+                self.selected
+                    .as_ref()
+                    .map_or(true, |selected| selected.contains(&entry.message_id))
+            })
+            .map(|skipped| self.next + skipped);
+        let Some(position) = selected else {
+            self.next = self.index.len();
+            return Err(MessageReadError::eof());
+        };
+
+        let offset = self.index.entries[position].offset;
+        if !self.positioned || self.log.position() != offset {
+            self.log.seek(offset)?;
+            self.positioned = true;
+        }
+        let record = self.log.read_record()?;
+        self.next = position + 1;
+        Ok(record)
+    }
+}
+
+impl<R: Read + Seek> Iterator for IndexedTlogReader<R> {
+    type Item = Result<(u64, MAVLinkMessageRaw), MessageReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_record() {
+            Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            result => Some(result),
+        }
+    }
+}
@@ -28,7 +28,7 @@ use arbitrary::{Arbitrary, Unstructured};
 /// ```
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "serde", serde(transparent))]
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct CharArray<const N: usize> {
     #[cfg_attr(
         feature = "serde",
@@ -7,7 +7,7 @@ fn main() {
 
     if args.len() < 2 {
         println!(
-            "Usage: mavlink-dump (tcpout|tcpin|udpout|udpin|udpbcast|serial|file):(ip|dev|path):(port|baud)"
+            "Usage: mavlink-dump (tcpout|tcpin|tcpserver|udpout|udpin|udpserver|udpbcast|serial|file):(ip|dev|path):(port|baud)"
         );
         return;
     }
@@ -0,0 +1,384 @@
+//! Client for the [command protocol](https://mavlink.io/en/services/command.html)
+//!
+//! The [`CommandClient`] sends a [`Command`] as `COMMAND_LONG` or `COMMAND_INT` and waits for the
+//! matching `COMMAND_ACK`. Unacknowledged `COMMAND_LONG`s are sent again with an incremented
+//! `confirmation` field. Once the target reports `MAV_RESULT_IN_PROGRESS` the command is no longer
+//! repeated and the progress updates are passed to a callback until the final result arrives.
+//! The [`AsyncCommandClient`] does the same on an async connection.
+//!
+//! The progress percentage is an extension field of `COMMAND_ACK`, without the `emit-extensions`
+//! feature it is always reported as unknown.
+//!
+//! ```ignore
+//! let connection = mavlink::connect::<MavMessage>("udpout:127.0.0.1:14550")?;
+//! let client = CommandClient::new(&connection, 1, 1);
+//! client.send(Command::long(
+//!     MavCmd::MAV_CMD_COMPONENT_ARM_DISARM,
+//!     [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+//! ))?;
+//! ```
+
+use core::fmt::{Display, Formatter};
+use std::error::Error;
+use std::time::Duration;
+
+use crate::common::{
+    MavCmd, MavFrame, MavMessage, MavResult, COMMAND_ACK_DATA, COMMAND_INT_DATA, COMMAND_LONG_DATA,
+};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::service::{Client, Progress, ServiceError, Settings, Transaction};
+use crate::{Connection, MavConnection, MavHeader, Message};
+
+#[cfg(feature = "tokio-1")]
+use crate::{service::AsyncClient, AsyncMavConnection};
+
+/// Progress value of a `MAV_RESULT_IN_PROGRESS` acknowledgement that does not report a percentage
+pub const PROGRESS_UNKNOWN: u8 = u8::MAX;
+
+/// Error of a command
+#[derive(Debug)]
+pub enum CommandError {
+    /// Receiving from the connection failed
+    Read(MessageReadError),
+    /// Sending on the connection failed
+    Write(MessageWriteError),
+    /// The target did not acknowledge the command in time, even after all retransmissions
+    Timeout,
+    /// The target acknowledged the command with a result other than `MAV_RESULT_ACCEPTED`
+    Rejected(MavResult),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "{e}"),
+            Self::Write(e) => write!(f, "{e}"),
+            Self::Timeout => write!(f, "Command was not acknowledged"),
+            Self::Rejected(result) => write!(f, "Command rejected with {result:?}"),
+        }
+    }
+}
+
+impl Error for CommandError {}
+
+impl From<MessageReadError> for CommandError {
+    fn from(e: MessageReadError) -> Self {
+        Self::Read(e)
+    }
+}
+
+impl From<MessageWriteError> for CommandError {
+    fn from(e: MessageWriteError) -> Self {
+        Self::Write(e)
+    }
+}
+
+impl ServiceError for CommandError {
+    fn timeout() -> Self {
+        Self::Timeout
+    }
+}
+
+/// A command and its parameters, the target is set by the client
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    /// Sent as `COMMAND_LONG`
+    Long { command: MavCmd, params: [f32; 7] },
+    /// Sent as `COMMAND_INT`, with the position in `x`, `y` and `z`
+    Int {
+        command: MavCmd,
+        frame: MavFrame,
+        params: [f32; 4],
+        x: i32,
+        y: i32,
+        z: f32,
+    },
+}
+
+impl Command {
+    /// A command sent as `COMMAND_LONG`
+    pub fn long(command: MavCmd, params: [f32; 7]) -> Self {
+        Self::Long { command, params }
+    }
+
+    /// A command sent as `COMMAND_INT`
+    pub fn int(command: MavCmd, frame: MavFrame, params: [f32; 4], x: i32, y: i32, z: f32) -> Self {
+        Self::Int {
+            command,
+            frame,
+            params,
+            x,
+            y,
+            z,
+        }
+    }
+
+    /// The command ID
+    pub fn command(&self) -> MavCmd {
+        match self {
+            Self::Long { command, .. } | Self::Int { command, .. } => *command,
+        }
+    }
+
+    fn message(&self, target_system: u8, target_component: u8, confirmation: u8) -> MavMessage {
+        match *self {
+            Self::Long { command, params } => MavMessage::COMMAND_LONG(COMMAND_LONG_DATA {
+                param1: params[0],
+                param2: params[1],
+                param3: params[2],
+                param4: params[3],
+                param5: params[4],
+                param6: params[5],
+                param7: params[6],
+                command,
+                target_system,
+                target_component,
+                confirmation,
+            }),
+            Self::Int {
+                command,
+                frame,
+                params,
+                x,
+                y,
+                z,
+            } => MavMessage::COMMAND_INT(COMMAND_INT_DATA {
+                param1: params[0],
+                param2: params[1],
+                param3: params[2],
+                param4: params[3],
+                x,
+                y,
+                z,
+                command,
+                target_system,
+                target_component,
+                frame,
+                current: 0,
+                autocontinue: 0,
+            }),
+        }
+    }
+}
+
+/// Progress reported by a `MAV_RESULT_IN_PROGRESS` acknowledgement
+#[cfg(feature = "emit-extensions")]
+fn progress_of(ack: &COMMAND_ACK_DATA) -> u8 {
+    ack.progress
+}
+
+/// Progress reported by a `MAV_RESULT_IN_PROGRESS` acknowledgement, unknown without extensions
+#[cfg(not(feature = "emit-extensions"))]
+fn progress_of(_ack: &COMMAND_ACK_DATA) -> u8 {
+    PROGRESS_UNKNOWN
+}
+
+/// Whether an acknowledgement is addressed to us, only known with extensions
+#[cfg(feature = "emit-extensions")]
+fn addressed_to(ack: &COMMAND_ACK_DATA, header: &MavHeader) -> bool {
+    ack.target_component == 0 || ack.target_component == header.component_id
+}
+
+/// Whether an acknowledgement is addressed to us, only known with extensions
+#[cfg(not(feature = "emit-extensions"))]
+fn addressed_to(_ack: &COMMAND_ACK_DATA, _header: &MavHeader) -> bool {
+    true
+}
+
+struct Execute<F> {
+    command: Command,
+    header: MavHeader,
+    target_system: u8,
+    target_component: u8,
+    confirmation: u8,
+    in_progress: bool,
+    on_progress: F,
+}
+
+impl<F: FnMut(u8)> Execute<F> {
+    fn new(command: Command, settings: &Settings, on_progress: F) -> Self {
+        Self {
+            command,
+            header: settings.header,
+            target_system: settings.target_system,
+            target_component: settings.target_component,
+            confirmation: 0,
+            in_progress: false,
+            on_progress,
+        }
+    }
+
+    fn message(&self) -> MavMessage {
+        self.command
+            .message(self.target_system, self.target_component, self.confirmation)
+    }
+}
+
+impl<F: FnMut(u8)> Transaction for Execute<F> {
+    type Output = ();
+    type Error = CommandError;
+
+    fn handle(&mut self, message: MavMessage) -> Progress<(), CommandError> {
+        let MavMessage::COMMAND_ACK(ack) = message else {
+            return Progress::Pending;
+        };
+        if ack.command != self.command.command() || !addressed_to(&ack, &self.header) {
+            return Progress::Pending;
+        }
+        match ack.result {
+            MavResult::MAV_RESULT_ACCEPTED => Progress::done(Ok(())),
+            MavResult::MAV_RESULT_IN_PROGRESS => {
+                self.in_progress = true;
+                (self.on_progress)(progress_of(&ack));
+                Progress::Continue
+            }
+            result => Progress::done(Err(CommandError::Rejected(result))),
+        }
+    }
+
+    fn retransmit(&mut self, _last: &MavMessage) -> Option<MavMessage> {
+        // a command that is being executed must not be started again
+        if self.in_progress {
+            return None;
+        }
+        // only `COMMAND_LONG` has a confirmation field, it is left at 0 for `COMMAND_INT`
+        if matches!(self.command, Command::Long { .. }) {
+            self.confirmation = self.confirmation.wrapping_add(1);
+        }
+        Some(self.message())
+    }
+}
+
+/// Client for the commands of a target component
+pub struct CommandClient<'a, M: Message, C: MavConnection<M> + ?Sized = Connection<M>> {
+    client: Client<'a, M, C>,
+}
+
+impl<'a, M: Message, C: MavConnection<M> + ?Sized> CommandClient<'a, M, C> {
+    /// Create a client for the commands of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: Client::new(connection, target_system, target_component),
+        }
+    }
+
+    /// Sets the system and component ID the commands are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for an acknowledgement before a command is sent again, 1.5 s by
+    /// default
+    ///
+    /// After `MAV_RESULT_IN_PROGRESS` this is the longest time allowed between two progress
+    /// updates.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before a command fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// Send a command and wait until it is accepted
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CommandError::Rejected`] with the result of any other acknowledgement.
+    pub fn send(&self, command: Command) -> Result<(), CommandError> {
+        self.send_with_progress(command, |_| {})
+    }
+
+    /// Send a command and wait until it is accepted, passing the progress of a command that is
+    /// in progress to `on_progress`
+    ///
+    /// The progress is a percentage or [`PROGRESS_UNKNOWN`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CommandError::Rejected`] with the result of any other acknowledgement.
+    pub fn send_with_progress(
+        &self,
+        command: Command,
+        on_progress: impl FnMut(u8),
+    ) -> Result<(), CommandError> {
+        let execute = Execute::new(command, &self.client.settings, on_progress);
+        let request = execute.message();
+        self.client.run(execute, request)
+    }
+}
+
+/// Client for the commands of a target component on an async connection
+///
+/// This is the `async` version of [`CommandClient`].
+#[cfg(feature = "tokio-1")]
+pub struct AsyncCommandClient<
+    'a,
+    M: Message + Sync + Send,
+    C: AsyncMavConnection<M> + ?Sized = dyn AsyncMavConnection<M> + Sync + Send,
+> {
+    client: AsyncClient<'a, M, C>,
+}
+
+#[cfg(feature = "tokio-1")]
+impl<'a, M: Message + Sync + Send, C: AsyncMavConnection<M> + ?Sized> AsyncCommandClient<'a, M, C> {
+    /// Create a client for the commands of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: AsyncClient::new(connection, target_system, target_component),
+        }
+    }
+
+    /// Sets the system and component ID the commands are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for an acknowledgement before a command is sent again, 1.5 s by
+    /// default
+    ///
+    /// After `MAV_RESULT_IN_PROGRESS` this is the longest time allowed between two progress
+    /// updates.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before a command fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// Send a command and wait until it is accepted
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CommandError::Rejected`] with the result of any other acknowledgement.
+    pub async fn send(&self, command: Command) -> Result<(), CommandError> {
+        self.send_with_progress(command, |_| {}).await
+    }
+
+    /// Send a command and wait until it is accepted, passing the progress of a command that is
+    /// in progress to `on_progress`
+    ///
+    /// The progress is a percentage or [`PROGRESS_UNKNOWN`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CommandError::Rejected`] with the result of any other acknowledgement.
+    pub async fn send_with_progress(
+        &self,
+        command: Command,
+        on_progress: impl FnMut(u8),
+    ) -> Result<(), CommandError> {
+        let execute = Execute::new(command, &self.client.settings, on_progress);
+        let request = execute.message();
+        self.client.run(execute, request).await
+    }
+}
@@ -0,0 +1,29 @@
+//! Conversion between messages of any dialect and the `common` message set used by the services
+//!
+//! Dialects share the definitions of the messages they include, so a message is converted by
+//! serializing its payload with one dialect and parsing it with the other.
+
+use crate::error::ParserError;
+use crate::{MavlinkVersion, Message, MessageData, MAX_FRAME_SIZE};
+
+/// Convert `common` message data into a message of any dialect that includes it
+///
+/// # Errors
+///
+/// Returns [`ParserError::UnknownMessage`] if the dialect does not include the message.
+pub(crate) fn to_message<D: MessageData, M: Message>(data: &D) -> Result<M, ParserError> {
+    let mut payload = [0; MAX_FRAME_SIZE];
+    let len = data.ser(MavlinkVersion::V2, &mut payload);
+    M::parse(MavlinkVersion::V2, D::ID, &payload[..len])
+}
+
+/// Convert a message of one dialect into a message of another dialect
+///
+/// # Errors
+///
+/// Returns [`ParserError::UnknownMessage`] if the target dialect does not include the message.
+pub(crate) fn convert<A: Message, B: Message>(message: &A) -> Result<B, ParserError> {
+    let mut payload = [0; MAX_FRAME_SIZE];
+    let len = message.ser(MavlinkVersion::V2, &mut payload);
+    B::parse(MavlinkVersion::V2, message.message_id(), &payload[..len])
+}
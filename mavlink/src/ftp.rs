@@ -0,0 +1,801 @@
+//! Client for the [file transfer protocol](https://mavlink.io/en/services/ftp.html) (MAVLink FTP)
+//!
+//! MAVLink FTP tunnels its own request and response packets through the `payload` of
+//! `FILE_TRANSFER_PROTOCOL`. [`FtpPayload`] encodes and decodes these packets, the [`FtpClient`]
+//! implements the file operations on top of them and the [`AsyncFtpClient`] does the same on an
+//! async connection.
+//!
+//! Every request carries a sequence number that the response increments. Requests that are not
+//! answered in time are sent again unchanged, files are read with burst reads that request the
+//! missing part again if packets were lost.
+//!
+//! ```ignore
+//! let connection = mavlink::connect::<MavMessage>("udpout:127.0.0.1:14550")?;
+//! let ftp = FtpClient::new(&connection, 1, 1);
+//! for entry in ftp.list_directory("/APM/LOGS")? {
+//!     println!("{entry:?}");
+//! }
+//! let params = ftp.read_file("@PARAM/param.pck")?;
+//! ```
+
+use core::fmt::{Display, Formatter};
+use std::error::Error;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::Duration;
+
+use crate::common::{MavMessage, FILE_TRANSFER_PROTOCOL_DATA};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::service::{Client, Progress, ServiceError, Settings, Transaction};
+use crate::{Connection, MavConnection, MavHeader, Message};
+
+#[cfg(feature = "tokio-1")]
+use crate::{service::AsyncClient, AsyncMavConnection};
+
+/// Length of the `payload` field of `FILE_TRANSFER_PROTOCOL`
+pub const FTP_PAYLOAD_LEN: usize = 251;
+/// Maximum length of the data of a single FTP packet
+pub const FTP_MAX_DATA_LEN: usize = FTP_PAYLOAD_LEN - HEADER_LEN;
+
+const HEADER_LEN: usize = 12;
+
+/// FTP operation or response code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FtpOpcode {
+    None = 0,
+    TerminateSession = 1,
+    ResetSessions = 2,
+    ListDirectory = 3,
+    OpenFileRO = 4,
+    ReadFile = 5,
+    CreateFile = 6,
+    WriteFile = 7,
+    RemoveFile = 8,
+    CreateDirectory = 9,
+    RemoveDirectory = 10,
+    OpenFileWO = 11,
+    TruncateFile = 12,
+    Rename = 13,
+    CalcFileCRC32 = 14,
+    BurstReadFile = 15,
+    Ack = 128,
+    Nak = 129,
+}
+
+impl TryFrom<u8> for FtpOpcode {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::None,
+            1 => Self::TerminateSession,
+            2 => Self::ResetSessions,
+            3 => Self::ListDirectory,
+            4 => Self::OpenFileRO,
+            5 => Self::ReadFile,
+            6 => Self::CreateFile,
+            7 => Self::WriteFile,
+            8 => Self::RemoveFile,
+            9 => Self::CreateDirectory,
+            10 => Self::RemoveDirectory,
+            11 => Self::OpenFileWO,
+            12 => Self::TruncateFile,
+            13 => Self::Rename,
+            14 => Self::CalcFileCRC32,
+            15 => Self::BurstReadFile,
+            128 => Self::Ack,
+            129 => Self::Nak,
+            value => return Err(value),
+        })
+    }
+}
+
+/// Error reported by a NAK response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FtpNak {
+    /// Unknown failure
+    Fail,
+    /// The operation failed with the included `errno` of the target
+    FailErrno(u8),
+    /// The size of the request is invalid
+    InvalidDataSize,
+    /// The session is not open
+    InvalidSession,
+    /// All sessions of the target are in use
+    NoSessionsAvailable,
+    /// The offset is past the end of the file or directory
+    Eof,
+    /// The target does not support the operation
+    UnknownCommand,
+    /// The file or directory to create already exists
+    FileExists,
+    /// The file or directory is write protected
+    FileProtected,
+    /// The file or directory does not exist
+    FileNotFound,
+    /// An error code not known to this implementation
+    Other(u8),
+}
+
+impl FtpNak {
+    fn from_data(data: &[u8]) -> Self {
+        match data.first().copied().unwrap_or(1) {
+            1 => Self::Fail,
+            2 => Self::FailErrno(data.get(1).copied().unwrap_or(0)),
+            3 => Self::InvalidDataSize,
+            4 => Self::InvalidSession,
+            5 => Self::NoSessionsAvailable,
+            6 => Self::Eof,
+            7 => Self::UnknownCommand,
+            8 => Self::FileExists,
+            9 => Self::FileProtected,
+            10 => Self::FileNotFound,
+            code => Self::Other(code),
+        }
+    }
+}
+
+/// An FTP packet, carried in the `payload` of `FILE_TRANSFER_PROTOCOL`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FtpPayload {
+    /// Sequence number of the request, a response has the number of its request plus one
+    pub seq_number: u16,
+    /// Session of the operation
+    pub session: u8,
+    /// Operation of a request, or [`FtpOpcode::Ack`] or [`FtpOpcode::Nak`] for a response
+    pub opcode: FtpOpcode,
+    /// Length of `data`, or the number of bytes to read for read requests
+    pub size: u8,
+    /// Operation a response answers
+    pub req_opcode: FtpOpcode,
+    /// Set on the last packet of a burst read
+    pub burst_complete: bool,
+    /// Offset in the file or directory
+    pub offset: u32,
+    /// Data of the packet, at most [`FTP_MAX_DATA_LEN`] bytes
+    pub data: Vec<u8>,
+}
+
+impl FtpPayload {
+    /// A packet without data for the given operation
+    pub fn new(opcode: FtpOpcode) -> Self {
+        Self {
+            seq_number: 0,
+            session: 0,
+            opcode,
+            size: 0,
+            req_opcode: FtpOpcode::None,
+            burst_complete: false,
+            offset: 0,
+            data: Vec::new(),
+        }
+    }
+
+    /// Sets the data and its size, the data is truncated to [`FTP_MAX_DATA_LEN`] bytes
+    pub fn with_data(mut self, data: &[u8]) -> Self {
+        let len = data.len().min(FTP_MAX_DATA_LEN);
+        self.data = data[..len].to_vec();
+        self.size = len as u8;
+        self
+    }
+
+    /// Encode the packet into the `payload` of `FILE_TRANSFER_PROTOCOL`
+    pub fn encode(&self) -> [u8; FTP_PAYLOAD_LEN] {
+        let mut payload = [0; FTP_PAYLOAD_LEN];
+        payload[0..2].copy_from_slice(&self.seq_number.to_le_bytes());
+        payload[2] = self.session;
+        payload[3] = self.opcode as u8;
+        payload[4] = self.size;
+        payload[5] = self.req_opcode as u8;
+        payload[6] = self.burst_complete.into();
+        payload[8..12].copy_from_slice(&self.offset.to_le_bytes());
+        let len = self.data.len().min(FTP_MAX_DATA_LEN);
+        payload[HEADER_LEN..HEADER_LEN + len].copy_from_slice(&self.data[..len]);
+        payload
+    }
+
+    /// Decode the `payload` of `FILE_TRANSFER_PROTOCOL`
+    ///
+    /// Returns `None` if the packet has an unknown opcode.
+    pub fn decode(payload: &[u8; FTP_PAYLOAD_LEN]) -> Option<Self> {
+        let size = payload[4];
+        let len = usize::from(size).min(FTP_MAX_DATA_LEN);
+        Some(Self {
+            seq_number: u16::from_le_bytes([payload[0], payload[1]]),
+            session: payload[2],
+            opcode: FtpOpcode::try_from(payload[3]).ok()?,
+            size,
+            req_opcode: FtpOpcode::try_from(payload[5]).ok()?,
+            burst_complete: payload[6] != 0,
+            offset: u32::from_le_bytes([payload[8], payload[9], payload[10], payload[11]]),
+            data: payload[HEADER_LEN..HEADER_LEN + len].to_vec(),
+        })
+    }
+
+    /// The error of a NAK response
+    pub fn nak(&self) -> Option<FtpNak> {
+        (self.opcode == FtpOpcode::Nak).then(|| FtpNak::from_data(&self.data))
+    }
+}
+
+/// Entry of a directory listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirEntry {
+    File { name: String, size: u32 },
+    Directory { name: String },
+}
+
+impl DirEntry {
+    /// Parse an entry of a `ListDirectory` response, `None` for skipped entries
+    fn parse(entry: &[u8]) -> Option<Self> {
+        let (kind, entry) = entry.split_first()?;
+        let entry = String::from_utf8_lossy(entry);
+        match kind {
+            b'F' => {
+                let (name, size) = entry.split_once('\t').unwrap_or((&entry, "0"));
+                Some(Self::File {
+                    name: name.to_owned(),
+                    size: size.parse().unwrap_or(0),
+                })
+            }
+            b'D' => Some(Self::Directory {
+                name: entry.into_owned(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Error of an FTP operation
+#[derive(Debug)]
+pub enum FtpError {
+    /// Receiving from the connection failed
+    Read(MessageReadError),
+    /// Sending on the connection failed
+    Write(MessageWriteError),
+    /// The target did not respond in time, even after all retransmissions
+    Timeout,
+    /// The target answered with a NAK
+    Nak(FtpNak),
+    /// The path does not fit into a single packet
+    PathTooLong,
+}
+
+impl Display for FtpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "{e}"),
+            Self::Write(e) => write!(f, "{e}"),
+            Self::Timeout => write!(f, "FTP request timed out"),
+            Self::Nak(nak) => write!(f, "FTP request failed with {nak:?}"),
+            Self::PathTooLong => write!(f, "FTP path is longer than {FTP_MAX_DATA_LEN} bytes"),
+        }
+    }
+}
+
+impl Error for FtpError {}
+
+impl From<MessageReadError> for FtpError {
+    fn from(e: MessageReadError) -> Self {
+        Self::Read(e)
+    }
+}
+
+impl From<MessageWriteError> for FtpError {
+    fn from(e: MessageWriteError) -> Self {
+        Self::Write(e)
+    }
+}
+
+impl ServiceError for FtpError {
+    fn timeout() -> Self {
+        Self::Timeout
+    }
+}
+
+fn path_data(path: &str) -> Result<&[u8], FtpError> {
+    if path.len() > FTP_MAX_DATA_LEN {
+        return Err(FtpError::PathTooLong);
+    }
+    Ok(path.as_bytes())
+}
+
+fn rename_data(from: &str, to: &str) -> Result<Vec<u8>, FtpError> {
+    let data = [from.as_bytes(), &[0], to.as_bytes()].concat();
+    if data.len() > FTP_MAX_DATA_LEN {
+        return Err(FtpError::PathTooLong);
+    }
+    Ok(data)
+}
+
+/// The `u32` at the start of the data of a response, e.g. a file size or CRC
+fn u32_data(payload: &FtpPayload) -> u32 {
+    payload
+        .data
+        .get(..4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map_or(0, u32::from_le_bytes)
+}
+
+/// Number the next request, its response takes the number in between
+fn next_seq(seq: &AtomicU16) -> u16 {
+    seq.fetch_add(2, Ordering::Relaxed)
+}
+
+fn message(settings: &Settings, payload: &FtpPayload) -> MavMessage {
+    MavMessage::FILE_TRANSFER_PROTOCOL(FILE_TRANSFER_PROTOCOL_DATA {
+        target_network: 0,
+        target_system: settings.target_system,
+        target_component: settings.target_component,
+        payload: payload.encode(),
+    })
+}
+
+fn payload_of(message: &MavMessage) -> Option<FtpPayload> {
+    match message {
+        MavMessage::FILE_TRANSFER_PROTOCOL(data) => FtpPayload::decode(&data.payload),
+        _ => None,
+    }
+}
+
+/// Waits for the response to a single request
+struct Reply {
+    seq_number: u16,
+    opcode: FtpOpcode,
+}
+
+impl Transaction for Reply {
+    type Output = FtpPayload;
+    type Error = FtpError;
+
+    fn handle(&mut self, message: MavMessage) -> Progress<FtpPayload, FtpError> {
+        let Some(payload) = payload_of(&message) else {
+            return Progress::Pending;
+        };
+        if payload.req_opcode != self.opcode
+            || payload.seq_number != self.seq_number.wrapping_add(1)
+        {
+            return Progress::Pending;
+        }
+        match (payload.opcode, payload.nak()) {
+            (FtpOpcode::Ack, _) => Progress::done(Ok(payload)),
+            (_, Some(nak)) => Progress::done(Err(FtpError::Nak(nak))),
+            _ => Progress::Pending,
+        }
+    }
+}
+
+/// Reads a file of an open session in bursts, a new burst starts at the first missing byte
+struct BurstRead<'c> {
+    settings: Settings,
+    seq: &'c AtomicU16,
+    session: u8,
+    size: usize,
+    data: Vec<u8>,
+}
+
+impl<'c> BurstRead<'c> {
+    fn new(settings: Settings, seq: &'c AtomicU16, session: u8, size: u32) -> Self {
+        Self {
+            settings,
+            seq,
+            session,
+            size: size as usize,
+            data: Vec::new(),
+        }
+    }
+
+    fn request(&self) -> MavMessage {
+        let mut payload = FtpPayload::new(FtpOpcode::BurstReadFile);
+        payload.seq_number = next_seq(self.seq);
+        payload.session = self.session;
+        payload.size = FTP_MAX_DATA_LEN as u8;
+        // files are limited to `u32` offsets
+        payload.offset = self.data.len() as u32;
+        message(&self.settings, &payload)
+    }
+}
+
+impl Transaction for BurstRead<'_> {
+    type Output = Vec<u8>;
+    type Error = FtpError;
+
+    fn handle(&mut self, message: MavMessage) -> Progress<Vec<u8>, FtpError> {
+        let Some(payload) = payload_of(&message) else {
+            return Progress::Pending;
+        };
+        if payload.req_opcode != FtpOpcode::BurstReadFile || payload.session != self.session {
+            return Progress::Pending;
+        }
+        match payload.nak() {
+            Some(FtpNak::Eof) => return Progress::done(Ok(std::mem::take(&mut self.data))),
+            Some(nak) => return Progress::done(Err(FtpError::Nak(nak))),
+            None if payload.opcode != FtpOpcode::Ack => return Progress::Pending,
+            None => {}
+        }
+
+        let in_order = payload.offset as usize == self.data.len();
+        if in_order {
+            self.data.extend_from_slice(&payload.data);
+        }
+        if self.data.len() >= self.size {
+            Progress::done(Ok(std::mem::take(&mut self.data)))
+        } else if payload.burst_complete {
+            Progress::Send(self.request())
+        } else if in_order {
+            Progress::Continue
+        } else {
+            Progress::Pending
+        }
+    }
+
+    fn retransmit(&mut self, _last: &MavMessage) -> Option<MavMessage> {
+        Some(self.request())
+    }
+}
+
+/// Client for the file system of a target component
+pub struct FtpClient<'a, M: Message, C: MavConnection<M> + ?Sized = Connection<M>> {
+    client: Client<'a, M, C>,
+    seq: AtomicU16,
+}
+
+impl<'a, M: Message, C: MavConnection<M> + ?Sized> FtpClient<'a, M, C> {
+    /// Create a client for the file system of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: Client::new(connection, target_system, target_component),
+            seq: AtomicU16::new(0),
+        }
+    }
+
+    /// Sets the system and component ID the requests are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for a response before a request is sent again, 1.5 s by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before an operation fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// List the files and directories in a directory
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub fn list_directory(&self, path: &str) -> Result<Vec<DirEntry>, FtpError> {
+        let path = path_data(path)?;
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        loop {
+            let mut payload = FtpPayload::new(FtpOpcode::ListDirectory).with_data(path);
+            payload.offset = offset;
+            let reply = match self.request(payload) {
+                Ok(reply) => reply,
+                Err(FtpError::Nak(FtpNak::Eof)) => return Ok(entries),
+                Err(e) => return Err(e),
+            };
+            let listed: Vec<_> = reply
+                .data
+                .split(|b| *b == 0)
+                .filter(|e| !e.is_empty())
+                .collect();
+            if listed.is_empty() {
+                return Ok(entries);
+            }
+            // skipped entries count for the offset as well
+            offset += listed.len() as u32;
+            entries.extend(listed.into_iter().filter_map(DirEntry::parse));
+        }
+    }
+
+    /// Read a file with burst reads
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub fn read_file(&self, path: &str) -> Result<Vec<u8>, FtpError> {
+        let open = self.path_request(FtpOpcode::OpenFileRO, path)?;
+        let size = u32_data(&open);
+        let result = if size == 0 {
+            Ok(Vec::new())
+        } else {
+            let burst = BurstRead::new(self.client.settings, &self.seq, open.session, size);
+            let request = burst.request();
+            self.client.run(burst, request)
+        };
+        self.terminate(open.session, result)
+    }
+
+    /// Create or truncate a file and write `data` to it
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub fn write_file(&self, path: &str, data: &[u8]) -> Result<(), FtpError> {
+        let create = self.path_request(FtpOpcode::CreateFile, path)?;
+        let mut result = Ok(());
+        for (i, chunk) in data.chunks(FTP_MAX_DATA_LEN).enumerate() {
+            let mut payload = FtpPayload::new(FtpOpcode::WriteFile).with_data(chunk);
+            payload.session = create.session;
+            payload.offset = (i * FTP_MAX_DATA_LEN) as u32;
+            result = self.request(payload).map(drop);
+            if result.is_err() {
+                break;
+            }
+        }
+        self.terminate(create.session, result)
+    }
+
+    /// Remove a file
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub fn remove_file(&self, path: &str) -> Result<(), FtpError> {
+        self.path_request(FtpOpcode::RemoveFile, path).map(drop)
+    }
+
+    /// Create a directory
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub fn create_directory(&self, path: &str) -> Result<(), FtpError> {
+        self.path_request(FtpOpcode::CreateDirectory, path)
+            .map(drop)
+    }
+
+    /// Remove an empty directory
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub fn remove_directory(&self, path: &str) -> Result<(), FtpError> {
+        self.path_request(FtpOpcode::RemoveDirectory, path)
+            .map(drop)
+    }
+
+    /// Rename or move a file or directory
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), FtpError> {
+        let payload = FtpPayload::new(FtpOpcode::Rename).with_data(&rename_data(from, to)?);
+        self.request(payload).map(drop)
+    }
+
+    /// Calculate the CRC32 of a file on the target
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub fn crc32(&self, path: &str) -> Result<u32, FtpError> {
+        let reply = self.path_request(FtpOpcode::CalcFileCRC32, path)?;
+        Ok(u32_data(&reply))
+    }
+
+    fn request(&self, mut payload: FtpPayload) -> Result<FtpPayload, FtpError> {
+        payload.seq_number = next_seq(&self.seq);
+        let reply = Reply {
+            seq_number: payload.seq_number,
+            opcode: payload.opcode,
+        };
+        self.client
+            .run(reply, message(&self.client.settings, &payload))
+    }
+
+    fn path_request(&self, opcode: FtpOpcode, path: &str) -> Result<FtpPayload, FtpError> {
+        self.request(FtpPayload::new(opcode).with_data(path_data(path)?))
+    }
+
+    /// Close a session, the result of the operation takes precedence over a failure to close
+    fn terminate<T>(&self, session: u8, result: Result<T, FtpError>) -> Result<T, FtpError> {
+        let mut payload = FtpPayload::new(FtpOpcode::TerminateSession);
+        payload.session = session;
+        let terminated = self.request(payload);
+        let value = result?;
+        terminated?;
+        Ok(value)
+    }
+}
+
+/// Client for the file system of a target component on an async connection
+///
+/// This is the `async` version of [`FtpClient`].
+#[cfg(feature = "tokio-1")]
+pub struct AsyncFtpClient<
+    'a,
+    M: Message + Sync + Send,
+    C: AsyncMavConnection<M> + ?Sized = dyn AsyncMavConnection<M> + Sync + Send,
+> {
+    client: AsyncClient<'a, M, C>,
+    seq: AtomicU16,
+}
+
+#[cfg(feature = "tokio-1")]
+impl<'a, M: Message + Sync + Send, C: AsyncMavConnection<M> + ?Sized> AsyncFtpClient<'a, M, C> {
+    /// Create a client for the file system of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: AsyncClient::new(connection, target_system, target_component),
+            seq: AtomicU16::new(0),
+        }
+    }
+
+    /// Sets the system and component ID the requests are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for a response before a request is sent again, 1.5 s by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before an operation fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// List the files and directories in a directory
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub async fn list_directory(&self, path: &str) -> Result<Vec<DirEntry>, FtpError> {
+        let path = path_data(path)?;
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        loop {
+            let mut payload = FtpPayload::new(FtpOpcode::ListDirectory).with_data(path);
+            payload.offset = offset;
+            let reply = match self.request(payload).await {
+                Ok(reply) => reply,
+                Err(FtpError::Nak(FtpNak::Eof)) => return Ok(entries),
+                Err(e) => return Err(e),
+            };
+            let listed: Vec<_> = reply
+                .data
+                .split(|b| *b == 0)
+                .filter(|e| !e.is_empty())
+                .collect();
+            if listed.is_empty() {
+                return Ok(entries);
+            }
+            // skipped entries count for the offset as well
+            offset += listed.len() as u32;
+            entries.extend(listed.into_iter().filter_map(DirEntry::parse));
+        }
+    }
+
+    /// Read a file with burst reads
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub async fn read_file(&self, path: &str) -> Result<Vec<u8>, FtpError> {
+        let open = self.path_request(FtpOpcode::OpenFileRO, path).await?;
+        let size = u32_data(&open);
+        let result = if size == 0 {
+            Ok(Vec::new())
+        } else {
+            let burst = BurstRead::new(self.client.settings, &self.seq, open.session, size);
+            let request = burst.request();
+            self.client.run(burst, request).await
+        };
+        self.terminate(open.session, result).await
+    }
+
+    /// Create or truncate a file and write `data` to it
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub async fn write_file(&self, path: &str, data: &[u8]) -> Result<(), FtpError> {
+        let create = self.path_request(FtpOpcode::CreateFile, path).await?;
+        let mut result = Ok(());
+        for (i, chunk) in data.chunks(FTP_MAX_DATA_LEN).enumerate() {
+            let mut payload = FtpPayload::new(FtpOpcode::WriteFile).with_data(chunk);
+            payload.session = create.session;
+            payload.offset = (i * FTP_MAX_DATA_LEN) as u32;
+            result = self.request(payload).await.map(drop);
+            if result.is_err() {
+                break;
+            }
+        }
+        self.terminate(create.session, result).await
+    }
+
+    /// Remove a file
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub async fn remove_file(&self, path: &str) -> Result<(), FtpError> {
+        self.path_request(FtpOpcode::RemoveFile, path)
+            .await
+            .map(drop)
+    }
+
+    /// Create a directory
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub async fn create_directory(&self, path: &str) -> Result<(), FtpError> {
+        self.path_request(FtpOpcode::CreateDirectory, path)
+            .await
+            .map(drop)
+    }
+
+    /// Remove an empty directory
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub async fn remove_directory(&self, path: &str) -> Result<(), FtpError> {
+        self.path_request(FtpOpcode::RemoveDirectory, path)
+            .await
+            .map(drop)
+    }
+
+    /// Rename or move a file or directory
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub async fn rename(&self, from: &str, to: &str) -> Result<(), FtpError> {
+        let payload = FtpPayload::new(FtpOpcode::Rename).with_data(&rename_data(from, to)?);
+        self.request(payload).await.map(drop)
+    }
+
+    /// Calculate the CRC32 of a file on the target
+    ///
+    /// # Errors
+    ///
+    /// See [`FtpError`]
+    pub async fn crc32(&self, path: &str) -> Result<u32, FtpError> {
+        let reply = self.path_request(FtpOpcode::CalcFileCRC32, path).await?;
+        Ok(u32_data(&reply))
+    }
+
+    async fn request(&self, mut payload: FtpPayload) -> Result<FtpPayload, FtpError> {
+        payload.seq_number = next_seq(&self.seq);
+        let reply = Reply {
+            seq_number: payload.seq_number,
+            opcode: payload.opcode,
+        };
+        self.client
+            .run(reply, message(&self.client.settings, &payload))
+            .await
+    }
+
+    async fn path_request(&self, opcode: FtpOpcode, path: &str) -> Result<FtpPayload, FtpError> {
+        self.request(FtpPayload::new(opcode).with_data(path_data(path)?))
+            .await
+    }
+
+    /// Close a session, the result of the operation takes precedence over a failure to close
+    async fn terminate<T>(&self, session: u8, result: Result<T, FtpError>) -> Result<T, FtpError> {
+        let mut payload = FtpPayload::new(FtpOpcode::TerminateSession);
+        payload.session = session;
+        let terminated = self.request(payload).await;
+        let value = result?;
+        terminated?;
+        Ok(value)
+    }
+}
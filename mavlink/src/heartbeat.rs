@@ -0,0 +1,339 @@
+//! Discovery and liveness tracking of the systems on a link based on their `HEARTBEAT` messages
+//!
+//! A [`HeartbeatMonitor`] wraps a connection and keeps a table of every system and component it
+//! received a heartbeat from. Peers that stop sending heartbeats for longer than the timeout are
+//! removed again, both changes are reported as [`PeerEvent`]s. The monitor can also send a
+//! heartbeat of its own at a fixed interval.
+//!
+//! The monitor works with any dialect that includes the `HEARTBEAT` message. Heartbeats are
+//! recorded with their raw values, so peers using types or autopilots unknown to `common` are
+//! tracked as well.
+//!
+//! ```ignore
+//! let monitor = HeartbeatMonitor::new(mavlink::connect::<MavMessage>("udpin:0.0.0.0:14550")?)
+//!     .with_heartbeat(header, heartbeat, Duration::from_secs(1));
+//! std::thread::scope(|scope| {
+//!     // sends the heartbeats and detects lost peers while `recv` blocks
+//!     scope.spawn(|| monitor.run());
+//!     while let Ok((header, msg)) = monitor.recv() {
+//!         while let Some(event) = monitor.poll_event() {
+//!             println!("{event:?}");
+//!         }
+//!     }
+//!     monitor.stop();
+//! });
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::marker::PhantomData;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use num_traits::FromPrimitive;
+
+use crate::common::{MavAutopilot, MavModeFlag, MavState, MavType, HEARTBEAT_DATA};
+use crate::convert::to_message;
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::{
+    Connection, MAVLinkMessageRaw, MavConnection, MavHeader, MavlinkVersion, Message, MessageData,
+    MAX_FRAME_SIZE,
+};
+
+/// Time after which a peer that did not send a heartbeat is considered lost by default
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A system or component discovered through its heartbeats
+///
+/// The fields hold the raw values of the last heartbeat, use the methods of the same name to
+/// interpret them with the `common` enums.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerInfo {
+    /// System ID of the peer
+    pub system_id: u8,
+    /// Component ID of the peer
+    pub component_id: u8,
+    /// Type of the peer, e.g. quadrotor or ground control station
+    pub mavtype: u8,
+    /// Autopilot running on the peer
+    pub autopilot: u8,
+    /// Base mode from the last heartbeat
+    pub base_mode: u8,
+    /// Autopilot specific mode from the last heartbeat
+    pub custom_mode: u32,
+    /// System status from the last heartbeat
+    pub system_status: u8,
+    /// Time the last heartbeat was received
+    pub last_seen: Instant,
+}
+
+impl PeerInfo {
+    /// Read the peer from a `HEARTBEAT` payload
+    fn new(header: &MavHeader, payload: &[u8]) -> Self {
+        // payloads are truncated to the last non-zero byte
+        let mut buf = [0; HEARTBEAT_DATA::ENCODED_LEN];
+        let len = payload.len().min(buf.len());
+        buf[..len].copy_from_slice(&payload[..len]);
+        // fields in wire order: custom_mode, type, autopilot, base_mode, system_status
+        let [m0, m1, m2, m3, mavtype, autopilot, base_mode, system_status, _] = buf;
+        Self {
+            system_id: header.system_id,
+            component_id: header.component_id,
+            mavtype,
+            autopilot,
+            base_mode,
+            custom_mode: u32::from_le_bytes([m0, m1, m2, m3]),
+            system_status,
+            last_seen: Instant::now(),
+        }
+    }
+
+    /// Type of the peer, `None` if it is not known to `common`
+    pub fn mavtype(&self) -> Option<MavType> {
+        MavType::from_u8(self.mavtype)
+    }
+
+    /// Autopilot of the peer, `None` if it is not known to `common`
+    pub fn autopilot(&self) -> Option<MavAutopilot> {
+        MavAutopilot::from_u8(self.autopilot)
+    }
+
+    /// Base mode flags of the peer, ignoring flags unknown to `common`
+    pub fn base_mode(&self) -> MavModeFlag {
+        MavModeFlag::from_bits_truncate(self.base_mode)
+    }
+
+    /// System status of the peer, `None` if it is not known to `common`
+    pub fn system_status(&self) -> Option<MavState> {
+        MavState::from_u8(self.system_status)
+    }
+}
+
+/// Change of the peers known to a [`HeartbeatMonitor`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PeerEvent {
+    /// The first heartbeat of a peer was received
+    Connected(PeerInfo),
+    /// A peer did not send a heartbeat within the timeout, contains its last known state
+    Lost(PeerInfo),
+}
+
+#[derive(Default)]
+struct State {
+    peers: HashMap<(u8, u8), PeerInfo>,
+    events: VecDeque<PeerEvent>,
+    last_sent: Option<Instant>,
+    stopped: bool,
+}
+
+struct OwnHeartbeat {
+    header: MavHeader,
+    heartbeat: HEARTBEAT_DATA,
+    interval: Duration,
+}
+
+/// Tracks the peers on a connection by their heartbeats
+pub struct HeartbeatMonitor<M: Message, C: MavConnection<M> = Connection<M>> {
+    connection: C,
+    state: Mutex<State>,
+    stop: Condvar,
+    timeout: Duration,
+    own: Option<OwnHeartbeat>,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<M: Message, C: MavConnection<M>> HeartbeatMonitor<M, C> {
+    /// Create a monitor for the given connection
+    pub fn new(connection: C) -> Self {
+        Self {
+            connection,
+            state: Mutex::default(),
+            stop: Condvar::new(),
+            timeout: DEFAULT_TIMEOUT,
+            own: None,
+            _message: PhantomData,
+        }
+    }
+
+    /// Sets the time after which a peer that did not send a heartbeat is considered lost
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends `heartbeat` with the system and component ID of `header` every `interval`
+    ///
+    /// Heartbeats are sent by [`update`](Self::update) and [`run`](Self::run).
+    pub fn with_heartbeat(
+        mut self,
+        header: MavHeader,
+        heartbeat: HEARTBEAT_DATA,
+        interval: Duration,
+    ) -> Self {
+        self.own = Some(OwnHeartbeat {
+            header,
+            heartbeat,
+            interval,
+        });
+        self
+    }
+
+    /// The wrapped connection
+    pub fn connection(&self) -> &C {
+        &self.connection
+    }
+
+    /// Returns the wrapped connection
+    pub fn into_inner(self) -> C {
+        self.connection
+    }
+
+    /// Receive a message, recording it if it is a heartbeat
+    ///
+    /// Peers that timed out are removed before receiving.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of receiving from the connection.
+    pub fn recv(&self) -> Result<(MavHeader, M), MessageReadError> {
+        self.expire(&mut self.state.lock().unwrap());
+        // heartbeats are recorded before parsing, as their enum values may not be valid for `M`
+        let raw = self.connection.recv_raw()?;
+        let header = MavHeader {
+            sequence: raw.sequence(),
+            system_id: raw.system_id(),
+            component_id: raw.component_id(),
+        };
+        self.record(&header, raw.message_id(), raw.payload());
+        let message = M::parse(raw.version(), raw.message_id(), raw.payload())?;
+        Ok((header, message))
+    }
+
+    /// Record a message received by other means than [`recv`](Self::recv)
+    ///
+    /// Messages other than heartbeats are ignored.
+    pub fn observe(&self, header: &MavHeader, message: &M) {
+        let mut payload = [0; MAX_FRAME_SIZE];
+        let len = message.ser(MavlinkVersion::V2, &mut payload);
+        self.record(header, message.message_id(), &payload[..len]);
+    }
+
+    /// Record a raw message received by other means than [`recv`](Self::recv)
+    ///
+    /// Messages other than heartbeats are ignored.
+    pub fn observe_raw(&self, raw: &MAVLinkMessageRaw) {
+        let header = MavHeader {
+            sequence: raw.sequence(),
+            system_id: raw.system_id(),
+            component_id: raw.component_id(),
+        };
+        self.record(&header, raw.message_id(), raw.payload());
+    }
+
+    fn record(&self, header: &MavHeader, message_id: u32, payload: &[u8]) {
+        if message_id != HEARTBEAT_DATA::ID {
+            return;
+        }
+        let peer = PeerInfo::new(header, payload);
+        let mut state = self.state.lock().unwrap();
+        if state
+            .peers
+            .insert((peer.system_id, peer.component_id), peer.clone())
+            .is_none()
+        {
+            state.events.push_back(PeerEvent::Connected(peer));
+        }
+    }
+
+    /// Remove the peers that timed out and send the own heartbeat if it is due
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that occurred while sending the heartbeat.
+    pub fn update(&self) -> Result<(), MessageWriteError> {
+        let due = {
+            let mut state = self.state.lock().unwrap();
+            self.expire(&mut state);
+            self.own.as_ref().filter(|own| {
+                let due = !state
+                    .last_sent
+                    .is_some_and(|last_sent| last_sent.elapsed() < own.interval);
+                if due {
+                    state.last_sent = Some(Instant::now());
+                }
+                due
+            })
+        };
+
+        if let Some(own) = due {
+            let message = to_message(&own.heartbeat)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            self.connection.send(&own.header, &message)?;
+        }
+        Ok(())
+    }
+
+    fn expire(&self, state: &mut State) {
+        let State { peers, events, .. } = state;
+        peers.retain(|_, peer| {
+            let alive = peer.last_seen.elapsed() < self.timeout;
+            if !alive {
+                events.push_back(PeerEvent::Lost(peer.clone()));
+            }
+            alive
+        });
+    }
+
+    /// Call [`update`](Self::update) periodically until [`stop`](Self::stop) is called or
+    /// sending the own heartbeat fails
+    ///
+    /// This keeps heartbeats going out and lost peers being detected while another thread is
+    /// blocked in [`recv`](Self::recv).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that occurred while sending the heartbeat.
+    pub fn run(&self) -> Result<(), MessageWriteError> {
+        let period = match &self.own {
+            Some(own) => own.interval.min(self.timeout),
+            None => self.timeout,
+        };
+        loop {
+            self.update()?;
+            let state = self.state.lock().unwrap();
+            let (state, _) = self
+                .stop
+                .wait_timeout_while(state, period / 2, |state| !state.stopped)
+                .unwrap();
+            if state.stopped {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Make [`run`](Self::run) return, now and on any later call
+    pub fn stop(&self) {
+        self.state.lock().unwrap().stopped = true;
+        self.stop.notify_all();
+    }
+
+    /// Next change of the known peers, if any
+    pub fn poll_event(&self) -> Option<PeerEvent> {
+        self.state.lock().unwrap().events.pop_front()
+    }
+
+    /// The peers that sent a heartbeat within the timeout, as of the last update
+    pub fn peers(&self) -> Vec<PeerInfo> {
+        self.state.lock().unwrap().peers.values().cloned().collect()
+    }
+
+    /// The peer with the given IDs, if it sent a heartbeat within the timeout
+    pub fn peer(&self, system_id: u8, component_id: u8) -> Option<PeerInfo> {
+        self.state
+            .lock()
+            .unwrap()
+            .peers
+            .get(&(system_id, component_id))
+            .cloned()
+    }
+}
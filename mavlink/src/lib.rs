@@ -16,6 +16,7 @@
 //! - `embedded`: Enables embedded support using the [embedded-io] crate, incompatible with `embedded-hal-02` and `tokio-1`.
 //! - `embedded-hal-02`: Enables embedded support using version 0.2 of the [embedded-hal] crate, incompatible with `embedded`.
 //! - `tokio-1`: Enable support for asynchronous I/O using [tokio], incompatible with `embedded`.
+//! - `futures-io`: Enable support for asynchronous I/O using the [futures] I/O traits, e.g. with `smol` or `async-std`, incompatible with `embedded`. Only streams opened by the runtime can be used, connecting by address (TCP, UDP, ...) requires `tokio-1`.
 //! - `serde`: Enables [serde] support in generated message sets, enabled by default.
 //! - `format-generated-code`: Generated MAVLink message set code will be formatted, requires `rustfmt` to be installed, enabled by default.
 //! - `emit-extensions`: Generated MAVLink message set code will include [MAVLink 2 message extensions].
@@ -56,6 +57,7 @@
 //! [embedded-io]: https://crates.io/crates/embedded-io
 //! [embedded-hal]: https://crates.io/crates/embedded-hal
 //! [tokio]: https://crates.io/crates/tokio
+//! [futures]: https://crates.io/crates/futures
 //! [serde]: https://crates.io/crates/serde
 //! [arbitrary]: https://crates.io/crates/arbitrary
 //! [ts-rs]: https://crates.io/crates/ts-rs
@@ -65,6 +67,30 @@ include!(concat!(env!("OUT_DIR"), "/mod.rs"));
 
 pub use mavlink_core::*;
 
+#[cfg(all(feature = "std", feature = "common"))]
+pub mod command;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod convert;
+
+#[cfg(all(feature = "std", feature = "common"))]
+pub mod ftp;
+
+#[cfg(all(feature = "std", feature = "common"))]
+pub mod heartbeat;
+
+#[cfg(all(feature = "std", feature = "common"))]
+pub mod log_download;
+
+#[cfg(all(feature = "std", feature = "common"))]
+pub mod mission;
+
+#[cfg(all(feature = "std", feature = "common"))]
+pub mod param;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod service;
+
 #[cfg(feature = "emit-extensions")]
 #[allow(unused_imports)]
 pub(crate) use mavlink_core::utils::RustDefault;
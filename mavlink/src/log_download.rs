@@ -0,0 +1,437 @@
+//! Client for the [onboard log download](https://mavlink.io/en/services/log_download.html) protocol
+//!
+//! The [`LogClient`] lists the logs stored on a vehicle and downloads them into any
+//! [`Write`] sink. The [`AsyncLogClient`] does the same on an async connection.
+//!
+//! Logs are streamed as `LOG_DATA` messages of up to 90 bytes. Chunks that arrive after a lost one
+//! are kept in memory until the gap is filled, missing ranges are requested again once the stream
+//! stops.
+//!
+//! ```ignore
+//! let connection = mavlink::connect::<MavMessage>("udpout:127.0.0.1:14550")?;
+//! let client = LogClient::new(&connection, 1, 1);
+//! let logs = client.list()?;
+//! let mut file = std::fs::File::create("flight.bin")?;
+//! client.download_with_progress(&logs[0], &mut file, |received, total| {
+//!     println!("{received}/{total} bytes");
+//! })?;
+//! ```
+
+use core::fmt::{Display, Formatter};
+use core::ops::RangeInclusive;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::common::{
+    MavMessage, LOG_ENTRY_DATA, LOG_REQUEST_DATA_DATA, LOG_REQUEST_END_DATA, LOG_REQUEST_LIST_DATA,
+};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::service::{Client, Progress, ServiceError, Settings, Transaction};
+use crate::{Connection, MavConnection, MavHeader, Message};
+
+#[cfg(feature = "tokio-1")]
+use crate::{service::AsyncClient, AsyncMavConnection};
+
+/// Error of a log operation
+#[derive(Debug)]
+pub enum LogError {
+    /// Receiving from the connection failed
+    Read(MessageReadError),
+    /// Sending on the connection failed
+    Write(MessageWriteError),
+    /// The target did not respond in time, even after all retransmissions
+    Timeout,
+    /// Writing the downloaded data to the sink failed
+    Output(io::Error),
+}
+
+impl Display for LogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "{e}"),
+            Self::Write(e) => write!(f, "{e}"),
+            Self::Timeout => write!(f, "Log transfer timed out"),
+            Self::Output(e) => write!(f, "Failed to write log: {e}"),
+        }
+    }
+}
+
+impl Error for LogError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Output(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<MessageReadError> for LogError {
+    fn from(e: MessageReadError) -> Self {
+        Self::Read(e)
+    }
+}
+
+impl From<MessageWriteError> for LogError {
+    fn from(e: MessageWriteError) -> Self {
+        Self::Write(e)
+    }
+}
+
+impl ServiceError for LogError {
+    fn timeout() -> Self {
+        Self::Timeout
+    }
+}
+
+/// A log stored on the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Log ID
+    pub id: u16,
+    /// UTC timestamp of the log in seconds since 1970, or 0 if not available
+    pub time_utc: u32,
+    /// Size of the log in bytes
+    pub size: u32,
+}
+
+impl From<&LOG_ENTRY_DATA> for LogEntry {
+    fn from(data: &LOG_ENTRY_DATA) -> Self {
+        Self {
+            id: data.id,
+            time_utc: data.time_utc,
+            size: data.size,
+        }
+    }
+}
+
+fn request_list(settings: &Settings, start: u16, end: u16) -> MavMessage {
+    MavMessage::LOG_REQUEST_LIST(LOG_REQUEST_LIST_DATA {
+        start,
+        end,
+        target_system: settings.target_system,
+        target_component: settings.target_component,
+    })
+}
+
+fn request_data(settings: &Settings, id: u16, ofs: u32, count: u32) -> MavMessage {
+    MavMessage::LOG_REQUEST_DATA(LOG_REQUEST_DATA_DATA {
+        ofs,
+        count,
+        id,
+        target_system: settings.target_system,
+        target_component: settings.target_component,
+    })
+}
+
+fn request_end(settings: &Settings) -> MavMessage {
+    MavMessage::LOG_REQUEST_END(LOG_REQUEST_END_DATA {
+        target_system: settings.target_system,
+        target_component: settings.target_component,
+    })
+}
+
+/// Collects the log entries, missing entries are requested one by one once the stream stops
+struct List {
+    settings: Settings,
+    ids: Option<RangeInclusive<u16>>,
+    entries: BTreeMap<u16, LogEntry>,
+}
+
+impl List {
+    fn missing(&self) -> Option<u16> {
+        self.ids.clone()?.find(|id| !self.entries.contains_key(id))
+    }
+}
+
+impl Transaction for List {
+    type Output = Vec<LogEntry>;
+    type Error = LogError;
+
+    fn handle(&mut self, message: MavMessage) -> Progress<Vec<LogEntry>, LogError> {
+        let MavMessage::LOG_ENTRY(data) = message else {
+            return Progress::Pending;
+        };
+        if data.num_logs == 0 {
+            return Progress::done(Ok(Vec::new()));
+        }
+        // IDs are consecutive, starting at 0 or 1 depending on the autopilot
+        let first = data.last_log_num.saturating_sub(data.num_logs - 1);
+        let ids = self.ids.get_or_insert(first..=data.last_log_num);
+        if !ids.contains(&data.id) {
+            return Progress::Pending;
+        }
+        self.entries.insert(data.id, LogEntry::from(&data));
+        match self.missing() {
+            None => Progress::done(Ok(std::mem::take(&mut self.entries)
+                .into_values()
+                .collect())),
+            Some(_) => Progress::Continue,
+        }
+    }
+
+    fn retransmit(&mut self, last: &MavMessage) -> Option<MavMessage> {
+        match self.missing() {
+            Some(id) => Some(request_list(&self.settings, id, id)),
+            // nothing received yet
+            None => Some(last.clone()),
+        }
+    }
+}
+
+/// Writes a log to the sink in order, chunks after a gap wait in `pending` until it is filled
+struct Download<'s, W, F> {
+    settings: Settings,
+    id: u16,
+    size: u32,
+    written: u32,
+    pending: BTreeMap<u32, Vec<u8>>,
+    /// End of the gap that was requested again, the next gap is requested once it is filled
+    refill_end: Option<u32>,
+    sink: &'s mut W,
+    on_progress: F,
+}
+
+impl<'s, W: Write, F: FnMut(u32, u32)> Download<'s, W, F> {
+    fn new(settings: Settings, entry: &LogEntry, sink: &'s mut W, on_progress: F) -> Self {
+        Self {
+            settings,
+            id: entry.id,
+            size: entry.size,
+            written: 0,
+            pending: BTreeMap::new(),
+            refill_end: None,
+            sink,
+            on_progress,
+        }
+    }
+
+    fn write(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.sink.write_all(chunk)?;
+        // chunks are at most 90 bytes
+        self.written += chunk.len() as u32;
+        Ok(())
+    }
+
+    /// Request the first missing range again
+    fn request_gap(&mut self) -> MavMessage {
+        let end = self.pending.keys().next().copied().unwrap_or(self.size);
+        self.refill_end = Some(end);
+        request_data(
+            &self.settings,
+            self.id,
+            self.written,
+            end.saturating_sub(self.written),
+        )
+    }
+}
+
+impl<W: Write, F: FnMut(u32, u32)> Transaction for Download<'_, W, F> {
+    type Output = ();
+    type Error = LogError;
+
+    fn handle(&mut self, message: MavMessage) -> Progress<(), LogError> {
+        let MavMessage::LOG_DATA(data) = message else {
+            return Progress::Pending;
+        };
+        if data.id != self.id || data.ofs < self.written {
+            return Progress::Pending;
+        }
+        let chunk = &data.data[..usize::from(data.count).min(data.data.len())];
+        if chunk.is_empty() {
+            // the log is shorter than announced
+            self.size = self.size.min(data.ofs);
+        } else if data.ofs > self.written {
+            self.pending.insert(data.ofs, chunk.to_vec());
+        } else if let Err(e) = self.write(chunk) {
+            return Progress::done(Err(LogError::Output(e)));
+        }
+        while let Some(chunk) = self.pending.remove(&self.written) {
+            if let Err(e) = self.write(&chunk) {
+                return Progress::done(Err(LogError::Output(e)));
+            }
+        }
+        (self.on_progress)(self.written, self.size);
+
+        if self.written >= self.size {
+            return Progress::Done {
+                reply: Some(request_end(&self.settings)),
+                result: Ok(()),
+            };
+        }
+        match self.refill_end {
+            Some(end) if self.written >= end => Progress::Send(self.request_gap()),
+            _ => Progress::Continue,
+        }
+    }
+
+    fn retransmit(&mut self, _last: &MavMessage) -> Option<MavMessage> {
+        Some(self.request_gap())
+    }
+}
+
+/// Client for the logs stored on a target
+pub struct LogClient<'a, M: Message, C: MavConnection<M> + ?Sized = Connection<M>> {
+    client: Client<'a, M, C>,
+}
+
+impl<'a, M: Message, C: MavConnection<M> + ?Sized> LogClient<'a, M, C> {
+    /// Create a client for the logs of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: Client::new(connection, target_system, target_component),
+        }
+    }
+
+    /// Sets the system and component ID the requests are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for a response before a request is sent again, 1.5 s by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before an operation fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// List the logs stored on the target, ordered by ID
+    ///
+    /// # Errors
+    ///
+    /// See [`LogError`]
+    pub fn list(&self) -> Result<Vec<LogEntry>, LogError> {
+        let settings = self.client.settings;
+        let list = List {
+            settings,
+            ids: None,
+            entries: BTreeMap::new(),
+        };
+        self.client.run(list, request_list(&settings, 0, u16::MAX))
+    }
+
+    /// Download a log into `sink`
+    ///
+    /// # Errors
+    ///
+    /// See [`LogError`]
+    pub fn download<W: Write>(&self, entry: &LogEntry, sink: &mut W) -> Result<(), LogError> {
+        self.download_with_progress(entry, sink, |_, _| {})
+    }
+
+    /// Download a log into `sink`, passing the number of bytes written and the size of the log
+    /// to `on_progress` whenever data is received
+    ///
+    /// # Errors
+    ///
+    /// See [`LogError`]
+    pub fn download_with_progress<W: Write>(
+        &self,
+        entry: &LogEntry,
+        sink: &mut W,
+        on_progress: impl FnMut(u32, u32),
+    ) -> Result<(), LogError> {
+        let settings = self.client.settings;
+        if entry.size == 0 {
+            return Ok(());
+        }
+        let download = Download::new(settings, entry, sink, on_progress);
+        self.client
+            .run(download, request_data(&settings, entry.id, 0, entry.size))
+    }
+}
+
+/// Client for the logs stored on a target on an async connection
+///
+/// This is the `async` version of [`LogClient`].
+#[cfg(feature = "tokio-1")]
+pub struct AsyncLogClient<
+    'a,
+    M: Message + Sync + Send,
+    C: AsyncMavConnection<M> + ?Sized = dyn AsyncMavConnection<M> + Sync + Send,
+> {
+    client: AsyncClient<'a, M, C>,
+}
+
+#[cfg(feature = "tokio-1")]
+impl<'a, M: Message + Sync + Send, C: AsyncMavConnection<M> + ?Sized> AsyncLogClient<'a, M, C> {
+    /// Create a client for the logs of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: AsyncClient::new(connection, target_system, target_component),
+        }
+    }
+
+    /// Sets the system and component ID the requests are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for a response before a request is sent again, 1.5 s by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before an operation fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// List the logs stored on the target, ordered by ID
+    ///
+    /// # Errors
+    ///
+    /// See [`LogError`]
+    pub async fn list(&self) -> Result<Vec<LogEntry>, LogError> {
+        let settings = self.client.settings;
+        let list = List {
+            settings,
+            ids: None,
+            entries: BTreeMap::new(),
+        };
+        self.client
+            .run(list, request_list(&settings, 0, u16::MAX))
+            .await
+    }
+
+    /// Download a log into `sink`
+    ///
+    /// # Errors
+    ///
+    /// See [`LogError`]
+    pub async fn download<W: Write>(&self, entry: &LogEntry, sink: &mut W) -> Result<(), LogError> {
+        self.download_with_progress(entry, sink, |_, _| {}).await
+    }
+
+    /// Download a log into `sink`, passing the number of bytes written and the size of the log
+    /// to `on_progress` whenever data is received
+    ///
+    /// # Errors
+    ///
+    /// See [`LogError`]
+    pub async fn download_with_progress<W: Write>(
+        &self,
+        entry: &LogEntry,
+        sink: &mut W,
+        on_progress: impl FnMut(u32, u32),
+    ) -> Result<(), LogError> {
+        let settings = self.client.settings;
+        if entry.size == 0 {
+            return Ok(());
+        }
+        let download = Download::new(settings, entry, sink, on_progress);
+        self.client
+            .run(download, request_data(&settings, entry.id, 0, entry.size))
+            .await
+    }
+}
@@ -0,0 +1,489 @@
+//! Client for the [mission protocol](https://mavlink.io/en/services/mission.html)
+//!
+//! The [`MissionClient`] uploads, downloads and clears the mission, geofence and rally point plans
+//! of a system using `MISSION_ITEM_INT`. The [`AsyncMissionClient`] does the same on an async
+//! connection. Any dialect that includes the mission messages of the `common` set can be used.
+//!
+//! Geofence and rally point plans are addressed with the `mission_type` extension field, so only
+//! missions can be transferred unless the `emit-extensions` feature is enabled.
+//!
+//! While a transfer is in progress the client receives from the connection and discards every
+//! message that does not belong to it.
+//!
+//! ```ignore
+//! let connection = mavlink::connect::<MavMessage>("udpout:127.0.0.1:14550")?;
+//! let client = MissionClient::new(&connection, 1, 1);
+//! let items = client.download(MavMissionType::MAV_MISSION_TYPE_MISSION)?;
+//! client.upload(MavMissionType::MAV_MISSION_TYPE_MISSION, &items)?;
+//! ```
+
+use core::fmt::{Display, Formatter};
+use std::error::Error;
+use std::time::Duration;
+
+use crate::common::{
+    MavMessage, MavMissionResult, MavMissionType, MISSION_ACK_DATA, MISSION_CLEAR_ALL_DATA,
+    MISSION_COUNT_DATA, MISSION_ITEM_INT_DATA, MISSION_REQUEST_INT_DATA, MISSION_REQUEST_LIST_DATA,
+};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::service::{Client, Progress, ServiceError, Transaction};
+use crate::{Connection, MavConnection, MavHeader, Message};
+
+#[cfg(feature = "tokio-1")]
+use crate::{service::AsyncClient, AsyncMavConnection};
+
+/// Error of a mission protocol operation
+#[derive(Debug)]
+pub enum MissionError {
+    /// Receiving from the connection failed
+    Read(MessageReadError),
+    /// Sending on the connection failed
+    Write(MessageWriteError),
+    /// The target did not respond in time, even after all retransmissions
+    Timeout,
+    /// The target answered with a `MISSION_ACK` reporting an error
+    Rejected(MavMissionResult),
+    /// Plan types other than missions require the `emit-extensions` feature
+    UnsupportedType(MavMissionType),
+}
+
+impl Display for MissionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "{e}"),
+            Self::Write(e) => write!(f, "{e}"),
+            Self::Timeout => write!(f, "Mission transfer timed out"),
+            Self::Rejected(result) => write!(f, "Mission transfer rejected with {result:?}"),
+            Self::UnsupportedType(mission_type) => write!(
+                f,
+                "Mission type {mission_type:?} requires the `emit-extensions` feature"
+            ),
+        }
+    }
+}
+
+impl Error for MissionError {}
+
+impl From<MessageReadError> for MissionError {
+    fn from(e: MessageReadError) -> Self {
+        Self::Read(e)
+    }
+}
+
+impl From<MessageWriteError> for MissionError {
+    fn from(e: MessageWriteError) -> Self {
+        Self::Write(e)
+    }
+}
+
+impl ServiceError for MissionError {
+    fn timeout() -> Self {
+        Self::Timeout
+    }
+}
+
+fn ack_result(result: MavMissionResult) -> Result<(), MissionError> {
+    match result {
+        MavMissionResult::MAV_MISSION_ACCEPTED => Ok(()),
+        result => Err(MissionError::Rejected(result)),
+    }
+}
+
+/// A plan of a target system, builds the messages of the protocol
+#[derive(Clone, Copy)]
+struct Plan {
+    target_system: u8,
+    target_component: u8,
+    #[cfg_attr(not(feature = "emit-extensions"), allow(dead_code))]
+    mission_type: MavMissionType,
+}
+
+impl Plan {
+    fn new(
+        target_system: u8,
+        target_component: u8,
+        mission_type: MavMissionType,
+    ) -> Result<Self, MissionError> {
+        #[cfg(not(feature = "emit-extensions"))]
+        if mission_type != MavMissionType::MAV_MISSION_TYPE_MISSION {
+            return Err(MissionError::UnsupportedType(mission_type));
+        }
+        Ok(Self {
+            target_system,
+            target_component,
+            mission_type,
+        })
+    }
+
+    fn count(&self, count: u16) -> MavMessage {
+        MavMessage::MISSION_COUNT(MISSION_COUNT_DATA {
+            target_system: self.target_system,
+            target_component: self.target_component,
+            count,
+            #[cfg(feature = "emit-extensions")]
+            mission_type: self.mission_type,
+            #[cfg(feature = "emit-extensions")]
+            opaque_id: 0,
+        })
+    }
+
+    fn item(&self, seq: u16, item: &MISSION_ITEM_INT_DATA) -> MavMessage {
+        MavMessage::MISSION_ITEM_INT(MISSION_ITEM_INT_DATA {
+            target_system: self.target_system,
+            target_component: self.target_component,
+            seq,
+            #[cfg(feature = "emit-extensions")]
+            mission_type: self.mission_type,
+            ..item.clone()
+        })
+    }
+
+    fn request_list(&self) -> MavMessage {
+        MavMessage::MISSION_REQUEST_LIST(MISSION_REQUEST_LIST_DATA {
+            target_system: self.target_system,
+            target_component: self.target_component,
+            #[cfg(feature = "emit-extensions")]
+            mission_type: self.mission_type,
+        })
+    }
+
+    fn request(&self, seq: u16) -> MavMessage {
+        MavMessage::MISSION_REQUEST_INT(MISSION_REQUEST_INT_DATA {
+            target_system: self.target_system,
+            target_component: self.target_component,
+            seq,
+            #[cfg(feature = "emit-extensions")]
+            mission_type: self.mission_type,
+        })
+    }
+
+    fn clear_all(&self) -> MavMessage {
+        MavMessage::MISSION_CLEAR_ALL(MISSION_CLEAR_ALL_DATA {
+            target_system: self.target_system,
+            target_component: self.target_component,
+            #[cfg(feature = "emit-extensions")]
+            mission_type: self.mission_type,
+        })
+    }
+
+    fn ack(&self, result: MavMissionResult) -> MavMessage {
+        MavMessage::MISSION_ACK(MISSION_ACK_DATA {
+            target_system: self.target_system,
+            target_component: self.target_component,
+            mavtype: result,
+            #[cfg(feature = "emit-extensions")]
+            mission_type: self.mission_type,
+            #[cfg(feature = "emit-extensions")]
+            opaque_id: 0,
+        })
+    }
+
+    /// Whether a received message belongs to this plan
+    #[cfg(feature = "emit-extensions")]
+    #[allow(deprecated)]
+    fn concerns(&self, message: &MavMessage) -> bool {
+        let mission_type = match message {
+            MavMessage::MISSION_COUNT(data) => data.mission_type,
+            MavMessage::MISSION_ITEM_INT(data) => data.mission_type,
+            MavMessage::MISSION_REQUEST_INT(data) => data.mission_type,
+            MavMessage::MISSION_REQUEST(data) => data.mission_type,
+            MavMessage::MISSION_ACK(data) => data.mission_type,
+            _ => return true,
+        };
+        mission_type == self.mission_type
+    }
+
+    /// Whether a received message belongs to this plan, always the mission without extensions
+    #[cfg(not(feature = "emit-extensions"))]
+    fn concerns(&self, _message: &MavMessage) -> bool {
+        true
+    }
+}
+
+struct Upload {
+    plan: Plan,
+    items: Vec<MISSION_ITEM_INT_DATA>,
+}
+
+impl Transaction for Upload {
+    type Output = ();
+    type Error = MissionError;
+
+    #[allow(deprecated)]
+    fn handle(&mut self, message: MavMessage) -> Progress<(), MissionError> {
+        if !self.plan.concerns(&message) {
+            return Progress::Pending;
+        }
+        let seq = match message {
+            MavMessage::MISSION_REQUEST_INT(request) => request.seq,
+            // deprecated, but still answered with `MISSION_ITEM_INT` as the protocol requires
+            MavMessage::MISSION_REQUEST(request) => request.seq,
+            MavMessage::MISSION_ACK(ack) => return Progress::done(ack_result(ack.mavtype)),
+            _ => return Progress::Pending,
+        };
+        match self.items.get(usize::from(seq)) {
+            Some(item) => Progress::Send(self.plan.item(seq, item)),
+            None => Progress::Pending,
+        }
+    }
+}
+
+struct Download {
+    plan: Plan,
+    count: Option<u16>,
+    items: Vec<MISSION_ITEM_INT_DATA>,
+}
+
+impl Download {
+    fn next(&mut self, count: u16) -> Progress<Vec<MISSION_ITEM_INT_DATA>, MissionError> {
+        // `items` is never longer than `count`
+        let received = self.items.len() as u16;
+        if received < count {
+            return Progress::Send(self.plan.request(received));
+        }
+        Progress::Done {
+            reply: Some(self.plan.ack(MavMissionResult::MAV_MISSION_ACCEPTED)),
+            result: Ok(std::mem::take(&mut self.items)),
+        }
+    }
+}
+
+impl Transaction for Download {
+    type Output = Vec<MISSION_ITEM_INT_DATA>;
+    type Error = MissionError;
+
+    fn handle(&mut self, message: MavMessage) -> Progress<Self::Output, MissionError> {
+        if !self.plan.concerns(&message) {
+            return Progress::Pending;
+        }
+        match (message, self.count) {
+            (MavMessage::MISSION_COUNT(data), None) => {
+                self.count = Some(data.count);
+                self.next(data.count)
+            }
+            (MavMessage::MISSION_ITEM_INT(item), Some(count))
+                if usize::from(item.seq) == self.items.len() =>
+            {
+                self.items.push(item);
+                self.next(count)
+            }
+            // the target may refuse to send a plan, e.g. because of an unsupported mission type
+            (MavMessage::MISSION_ACK(ack), _) => match ack_result(ack.mavtype) {
+                Ok(()) => Progress::Pending,
+                Err(e) => Progress::done(Err(e)),
+            },
+            _ => Progress::Pending,
+        }
+    }
+}
+
+struct Clear {
+    plan: Plan,
+}
+
+impl Transaction for Clear {
+    type Output = ();
+    type Error = MissionError;
+
+    fn handle(&mut self, message: MavMessage) -> Progress<(), MissionError> {
+        if !self.plan.concerns(&message) {
+            return Progress::Pending;
+        }
+        match message {
+            MavMessage::MISSION_ACK(ack) => Progress::done(ack_result(ack.mavtype)),
+            _ => Progress::Pending,
+        }
+    }
+}
+
+fn count_of(items: &[MISSION_ITEM_INT_DATA]) -> Result<u16, MissionError> {
+    // the protocol can not address more items, no system has room for them anyway
+    u16::try_from(items.len())
+        .map_err(|_| MissionError::Rejected(MavMissionResult::MAV_MISSION_NO_SPACE))
+}
+
+/// Client for the mission protocol of a target system
+pub struct MissionClient<'a, M: Message, C: MavConnection<M> + ?Sized = Connection<M>> {
+    client: Client<'a, M, C>,
+}
+
+impl<'a, M: Message, C: MavConnection<M> + ?Sized> MissionClient<'a, M, C> {
+    /// Create a client for the plans of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: Client::new(connection, target_system, target_component),
+        }
+    }
+
+    /// Sets the system and component ID the requests are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for a response before the last message is sent again, 1.5 s by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before an operation fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// Replace the plan of the given type with `items`
+    ///
+    /// The sequence numbers and targets of the items are set by the client.
+    ///
+    /// # Errors
+    ///
+    /// See [`MissionError`]
+    pub fn upload(
+        &self,
+        mission_type: MavMissionType,
+        items: &[MISSION_ITEM_INT_DATA],
+    ) -> Result<(), MissionError> {
+        let plan = self.plan(mission_type)?;
+        let upload = Upload {
+            plan,
+            items: items.to_vec(),
+        };
+        self.client.run(upload, plan.count(count_of(items)?))
+    }
+
+    /// Read the plan of the given type
+    ///
+    /// # Errors
+    ///
+    /// See [`MissionError`]
+    pub fn download(
+        &self,
+        mission_type: MavMissionType,
+    ) -> Result<Vec<MISSION_ITEM_INT_DATA>, MissionError> {
+        let plan = self.plan(mission_type)?;
+        let download = Download {
+            plan,
+            count: None,
+            items: Vec::new(),
+        };
+        self.client.run(download, plan.request_list())
+    }
+
+    /// Remove the plan of the given type, or all plans with `MAV_MISSION_TYPE_ALL`
+    ///
+    /// # Errors
+    ///
+    /// See [`MissionError`]
+    pub fn clear(&self, mission_type: MavMissionType) -> Result<(), MissionError> {
+        let plan = self.plan(mission_type)?;
+        self.client.run(Clear { plan }, plan.clear_all())
+    }
+
+    fn plan(&self, mission_type: MavMissionType) -> Result<Plan, MissionError> {
+        let settings = &self.client.settings;
+        Plan::new(
+            settings.target_system,
+            settings.target_component,
+            mission_type,
+        )
+    }
+}
+
+/// Client for the mission protocol of a target system on an async connection
+///
+/// This is the `async` version of [`MissionClient`].
+#[cfg(feature = "tokio-1")]
+pub struct AsyncMissionClient<
+    'a,
+    M: Message + Sync + Send,
+    C: AsyncMavConnection<M> + ?Sized = dyn AsyncMavConnection<M> + Sync + Send,
+> {
+    client: AsyncClient<'a, M, C>,
+}
+
+#[cfg(feature = "tokio-1")]
+impl<'a, M: Message + Sync + Send, C: AsyncMavConnection<M> + ?Sized> AsyncMissionClient<'a, M, C> {
+    /// Create a client for the plans of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: AsyncClient::new(connection, target_system, target_component),
+        }
+    }
+
+    /// Sets the system and component ID the requests are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for a response before the last message is sent again, 1.5 s by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before an operation fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// Replace the plan of the given type with `items`
+    ///
+    /// The sequence numbers and targets of the items are set by the client.
+    ///
+    /// # Errors
+    ///
+    /// See [`MissionError`]
+    pub async fn upload(
+        &self,
+        mission_type: MavMissionType,
+        items: &[MISSION_ITEM_INT_DATA],
+    ) -> Result<(), MissionError> {
+        let plan = self.plan(mission_type)?;
+        let upload = Upload {
+            plan,
+            items: items.to_vec(),
+        };
+        self.client.run(upload, plan.count(count_of(items)?)).await
+    }
+
+    /// Read the plan of the given type
+    ///
+    /// # Errors
+    ///
+    /// See [`MissionError`]
+    pub async fn download(
+        &self,
+        mission_type: MavMissionType,
+    ) -> Result<Vec<MISSION_ITEM_INT_DATA>, MissionError> {
+        let plan = self.plan(mission_type)?;
+        let download = Download {
+            plan,
+            count: None,
+            items: Vec::new(),
+        };
+        self.client.run(download, plan.request_list()).await
+    }
+
+    /// Remove the plan of the given type, or all plans with `MAV_MISSION_TYPE_ALL`
+    ///
+    /// # Errors
+    ///
+    /// See [`MissionError`]
+    pub async fn clear(&self, mission_type: MavMissionType) -> Result<(), MissionError> {
+        let plan = self.plan(mission_type)?;
+        self.client.run(Clear { plan }, plan.clear_all()).await
+    }
+
+    fn plan(&self, mission_type: MavMissionType) -> Result<Plan, MissionError> {
+        let settings = &self.client.settings;
+        Plan::new(
+            settings.target_system,
+            settings.target_component,
+            mission_type,
+        )
+    }
+}
@@ -0,0 +1,575 @@
+//! Client for the [parameter protocol](https://mavlink.io/en/services/parameter.html)
+//!
+//! The [`ParamManager`] reads and writes the parameters of a component and keeps the values it
+//! has seen in a cache keyed by the parameter id. The [`AsyncParamManager`] does the same on an
+//! async connection.
+//!
+//! `PARAM_VALUE` transports every value in a `float` field. Depending on the autopilot, integers
+//! are either stored in its bytes ([`ParamEncoding::Bytewise`], e.g. PX4) or converted to a float
+//! ([`ParamEncoding::CCast`], e.g. ArduPilot), the encoding of the target has to be configured
+//! with [`ParamManager::with_encoding`]. 64 bit parameters can not be transferred losslessly in
+//! either encoding and are left out of the cache.
+//!
+//! ```ignore
+//! let connection = mavlink::connect::<MavMessage>("udpout:127.0.0.1:14550")?;
+//! let mut params = ParamManager::new(&connection, 1, 1).with_encoding(ParamEncoding::CCast);
+//! params.fetch_all()?;
+//! params.set("SYSID_THISMAV", ParamValue::F32(2.0))?;
+//! ```
+
+use core::fmt::{Display, Formatter};
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Duration;
+
+use crate::common::{
+    MavMessage, MavParamType, PARAM_REQUEST_LIST_DATA, PARAM_REQUEST_READ_DATA, PARAM_SET_DATA,
+    PARAM_VALUE_DATA,
+};
+use crate::error::{MessageReadError, MessageWriteError};
+use crate::service::{Client, Progress, ServiceError, Transaction};
+use crate::types::CharArray;
+use crate::{Connection, MavConnection, MavHeader, Message};
+
+#[cfg(feature = "tokio-1")]
+use crate::{service::AsyncClient, AsyncMavConnection};
+
+/// Index of a `PARAM_VALUE` that is not part of a list transfer, e.g. the response to a set
+const NO_INDEX: u16 = u16::MAX;
+
+/// How integer parameters are stored in the `float` field of the parameter messages
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParamEncoding {
+    /// The bytes of the integer are copied into the float
+    #[default]
+    Bytewise,
+    /// The integer is converted to a float, large values lose precision
+    CCast,
+}
+
+/// Value of a parameter, tagged with its type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamValue {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    F32(f32),
+}
+
+impl ParamValue {
+    /// The `MAV_PARAM_TYPE` of the value
+    pub fn param_type(&self) -> MavParamType {
+        match self {
+            Self::U8(_) => MavParamType::MAV_PARAM_TYPE_UINT8,
+            Self::I8(_) => MavParamType::MAV_PARAM_TYPE_INT8,
+            Self::U16(_) => MavParamType::MAV_PARAM_TYPE_UINT16,
+            Self::I16(_) => MavParamType::MAV_PARAM_TYPE_INT16,
+            Self::U32(_) => MavParamType::MAV_PARAM_TYPE_UINT32,
+            Self::I32(_) => MavParamType::MAV_PARAM_TYPE_INT32,
+            Self::F32(_) => MavParamType::MAV_PARAM_TYPE_REAL32,
+        }
+    }
+
+    /// Decode the `param_value` field of a parameter message
+    ///
+    /// Returns `None` for the 64 bit types.
+    pub fn decode(value: f32, param_type: MavParamType, encoding: ParamEncoding) -> Option<Self> {
+        let bytes = value.to_le_bytes();
+        let value = match (encoding, param_type) {
+            (_, MavParamType::MAV_PARAM_TYPE_REAL32) => Self::F32(value),
+            (ParamEncoding::Bytewise, MavParamType::MAV_PARAM_TYPE_UINT8) => Self::U8(bytes[0]),
+            (ParamEncoding::Bytewise, MavParamType::MAV_PARAM_TYPE_INT8) => {
+                Self::I8(i8::from_le_bytes([bytes[0]]))
+            }
+            (ParamEncoding::Bytewise, MavParamType::MAV_PARAM_TYPE_UINT16) => {
+                Self::U16(u16::from_le_bytes([bytes[0], bytes[1]]))
+            }
+            (ParamEncoding::Bytewise, MavParamType::MAV_PARAM_TYPE_INT16) => {
+                Self::I16(i16::from_le_bytes([bytes[0], bytes[1]]))
+            }
+            (ParamEncoding::Bytewise, MavParamType::MAV_PARAM_TYPE_UINT32) => {
+                Self::U32(u32::from_le_bytes(bytes))
+            }
+            (ParamEncoding::Bytewise, MavParamType::MAV_PARAM_TYPE_INT32) => {
+                Self::I32(i32::from_le_bytes(bytes))
+            }
+            (ParamEncoding::CCast, MavParamType::MAV_PARAM_TYPE_UINT8) => Self::U8(value as u8),
+            (ParamEncoding::CCast, MavParamType::MAV_PARAM_TYPE_INT8) => Self::I8(value as i8),
+            (ParamEncoding::CCast, MavParamType::MAV_PARAM_TYPE_UINT16) => Self::U16(value as u16),
+            (ParamEncoding::CCast, MavParamType::MAV_PARAM_TYPE_INT16) => Self::I16(value as i16),
+            (ParamEncoding::CCast, MavParamType::MAV_PARAM_TYPE_UINT32) => Self::U32(value as u32),
+            (ParamEncoding::CCast, MavParamType::MAV_PARAM_TYPE_INT32) => Self::I32(value as i32),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Encode the value for the `param_value` field of a parameter message
+    pub fn encode(&self, encoding: ParamEncoding) -> f32 {
+        let bytes = match (encoding, *self) {
+            (_, Self::F32(value)) => return value,
+            (ParamEncoding::CCast, value) => return value.as_f64() as f32,
+            (ParamEncoding::Bytewise, Self::U8(value)) => [value, 0, 0, 0],
+            (ParamEncoding::Bytewise, Self::I8(value)) => [value.to_le_bytes()[0], 0, 0, 0],
+            (ParamEncoding::Bytewise, Self::U16(value)) => {
+                let [low, high] = value.to_le_bytes();
+                [low, high, 0, 0]
+            }
+            (ParamEncoding::Bytewise, Self::I16(value)) => {
+                let [low, high] = value.to_le_bytes();
+                [low, high, 0, 0]
+            }
+            (ParamEncoding::Bytewise, Self::U32(value)) => value.to_le_bytes(),
+            (ParamEncoding::Bytewise, Self::I32(value)) => value.to_le_bytes(),
+        };
+        f32::from_le_bytes(bytes)
+    }
+
+    /// The value as a float, this is lossless for all types
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Self::U8(value) => value.into(),
+            Self::I8(value) => value.into(),
+            Self::U16(value) => value.into(),
+            Self::I16(value) => value.into(),
+            Self::U32(value) => value.into(),
+            Self::I32(value) => value.into(),
+            Self::F32(value) => value.into(),
+        }
+    }
+}
+
+/// Error of a parameter protocol operation
+#[derive(Debug)]
+pub enum ParamError {
+    /// Receiving from the connection failed
+    Read(MessageReadError),
+    /// Sending on the connection failed
+    Write(MessageWriteError),
+    /// The target did not respond in time, even after all retransmissions
+    Timeout,
+    /// The target kept a different value than the one set, the value it reported is included
+    Rejected(ParamValue),
+    /// The parameter has a 64 bit type that can not be represented by [`ParamValue`]
+    UnsupportedType(MavParamType),
+}
+
+impl Display for ParamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "{e}"),
+            Self::Write(e) => write!(f, "{e}"),
+            Self::Timeout => write!(f, "Parameter transfer timed out"),
+            Self::Rejected(value) => write!(f, "Parameter set rejected, value is {value:?}"),
+            Self::UnsupportedType(param_type) => {
+                write!(f, "Parameter type {param_type:?} is not supported")
+            }
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+impl From<MessageReadError> for ParamError {
+    fn from(e: MessageReadError) -> Self {
+        Self::Read(e)
+    }
+}
+
+impl From<MessageWriteError> for ParamError {
+    fn from(e: MessageWriteError) -> Self {
+        Self::Write(e)
+    }
+}
+
+impl ServiceError for ParamError {
+    fn timeout() -> Self {
+        Self::Timeout
+    }
+}
+
+/// Values received from the target
+#[derive(Default)]
+struct Cache {
+    encoding: ParamEncoding,
+    values: HashMap<CharArray<16>, ParamValue>,
+}
+
+impl Cache {
+    /// Decode a received value and store it if it has a supported type
+    fn store(&mut self, data: &PARAM_VALUE_DATA) -> Result<ParamValue, ParamError> {
+        let value = ParamValue::decode(data.param_value, data.param_type, self.encoding)
+            .ok_or(ParamError::UnsupportedType(data.param_type))?;
+        self.values.insert(data.param_id, value);
+        Ok(value)
+    }
+}
+
+/// The component whose parameters are managed, builds the requests
+#[derive(Clone, Copy)]
+struct Target {
+    system: u8,
+    component: u8,
+}
+
+impl Target {
+    fn request_list(&self) -> MavMessage {
+        MavMessage::PARAM_REQUEST_LIST(PARAM_REQUEST_LIST_DATA {
+            target_system: self.system,
+            target_component: self.component,
+        })
+    }
+
+    fn request_read(&self, param_index: i16, param_id: CharArray<16>) -> MavMessage {
+        MavMessage::PARAM_REQUEST_READ(PARAM_REQUEST_READ_DATA {
+            param_index,
+            target_system: self.system,
+            target_component: self.component,
+            param_id,
+        })
+    }
+
+    fn set(
+        &self,
+        param_id: CharArray<16>,
+        value: ParamValue,
+        encoding: ParamEncoding,
+    ) -> MavMessage {
+        MavMessage::PARAM_SET(PARAM_SET_DATA {
+            param_value: value.encode(encoding),
+            target_system: self.system,
+            target_component: self.component,
+            param_id,
+            param_type: value.param_type(),
+        })
+    }
+}
+
+/// Reads the full list, missing indices are requested one by one once the stream stops
+struct FetchAll<'c> {
+    target: Target,
+    cache: &'c mut Cache,
+    received: Option<Vec<bool>>,
+    filling_gaps: bool,
+}
+
+impl FetchAll<'_> {
+    fn missing(&self) -> Option<u16> {
+        let received = self.received.as_ref()?;
+        // indices are limited to `param_count`, a `u16`
+        received.iter().position(|r| !r).map(|i| i as u16)
+    }
+
+    /// `None` if the index can not be requested, it is sent as `i16`
+    fn request_missing(&self, index: u16) -> Option<MavMessage> {
+        let index = i16::try_from(index).ok()?;
+        Some(self.target.request_read(index, CharArray::new([0; 16])))
+    }
+}
+
+impl Transaction for FetchAll<'_> {
+    type Output = ();
+    type Error = ParamError;
+
+    fn handle(&mut self, message: MavMessage) -> Progress<(), ParamError> {
+        let MavMessage::PARAM_VALUE(data) = message else {
+            return Progress::Pending;
+        };
+        // unsupported types are part of the list, they are just not cached
+        let _ = self.cache.store(&data);
+        if data.param_index == NO_INDEX {
+            return Progress::Pending;
+        }
+
+        let received = self
+            .received
+            .get_or_insert_with(|| vec![false; usize::from(data.param_count)]);
+        if let Some(received) = received.get_mut(usize::from(data.param_index)) {
+            *received = true;
+        }
+        match self.missing() {
+            None => Progress::done(Ok(())),
+            Some(index) if self.filling_gaps => match self.request_missing(index) {
+                Some(request) => Progress::Send(request),
+                None => Progress::Continue,
+            },
+            Some(_) => Progress::Continue,
+        }
+    }
+
+    fn retransmit(&mut self, last: &MavMessage) -> Option<MavMessage> {
+        match self.missing() {
+            Some(index) => {
+                self.filling_gaps = true;
+                // the remaining gaps can not be requested, wait for the list to be resent
+                self.request_missing(index)
+            }
+            // nothing received yet
+            None => Some(last.clone()),
+        }
+    }
+}
+
+/// Reads a single parameter, or confirms a set by the echoed value
+struct Fetch<'c> {
+    cache: &'c mut Cache,
+    param_id: CharArray<16>,
+    /// The encoded value sent with `PARAM_SET`
+    expected: Option<f32>,
+}
+
+impl Transaction for Fetch<'_> {
+    type Output = ParamValue;
+    type Error = ParamError;
+
+    fn handle(&mut self, message: MavMessage) -> Progress<ParamValue, ParamError> {
+        match message {
+            MavMessage::PARAM_VALUE(data) if data.param_id == self.param_id => {
+                let result = self.cache.store(&data).and_then(|value| {
+                    // the target converts the sent value to the type of the parameter,
+                    // compare with the sent value decoded the same way
+                    let sent = self.expected.map(|expected| {
+                        ParamValue::decode(expected, data.param_type, self.cache.encoding)
+                    });
+                    match sent {
+                        Some(sent) if sent != Some(value) => Err(ParamError::Rejected(value)),
+                        _ => Ok(value),
+                    }
+                });
+                Progress::done(result)
+            }
+            MavMessage::PARAM_VALUE(data) => {
+                let _ = self.cache.store(&data);
+                Progress::Pending
+            }
+            _ => Progress::Pending,
+        }
+    }
+}
+
+/// Manages the parameters of a target component
+pub struct ParamManager<'a, M: Message, C: MavConnection<M> + ?Sized = Connection<M>> {
+    client: Client<'a, M, C>,
+    cache: Cache,
+}
+
+impl<'a, M: Message, C: MavConnection<M> + ?Sized> ParamManager<'a, M, C> {
+    /// Create a manager for the parameters of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: Client::new(connection, target_system, target_component),
+            cache: Cache::default(),
+        }
+    }
+
+    /// Sets the system and component ID the requests are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for a response before a request is sent again, 1.5 s by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before an operation fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// Sets the encoding of integer parameters used by the target, bytewise by default
+    pub fn with_encoding(mut self, encoding: ParamEncoding) -> Self {
+        self.cache.encoding = encoding;
+        self
+    }
+
+    /// Read all parameters of the target into the cache
+    ///
+    /// # Errors
+    ///
+    /// See [`ParamError`]
+    pub fn fetch_all(&mut self) -> Result<&HashMap<CharArray<16>, ParamValue>, ParamError> {
+        let target = self.target();
+        let fetch = FetchAll {
+            target,
+            cache: &mut self.cache,
+            received: None,
+            filling_gaps: false,
+        };
+        self.client.run(fetch, target.request_list())?;
+        Ok(&self.cache.values)
+    }
+
+    /// Read a single parameter from the target
+    ///
+    /// # Errors
+    ///
+    /// See [`ParamError`]
+    pub fn fetch(&mut self, name: &str) -> Result<ParamValue, ParamError> {
+        let param_id = CharArray::from(name);
+        let request = self.target().request_read(-1, param_id);
+        let fetch = Fetch {
+            cache: &mut self.cache,
+            param_id,
+            expected: None,
+        };
+        self.client.run(fetch, request)
+    }
+
+    /// Write a parameter and wait until the target confirms the new value
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParamError::Rejected`] if the target reports a different value. The value is
+    /// compared after converting it to the type reported by the target, like the target does.
+    pub fn set(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        let param_id = CharArray::from(name);
+        let request = self.target().set(param_id, value, self.cache.encoding);
+        let fetch = Fetch {
+            cache: &mut self.cache,
+            param_id,
+            expected: Some(value.encode(self.cache.encoding)),
+        };
+        self.client.run(fetch, request)?;
+        Ok(())
+    }
+
+    /// The cached value of a parameter
+    pub fn get(&self, name: &str) -> Option<ParamValue> {
+        self.cache.values.get(&CharArray::from(name)).copied()
+    }
+
+    /// All cached parameters
+    pub fn params(&self) -> &HashMap<CharArray<16>, ParamValue> {
+        &self.cache.values
+    }
+
+    fn target(&self) -> Target {
+        Target {
+            system: self.client.settings.target_system,
+            component: self.client.settings.target_component,
+        }
+    }
+}
+
+/// Manages the parameters of a target component on an async connection
+///
+/// This is the `async` version of [`ParamManager`].
+#[cfg(feature = "tokio-1")]
+pub struct AsyncParamManager<
+    'a,
+    M: Message + Sync + Send,
+    C: AsyncMavConnection<M> + ?Sized = dyn AsyncMavConnection<M> + Sync + Send,
+> {
+    client: AsyncClient<'a, M, C>,
+    cache: Cache,
+}
+
+#[cfg(feature = "tokio-1")]
+impl<'a, M: Message + Sync + Send, C: AsyncMavConnection<M> + ?Sized> AsyncParamManager<'a, M, C> {
+    /// Create a manager for the parameters of the given target
+    pub fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            client: AsyncClient::new(connection, target_system, target_component),
+            cache: Cache::default(),
+        }
+    }
+
+    /// Sets the system and component ID the requests are sent with, the sequence is ignored
+    pub fn with_header(mut self, header: MavHeader) -> Self {
+        self.client.settings.header = header;
+        self
+    }
+
+    /// Sets the time waited for a response before a request is sent again, 1.5 s by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.settings.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retransmissions before an operation fails, 5 by default
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.client.settings.retries = retries;
+        self
+    }
+
+    /// Sets the encoding of integer parameters used by the target, bytewise by default
+    pub fn with_encoding(mut self, encoding: ParamEncoding) -> Self {
+        self.cache.encoding = encoding;
+        self
+    }
+
+    /// Read all parameters of the target into the cache
+    ///
+    /// # Errors
+    ///
+    /// See [`ParamError`]
+    pub async fn fetch_all(&mut self) -> Result<&HashMap<CharArray<16>, ParamValue>, ParamError> {
+        let target = self.target();
+        let fetch = FetchAll {
+            target,
+            cache: &mut self.cache,
+            received: None,
+            filling_gaps: false,
+        };
+        self.client.run(fetch, target.request_list()).await?;
+        Ok(&self.cache.values)
+    }
+
+    /// Read a single parameter from the target
+    ///
+    /// # Errors
+    ///
+    /// See [`ParamError`]
+    pub async fn fetch(&mut self, name: &str) -> Result<ParamValue, ParamError> {
+        let param_id = CharArray::from(name);
+        let request = self.target().request_read(-1, param_id);
+        let fetch = Fetch {
+            cache: &mut self.cache,
+            param_id,
+            expected: None,
+        };
+        self.client.run(fetch, request).await
+    }
+
+    /// Write a parameter and wait until the target confirms the new value
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParamError::Rejected`] if the target reports a different value. The value is
+    /// compared after converting it to the type reported by the target, like the target does.
+    pub async fn set(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        let param_id = CharArray::from(name);
+        let request = self.target().set(param_id, value, self.cache.encoding);
+        let fetch = Fetch {
+            cache: &mut self.cache,
+            param_id,
+            expected: Some(value.encode(self.cache.encoding)),
+        };
+        self.client.run(fetch, request).await?;
+        Ok(())
+    }
+
+    /// The cached value of a parameter
+    pub fn get(&self, name: &str) -> Option<ParamValue> {
+        self.cache.values.get(&CharArray::from(name)).copied()
+    }
+
+    /// All cached parameters
+    pub fn params(&self) -> &HashMap<CharArray<16>, ParamValue> {
+        &self.cache.values
+    }
+
+    fn target(&self) -> Target {
+        Target {
+            system: self.client.settings.target_system,
+            component: self.client.settings.target_component,
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Request and response handling shared by the microservice clients
+//!
+//! Every operation of a microservice is a [`Transaction`], a state machine that is fed the
+//! messages received from the target and answers with the messages to send next. The [`Client`]
+//! and [`AsyncClient`] drive transactions over a connection and take care of addressing,
+//! timeouts and retransmissions, so the protocol logic is shared by the blocking and async
+//! clients.
+
+use std::io;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use crate::common::MavMessage;
+use crate::convert::convert;
+use crate::error::{MessageReadError, MessageWriteError, ParserError};
+use crate::{MavConnection, MavHeader, Message};
+
+#[cfg(feature = "tokio-1")]
+use crate::AsyncMavConnection;
+
+/// Time waited for a response before the last message is sent again by default
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1500);
+/// Number of retransmissions before an operation fails by default
+pub(crate) const DEFAULT_RETRIES: u32 = 5;
+
+/// Errors of a microservice client that are not specific to the microservice
+pub(crate) trait ServiceError: From<MessageReadError> + From<MessageWriteError> {
+    /// The target did not respond in time, even after all retransmissions
+    fn timeout() -> Self;
+}
+
+/// How a transaction continues after handling a message
+pub(crate) enum Progress<T, E> {
+    /// Keep waiting, the message was not relevant
+    Pending,
+    /// Keep waiting, the message was relevant so the retransmission timer restarts
+    Continue,
+    /// Send the next request, the retransmission timer restarts
+    Send(MavMessage),
+    /// The transaction is finished, `reply` is sent before returning the result
+    Done {
+        reply: Option<MavMessage>,
+        result: Result<T, E>,
+    },
+}
+
+impl<T, E> Progress<T, E> {
+    pub(crate) fn done(result: Result<T, E>) -> Self {
+        Self::Done {
+            reply: None,
+            result,
+        }
+    }
+}
+
+/// A single operation of a microservice
+pub(crate) trait Transaction {
+    type Output;
+    type Error: ServiceError;
+
+    /// Handle a message received from the target
+    fn handle(&mut self, message: MavMessage) -> Progress<Self::Output, Self::Error>;
+
+    /// The message to send when the target stopped responding, `last` again by default
+    ///
+    /// Returning `None` keeps waiting without sending anything, the attempt still counts as a
+    /// retransmission.
+    fn retransmit(&mut self, last: &MavMessage) -> Option<MavMessage> {
+        Some(last.clone())
+    }
+}
+
+/// Addressing and retransmission settings of a client
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Settings {
+    pub(crate) header: MavHeader,
+    pub(crate) target_system: u8,
+    pub(crate) target_component: u8,
+    pub(crate) timeout: Duration,
+    pub(crate) retries: u32,
+}
+
+impl Settings {
+    fn new(target_system: u8, target_component: u8) -> Self {
+        Self {
+            header: MavHeader::default(),
+            target_system,
+            target_component,
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+        }
+    }
+
+    /// Pass a received message to the transaction if it was sent by the target to us
+    fn handle<M: Message, T: Transaction>(
+        &self,
+        transaction: &mut T,
+        header: &MavHeader,
+        message: &M,
+    ) -> Progress<T::Output, T::Error> {
+        let from_target = header.system_id == self.target_system
+            && (self.target_component == 0 || header.component_id == self.target_component);
+        if !from_target {
+            return Progress::Pending;
+        }
+        let Ok(message) = convert::<_, MavMessage>(message) else {
+            return Progress::Pending;
+        };
+        if matches!(
+            message.target_system_id(),
+            Some(id) if id != 0 && id != self.header.system_id
+        ) {
+            return Progress::Pending;
+        }
+        transaction.handle(message)
+    }
+}
+
+/// Drives transactions over a blocking connection
+pub(crate) struct Client<'a, M: Message, C: MavConnection<M> + ?Sized> {
+    connection: &'a C,
+    pub(crate) settings: Settings,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<'a, M: Message, C: MavConnection<M> + ?Sized> Client<'a, M, C> {
+    pub(crate) fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            connection,
+            settings: Settings::new(target_system, target_component),
+            _message: PhantomData,
+        }
+    }
+
+    pub(crate) fn send(&self, message: &MavMessage) -> Result<(), MessageWriteError> {
+        let message = convert::<_, M>(message).map_err(invalid_input)?;
+        self.connection.send(&self.settings.header, &message)?;
+        Ok(())
+    }
+
+    /// Send `request` and feed the responses to `transaction` until it is done
+    ///
+    /// A message is sent again whenever no progress is made within the timeout.
+    pub(crate) fn run<T: Transaction>(
+        &self,
+        mut transaction: T,
+        request: MavMessage,
+    ) -> Result<T::Output, T::Error> {
+        let mut last = request;
+        self.send(&last)?;
+        let mut deadline = Instant::now() + self.settings.timeout;
+        let mut retries = 0;
+
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.connection.recv_timeout(timeout) {
+                Ok((header, message)) => {
+                    match self.settings.handle(&mut transaction, &header, &message) {
+                        Progress::Pending => {}
+                        Progress::Continue => {
+                            deadline = Instant::now() + self.settings.timeout;
+                            retries = 0;
+                        }
+                        Progress::Send(message) => {
+                            self.send(&message)?;
+                            last = message;
+                            deadline = Instant::now() + self.settings.timeout;
+                            retries = 0;
+                        }
+                        Progress::Done { reply, result } => {
+                            if let Some(reply) = reply {
+                                self.send(&reply)?;
+                            }
+                            return result;
+                        }
+                    }
+                }
+                Err(MessageReadError::Io(e)) if is_no_data(&e) => {}
+                Err(MessageReadError::Parse(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            if Instant::now() >= deadline {
+                if retries >= self.settings.retries {
+                    return Err(T::Error::timeout());
+                }
+                retries += 1;
+                if let Some(message) = transaction.retransmit(&last) {
+                    self.send(&message)?;
+                    last = message;
+                }
+                deadline = Instant::now() + self.settings.timeout;
+            }
+        }
+    }
+}
+
+/// Drives transactions over an async connection
+#[cfg(feature = "tokio-1")]
+pub(crate) struct AsyncClient<'a, M: Message + Sync + Send, C: AsyncMavConnection<M> + ?Sized> {
+    connection: &'a C,
+    pub(crate) settings: Settings,
+    _message: PhantomData<fn() -> M>,
+}
+
+#[cfg(feature = "tokio-1")]
+impl<'a, M: Message + Sync + Send, C: AsyncMavConnection<M> + ?Sized> AsyncClient<'a, M, C> {
+    pub(crate) fn new(connection: &'a C, target_system: u8, target_component: u8) -> Self {
+        Self {
+            connection,
+            settings: Settings::new(target_system, target_component),
+            _message: PhantomData,
+        }
+    }
+
+    pub(crate) async fn send(&self, message: &MavMessage) -> Result<(), MessageWriteError> {
+        let message = convert::<_, M>(message).map_err(invalid_input)?;
+        self.connection
+            .send(&self.settings.header, &message)
+            .await?;
+        Ok(())
+    }
+
+    /// Send `request` and feed the responses to `transaction` until it is done
+    ///
+    /// A message is sent again whenever no progress is made within the timeout.
+    ///
+    /// Receiving is not cancel safe, dropping a receive in progress may lose the part of a frame
+    /// it read already. So a receive that is still waiting when the timeout elapses is resumed
+    /// afterwards instead of being started again, only the one pending when the transaction
+    /// fails is dropped.
+    pub(crate) async fn run<T: Transaction>(
+        &self,
+        mut transaction: T,
+        request: MavMessage,
+    ) -> Result<T::Output, T::Error> {
+        let mut last = request;
+        self.send(&last).await?;
+        let mut deadline = tokio::time::Instant::now() + self.settings.timeout;
+        let mut retries = 0;
+        let mut recv = None;
+
+        loop {
+            let received = tokio::time::timeout_at(
+                deadline,
+                recv.get_or_insert_with(|| self.connection.recv()),
+            )
+            .await;
+            if received.is_ok() {
+                recv = None;
+            }
+            match received {
+                Ok(Ok((header, message))) => {
+                    match self.settings.handle(&mut transaction, &header, &message) {
+                        Progress::Pending => {}
+                        Progress::Continue => {
+                            deadline = tokio::time::Instant::now() + self.settings.timeout;
+                            retries = 0;
+                        }
+                        Progress::Send(message) => {
+                            self.send(&message).await?;
+                            last = message;
+                            deadline = tokio::time::Instant::now() + self.settings.timeout;
+                            retries = 0;
+                        }
+                        Progress::Done { reply, result } => {
+                            if let Some(reply) = reply {
+                                self.send(&reply).await?;
+                            }
+                            return result;
+                        }
+                    }
+                }
+                Ok(Err(MessageReadError::Io(e))) if is_no_data(&e) => {}
+                Ok(Err(MessageReadError::Parse(_))) => {}
+                Ok(Err(e)) => return Err(e.into()),
+                Err(_elapsed) => {
+                    if retries >= self.settings.retries {
+                        return Err(T::Error::timeout());
+                    }
+                    retries += 1;
+                    if let Some(message) = transaction.retransmit(&last) {
+                        self.send(&message).await?;
+                        last = message;
+                    }
+                    deadline = tokio::time::Instant::now() + self.settings.timeout;
+                }
+            }
+        }
+    }
+}
+
+fn is_no_data(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted
+    )
+}
+
+fn invalid_input(error: ParserError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
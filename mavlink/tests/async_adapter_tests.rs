@@ -0,0 +1,118 @@
+mod test_shared;
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_adapters {
+    use std::io;
+
+    use futures::{SinkExt, StreamExt};
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::{AsyncMavConnection, AsyncMemConnection};
+
+    fn split(
+        connection: AsyncMemConnection,
+    ) -> (
+        mavlink::AsyncMavReceiver<MavMessage>,
+        mavlink::AsyncMavSender<MavMessage>,
+    ) {
+        let connection: Box<dyn AsyncMavConnection<MavMessage> + Sync + Send> =
+            Box::new(connection);
+        connection.split()
+    }
+
+    #[tokio::test]
+    pub async fn test_stream_and_sink() {
+        let (vehicle, gcs) = AsyncMemConnection::pair();
+        let (_, vehicle_sender) = split(vehicle);
+        let (gcs_receiver, _) = split(gcs);
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+
+        let mut sink = vehicle_sender.into_sink();
+        for _ in 0..3 {
+            sink.send((COMMON_MSG_HEADER, heartbeat.clone()))
+                .await
+                .unwrap();
+        }
+
+        let received: Vec<_> = gcs_receiver
+            .into_stream()
+            .take(3)
+            .map(|result| result.unwrap().0.sequence)
+            .collect()
+            .await;
+        assert_eq!(received, [0, 1, 2]);
+    }
+
+    #[tokio::test]
+    pub async fn test_stream_ends_on_io_error() {
+        let (vehicle, gcs) = AsyncMemConnection::pair();
+        let (gcs_receiver, _) = split(gcs);
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+
+        AsyncMavConnection::<MavMessage>::send_default(&vehicle, &heartbeat)
+            .await
+            .unwrap();
+        drop(vehicle);
+
+        let mut stream = gcs_receiver.into_stream();
+        assert_eq!(stream.next().await.unwrap().unwrap().1, heartbeat);
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+        assert!(stream.next().await.is_none());
+    }
+}
+
+#[cfg(all(feature = "tokio-codec", feature = "common"))]
+mod test_codec {
+    use futures::StreamExt;
+    use tokio_util::bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder, FramedRead};
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER, HEARTBEAT_V1, HEARTBEAT_V2};
+    use mavlink::codec::MavCodec;
+    use mavlink::common::MavMessage;
+    use mavlink::{MavFrame, MavlinkVersion, ReadVersion};
+
+    #[test]
+    pub fn test_codec_round_trip() {
+        let mut codec = MavCodec::<MavMessage>::default();
+        let frame = MavFrame {
+            header: COMMON_MSG_HEADER,
+            msg: MavMessage::HEARTBEAT(get_heartbeat_msg()),
+            protocol_version: MavlinkVersion::V2,
+        };
+        let mut buf = BytesMut::from(&b"junk"[..]);
+        codec.encode(frame.clone(), &mut buf).unwrap();
+        assert_eq!(&buf[4..], HEARTBEAT_V2);
+
+        // the frame is only decoded once it is complete
+        let mut partial = buf.split_to(10);
+        assert!(codec.decode(&mut partial).unwrap().is_none());
+        partial.unsplit(buf);
+        let decoded = codec.decode(&mut partial).unwrap().unwrap();
+        assert_eq!(decoded.header, frame.header);
+        assert_eq!(decoded.msg, frame.msg);
+        assert!(partial.is_empty());
+    }
+
+    #[test]
+    pub fn test_codec_discards_partial_frame_at_eof() {
+        let mut codec = MavCodec::<MavMessage>::new(ReadVersion::Any);
+        let mut buf = BytesMut::from(&HEARTBEAT_V2[..HEARTBEAT_V2.len() - 1]);
+        assert!(codec.decode_eof(&mut buf).unwrap().is_none());
+        assert!(buf.is_empty());
+    }
+
+    #[tokio::test]
+    pub async fn test_framed_read() {
+        let data = [HEARTBEAT_V1, HEARTBEAT_V2, HEARTBEAT_V1].concat();
+        let codec = MavCodec::<MavMessage>::new(ReadVersion::Single(MavlinkVersion::V2));
+        let frames: Vec<_> = FramedRead::new(&data[..], codec).collect().await;
+        assert_eq!(frames.len(), 1);
+        let frame = frames.into_iter().next().unwrap().unwrap();
+        assert_eq!(frame.protocol_version, MavlinkVersion::V2);
+        assert_eq!(frame.msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+    }
+}
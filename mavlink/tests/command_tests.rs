@@ -0,0 +1,147 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_command {
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_responder;
+    use mavlink::command::{Command, CommandClient, CommandError};
+    use mavlink::common::{MavCmd, MavFrame, MavMessage, MavResult, COMMAND_ACK_DATA};
+
+    fn ack(command: MavCmd, result: MavResult, progress: u8) -> MavMessage {
+        #[cfg(not(feature = "emit-extensions"))]
+        let _ = progress;
+        MavMessage::COMMAND_ACK(COMMAND_ACK_DATA {
+            command,
+            result,
+            #[cfg(feature = "emit-extensions")]
+            progress,
+            #[cfg(feature = "emit-extensions")]
+            result_param2: 0,
+            #[cfg(feature = "emit-extensions")]
+            target_system: 255,
+            #[cfg(feature = "emit-extensions")]
+            target_component: 0,
+        })
+    }
+
+    /// Serve commands like a vehicle:
+    /// - arming is only acknowledged once it was repeated with a confirmation
+    /// - a calibration reports its progress twice before it is accepted
+    /// - landing is denied
+    /// - every `COMMAND_INT` is accepted
+    pub fn vehicle(msg: MavMessage) -> Vec<MavMessage> {
+        match msg {
+            MavMessage::COMMAND_LONG(data) => match data.command {
+                MavCmd::MAV_CMD_COMPONENT_ARM_DISARM if data.confirmation == 0 => Vec::new(),
+                MavCmd::MAV_CMD_PREFLIGHT_CALIBRATION => vec![
+                    ack(data.command, MavResult::MAV_RESULT_IN_PROGRESS, 30),
+                    ack(data.command, MavResult::MAV_RESULT_IN_PROGRESS, 60),
+                    ack(data.command, MavResult::MAV_RESULT_ACCEPTED, 0),
+                ],
+                MavCmd::MAV_CMD_NAV_LAND => {
+                    vec![ack(data.command, MavResult::MAV_RESULT_DENIED, 0)]
+                }
+                command => vec![ack(command, MavResult::MAV_RESULT_ACCEPTED, 0)],
+            },
+            MavMessage::COMMAND_INT(data) => {
+                vec![ack(data.command, MavResult::MAV_RESULT_ACCEPTED, 0)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Test acknowledgements, confirmations and progress updates
+    #[test]
+    fn test_command_ack() {
+        spawn_responder("test_command_ack", vehicle);
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_command_ack").expect("Couldn't connect");
+        let client = CommandClient::new(&connection, 1, 1).with_timeout(Duration::from_millis(100));
+
+        client
+            .send(Command::long(
+                MavCmd::MAV_CMD_COMPONENT_ARM_DISARM,
+                [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            ))
+            .unwrap();
+
+        let mut updates = Vec::new();
+        client
+            .send_with_progress(
+                Command::long(MavCmd::MAV_CMD_PREFLIGHT_CALIBRATION, [0.0; 7]),
+                |progress| updates.push(progress),
+            )
+            .unwrap();
+        #[cfg(feature = "emit-extensions")]
+        assert_eq!(updates, [30, 60]);
+        #[cfg(not(feature = "emit-extensions"))]
+        assert_eq!(updates, [mavlink::command::PROGRESS_UNKNOWN; 2]);
+
+        let err = client
+            .send(Command::long(MavCmd::MAV_CMD_NAV_LAND, [0.0; 7]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CommandError::Rejected(MavResult::MAV_RESULT_DENIED)
+        ));
+
+        client
+            .send(Command::int(
+                MavCmd::MAV_CMD_NAV_TAKEOFF,
+                MavFrame::MAV_FRAME_GLOBAL_RELATIVE_ALT_INT,
+                [0.0; 4],
+                473_977_420,
+                85_455_940,
+                10.0,
+            ))
+            .unwrap();
+    }
+
+    /// Test that a command fails once all retransmissions are used up
+    #[test]
+    fn test_command_timeout() {
+        let _vehicle = mavlink::connect::<MavMessage>("mem:test_command_timeout").unwrap();
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_command_timeout").expect("Couldn't connect");
+        let client = CommandClient::new(&connection, 1, 1)
+            .with_timeout(Duration::from_millis(20))
+            .with_retries(2);
+
+        let err = client
+            .send(Command::long(
+                MavCmd::MAV_CMD_NAV_RETURN_TO_LAUNCH,
+                [0.0; 7],
+            ))
+            .unwrap_err();
+        assert!(matches!(err, CommandError::Timeout));
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_command {
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_async_responder;
+    use mavlink::command::{AsyncCommandClient, Command};
+    use mavlink::common::{MavCmd, MavMessage};
+
+    /// Test a command with confirmation on an async connection
+    #[tokio::test]
+    async fn test_async_command_ack() {
+        spawn_async_responder("test_async_command_ack", crate::test_command::vehicle).await;
+        let connection = mavlink::connect_async::<MavMessage>("mem:test_async_command_ack")
+            .await
+            .expect("Couldn't connect");
+        let client =
+            AsyncCommandClient::new(&*connection, 1, 1).with_timeout(Duration::from_millis(100));
+
+        client
+            .send(Command::long(
+                MavCmd::MAV_CMD_COMPONENT_ARM_DISARM,
+                [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            ))
+            .await
+            .unwrap();
+    }
+}
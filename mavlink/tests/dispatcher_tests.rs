@@ -0,0 +1,134 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_dispatcher {
+    use std::io::{self, Cursor};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::test_shared::{get_cmd_nav_takeoff_msg, get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::{MavMessage, COMMAND_INT_DATA, HEARTBEAT_DATA};
+    use mavlink::dispatcher::{Dispatcher, Filter, RecvError};
+    use mavlink::{MavConnection, MavHeader, MessageData, StreamConnection};
+
+    const OTHER_HEADER: MavHeader = MavHeader {
+        system_id: 42,
+        component_id: 84,
+        sequence: 0,
+    };
+
+    fn heartbeat() -> MavMessage {
+        MavMessage::HEARTBEAT(get_heartbeat_msg())
+    }
+
+    fn command() -> MavMessage {
+        MavMessage::COMMAND_INT(get_cmd_nav_takeoff_msg())
+    }
+
+    fn idle_dispatcher() -> Dispatcher<MavMessage> {
+        Dispatcher::new(StreamConnection::from_halves(io::empty(), io::sink()).into())
+    }
+
+    #[test]
+    fn test_filter() {
+        let all = Filter::all();
+        assert!(all.matches(&COMMON_MSG_HEADER, HEARTBEAT_DATA::ID));
+
+        let messages = Filter::all()
+            .message::<HEARTBEAT_DATA>()
+            .message_id(COMMAND_INT_DATA::ID);
+        assert!(messages.matches(&COMMON_MSG_HEADER, HEARTBEAT_DATA::ID));
+        assert!(messages.matches(&COMMON_MSG_HEADER, COMMAND_INT_DATA::ID));
+        assert!(!messages.matches(&COMMON_MSG_HEADER, 1));
+
+        let source = Filter::all().system_id(42).component_id(84);
+        assert!(source.matches(&OTHER_HEADER, HEARTBEAT_DATA::ID));
+        assert!(!source.matches(&COMMON_MSG_HEADER, HEARTBEAT_DATA::ID));
+        assert!(!Filter::all()
+            .system_id(42)
+            .component_id(1)
+            .matches(&OTHER_HEADER, HEARTBEAT_DATA::ID));
+    }
+
+    #[test]
+    fn test_dispatch_filtered() {
+        let dispatcher = idle_dispatcher();
+        let all = dispatcher.subscribe(Filter::all(), 8);
+        let heartbeats = dispatcher.subscribe(Filter::all().message::<HEARTBEAT_DATA>(), 8);
+        let other = dispatcher.subscribe(Filter::all().system_id(OTHER_HEADER.system_id), 8);
+
+        assert_eq!(dispatcher.dispatch(COMMON_MSG_HEADER, &heartbeat()), 2);
+        assert_eq!(dispatcher.dispatch(OTHER_HEADER, &command()), 2);
+
+        assert_eq!(all.try_recv(), Ok((COMMON_MSG_HEADER, heartbeat())));
+        assert_eq!(all.try_recv(), Ok((OTHER_HEADER, command())));
+        assert_eq!(heartbeats.try_recv(), Ok((COMMON_MSG_HEADER, heartbeat())));
+        assert_eq!(heartbeats.try_recv(), Err(RecvError::Empty));
+        assert_eq!(other.try_recv(), Ok((OTHER_HEADER, command())));
+        assert_eq!(
+            other.recv_timeout(Duration::from_millis(10)),
+            Err(RecvError::Empty)
+        );
+
+        // dropped subscriptions are removed once a message for them is dispatched
+        drop(other);
+        assert_eq!(dispatcher.subscriber_count(), 3);
+        dispatcher.dispatch(OTHER_HEADER, &command());
+        assert_eq!(dispatcher.subscriber_count(), 2);
+    }
+
+    #[test]
+    fn test_dispatch_lagged() {
+        let dispatcher = idle_dispatcher();
+        let slow = dispatcher.subscribe(Filter::all(), 2);
+
+        for _ in 0..5 {
+            dispatcher.dispatch(COMMON_MSG_HEADER, &heartbeat());
+        }
+
+        assert_eq!(slow.try_recv(), Err(RecvError::Lagged(3)));
+        assert!(slow.try_recv().is_ok());
+        assert!(slow.try_recv().is_ok());
+        assert_eq!(slow.try_recv(), Err(RecvError::Empty));
+    }
+
+    #[test]
+    fn test_dispatcher_run() {
+        let mut input = Vec::new();
+        for (header, msg) in [
+            (COMMON_MSG_HEADER, heartbeat()),
+            (OTHER_HEADER, command()),
+            (OTHER_HEADER, heartbeat()),
+        ] {
+            mavlink::write_versioned_msg(&mut input, mavlink::MavlinkVersion::V2, header, &msg)
+                .unwrap();
+        }
+        let connection = StreamConnection::from_halves(Cursor::new(input), io::sink());
+        let dispatcher = Arc::new(Dispatcher::<MavMessage>::new(connection.into()));
+
+        let heartbeats = dispatcher.subscribe(Filter::all().message::<HEARTBEAT_DATA>(), 8);
+        let other = dispatcher.subscribe(Filter::all().system_id(OTHER_HEADER.system_id), 8);
+        let runner = thread::spawn({
+            let dispatcher = dispatcher.clone();
+            move || dispatcher.run()
+        });
+
+        let (header, msg) = heartbeats.recv().unwrap();
+        assert_eq!(header.system_id, COMMON_MSG_HEADER.system_id);
+        assert_eq!(msg, heartbeat());
+        let (header, _) = heartbeats.recv().unwrap();
+        assert_eq!(header.system_id, OTHER_HEADER.system_id);
+        assert_eq!(other.recv().unwrap().1, command());
+        assert_eq!(other.recv().unwrap().1, heartbeat());
+
+        // the dispatcher stops at the end of the stream
+        runner.join().unwrap();
+        assert_eq!(heartbeats.recv(), Err(RecvError::Closed));
+        assert_eq!(
+            dispatcher.subscribe(Filter::all(), 1).recv(),
+            Err(RecvError::Closed)
+        );
+        assert_eq!(dispatcher.connection().link_stats().read.frames_received, 3);
+    }
+}
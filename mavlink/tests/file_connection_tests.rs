@@ -113,3 +113,98 @@ mod test_file_connections {
         );
     }
 }
+
+#[cfg(feature = "common")]
+mod test_file_follow {
+    use std::fs::{self, OpenOptions};
+    use std::io::{self, Write};
+    use std::path::Path;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::test_shared::{get_heartbeat_msg, HEARTBEAT_V2};
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::{FileConfig, FileMode};
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Append a frame in two parts, as a writer flushing a partial frame would
+    fn append_split_frame(path: &Path) {
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        file.write_all(&HEARTBEAT_V2[..5]).unwrap();
+        file.flush().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        file.write_all(&HEARTBEAT_V2[5..]).unwrap();
+    }
+
+    #[test]
+    pub fn test_file_follow() {
+        use mavlink::{Connectable, MavConnection};
+
+        let path = std::env::temp_dir().join("mavlink_follow_test.tlog");
+        fs::write(&path, HEARTBEAT_V2).unwrap();
+
+        let config = FileConfig::new(path.clone())
+            .mode(FileMode::Follow)
+            .poll_interval(POLL_INTERVAL);
+        assert_eq!(config.to_string(), format!("filefollow:{}", path.display()));
+        let connection = config.connect::<MavMessage>().unwrap();
+
+        let (_, msg) = connection.recv().unwrap();
+        assert_eq!(msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+
+        // at the end of the file there is nothing to receive yet
+        let err = connection.try_recv().unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::WouldBlock));
+
+        // or until the timeout expires
+        let start = Instant::now();
+        let err = connection.recv_timeout(POLL_INTERVAL * 5).unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::WouldBlock));
+        assert!(start.elapsed() >= POLL_INTERVAL * 5);
+
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || append_split_frame(&path))
+        };
+        let (_, msg) = connection.recv().unwrap();
+        assert_eq!(msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+        writer.join().unwrap();
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[cfg(feature = "tokio-1")]
+    #[tokio::test]
+    pub async fn test_async_file_follow() {
+        use mavlink::AsyncConnectable;
+
+        let path = std::env::temp_dir().join("mavlink_async_follow_test.tlog");
+        fs::write(&path, HEARTBEAT_V2).unwrap();
+
+        let connection = FileConfig::new(path.clone())
+            .mode(FileMode::Follow)
+            .poll_interval(POLL_INTERVAL)
+            .connect_async::<MavMessage>()
+            .await
+            .unwrap();
+
+        let (_, msg) = connection.recv().await.unwrap();
+        assert_eq!(msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+
+        let err = connection.try_recv().await.unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::WouldBlock));
+
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || append_split_frame(&path))
+        };
+        let (_, msg) = connection.recv().await.unwrap();
+        assert_eq!(msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+        writer.join().unwrap();
+
+        fs::remove_file(&path).ok();
+    }
+}
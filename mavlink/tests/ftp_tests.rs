@@ -0,0 +1,254 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_ftp_payload {
+    use mavlink::ftp::{FtpNak, FtpOpcode, FtpPayload, FTP_MAX_DATA_LEN};
+
+    #[test]
+    fn test_payload_encoding() {
+        let mut payload = FtpPayload::new(FtpOpcode::Ack).with_data(&[6]);
+        payload.seq_number = 0x1234;
+        payload.session = 2;
+        payload.req_opcode = FtpOpcode::BurstReadFile;
+        payload.offset = 0xdead_beef;
+
+        let encoded = payload.encode();
+        assert_eq!(
+            encoded[..13],
+            [0x34, 0x12, 2, 128, 1, 15, 0, 0, 0xef, 0xbe, 0xad, 0xde, 6]
+        );
+        assert_eq!(FtpPayload::decode(&encoded), Some(payload.clone()));
+
+        payload.opcode = FtpOpcode::Nak;
+        assert_eq!(payload.nak(), Some(FtpNak::Eof));
+
+        let long = FtpPayload::new(FtpOpcode::WriteFile).with_data(&[1; 300]);
+        assert_eq!(long.data.len(), FTP_MAX_DATA_LEN);
+    }
+}
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_ftp {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_responder;
+    use mavlink::common::{MavMessage, FILE_TRANSFER_PROTOCOL_DATA};
+    use mavlink::ftp::{DirEntry, FtpClient, FtpError, FtpNak, FtpOpcode, FtpPayload};
+
+    /// Packets sent per burst by the simulated server
+    const BURST_LEN: usize = 3;
+    const CHUNK_LEN: usize = 100;
+
+    pub fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in data {
+            crc ^= u32::from(*byte);
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    pub fn log_file() -> Vec<u8> {
+        (0..1000).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn nak(request: &FtpPayload, nak: u8) -> FtpPayload {
+        let mut reply = FtpPayload::new(FtpOpcode::Nak).with_data(&[nak]);
+        reply.req_opcode = request.opcode;
+        reply.session = request.session;
+        reply
+    }
+
+    fn ack(request: &FtpPayload, data: &[u8]) -> FtpPayload {
+        let mut reply = FtpPayload::new(FtpOpcode::Ack).with_data(data);
+        reply.req_opcode = request.opcode;
+        reply.session = request.session;
+        reply.offset = request.offset;
+        reply
+    }
+
+    /// Serve an in-memory file system, the second packet of the first burst read is dropped
+    pub fn server() -> impl FnMut(MavMessage) -> Vec<MavMessage> + Send {
+        let mut files = BTreeMap::from([("/logs/00000001.BIN".to_owned(), log_file())]);
+        let mut open: Option<String> = None;
+        let mut dropped_packet = false;
+        move |msg| {
+            let MavMessage::FILE_TRANSFER_PROTOCOL(data) = msg else {
+                return Vec::new();
+            };
+            let request = FtpPayload::decode(&data.payload).unwrap();
+            let path = String::from_utf8(request.data.clone()).unwrap();
+            let replies = match request.opcode {
+                FtpOpcode::ListDirectory => {
+                    let listing: Vec<u8> = files
+                        .iter()
+                        .filter_map(|(name, data)| {
+                            let name = name.strip_prefix(&format!("{path}/"))?;
+                            Some(format!("F{name}\t{}\0", data.len()).into_bytes())
+                        })
+                        .chain([b"S\0".to_vec()])
+                        .skip(request.offset as usize)
+                        .flatten()
+                        .collect();
+                    if listing.is_empty() {
+                        vec![nak(&request, 6)]
+                    } else {
+                        vec![ack(&request, &listing)]
+                    }
+                }
+                FtpOpcode::OpenFileRO | FtpOpcode::CreateFile => {
+                    if request.opcode == FtpOpcode::CreateFile {
+                        files.insert(path.clone(), Vec::new());
+                    }
+                    match files.get(&path) {
+                        Some(data) => {
+                            open = Some(path);
+                            vec![ack(&request, &(data.len() as u32).to_le_bytes())]
+                        }
+                        None => vec![nak(&request, 10)],
+                    }
+                }
+                FtpOpcode::BurstReadFile => {
+                    let data = &files[open.as_ref().unwrap()];
+                    let offset = request.offset as usize;
+                    let mut burst: Vec<_> = data[offset..]
+                        .chunks(CHUNK_LEN)
+                        .take(BURST_LEN)
+                        .enumerate()
+                        .map(|(i, chunk)| {
+                            let mut reply = ack(&request, chunk);
+                            reply.offset = (offset + i * CHUNK_LEN) as u32;
+                            reply
+                        })
+                        .collect();
+                    match burst.last_mut() {
+                        Some(last) => last.burst_complete = true,
+                        None => burst.push(nak(&request, 6)),
+                    }
+                    if !dropped_packet {
+                        dropped_packet = true;
+                        burst.remove(1);
+                    }
+                    burst
+                }
+                FtpOpcode::WriteFile => {
+                    let file = files.get_mut(open.as_ref().unwrap()).unwrap();
+                    file.truncate(request.offset as usize);
+                    file.extend_from_slice(&request.data);
+                    vec![ack(&request, &[])]
+                }
+                FtpOpcode::TerminateSession => {
+                    open = None;
+                    vec![ack(&request, &[])]
+                }
+                FtpOpcode::RemoveFile => match files.remove(&path) {
+                    Some(_) => vec![ack(&request, &[])],
+                    None => vec![nak(&request, 10)],
+                },
+                FtpOpcode::Rename => {
+                    let (from, to) = path.split_once('\0').unwrap();
+                    let data = files.remove(from).unwrap();
+                    files.insert(to.to_owned(), data);
+                    vec![ack(&request, &[])]
+                }
+                FtpOpcode::CalcFileCRC32 => {
+                    vec![ack(&request, &crc32(&files[&path]).to_le_bytes())]
+                }
+                _ => vec![nak(&request, 7)],
+            };
+
+            replies
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut reply)| {
+                    reply.seq_number = request.seq_number.wrapping_add(1 + i as u16);
+                    MavMessage::FILE_TRANSFER_PROTOCOL(FILE_TRANSFER_PROTOCOL_DATA {
+                        target_network: 0,
+                        target_system: 255,
+                        target_component: 0,
+                        payload: reply.encode(),
+                    })
+                })
+                .collect()
+        }
+    }
+
+    /// Test the file operations, including a burst read with a lost packet
+    #[test]
+    fn test_ftp_operations() {
+        spawn_responder("test_ftp_operations", server());
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_ftp_operations").expect("Couldn't connect");
+        let ftp = FtpClient::new(&connection, 1, 1).with_timeout(Duration::from_millis(100));
+
+        let entries = ftp.list_directory("/logs").unwrap();
+        assert_eq!(
+            entries,
+            [DirEntry::File {
+                name: "00000001.BIN".to_owned(),
+                size: 1000
+            }]
+        );
+        assert_eq!(ftp.read_file("/logs/00000001.BIN").unwrap(), log_file());
+        assert_eq!(ftp.crc32("/logs/00000001.BIN").unwrap(), crc32(&log_file()));
+
+        let params = vec![7; 600];
+        ftp.write_file("/params.txt", &params).unwrap();
+        ftp.rename("/params.txt", "/logs/params.txt").unwrap();
+        assert_eq!(ftp.read_file("/logs/params.txt").unwrap(), params);
+        assert_eq!(ftp.list_directory("/logs").unwrap().len(), 2);
+
+        ftp.remove_file("/logs/params.txt").unwrap();
+        assert!(matches!(
+            ftp.read_file("/logs/params.txt"),
+            Err(FtpError::Nak(FtpNak::FileNotFound))
+        ));
+    }
+
+    /// Test that a request fails once all retransmissions are used up
+    #[test]
+    fn test_ftp_timeout() {
+        let _server = mavlink::connect::<MavMessage>("mem:test_ftp_timeout").unwrap();
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_ftp_timeout").expect("Couldn't connect");
+        let ftp = FtpClient::new(&connection, 1, 1)
+            .with_timeout(Duration::from_millis(20))
+            .with_retries(2);
+
+        assert!(matches!(ftp.crc32("/missing"), Err(FtpError::Timeout)));
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_ftp {
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_async_responder;
+    use mavlink::common::MavMessage;
+    use mavlink::ftp::AsyncFtpClient;
+
+    /// Test reading and writing files on an async connection
+    #[tokio::test]
+    async fn test_async_ftp_operations() {
+        spawn_async_responder("test_async_ftp_operations", crate::test_ftp::server()).await;
+        let connection = mavlink::connect_async::<MavMessage>("mem:test_async_ftp_operations")
+            .await
+            .expect("Couldn't connect");
+        let ftp = AsyncFtpClient::new(&*connection, 1, 1).with_timeout(Duration::from_millis(100));
+
+        assert_eq!(
+            ftp.read_file("/logs/00000001.BIN").await.unwrap(),
+            crate::test_ftp::log_file()
+        );
+        ftp.write_file("/empty", &[]).await.unwrap();
+        assert!(ftp.read_file("/empty").await.unwrap().is_empty());
+        assert_eq!(ftp.list_directory("/logs").await.unwrap().len(), 1);
+    }
+}
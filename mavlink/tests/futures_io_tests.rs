@@ -0,0 +1,113 @@
+mod test_shared;
+
+#[cfg(all(feature = "futures-io", feature = "common"))]
+mod test_futures_io {
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER, HEARTBEAT_V2};
+    use futures::executor::block_on;
+    use futures::io::{AsyncRead, AsyncWrite, Cursor};
+    use mavlink::async_io::FuturesIo;
+    use mavlink::async_peek_reader::AsyncPeekReader;
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::{AsyncMavConnection, AsyncStreamConnection, MavlinkVersion};
+
+    /// Stream reading from a buffer and recording what is written to it
+    struct Loopback {
+        input: Cursor<Vec<u8>>,
+        output: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl AsyncRead for Loopback {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.input).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for Loopback {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.output.lock().unwrap().extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    pub fn test_futures_read_write() {
+        block_on(async {
+            let mut reader = AsyncPeekReader::new(FuturesIo::new(Cursor::new(HEARTBEAT_V2)));
+            let (header, msg) = mavlink::read_v2_msg_async::<MavMessage, _>(&mut reader)
+                .await
+                .unwrap();
+            assert_eq!(header, COMMON_MSG_HEADER);
+            assert_eq!(msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+
+            let err = mavlink::read_v2_msg_async::<MavMessage, _>(&mut reader)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof)
+            );
+
+            let mut writer = FuturesIo::new(Vec::new());
+            let len = mavlink::write_versioned_msg_async(
+                &mut writer,
+                MavlinkVersion::V2,
+                COMMON_MSG_HEADER,
+                &msg,
+            )
+            .await
+            .unwrap();
+            assert_eq!(len, HEARTBEAT_V2.len());
+            assert_eq!(writer.into_inner(), HEARTBEAT_V2);
+        });
+    }
+
+    #[test]
+    pub fn test_futures_stream_connection() {
+        let output = Arc::default();
+        let stream = Loopback {
+            input: Cursor::new(HEARTBEAT_V2.to_vec()),
+            output: Arc::clone(&output),
+        };
+        let connection = AsyncStreamConnection::from_futures(stream);
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+
+        block_on(async {
+            let (header, msg) = AsyncMavConnection::<MavMessage>::recv(&connection)
+                .await
+                .unwrap();
+            assert_eq!(header, COMMON_MSG_HEADER);
+            assert_eq!(msg, heartbeat);
+
+            AsyncMavConnection::<MavMessage>::send(&connection, &COMMON_MSG_HEADER, &heartbeat)
+                .await
+                .unwrap();
+        });
+
+        // the connection assigns its own sequence numbers
+        let written = output.lock().unwrap().clone();
+        assert_eq!(written.len(), HEARTBEAT_V2.len());
+        assert_eq!(written[4], 0);
+        assert_eq!(written[5..10], HEARTBEAT_V2[5..10]);
+    }
+}
@@ -0,0 +1,120 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "udp", feature = "common"))]
+mod test_heartbeat {
+    use std::net::UdpSocket;
+    use std::thread;
+    use std::time::Duration;
+
+    use mavlink::common::{MavMessage, MavType, HEARTBEAT_DATA};
+    use mavlink::error::MessageReadError;
+    use mavlink::heartbeat::{HeartbeatMonitor, PeerEvent};
+    use mavlink::{MAVLinkV2MessageRaw, MavConnection, MavHeader, MessageData};
+
+    /// Test that peers are discovered by their heartbeats and lost after the timeout
+    #[test]
+    fn test_peer_discovery() {
+        let monitor = HeartbeatMonitor::new(
+            mavlink::connect::<MavMessage>("udpin:127.0.0.1:14582").expect("Couldn't bind"),
+        )
+        .with_timeout(Duration::from_millis(200));
+        let client =
+            mavlink::connect::<MavMessage>("udpout:127.0.0.1:14582").expect("Couldn't connect");
+
+        let heartbeat = crate::test_shared::get_heartbeat_msg();
+        let header = MavHeader {
+            system_id: 3,
+            component_id: 1,
+            sequence: 0,
+        };
+        client
+            .send(&header, &MavMessage::HEARTBEAT(heartbeat.clone()))
+            .unwrap();
+        monitor.recv().unwrap();
+
+        let Some(PeerEvent::Connected(peer)) = monitor.poll_event() else {
+            panic!("peer not discovered");
+        };
+        assert_eq!((peer.system_id, peer.component_id), (3, 1));
+        assert_eq!(peer.mavtype(), Some(heartbeat.mavtype));
+        assert_eq!(peer.autopilot(), Some(heartbeat.autopilot));
+        assert_eq!(peer.base_mode(), heartbeat.base_mode);
+        assert_eq!(monitor.peer(3, 1), Some(peer));
+
+        // further heartbeats only refresh the peer
+        client
+            .send(&header, &MavMessage::HEARTBEAT(heartbeat.clone()))
+            .unwrap();
+        monitor.recv().unwrap();
+        assert_eq!(monitor.poll_event(), None);
+        assert_eq!(monitor.peers().len(), 1);
+
+        thread::sleep(Duration::from_millis(300));
+        monitor.update().unwrap();
+        assert!(matches!(monitor.poll_event(), Some(PeerEvent::Lost(_))));
+        assert!(monitor.peers().is_empty());
+
+        // heartbeats with a type unknown to the dialect still announce the peer
+        let mut raw = MAVLinkV2MessageRaw::new();
+        raw.serialize_message_data(
+            MavHeader {
+                system_id: 4,
+                ..header
+            },
+            &crate::test_shared::get_heartbeat_msg(),
+        );
+        let mut frame = raw.raw_bytes().to_vec();
+        frame[10 + 4] = 250;
+        let crc_start = frame.len() - 2;
+        let crc = mavlink::calculate_crc(&frame[1..crc_start], HEARTBEAT_DATA::EXTRA_CRC);
+        frame[crc_start..].copy_from_slice(&crc.to_le_bytes());
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.send_to(&frame, "127.0.0.1:14582").unwrap();
+
+        assert!(matches!(monitor.recv(), Err(MessageReadError::Parse(_))));
+        let Some(PeerEvent::Connected(peer)) = monitor.poll_event() else {
+            panic!("peer not discovered");
+        };
+        assert_eq!((peer.system_id, peer.mavtype), (4, 250));
+        assert_eq!(peer.mavtype(), None);
+        assert_eq!(peer.autopilot(), Some(heartbeat.autopilot));
+    }
+
+    /// Test that the own heartbeat is sent at the configured interval
+    #[test]
+    fn test_own_heartbeat() {
+        let server =
+            mavlink::connect::<MavMessage>("udpin:127.0.0.1:14583").expect("Couldn't bind");
+        let mut heartbeat = crate::test_shared::get_heartbeat_msg();
+        heartbeat.mavtype = MavType::MAV_TYPE_GCS;
+        let header = MavHeader {
+            system_id: 255,
+            component_id: 190,
+            sequence: 0,
+        };
+        let monitor = HeartbeatMonitor::new(
+            mavlink::connect::<MavMessage>("udpout:127.0.0.1:14583").expect("Couldn't connect"),
+        )
+        .with_heartbeat(header, heartbeat, Duration::from_secs(60));
+
+        monitor.update().unwrap();
+        // not due yet
+        monitor.update().unwrap();
+
+        let (received, msg) = server.recv().unwrap();
+        assert_eq!(received.system_id, 255);
+        let MavMessage::HEARTBEAT(received) = msg else {
+            panic!("unexpected message {msg:?}");
+        };
+        assert_eq!(received.mavtype, MavType::MAV_TYPE_GCS);
+        assert!(server.try_recv().is_err());
+
+        // run returns once stopped
+        thread::scope(|scope| {
+            let runner = scope.spawn(|| monitor.run());
+            thread::sleep(Duration::from_millis(50));
+            monitor.stop();
+            runner.join().unwrap().unwrap();
+        });
+    }
+}
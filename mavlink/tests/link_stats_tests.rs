@@ -0,0 +1,110 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_link_stats {
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::MavMessage;
+    use mavlink::link_stats::LinkStats;
+    use mavlink::{MAVLinkV2MessageRaw, MavConnection, MavHeader};
+
+    fn heartbeat_frame(sequence: u8) -> MAVLinkV2MessageRaw {
+        let mut raw = MAVLinkV2MessageRaw::new();
+        raw.serialize_message(
+            MavHeader {
+                sequence,
+                ..COMMON_MSG_HEADER
+            },
+            &MavMessage::HEARTBEAT(get_heartbeat_msg()),
+        );
+        raw
+    }
+
+    fn file_link_stats(name: &str, data: &[u8], messages: usize) -> LinkStats {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, data).unwrap();
+
+        let conn = mavlink::connect::<MavMessage>(&format!("file:{}", path.display()))
+            .expect("Couldn't open file");
+        for _ in 0..messages {
+            conn.recv().expect("Failed to receive message");
+        }
+        std::fs::remove_file(&path).ok();
+        conn.link_stats()
+    }
+
+    #[test]
+    pub fn test_file_link_stats() {
+        let mut data = Vec::new();
+        data.extend_from_slice(heartbeat_frame(10).raw_bytes());
+        data.extend_from_slice(&[0x01, 0x02, 0x03]);
+
+        // corrupted payload
+        let mut corrupted = heartbeat_frame(11);
+        corrupted.as_mut_slice()[12] ^= 0xff;
+        data.extend_from_slice(corrupted.raw_bytes());
+
+        // message id not part of the dialect
+        let mut unknown = heartbeat_frame(12);
+        unknown.as_mut_slice()[8] = 0x7f;
+        data.extend_from_slice(unknown.raw_bytes());
+
+        data.extend_from_slice(heartbeat_frame(13).raw_bytes());
+        data.extend_from_slice(heartbeat_frame(14).raw_bytes());
+
+        let path = std::env::temp_dir().join("mavlink_link_stats_test.bin");
+        std::fs::write(&path, &data).unwrap();
+
+        let conn = mavlink::connect::<MavMessage>(&format!("file:{}", path.display()))
+            .expect("Couldn't open file");
+        for _ in 0..3 {
+            conn.recv().expect("Failed to receive message");
+        }
+        std::fs::remove_file(&path).ok();
+
+        let stats = conn.link_stats();
+        assert_eq!(stats.read.frames_received, 3);
+        assert_eq!(stats.read.crc_errors, 1);
+        assert_eq!(stats.read.unknown_messages, 1);
+        assert_eq!(stats.read.bad_signatures, 0);
+        assert_eq!(stats.read.bytes_received, data.len() as u64);
+
+        let source = stats.sources[&(COMMON_MSG_HEADER.system_id, COMMON_MSG_HEADER.component_id)];
+        assert_eq!(source.received, 3);
+        assert_eq!(source.lost, 2);
+        assert_eq!(source.last_sequence, 14);
+        assert_eq!(stats.lost(), 2);
+    }
+
+    #[test]
+    pub fn test_out_of_order_sequence_is_not_loss() {
+        let mut data = Vec::new();
+        for sequence in [10, 11, 11, 9, 12, 100, 200, 5] {
+            data.extend_from_slice(heartbeat_frame(sequence).raw_bytes());
+        }
+
+        let stats = file_link_stats("mavlink_link_stats_order_test.bin", &data, 8);
+        let source = stats.sources[&(COMMON_MSG_HEADER.system_id, COMMON_MSG_HEADER.component_id)];
+        assert_eq!(source.received, 8);
+        // the duplicated 11 and the late 9 went backwards
+        assert_eq!(source.out_of_order, 2);
+        // 13 to 99, 101 to 199 and 201 to 4 after the sequence wrapped around were skipped
+        assert_eq!(source.lost, 87 + 99 + 60);
+        assert_eq!(source.last_sequence, 5);
+    }
+
+    #[test]
+    pub fn test_implausible_frame_candidate_is_not_crc_error() {
+        let mut data = Vec::new();
+        data.extend_from_slice(heartbeat_frame(1).raw_bytes());
+        // STX marker in other data, followed by a HEARTBEAT message ID with a too long payload
+        data.extend_from_slice(&[0xfd, 0xf0, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00]);
+        data.extend_from_slice(&[0x00; 0xf0 + 2]);
+        data.extend_from_slice(heartbeat_frame(2).raw_bytes());
+
+        let stats = file_link_stats("mavlink_link_stats_candidate_test.bin", &data, 2);
+        assert_eq!(stats.read.frames_received, 2);
+        assert_eq!(stats.read.crc_errors, 0);
+        assert_eq!(stats.read.unknown_messages, 0);
+        assert_eq!(stats.read.bytes_received, data.len() as u64);
+    }
+}
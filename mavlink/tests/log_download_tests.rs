@@ -0,0 +1,160 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_log_download {
+    use std::io::{self, Write};
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_responder;
+    use mavlink::common::{MavMessage, LOG_DATA_DATA, LOG_ENTRY_DATA};
+    use mavlink::log_download::{LogClient, LogEntry, LogError};
+
+    pub fn log(id: u16) -> Vec<u8> {
+        let len = 400 * usize::from(id);
+        (0..len).map(|i| (i % 256) as u8 ^ id as u8).collect()
+    }
+
+    /// Serve two logs like a vehicle, the first listing misses the entry of log 2 and the first
+    /// download misses the chunk at offset 180
+    pub fn vehicle() -> impl FnMut(MavMessage) -> Vec<MavMessage> + Send {
+        let mut dropped_entry = false;
+        let mut dropped_chunk = false;
+        move |msg| match msg {
+            MavMessage::LOG_REQUEST_LIST(data) => (data.start.max(1)..=data.end.min(2))
+                .filter(|id| {
+                    let drop = *id == 2 && !dropped_entry;
+                    dropped_entry |= drop;
+                    !drop
+                })
+                .map(|id| {
+                    MavMessage::LOG_ENTRY(LOG_ENTRY_DATA {
+                        time_utc: 1_700_000_000 + u32::from(id),
+                        size: log(id).len() as u32,
+                        id,
+                        num_logs: 2,
+                        last_log_num: 2,
+                    })
+                })
+                .collect(),
+            MavMessage::LOG_REQUEST_DATA(data) if data.id <= 2 => {
+                let log = log(data.id);
+                let start = data.ofs as usize;
+                let end = (start + data.count as usize).min(log.len());
+                log[start..end]
+                    .chunks(90)
+                    .enumerate()
+                    .map(|(i, chunk)| {
+                        let mut data = LOG_DATA_DATA {
+                            ofs: (start + i * 90) as u32,
+                            id: data.id,
+                            count: chunk.len() as u8,
+                            ..Default::default()
+                        };
+                        data.data[..chunk.len()].copy_from_slice(chunk);
+                        data
+                    })
+                    .filter(|data| {
+                        let drop = data.ofs == 180 && !dropped_chunk;
+                        dropped_chunk |= drop;
+                        !drop
+                    })
+                    .map(MavMessage::LOG_DATA)
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Test listing and downloading logs with lost messages
+    #[test]
+    fn test_log_download() {
+        spawn_responder("test_log_download", vehicle());
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_log_download").expect("Couldn't connect");
+        let client = LogClient::new(&connection, 1, 1).with_timeout(Duration::from_millis(100));
+
+        let logs = client.list().unwrap();
+        assert_eq!(
+            logs.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(logs[1].size, 800);
+
+        let mut data = Vec::new();
+        let mut progress = Vec::new();
+        client
+            .download_with_progress(&logs[1], &mut data, |received, total| {
+                progress.push((received, total));
+            })
+            .unwrap();
+        assert_eq!(data, log(2));
+        assert_eq!(progress.last(), Some(&(800, 800)));
+        // nothing is written past the lost chunk until it was received again
+        assert!(progress.contains(&(180, 800)));
+
+        let mut data = Vec::new();
+        client.download(&logs[0], &mut data).unwrap();
+        assert_eq!(data, log(1));
+    }
+
+    struct FullSink;
+
+    impl Write for FullSink {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::Other.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Test that errors of the sink and timeouts end the download
+    #[test]
+    fn test_log_download_errors() {
+        spawn_responder("test_log_download_errors", vehicle());
+        let connection = mavlink::connect::<MavMessage>("mem:test_log_download_errors")
+            .expect("Couldn't connect");
+        let client = LogClient::new(&connection, 1, 1)
+            .with_timeout(Duration::from_millis(20))
+            .with_retries(2);
+
+        let entry = LogEntry {
+            id: 1,
+            time_utc: 0,
+            size: 400,
+        };
+        let err = client.download(&entry, &mut FullSink).unwrap_err();
+        assert!(matches!(err, LogError::Output(_)));
+
+        let missing = LogEntry { id: 3, ..entry };
+        let err = client.download(&missing, &mut Vec::new()).unwrap_err();
+        assert!(matches!(err, LogError::Timeout));
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_log_download {
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_async_responder;
+    use mavlink::common::MavMessage;
+    use mavlink::log_download::AsyncLogClient;
+
+    /// Test listing and downloading logs on an async connection
+    #[tokio::test]
+    async fn test_async_log_download() {
+        let vehicle = crate::test_log_download::vehicle();
+        spawn_async_responder("test_async_log_download", vehicle).await;
+        let connection = mavlink::connect_async::<MavMessage>("mem:test_async_log_download")
+            .await
+            .expect("Couldn't connect");
+        let client =
+            AsyncLogClient::new(&*connection, 1, 1).with_timeout(Duration::from_millis(100));
+
+        let logs = client.list().await.unwrap();
+        let mut data = Vec::new();
+        client.download(&logs[1], &mut data).await.unwrap();
+        assert_eq!(data, crate::test_log_download::log(2));
+    }
+}
@@ -0,0 +1,222 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_mem {
+    use std::io;
+    use std::time::{Duration, Instant};
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::MavMessage;
+    use mavlink::error::{MessageReadError, MessageWriteError};
+    use mavlink::{Connectable, MavConnection, MemConfig, MemConnection};
+
+    fn heartbeat() -> MavMessage {
+        MavMessage::HEARTBEAT(get_heartbeat_msg())
+    }
+
+    fn is_kind(err: &MessageReadError, kind: io::ErrorKind) -> bool {
+        matches!(err, MessageReadError::Io(e) if e.kind() == kind)
+    }
+
+    /// Receive all messages that were delivered so far
+    fn drain(conn: &impl MavConnection<MavMessage>) -> Vec<(mavlink::MavHeader, MavMessage)> {
+        let mut received = Vec::new();
+        loop {
+            match conn.try_recv() {
+                Ok(message) => received.push(message),
+                Err(e) if is_kind(&e, io::ErrorKind::WouldBlock) => return received,
+                Err(e) => panic!("unexpected error {e:?}"),
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_mem_connect() {
+        let vehicle = mavlink::connect::<MavMessage>("mem:test_mem_connect").unwrap();
+        let gcs = mavlink::connect::<MavMessage>("mem:test_mem_connect").unwrap();
+
+        vehicle.send(&COMMON_MSG_HEADER, &heartbeat()).unwrap();
+        let (header, msg) = gcs.recv().unwrap();
+        assert_eq!(header.system_id, COMMON_MSG_HEADER.system_id);
+        assert_eq!(header.component_id, COMMON_MSG_HEADER.component_id);
+        assert_eq!(msg, heartbeat());
+
+        gcs.send_default(&heartbeat()).unwrap();
+        let (_, msg) = vehicle.recv().unwrap();
+        assert_eq!(msg, heartbeat());
+        assert_eq!(gcs.link_stats().read.frames_received, 1);
+    }
+
+    #[test]
+    pub fn test_mem_dropped_waiting_connection() {
+        let stale = mavlink::connect::<MavMessage>("mem:test_mem_stale").unwrap();
+        drop(stale);
+
+        let vehicle = mavlink::connect::<MavMessage>("mem:test_mem_stale").unwrap();
+        let gcs = mavlink::connect::<MavMessage>("mem:test_mem_stale").unwrap();
+        vehicle.send_default(&heartbeat()).unwrap();
+        assert_eq!(gcs.recv().unwrap().1, heartbeat());
+    }
+
+    #[test]
+    pub fn test_mem_pair_raw() {
+        let (vehicle, gcs) = MemConnection::pair();
+
+        MavConnection::<MavMessage>::send(&vehicle, &COMMON_MSG_HEADER, &heartbeat()).unwrap();
+        let raw = MavConnection::<MavMessage>::recv_raw(&gcs).unwrap();
+        assert_eq!(raw.system_id(), COMMON_MSG_HEADER.system_id);
+
+        MavConnection::<MavMessage>::send_raw(&gcs, &raw).unwrap();
+        let (header, msg) = MavConnection::<MavMessage>::recv(&vehicle).unwrap();
+        assert_eq!(header.sequence, raw.sequence());
+        assert_eq!(msg, heartbeat());
+    }
+
+    #[test]
+    pub fn test_mem_disconnect() {
+        let (vehicle, gcs) = MemConnection::pair();
+        MavConnection::<MavMessage>::send_default(&vehicle, &heartbeat()).unwrap();
+        drop(vehicle);
+
+        // messages sent before disconnecting are still delivered
+        let (_, msg) = MavConnection::<MavMessage>::recv(&gcs).unwrap();
+        assert_eq!(msg, heartbeat());
+        let err = MavConnection::<MavMessage>::recv(&gcs).unwrap_err();
+        assert!(is_kind(&err, io::ErrorKind::UnexpectedEof));
+        let err = MavConnection::<MavMessage>::send_default(&gcs, &heartbeat()).unwrap_err();
+        assert!(matches!(err, MessageWriteError::Io(e) if e.kind() == io::ErrorKind::BrokenPipe));
+    }
+
+    #[test]
+    pub fn test_mem_loss() {
+        let vehicle = MemConfig::new("test_mem_loss".to_owned())
+            .loss(0.3)
+            .seed(42)
+            .connect::<MavMessage>()
+            .unwrap();
+        let gcs = mavlink::connect::<MavMessage>("mem:test_mem_loss").unwrap();
+
+        for _ in 0..200 {
+            vehicle.send_default(&heartbeat()).unwrap();
+        }
+        let received = drain(&gcs).len() as u64;
+        assert!((100..180).contains(&received), "received {received}");
+        let stats = gcs.link_stats();
+        assert!(stats.lost() > 0);
+        assert!(received + stats.lost() <= 200);
+
+        // the link is only lossy in the configured direction
+        for _ in 0..10 {
+            gcs.send_default(&heartbeat()).unwrap();
+        }
+        assert_eq!(drain(&vehicle).len(), 10);
+    }
+
+    #[test]
+    pub fn test_mem_corruption() {
+        let vehicle = MemConfig::new("test_mem_corruption".to_owned())
+            .corruption(1.0)
+            .connect::<MavMessage>()
+            .unwrap();
+        let gcs = mavlink::connect::<MavMessage>("mem:test_mem_corruption").unwrap();
+
+        for _ in 0..50 {
+            vehicle.send_default(&heartbeat()).unwrap();
+        }
+        assert!(drain(&gcs).is_empty());
+        let stats = gcs.link_stats();
+        assert_eq!(stats.read.frames_received, 0);
+        assert!(stats.read.crc_errors > 0);
+    }
+
+    #[test]
+    pub fn test_mem_latency() {
+        let latency = Duration::from_millis(50);
+        let vehicle = MemConfig::new("test_mem_latency".to_owned())
+            .latency(latency)
+            .connect::<MavMessage>()
+            .unwrap();
+        let gcs = mavlink::connect::<MavMessage>("mem:test_mem_latency").unwrap();
+
+        let start = Instant::now();
+        vehicle.send_default(&heartbeat()).unwrap();
+        let err = gcs.try_recv().unwrap_err();
+        assert!(is_kind(&err, io::ErrorKind::WouldBlock));
+
+        let (_, msg) = gcs.recv().unwrap();
+        assert!(start.elapsed() >= latency);
+        assert_eq!(msg, heartbeat());
+    }
+
+    #[test]
+    pub fn test_mem_recv_timeout() {
+        let latency = Duration::from_millis(50);
+        let vehicle = MemConfig::new("test_mem_recv_timeout".to_owned())
+            .latency(latency)
+            .connect::<MavMessage>()
+            .unwrap();
+        let gcs = mavlink::connect::<MavMessage>("mem:test_mem_recv_timeout").unwrap();
+
+        let err = gcs.recv_timeout(Duration::from_millis(10)).unwrap_err();
+        assert!(is_kind(&err, io::ErrorKind::WouldBlock));
+
+        let start = Instant::now();
+        vehicle.send_default(&heartbeat()).unwrap();
+        let err = gcs.recv_timeout(Duration::from_millis(10)).unwrap_err();
+        assert!(is_kind(&err, io::ErrorKind::WouldBlock));
+
+        let (_, msg) = gcs.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert!(start.elapsed() >= latency);
+        assert_eq!(msg, heartbeat());
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_mem {
+    use std::io;
+    use std::time::Duration;
+
+    use crate::test_shared::get_heartbeat_msg;
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::{AsyncConnectable, AsyncMavConnection, AsyncMemConnection, MemConfig};
+
+    #[tokio::test]
+    pub async fn test_async_mem_connect() {
+        let vehicle = MemConfig::new("test_async_mem_connect".to_owned())
+            .latency(Duration::from_millis(20))
+            .connect_async::<MavMessage>()
+            .await
+            .unwrap();
+        let gcs = mavlink::connect_async::<MavMessage>("mem:test_async_mem_connect")
+            .await
+            .unwrap();
+
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+        vehicle.send_default(&heartbeat).await.unwrap();
+        let err = gcs.try_recv().await.unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::WouldBlock));
+        let (_, msg) = gcs.recv().await.unwrap();
+        assert_eq!(msg, heartbeat);
+    }
+
+    #[tokio::test]
+    pub async fn test_async_mem_pair() {
+        let (vehicle, gcs) = AsyncMemConnection::pair();
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+
+        AsyncMavConnection::<MavMessage>::send_default(&gcs, &heartbeat)
+            .await
+            .unwrap();
+        let (_, msg) = AsyncMavConnection::<MavMessage>::recv(&vehicle)
+            .await
+            .unwrap();
+        assert_eq!(msg, heartbeat);
+
+        drop(gcs);
+        let err = AsyncMavConnection::<MavMessage>::recv(&vehicle)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+    }
+}
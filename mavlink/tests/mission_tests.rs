@@ -0,0 +1,200 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_mission {
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_responder;
+    use mavlink::common::{
+        MavCmd, MavFrame, MavMessage, MavMissionResult, MavMissionType, MISSION_ACK_DATA,
+        MISSION_COUNT_DATA, MISSION_ITEM_INT_DATA, MISSION_REQUEST_INT_DATA,
+    };
+    use mavlink::mission::{MissionClient, MissionError};
+
+    /// Maximum number of items the simulated vehicle accepts
+    const CAPACITY: u16 = 5;
+
+    fn request(seq: u16) -> MavMessage {
+        MavMessage::MISSION_REQUEST_INT(MISSION_REQUEST_INT_DATA {
+            target_system: 255,
+            target_component: 0,
+            seq,
+            #[cfg(feature = "emit-extensions")]
+            mission_type: MavMissionType::MAV_MISSION_TYPE_MISSION,
+        })
+    }
+
+    fn ack(result: MavMissionResult) -> MavMessage {
+        MavMessage::MISSION_ACK(MISSION_ACK_DATA {
+            target_system: 255,
+            target_component: 0,
+            mavtype: result,
+            #[cfg(feature = "emit-extensions")]
+            mission_type: MavMissionType::MAV_MISSION_TYPE_MISSION,
+            #[cfg(feature = "emit-extensions")]
+            opaque_id: 0,
+        })
+    }
+
+    /// Serve the mission protocol like a vehicle, the first MISSION_REQUEST_LIST is ignored to
+    /// exercise the retransmissions
+    pub fn vehicle() -> impl FnMut(MavMessage) -> Vec<MavMessage> + Send {
+        let mut items: Vec<MISSION_ITEM_INT_DATA> = Vec::new();
+        let mut count = 0;
+        let mut ignored_list_request = false;
+        move |msg| {
+            let reply = match msg {
+                MavMessage::MISSION_COUNT(data) if data.count > CAPACITY => {
+                    ack(MavMissionResult::MAV_MISSION_NO_SPACE)
+                }
+                MavMessage::MISSION_COUNT(data) => {
+                    items.clear();
+                    count = data.count;
+                    if count == 0 {
+                        ack(MavMissionResult::MAV_MISSION_ACCEPTED)
+                    } else {
+                        request(0)
+                    }
+                }
+                MavMessage::MISSION_ITEM_INT(item) if usize::from(item.seq) == items.len() => {
+                    items.push(item);
+                    if items.len() < usize::from(count) {
+                        request(items.len() as u16)
+                    } else {
+                        ack(MavMissionResult::MAV_MISSION_ACCEPTED)
+                    }
+                }
+                MavMessage::MISSION_REQUEST_LIST(_) if !ignored_list_request => {
+                    ignored_list_request = true;
+                    return Vec::new();
+                }
+                MavMessage::MISSION_REQUEST_LIST(_) => {
+                    MavMessage::MISSION_COUNT(MISSION_COUNT_DATA {
+                        target_system: 255,
+                        target_component: 0,
+                        count: items.len() as u16,
+                        #[cfg(feature = "emit-extensions")]
+                        mission_type: MavMissionType::MAV_MISSION_TYPE_MISSION,
+                        #[cfg(feature = "emit-extensions")]
+                        opaque_id: 0,
+                    })
+                }
+                MavMessage::MISSION_REQUEST_INT(data) => {
+                    MavMessage::MISSION_ITEM_INT(MISSION_ITEM_INT_DATA {
+                        target_system: 255,
+                        target_component: 0,
+                        ..items[usize::from(data.seq)].clone()
+                    })
+                }
+                MavMessage::MISSION_CLEAR_ALL(_) => {
+                    items.clear();
+                    ack(MavMissionResult::MAV_MISSION_ACCEPTED)
+                }
+                _ => return Vec::new(),
+            };
+            vec![reply]
+        }
+    }
+
+    pub fn waypoints(count: u16) -> Vec<MISSION_ITEM_INT_DATA> {
+        (0..count)
+            .map(|i| MISSION_ITEM_INT_DATA {
+                frame: MavFrame::MAV_FRAME_GLOBAL_RELATIVE_ALT_INT,
+                command: MavCmd::MAV_CMD_NAV_WAYPOINT,
+                autocontinue: 1,
+                x: 473_977_420 + i32::from(i),
+                y: 85_455_940,
+                z: 10.0,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Test the upload, download and clear handshakes
+    #[test]
+    fn test_mission_transfer() {
+        spawn_responder("test_mission_transfer", vehicle());
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_mission_transfer").expect("Couldn't connect");
+        let client = MissionClient::new(&connection, 1, 1).with_timeout(Duration::from_millis(100));
+        let mission = MavMissionType::MAV_MISSION_TYPE_MISSION;
+
+        client.upload(mission, &waypoints(3)).unwrap();
+        let items = client.download(mission).unwrap();
+        assert_eq!(items.len(), 3);
+        for (seq, (item, expected)) in items.iter().zip(waypoints(3)).enumerate() {
+            assert_eq!(usize::from(item.seq), seq);
+            assert_eq!((item.x, item.command), (expected.x, expected.command));
+        }
+
+        let err = client
+            .upload(mission, &waypoints(CAPACITY + 1))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            MissionError::Rejected(MavMissionResult::MAV_MISSION_NO_SPACE)
+        ));
+
+        client.clear(mission).unwrap();
+        assert!(client.download(mission).unwrap().is_empty());
+    }
+
+    /// Test that a transfer fails once all retransmissions are used up
+    #[test]
+    fn test_mission_timeout() {
+        let _vehicle = mavlink::connect::<MavMessage>("mem:test_mission_timeout").unwrap();
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_mission_timeout").expect("Couldn't connect");
+        let client = MissionClient::new(&connection, 1, 1)
+            .with_timeout(Duration::from_millis(20))
+            .with_retries(2);
+
+        let err = client
+            .download(MavMissionType::MAV_MISSION_TYPE_MISSION)
+            .unwrap_err();
+        assert!(matches!(err, MissionError::Timeout));
+    }
+
+    #[cfg(not(feature = "emit-extensions"))]
+    #[test]
+    fn test_mission_type_without_extensions() {
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_mission_type_without_extensions").unwrap();
+        let client = MissionClient::new(&connection, 1, 1);
+        assert!(matches!(
+            client.clear(MavMissionType::MAV_MISSION_TYPE_FENCE),
+            Err(MissionError::UnsupportedType(_))
+        ));
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_mission {
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_async_responder;
+    use mavlink::common::{MavMessage, MavMissionType};
+    use mavlink::mission::AsyncMissionClient;
+
+    /// Test the upload and download handshakes on an async connection
+    #[tokio::test]
+    async fn test_async_mission_transfer() {
+        spawn_async_responder(
+            "test_async_mission_transfer",
+            crate::test_mission::vehicle(),
+        )
+        .await;
+        let connection = mavlink::connect_async::<MavMessage>("mem:test_async_mission_transfer")
+            .await
+            .expect("Couldn't connect");
+        let client =
+            AsyncMissionClient::new(&*connection, 1, 1).with_timeout(Duration::from_millis(100));
+        let mission = MavMissionType::MAV_MISSION_TYPE_MISSION;
+
+        let waypoints = crate::test_mission::waypoints(4);
+        client.upload(mission, &waypoints).await.unwrap();
+        let items = client.download(mission).await.unwrap();
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[3].x, waypoints[3].x);
+    }
+}
@@ -0,0 +1,213 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_param_encoding {
+    use mavlink::common::MavParamType;
+    use mavlink::param::{ParamEncoding, ParamValue};
+
+    #[test]
+    fn test_bytewise_encoding() {
+        let value = ParamValue::I16(-2);
+        let encoded = value.encode(ParamEncoding::Bytewise);
+        assert_eq!(encoded.to_le_bytes(), [0xfe, 0xff, 0, 0]);
+        assert_eq!(
+            ParamValue::decode(
+                encoded,
+                MavParamType::MAV_PARAM_TYPE_INT16,
+                ParamEncoding::Bytewise
+            ),
+            Some(value)
+        );
+
+        let value = ParamValue::U32(0xdead_beef);
+        let encoded = value.encode(ParamEncoding::Bytewise);
+        assert_eq!(
+            ParamValue::decode(
+                encoded,
+                MavParamType::MAV_PARAM_TYPE_UINT32,
+                ParamEncoding::Bytewise
+            ),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn test_c_cast_encoding() {
+        let value = ParamValue::I32(-1234);
+        let encoded = value.encode(ParamEncoding::CCast);
+        assert_eq!(encoded, -1234.0);
+        assert_eq!(
+            ParamValue::decode(
+                encoded,
+                MavParamType::MAV_PARAM_TYPE_INT32,
+                ParamEncoding::CCast
+            ),
+            Some(value)
+        );
+        assert_eq!(
+            ParamValue::decode(
+                1.0,
+                MavParamType::MAV_PARAM_TYPE_REAL64,
+                ParamEncoding::CCast
+            ),
+            None
+        );
+    }
+}
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_param {
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_responder;
+    use mavlink::common::{MavMessage, PARAM_VALUE_DATA};
+    use mavlink::param::{ParamEncoding, ParamError, ParamManager, ParamValue};
+
+    /// Parameter that keeps its value when set
+    const READ_ONLY: &str = "SYS_READ_ONLY";
+
+    fn initial_params() -> Vec<(&'static str, ParamValue)> {
+        vec![
+            ("SYSID_THISMAV", ParamValue::U8(1)),
+            ("TRIM_PITCH", ParamValue::I16(-20)),
+            ("BAT_CAPACITY", ParamValue::U32(5000)),
+            ("MPC_XY_VEL_MAX", ParamValue::F32(12.5)),
+            (READ_ONLY, ParamValue::I32(7)),
+        ]
+    }
+
+    fn value(params: &[(&str, ParamValue)], index: usize, encoding: ParamEncoding) -> MavMessage {
+        let (name, value) = params[index];
+        MavMessage::PARAM_VALUE(PARAM_VALUE_DATA {
+            param_value: value.encode(encoding),
+            param_count: params.len() as u16,
+            param_index: index as u16,
+            param_id: name.into(),
+            param_type: value.param_type(),
+        })
+    }
+
+    /// Serve the parameter protocol like an autopilot, the value at index 2 is dropped from the
+    /// list stream so it has to be requested separately. Set values are converted to the type
+    /// of the parameter.
+    pub fn autopilot(encoding: ParamEncoding) -> impl FnMut(MavMessage) -> Vec<MavMessage> + Send {
+        let mut params = initial_params();
+        move |msg| match msg {
+            MavMessage::PARAM_REQUEST_LIST(_) => (0..params.len())
+                .filter(|index| *index != 2)
+                .map(|index| value(&params, index, encoding))
+                .collect(),
+            MavMessage::PARAM_REQUEST_READ(data) => {
+                let index = usize::try_from(data.param_index).unwrap_or_else(|_| {
+                    let name = data.param_id.to_str().unwrap();
+                    params.iter().position(|(n, _)| *n == name).unwrap()
+                });
+                vec![value(&params, index, encoding)]
+            }
+            MavMessage::PARAM_SET(data) => {
+                let name = data.param_id.to_str().unwrap();
+                let index = params.iter().position(|(n, _)| *n == name).unwrap();
+                if name != READ_ONLY {
+                    let param_type = params[index].1.param_type();
+                    params[index].1 =
+                        ParamValue::decode(data.param_value, param_type, encoding).unwrap();
+                }
+                vec![value(&params, index, encoding)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Test the list download, reading and setting of parameters
+    #[test]
+    fn test_param_transfer() {
+        spawn_responder("test_param_transfer", autopilot(ParamEncoding::Bytewise));
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_param_transfer").expect("Couldn't connect");
+        let mut params =
+            ParamManager::new(&connection, 1, 1).with_timeout(Duration::from_millis(100));
+
+        let all = params.fetch_all().unwrap();
+        assert_eq!(all.len(), initial_params().len());
+        for (name, value) in initial_params() {
+            assert_eq!(params.get(name), Some(value));
+        }
+
+        params.set("TRIM_PITCH", ParamValue::I16(15)).unwrap();
+        assert_eq!(params.get("TRIM_PITCH"), Some(ParamValue::I16(15)));
+        assert_eq!(params.fetch("TRIM_PITCH").unwrap(), ParamValue::I16(15));
+
+        let err = params.set(READ_ONLY, ParamValue::I32(8)).unwrap_err();
+        assert!(matches!(err, ParamError::Rejected(ParamValue::I32(7))));
+        assert_eq!(params.get(READ_ONLY), Some(ParamValue::I32(7)));
+    }
+
+    /// Test that a set is confirmed when the target converts the value to the type of the
+    /// parameter
+    #[test]
+    fn test_param_set_converted() {
+        spawn_responder("test_param_set_converted", autopilot(ParamEncoding::CCast));
+        let connection = mavlink::connect::<MavMessage>("mem:test_param_set_converted")
+            .expect("Couldn't connect");
+        let mut params = ParamManager::new(&connection, 1, 1)
+            .with_timeout(Duration::from_millis(100))
+            .with_encoding(ParamEncoding::CCast);
+
+        params.set("SYSID_THISMAV", ParamValue::F32(2.0)).unwrap();
+        assert_eq!(params.get("SYSID_THISMAV"), Some(ParamValue::U8(2)));
+
+        // not representable by the `f32` sent, the target keeps the rounded value
+        params
+            .set("BAT_CAPACITY", ParamValue::U32(16_777_217))
+            .unwrap();
+        assert_eq!(
+            params.get("BAT_CAPACITY"),
+            Some(ParamValue::U32(16_777_216))
+        );
+
+        let err = params.set(READ_ONLY, ParamValue::F32(8.0)).unwrap_err();
+        assert!(matches!(err, ParamError::Rejected(ParamValue::I32(7))));
+    }
+
+    /// Test that a read fails once all retransmissions are used up
+    #[test]
+    fn test_param_timeout() {
+        let _autopilot = mavlink::connect::<MavMessage>("mem:test_param_timeout").unwrap();
+        let connection =
+            mavlink::connect::<MavMessage>("mem:test_param_timeout").expect("Couldn't connect");
+        let mut params = ParamManager::new(&connection, 1, 1)
+            .with_timeout(Duration::from_millis(20))
+            .with_retries(2);
+
+        assert!(matches!(params.fetch("MISSING"), Err(ParamError::Timeout)));
+        assert!(params.params().is_empty());
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_param {
+    use std::time::Duration;
+
+    use crate::test_shared::spawn_async_responder;
+    use mavlink::common::MavMessage;
+    use mavlink::param::{AsyncParamManager, ParamEncoding, ParamValue};
+
+    /// Test the list download and setting of parameters on an async connection
+    #[tokio::test]
+    async fn test_async_param_transfer() {
+        let autopilot = crate::test_param::autopilot(ParamEncoding::Bytewise);
+        spawn_async_responder("test_async_param_transfer", autopilot).await;
+        let connection = mavlink::connect_async::<MavMessage>("mem:test_async_param_transfer")
+            .await
+            .expect("Couldn't connect");
+        let mut params =
+            AsyncParamManager::new(&*connection, 1, 1).with_timeout(Duration::from_millis(100));
+
+        assert_eq!(params.fetch_all().await.unwrap().len(), 5);
+        params
+            .set("BAT_CAPACITY", ParamValue::U32(6000))
+            .await
+            .unwrap();
+        assert_eq!(params.get("BAT_CAPACITY"), Some(ParamValue::U32(6000)));
+    }
+}
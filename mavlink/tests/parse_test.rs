@@ -14,6 +14,7 @@ mod parse_tests {
     fn test_parse_tcp() {
         assert_parse("tcpin:example.com:99");
         assert_parse("tcpout:127.0.0.1:14549");
+        assert_parse("tcpserver:0.0.0.0:5760");
     }
 
     #[cfg(feature = "tcp")]
@@ -21,6 +22,29 @@ mod parse_tests {
     fn test_parse_file() {
         assert_parse("file:/mnt/12_44-mav.bin");
         assert_parse("file:C:\\mav_logs\\test.bin");
+        assert_parse("fileout:/mnt/session.tlog");
+        assert_parse("filefollow:/mnt/live.tlog");
+    }
+
+    #[cfg(all(feature = "unix", unix))]
+    #[test]
+    fn test_parse_unix() {
+        assert_parse("unixin:/run/mavlink/vehicle.sock");
+        assert_parse("unix:/run/mavlink/vehicle.sock");
+        assert_parse("unixgram:/tmp/gcs.sock");
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn test_parse_websocket() {
+        assert_parse("ws://127.0.0.1:5761");
+        assert_parse("wss://gcs.example.com/mavlink");
+        assert_parse("wsserver:0.0.0.0:5761");
+    }
+
+    #[test]
+    fn test_parse_mem() {
+        assert_parse("mem:link");
     }
 
     #[cfg(feature = "udp")]
@@ -29,6 +53,7 @@ mod parse_tests {
         assert_parse("udpcast:[::1]:4567");
         assert_parse("udpin:[2001:db8:85a3:8d3:1319:8a2e:370:7348]:443");
         assert_parse("udpout:1.1.1.1:1");
+        assert_parse("udpserver:0.0.0.0:14550");
     }
 
     #[cfg(feature = "direct-serial")]
@@ -0,0 +1,74 @@
+mod test_shared;
+
+#[cfg(feature = "common")]
+mod test_parser {
+    use crate::test_shared::{HEARTBEAT_V1, HEARTBEAT_V2};
+    use mavlink::common::MavMessage;
+    use mavlink::parser::MavParser;
+    use mavlink::{MAVLinkMessageRaw, MavlinkVersion, ReadVersion};
+
+    fn raw_bytes(msg: &MAVLinkMessageRaw) -> &[u8] {
+        match msg {
+            MAVLinkMessageRaw::V1(msg) => msg.raw_bytes(),
+            MAVLinkMessageRaw::V2(msg) => msg.raw_bytes(),
+        }
+    }
+
+    #[test]
+    pub fn test_parse_char() {
+        let mut parser = MavParser::<MavMessage>::new(ReadVersion::Any);
+
+        let mut stream = vec![0x00, 0x13];
+        stream.extend_from_slice(HEARTBEAT_V2);
+        stream.extend_from_slice(HEARTBEAT_V1);
+
+        let mut messages = Vec::new();
+        for (i, byte) in stream.iter().enumerate() {
+            if let Some(msg) = parser.parse_char(*byte) {
+                messages.push((i, msg));
+            }
+        }
+
+        assert_eq!(messages.len(), 2);
+        // messages are emitted with their last byte
+        assert_eq!(messages[0].0, 2 + HEARTBEAT_V2.len() - 1);
+        assert_eq!(raw_bytes(&messages[0].1), HEARTBEAT_V2);
+        assert_eq!(messages[1].0, stream.len() - 1);
+        assert_eq!(raw_bytes(&messages[1].1), HEARTBEAT_V1);
+    }
+
+    #[test]
+    pub fn test_parse_slices() {
+        let mut parser = MavParser::<MavMessage>::default();
+
+        let mut stream = Vec::new();
+        for _ in 0..3 {
+            stream.extend_from_slice(HEARTBEAT_V2);
+        }
+
+        let mut count = 0;
+        for chunk in stream.chunks(7) {
+            for msg in parser.parse(chunk) {
+                assert_eq!(msg.version(), MavlinkVersion::V2);
+                assert_eq!(msg.message_id(), 0);
+                count += 1;
+            }
+        }
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    pub fn test_resync_after_false_stx() {
+        let mut parser = MavParser::<MavMessage>::new(MavlinkVersion::V1.into());
+
+        // a stray STX with a large payload length swallows the following messages
+        let mut stream = vec![mavlink::MAV_STX, 200];
+        stream.extend_from_slice(HEARTBEAT_V1);
+        stream.extend_from_slice(HEARTBEAT_V1);
+        stream.extend_from_slice(&[0; 200]);
+
+        let messages: Vec<_> = parser.parse(&stream).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages.iter().all(|msg| raw_bytes(msg) == HEARTBEAT_V1));
+    }
+}
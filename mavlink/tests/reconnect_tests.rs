@@ -0,0 +1,113 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "tcp", feature = "common"))]
+mod test_reconnect {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    use mavlink::common::MavMessage;
+    use mavlink::{
+        Connectable, MavConnection, MavlinkVersion, ReconnectPolicy, TcpConfig, TcpMode,
+    };
+
+    fn policy() -> ReconnectPolicy {
+        ReconnectPolicy::new()
+            .initial_backoff(Duration::from_millis(10))
+            .max_backoff(Duration::from_millis(20))
+    }
+
+    /// Test that a TCP client connects again after the server dropped it and keeps its settings
+    #[test]
+    fn test_tcp_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:14573").unwrap();
+        let mut client = TcpConfig::new("127.0.0.1:14573".to_owned(), TcpMode::TcpOut)
+            .reconnect(policy())
+            .connect::<MavMessage>()
+            .expect("Couldn't connect");
+        client.set_protocol_version(MavlinkVersion::V1);
+
+        let (stream, _) = listener.accept().unwrap();
+        drop(stream);
+
+        // the lost connection is noticed and replaced by a new one without any data yet
+        let err = client.recv().unwrap_err();
+        assert!(matches!(err, mavlink::error::MessageReadError::Io(_)));
+        let (mut stream, _) = listener.accept().unwrap();
+
+        stream.write_all(crate::test_shared::HEARTBEAT_V1).unwrap();
+        let (header, msg) = client.recv().unwrap();
+        assert_eq!(header, crate::test_shared::COMMON_MSG_HEADER);
+        assert!(matches!(msg, MavMessage::HEARTBEAT(_)));
+
+        client
+            .send_default(&MavMessage::HEARTBEAT(
+                crate::test_shared::get_heartbeat_msg(),
+            ))
+            .unwrap();
+        let mut magic = [0];
+        stream.read_exact(&mut magic).unwrap();
+        assert_eq!(magic[0], mavlink::MAV_STX);
+    }
+
+    /// Test that `try_recv` does not wait for the link to be re-established
+    #[test]
+    fn test_tcp_try_recv_marks_lost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let client = TcpConfig::new(address, TcpMode::TcpOut)
+            .reconnect(policy())
+            .connect::<MavMessage>()
+            .expect("Couldn't connect");
+
+        let (stream, _) = listener.accept().unwrap();
+        drop(stream);
+        thread::sleep(Duration::from_millis(50));
+
+        // the lost link is only reported, no new connection is made
+        assert!(client.try_recv().is_err());
+        let err = client.try_recv().unwrap_err();
+        let mavlink::error::MessageReadError::Io(err) = err else {
+            panic!("unexpected error {err:?}");
+        };
+        assert_eq!(err.kind(), std::io::ErrorKind::NotConnected);
+        listener.set_nonblocking(true).unwrap();
+        assert!(listener.accept().is_err());
+        listener.set_nonblocking(false).unwrap();
+
+        // the next blocking call re-establishes it
+        let mut stream = thread::scope(|scope| {
+            let accepted = scope.spawn(|| listener.accept().unwrap().0);
+            client
+                .send_default(&MavMessage::HEARTBEAT(
+                    crate::test_shared::get_heartbeat_msg(),
+                ))
+                .unwrap();
+            accepted.join().unwrap()
+        });
+        let mut magic = [0];
+        stream.read_exact(&mut magic).unwrap();
+        assert_eq!(magic[0], mavlink::MAV_STX_V2);
+    }
+
+    /// Test that the error is returned once the policy gives up
+    #[test]
+    fn test_tcp_reconnect_max_attempts() {
+        let listener = TcpListener::bind("127.0.0.1:14574").unwrap();
+        let client = TcpConfig::new("127.0.0.1:14574".to_owned(), TcpMode::TcpOut)
+            .reconnect(policy().max_attempts(3))
+            .connect::<MavMessage>()
+            .expect("Couldn't connect");
+
+        let (stream, _) = listener.accept().unwrap();
+        drop(listener);
+        drop(stream);
+
+        let err = client.recv().unwrap_err();
+        let mavlink::error::MessageReadError::Io(err) = err else {
+            panic!("unexpected error {err:?}");
+        };
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+    }
+}
@@ -0,0 +1,70 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "udp", feature = "common"))]
+mod test_routing {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use mavlink::common::MavMessage;
+    use mavlink::router::Router;
+    use mavlink::{MavConnection, MavHeader};
+
+    const HEADER_A: MavHeader = MavHeader {
+        system_id: 1,
+        component_id: 1,
+        sequence: 0,
+    };
+    const HEADER_B: MavHeader = MavHeader {
+        system_id: 42,
+        component_id: 84,
+        sequence: 0,
+    };
+
+    #[test]
+    fn test_udp_routing() {
+        let mut router = Router::<MavMessage>::new();
+        router.add_connection(mavlink::connect("udpin:127.0.0.1:14580").expect("Couldn't bind"));
+        router.add_connection(mavlink::connect("udpin:127.0.0.1:14581").expect("Couldn't bind"));
+        let router = Arc::new(router);
+        thread::spawn({
+            let router = router.clone();
+            move || router.run()
+        });
+
+        let client_a =
+            mavlink::connect::<MavMessage>("udpout:127.0.0.1:14580").expect("Couldn't connect");
+        let client_b =
+            mavlink::connect::<MavMessage>("udpout:127.0.0.1:14581").expect("Couldn't connect");
+
+        // the router has to learn about system 42 before messages can be routed to it
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        while !router.route_table().contains_key(&(42, 84)) {
+            client_b.send(&HEADER_B, &heartbeat).unwrap();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(router.route_table()[&(42, 84)].len(), 1);
+
+        // broadcast messages reach the other endpoint
+        client_a.send(&HEADER_A, &heartbeat).unwrap();
+        let (header, msg) = client_b.recv().unwrap();
+        assert_eq!(header.system_id, HEADER_A.system_id);
+        assert!(matches!(msg, MavMessage::HEARTBEAT(_)));
+
+        // messages for unknown systems are dropped
+        let mut command = crate::test_shared::get_cmd_nav_takeoff_msg();
+        command.target_system = 7;
+        client_a
+            .send(&HEADER_A, &MavMessage::COMMAND_INT(command.clone()))
+            .unwrap();
+
+        // targeted messages are forwarded unchanged
+        let command = crate::test_shared::get_cmd_nav_takeoff_msg();
+        client_a
+            .send(&HEADER_A, &MavMessage::COMMAND_INT(command.clone()))
+            .unwrap();
+        let (header, msg) = client_b.recv().unwrap();
+        assert_eq!(header.sequence, 2);
+        assert_eq!(msg, MavMessage::COMMAND_INT(command));
+    }
+}
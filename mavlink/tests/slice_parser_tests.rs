@@ -0,0 +1,82 @@
+mod test_shared;
+
+#[cfg(feature = "common")]
+mod test_slice_parser {
+    use crate::test_shared::{COMMON_MSG_HEADER, HEARTBEAT_V1, HEARTBEAT_V2};
+    use mavlink::common::MavMessage;
+    use mavlink::slice_parser::{parse_raw_message_ref, MAVLinkMessageRef, SliceParser};
+    use mavlink::{MAVLinkV2MessageRaw, MavlinkVersion, Message, ReadVersion};
+
+    #[test]
+    pub fn test_parse_v2_heartbeat_ref() {
+        let (consumed, msg) =
+            parse_raw_message_ref::<MavMessage>(HEARTBEAT_V2, MavlinkVersion::V2.into());
+        assert_eq!(consumed, HEARTBEAT_V2.len());
+        let Some(MAVLinkMessageRef::V2(msg)) = msg else {
+            panic!("Expected a MAVLink 2 frame");
+        };
+        assert_eq!(msg.raw_bytes(), HEARTBEAT_V2);
+        assert_eq!(msg.sequence(), COMMON_MSG_HEADER.sequence);
+        assert_eq!(msg.system_id(), COMMON_MSG_HEADER.system_id);
+        assert_eq!(msg.component_id(), COMMON_MSG_HEADER.component_id);
+        assert_eq!(msg.message_id(), 0);
+        assert!(!msg.is_signed());
+        assert_eq!(msg.signature(), None);
+
+        let parsed = MavMessage::parse(MavlinkVersion::V2, msg.message_id(), msg.payload())
+            .expect("Failed to parse payload");
+        assert_eq!(
+            parsed,
+            MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg())
+        );
+
+        let raw = MAVLinkV2MessageRaw::from(msg);
+        assert_eq!(raw.raw_bytes(), HEARTBEAT_V2);
+    }
+
+    #[test]
+    pub fn test_parse_skips_garbage_and_bad_crc() {
+        let mut buf = vec![0x00, 0x42];
+        buf.extend_from_slice(HEARTBEAT_V1);
+        // corrupt the checksum of the first copy
+        let last = buf.len() - 1;
+        buf[last] ^= 0xff;
+        buf.extend_from_slice(&[0x13, 0x37]);
+        buf.extend_from_slice(HEARTBEAT_V1);
+
+        let (consumed, msg) = parse_raw_message_ref::<MavMessage>(&buf, ReadVersion::Any);
+        assert_eq!(consumed, buf.len());
+        let msg = msg.expect("Expected a frame");
+        assert_eq!(msg.version(), MavlinkVersion::V1);
+        assert_eq!(msg.raw_bytes(), HEARTBEAT_V1);
+    }
+
+    #[test]
+    pub fn test_parse_incomplete() {
+        let mut buf = vec![0x01, 0x02, 0x03];
+        buf.extend_from_slice(&HEARTBEAT_V2[..HEARTBEAT_V2.len() - 1]);
+
+        let (consumed, msg) = parse_raw_message_ref::<MavMessage>(&buf, ReadVersion::Any);
+        assert_eq!(consumed, 3);
+        assert!(msg.is_none());
+
+        // only frames of the requested version are considered
+        let (consumed, msg) = parse_raw_message_ref::<MavMessage>(&buf, MavlinkVersion::V1.into());
+        assert_eq!(consumed, buf.len());
+        assert!(msg.is_none());
+    }
+
+    #[test]
+    pub fn test_slice_parser_iterates_frames() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(HEARTBEAT_V2);
+        buf.extend_from_slice(HEARTBEAT_V1);
+        buf.extend_from_slice(&HEARTBEAT_V2[..5]);
+
+        let mut parser = SliceParser::<MavMessage>::new(&buf, ReadVersion::Any);
+        let versions: Vec<_> = parser.by_ref().map(|msg| msg.version()).collect();
+        assert_eq!(versions, [MavlinkVersion::V2, MavlinkVersion::V1]);
+        assert_eq!(parser.consumed(), HEARTBEAT_V2.len() + HEARTBEAT_V1.len());
+        assert_eq!(parser.remaining(), &HEARTBEAT_V2[..5]);
+    }
+}
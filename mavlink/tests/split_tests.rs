@@ -0,0 +1,110 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_split {
+    use std::io;
+    use std::thread;
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::MavConnection;
+
+    fn heartbeat() -> MavMessage {
+        MavMessage::HEARTBEAT(get_heartbeat_msg())
+    }
+
+    #[test]
+    pub fn test_split_recv_does_not_block_send() {
+        let vehicle = mavlink::connect::<MavMessage>("mem:test_split").unwrap();
+        let gcs = mavlink::connect::<MavMessage>("mem:test_split").unwrap();
+        let (receiver, sender) = vehicle.split();
+
+        // blocks until the gcs answers the messages sent in the meantime
+        let receiving = thread::spawn(move || receiver.recv().unwrap());
+
+        let other_sender = sender.clone();
+        for _ in 0..2 {
+            sender.send(&COMMON_MSG_HEADER, &heartbeat()).unwrap();
+            other_sender.send(&COMMON_MSG_HEADER, &heartbeat()).unwrap();
+        }
+        // the clones share the sequence number of the connection
+        for sequence in 0..4 {
+            let (header, msg) = gcs.recv().unwrap();
+            assert_eq!(header.sequence, sequence);
+            assert_eq!(msg, heartbeat());
+        }
+
+        gcs.send_default(&heartbeat()).unwrap();
+        let (_, msg) = receiving.join().unwrap();
+        assert_eq!(msg, heartbeat());
+    }
+
+    #[test]
+    pub fn test_split_try_recv_while_sending() {
+        const COUNT: u32 = 1000;
+
+        let vehicle = mavlink::connect::<MavMessage>("mem:test_split_try_recv").unwrap();
+        let gcs = mavlink::connect::<MavMessage>("mem:test_split_try_recv").unwrap();
+        let server = thread::spawn(move || {
+            let mut received = 0;
+            while received < COUNT {
+                match vehicle.recv() {
+                    Ok(_) => received += 1,
+                    Err(e) => panic!("unexpected error {e:?}"),
+                }
+            }
+            vehicle.send_default(&heartbeat()).unwrap();
+        });
+
+        let (receiver, sender) = gcs.split();
+        let sending = thread::spawn(move || {
+            for _ in 0..COUNT {
+                sender.send_default(&heartbeat()).unwrap();
+            }
+        });
+
+        // polling the receiver must not fail any send made in the meantime
+        let msg = loop {
+            match receiver.try_recv() {
+                Ok((_, msg)) => break msg,
+                Err(MessageReadError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => panic!("unexpected error {e:?}"),
+            }
+        };
+        assert_eq!(msg, heartbeat());
+        sending.join().unwrap();
+        server.join().unwrap();
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_split {
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::MavMessage;
+    use mavlink::{AsyncMavConnection, AsyncMemConnection};
+
+    #[tokio::test]
+    pub async fn test_async_split() {
+        let (vehicle, gcs) = AsyncMemConnection::pair();
+        let vehicle: Box<dyn AsyncMavConnection<MavMessage> + Sync + Send> = Box::new(vehicle);
+        let (receiver, sender) = vehicle.split();
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+
+        let receiving = tokio::spawn(async move { receiver.recv().await.unwrap() });
+
+        for _ in 0..3 {
+            sender.send(&COMMON_MSG_HEADER, &heartbeat).await.unwrap();
+        }
+        for sequence in 0..3 {
+            let (header, _) = AsyncMavConnection::<MavMessage>::recv(&gcs).await.unwrap();
+            assert_eq!(header.sequence, sequence);
+        }
+
+        AsyncMavConnection::<MavMessage>::send_default(&gcs, &heartbeat)
+            .await
+            .unwrap();
+        let (_, msg) = receiving.await.unwrap();
+        assert_eq!(msg, heartbeat);
+    }
+}
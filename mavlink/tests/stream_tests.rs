@@ -0,0 +1,149 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_stream {
+    use std::io::{self, Cursor, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER, HEARTBEAT_V2};
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::{Connection, MavConnection, StreamConnection};
+
+    /// Writer recording what is written to it
+    struct Recorder(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Recorder {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    pub fn test_stream_connection() {
+        let output = Arc::default();
+        let input = Cursor::new(HEARTBEAT_V2.to_vec());
+        let connection: Connection<MavMessage> =
+            StreamConnection::from_halves(input, Recorder(Arc::clone(&output))).into();
+
+        let (header, msg) = connection.recv().unwrap();
+        assert_eq!(header, COMMON_MSG_HEADER);
+        assert_eq!(msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+        assert_eq!(connection.link_stats().read.frames_received, 1);
+
+        let err = connection.recv().unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+
+        // the connection assigns its own sequence numbers
+        connection.send(&COMMON_MSG_HEADER, &msg).unwrap();
+        let written = output.lock().unwrap().clone();
+        assert_eq!(written.len(), HEARTBEAT_V2.len());
+        assert_eq!(written[4], 0);
+        assert_eq!(written[5..10], HEARTBEAT_V2[5..10]);
+    }
+
+    /// Stream reading from a buffer and recording what is written to it
+    struct Loopback {
+        input: Cursor<Vec<u8>>,
+        output: Recorder,
+    }
+
+    impl Read for Loopback {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Loopback {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.output.flush()
+        }
+    }
+
+    #[test]
+    pub fn test_stream_connection_single_stream() {
+        let output = Arc::default();
+        let stream = Loopback {
+            input: Cursor::new(HEARTBEAT_V2.to_vec()),
+            output: Recorder(Arc::clone(&output)),
+        };
+        let connection: Connection<MavMessage> = StreamConnection::new(stream).into();
+
+        // waiting for a stream of unknown type can not be limited
+        let err = connection
+            .recv_timeout(Duration::from_millis(10))
+            .unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::Unsupported));
+        let err = connection.try_recv().unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::Unsupported));
+
+        let (_, msg) = connection.recv().unwrap();
+        assert_eq!(msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+        connection.send(&COMMON_MSG_HEADER, &msg).unwrap();
+        assert_eq!(output.lock().unwrap().len(), HEARTBEAT_V2.len());
+    }
+
+    #[test]
+    pub fn test_stream_connection_halves() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        let vehicle = StreamConnection::from_halves(client.try_clone().unwrap(), client);
+        let gcs = StreamConnection::from_halves(server.try_clone().unwrap(), server);
+
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+        for _ in 0..3 {
+            MavConnection::<MavMessage>::send(&vehicle, &COMMON_MSG_HEADER, &heartbeat).unwrap();
+        }
+        for sequence in 0..3 {
+            let (header, msg) = MavConnection::<MavMessage>::recv(&gcs).unwrap();
+            assert_eq!(header.sequence, sequence);
+            assert_eq!(msg, heartbeat);
+        }
+
+        drop(vehicle);
+        let err = MavConnection::<MavMessage>::recv_raw(&gcs).unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_stream {
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::MavMessage;
+    use mavlink::{AsyncMavConnection, AsyncStreamConnection};
+
+    #[tokio::test]
+    pub async fn test_async_stream_connection() {
+        let (a, b) = tokio::io::duplex(1024);
+        let vehicle = AsyncStreamConnection::new(a);
+        let gcs = AsyncStreamConnection::new(b);
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+
+        AsyncMavConnection::<MavMessage>::send(&vehicle, &COMMON_MSG_HEADER, &heartbeat)
+            .await
+            .unwrap();
+        let (header, msg) = AsyncMavConnection::<MavMessage>::recv(&gcs).await.unwrap();
+        assert_eq!(header.system_id, COMMON_MSG_HEADER.system_id);
+        assert_eq!(msg, heartbeat);
+
+        AsyncMavConnection::<MavMessage>::send_default(&gcs, &heartbeat)
+            .await
+            .unwrap();
+        let raw = AsyncMavConnection::<MavMessage>::recv_raw(&vehicle)
+            .await
+            .unwrap();
+        assert_eq!(raw.sequence(), 0);
+    }
+}
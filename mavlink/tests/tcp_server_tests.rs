@@ -0,0 +1,151 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "tcp", feature = "common"))]
+mod test_tcp_server {
+    use std::thread;
+    use std::time::Duration;
+
+    use mavlink::common::MavMessage;
+    use mavlink::{MavConnection, MavHeader, TcpServerConnection};
+
+    fn header(system_id: u8) -> MavHeader {
+        MavHeader {
+            system_id,
+            component_id: 1,
+            sequence: 0,
+        }
+    }
+
+    /// Test that the server serves multiple clients and tells them apart
+    #[test]
+    fn test_tcp_server_clients() {
+        let server = TcpServerConnection::bind("127.0.0.1:14570").expect("Couldn't bind server");
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let client_a =
+            mavlink::connect::<MavMessage>("tcpout:127.0.0.1:14570").expect("Couldn't connect");
+        let client_b =
+            mavlink::connect::<MavMessage>("tcpout:127.0.0.1:14570").expect("Couldn't connect");
+
+        client_a.send(&header(1), &heartbeat).unwrap();
+        let (peer_a, header_a, _) = server.recv_from::<MavMessage>().unwrap();
+        assert_eq!(header_a.system_id, 1);
+        client_b.send(&header(2), &heartbeat).unwrap();
+        let (peer_b, header_b, _) = server.recv_from::<MavMessage>().unwrap();
+        assert_eq!(header_b.system_id, 2);
+        assert_ne!(peer_a, peer_b);
+        assert_eq!(server.peers().len(), 2);
+
+        // messages are sent to all clients
+        server.send(&header(255), &heartbeat).unwrap();
+        assert_eq!(client_a.recv().unwrap().0.system_id, 255);
+        assert_eq!(client_b.recv().unwrap().0.system_id, 255);
+
+        // or just one of them
+        server.send_to(peer_b, &header(254), &heartbeat).unwrap();
+        assert_eq!(client_b.recv().unwrap().0.system_id, 254);
+
+        // disconnected clients are dropped
+        drop(client_a);
+        for _ in 0..50 {
+            if server.peers() == [peer_b] {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(server.peers(), [peer_b]);
+        assert!(server.send_to(peer_a, &header(255), &heartbeat).is_err());
+    }
+
+    /// Test that frames sent in parts by multiple clients are not mixed up
+    #[test]
+    fn test_tcp_server_interleaved() {
+        use std::io::Write;
+        use std::net::TcpStream;
+
+        let server = mavlink::connect::<MavMessage>("tcpserver:127.0.0.1:14571")
+            .expect("Couldn't bind server");
+        let mut client_a = TcpStream::connect("127.0.0.1:14571").unwrap();
+        let mut client_b = TcpStream::connect("127.0.0.1:14571").unwrap();
+
+        let (first, second) = crate::test_shared::HEARTBEAT_V2.split_at(8);
+        client_a.write_all(first).unwrap();
+        client_b.write_all(first).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        client_a.write_all(second).unwrap();
+        client_b.write_all(second).unwrap();
+
+        for _ in 0..2 {
+            let (header, msg) = server.recv().unwrap();
+            assert_eq!(header, crate::test_shared::COMMON_MSG_HEADER);
+            assert!(matches!(msg, MavMessage::HEARTBEAT(_)));
+        }
+        assert_eq!(server.link_stats().read.frames_received, 2);
+    }
+
+    /// Test that a client that stopped reading is dropped instead of blocking the server
+    #[test]
+    fn test_tcp_server_drops_stalled_client() {
+        use std::net::TcpStream;
+        use std::time::Instant;
+
+        let server = TcpServerConnection::bind("127.0.0.1:14578").expect("Couldn't bind server");
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        let _stalled = TcpStream::connect("127.0.0.1:14578").unwrap();
+        for _ in 0..50 {
+            if !server.peers().is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(server.peers().len(), 1);
+
+        // keep sending until the socket buffers are full and the write times out
+        for _ in 0..1_000_000 {
+            let start = Instant::now();
+            server.send(&header(255), &heartbeat).unwrap();
+            assert!(start.elapsed() < Duration::from_secs(2));
+            if server.peers().is_empty() {
+                return;
+            }
+        }
+        panic!("stalled client was not dropped");
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "tcp", feature = "common"))]
+mod test_async_tcp_server {
+    use mavlink::common::MavMessage;
+    use mavlink::{AsyncMavConnection, AsyncTcpServerConnection, MavHeader};
+
+    /// Test that the async server serves multiple clients and tells them apart
+    #[tokio::test]
+    async fn test_async_tcp_server_clients() {
+        let server = AsyncTcpServerConnection::bind("127.0.0.1:14572")
+            .await
+            .expect("Couldn't bind server");
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let mut clients = Vec::new();
+        for system_id in 1..=2 {
+            let client = mavlink::connect_async::<MavMessage>("tcpout:127.0.0.1:14572")
+                .await
+                .expect("Couldn't connect");
+            let header = MavHeader {
+                system_id,
+                ..Default::default()
+            };
+            client.send(&header, &heartbeat).await.unwrap();
+            let (_, received, _) = server.recv_from::<MavMessage>().await.unwrap();
+            assert_eq!(received.system_id, system_id);
+            clients.push(client);
+        }
+        assert_eq!(server.peers().await.len(), 2);
+
+        server.send_default(&heartbeat).await.unwrap();
+        for client in &clients {
+            let (header, _) = client.recv().await.unwrap();
+            assert_eq!(header.system_id, MavHeader::default().system_id);
+        }
+    }
+}
@@ -157,6 +157,59 @@ pub fn get_apm_mount_status() -> mavlink::ardupilotmega::MOUNT_STATUS_DATA {
     }
 }
 
+/// Header of the simulated vehicles the microservice clients talk to
+pub const VEHICLE_HEADER: mavlink::MavHeader = mavlink::MavHeader {
+    system_id: 1,
+    component_id: 1,
+    sequence: 0,
+};
+
+/// Answer the messages received on the in-memory connection `mem:<name>` like a vehicle
+///
+/// `respond` returns the replies to each received message. The responder stops once the other
+/// end of the connection is dropped.
+#[cfg(all(feature = "std", feature = "common"))]
+pub fn spawn_responder<F>(name: &str, mut respond: F)
+where
+    F: FnMut(mavlink::common::MavMessage) -> Vec<mavlink::common::MavMessage> + Send + 'static,
+{
+    use mavlink::MavConnection;
+
+    let vehicle = mavlink::connect::<mavlink::common::MavMessage>(&format!("mem:{name}"))
+        .expect("Couldn't connect");
+    std::thread::spawn(move || {
+        while let Ok((_, msg)) = vehicle.recv() {
+            for reply in respond(msg) {
+                if vehicle.send(&VEHICLE_HEADER, &reply).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Async version of [`spawn_responder`], answering on a tokio task
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+pub async fn spawn_async_responder<F>(name: &str, mut respond: F)
+where
+    F: FnMut(mavlink::common::MavMessage) -> Vec<mavlink::common::MavMessage> + Send + 'static,
+{
+    use mavlink::AsyncMavConnection;
+
+    let vehicle = mavlink::connect_async::<mavlink::common::MavMessage>(&format!("mem:{name}"))
+        .await
+        .expect("Couldn't connect");
+    tokio::spawn(async move {
+        while let Ok((_, msg)) = vehicle.recv().await {
+            for reply in respond(msg) {
+                if vehicle.send(&VEHICLE_HEADER, &reply).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+}
+
 pub struct BlockyReader<'a> {
     block_next_read: bool,
     data: &'a [u8],
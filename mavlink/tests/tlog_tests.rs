@@ -0,0 +1,350 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_tlog {
+    use std::io::{self, Cursor};
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER, HEARTBEAT_V1, HEARTBEAT_V2};
+    use mavlink::common::{MavMessage, PARAM_REQUEST_LIST_DATA};
+    use mavlink::error::MessageReadError;
+    use mavlink::tlog::{TlogReader, TlogReplay, TlogWriter, TLOG_TIMESTAMP_SIZE};
+    use mavlink::MavConnection;
+
+    pub fn now_us() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64
+    }
+
+    /// Split a telemetry log of unsigned MAVLink 2 frames into timestamps and frames
+    pub fn records(mut data: &[u8]) -> Vec<(u64, &[u8])> {
+        let mut records = Vec::new();
+        while !data.is_empty() {
+            let (timestamp, rest) = data.split_at(TLOG_TIMESTAMP_SIZE);
+            let (frame, rest) = rest.split_at(12 + usize::from(rest[1]));
+            records.push((u64::from_be_bytes(timestamp.try_into().unwrap()), frame));
+            data = rest;
+        }
+        records
+    }
+
+    #[test]
+    fn test_tlog_writer() {
+        let mut writer = TlogWriter::new(Vec::new());
+        writer
+            .write_frame_at(0x0102_0304_0506_0708, &[0xfd, 0])
+            .unwrap();
+        writer.write_frame(&[0xfd, 0]).unwrap();
+
+        let data = writer.into_inner();
+        assert_eq!(data[..10], [1, 2, 3, 4, 5, 6, 7, 8, 0xfd, 0]);
+        let timestamp = u64::from_be_bytes(data[10..18].try_into().unwrap());
+        assert!(timestamp.abs_diff(now_us()) < 10_000_000);
+    }
+
+    /// Telemetry log of heartbeats recorded at the given timestamps
+    fn heartbeat_log(timestamps: &[u64]) -> Vec<u8> {
+        let mut writer = TlogWriter::new(Vec::new());
+        for timestamp in timestamps {
+            writer.write_frame_at(*timestamp, HEARTBEAT_V2).unwrap();
+        }
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_tlog_reader() {
+        let mut data = Vec::new();
+        TlogWriter::new(&mut data)
+            .write_frame_at(1, HEARTBEAT_V2)
+            .unwrap();
+        data.extend_from_slice(&[0xfd, 0xfe, 0x00]);
+        let mut writer = TlogWriter::new(&mut data);
+        writer.write_frame_at(2, HEARTBEAT_V1).unwrap();
+        writer.write_frame_at(3, HEARTBEAT_V2).unwrap();
+        // truncated last record
+        data.truncate(data.len() - 4);
+
+        let mut reader = TlogReader::new(Cursor::new(data));
+        let records: Vec<_> = reader.by_ref().map(Result::unwrap).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].0, 1);
+        assert_eq!(records[0].1.raw_bytes(), HEARTBEAT_V2);
+        assert_eq!(records[1].0, 2);
+        assert_eq!(records[1].1.raw_bytes(), HEARTBEAT_V1);
+        assert_eq!(reader.skipped_bytes(), 3);
+    }
+
+    #[test]
+    fn test_tlog_reader_log_file() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/log.tlog");
+        let mut reader = TlogReader::open(path).unwrap();
+        let mut count = 0;
+        let mut last_timestamp = 0;
+        for record in reader.by_ref() {
+            let (timestamp, _) = record.unwrap();
+            assert!(timestamp >= last_timestamp);
+            last_timestamp = timestamp;
+            count += 1;
+        }
+        assert_eq!(count, 1426);
+        assert_eq!(reader.skipped_bytes(), 0);
+    }
+
+    /// Test that a replay keeps the scaled pace of the recording
+    #[test]
+    fn test_tlog_replay() {
+        let start = now_us();
+        let log = heartbeat_log(&[start, start + 100_000, start + 200_000]);
+        let replay = TlogReplay::new(Cursor::new(log)).speed(2.0);
+
+        let begin = Instant::now();
+        let (header, msg) = MavConnection::<MavMessage>::recv(&replay).unwrap();
+        assert_eq!(header, COMMON_MSG_HEADER);
+        assert_eq!(msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+
+        let err = MavConnection::<MavMessage>::try_recv(&replay).unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::WouldBlock));
+        std::thread::sleep(Duration::from_millis(60));
+        MavConnection::<MavMessage>::try_recv(&replay).unwrap();
+
+        MavConnection::<MavMessage>::recv(&replay).unwrap();
+        let elapsed = begin.elapsed();
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_millis(500));
+
+        let err = MavConnection::<MavMessage>::recv(&replay).unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+        let stats = MavConnection::<MavMessage>::link_stats(&replay);
+        assert_eq!(stats.read.frames_received, 3);
+    }
+
+    #[test]
+    fn test_tlog_replay_unpaced() {
+        let log = heartbeat_log(&[0, 3_600_000_000]);
+        let replay = TlogReplay::new(Cursor::new(log)).speed(f64::INFINITY);
+        for _ in 0..2 {
+            MavConnection::<MavMessage>::try_recv(&replay).unwrap();
+        }
+    }
+
+    /// Test writing a session with `fileout` and reading it back with `file`
+    #[test]
+    fn test_fileout_connection() {
+        let path = std::env::temp_dir().join("mavlink_fileout_test.tlog");
+        let address = format!("fileout:{}", path.display());
+        let start = now_us();
+
+        let messages = [
+            MavMessage::HEARTBEAT(get_heartbeat_msg()),
+            MavMessage::PARAM_REQUEST_LIST(PARAM_REQUEST_LIST_DATA {
+                target_system: 1,
+                target_component: 1,
+            }),
+        ];
+        let writer = mavlink::connect::<MavMessage>(&address).unwrap();
+        for msg in &messages {
+            writer.send(&COMMON_MSG_HEADER, msg).unwrap();
+        }
+        let err = writer.recv().unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::Unsupported));
+        drop(writer);
+
+        let data = std::fs::read(&path).unwrap();
+        let records = records(&data);
+        assert_eq!(records.len(), 2);
+        for (i, (timestamp, frame)) in records.iter().enumerate() {
+            assert!((start..=now_us()).contains(timestamp));
+            assert_eq!(frame[0], mavlink::MAV_STX_V2);
+            // the connection numbers its frames itself
+            assert_eq!(frame[4], i as u8);
+        }
+        assert!(records[0].0 <= records[1].0);
+
+        let reader = mavlink::connect::<MavMessage>(&format!("file:{}", path.display())).unwrap();
+        for msg in &messages {
+            let (header, received) = reader.recv().unwrap();
+            assert_eq!(header.system_id, COMMON_MSG_HEADER.system_id);
+            assert_eq!(&received, msg);
+        }
+        std::fs::remove_file(&path).ok();
+    }
+}
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_tlog_index {
+    use std::io::Cursor;
+
+    use crate::test_shared::{COMMON_MSG_HEADER, HEARTBEAT_V2};
+    use mavlink::common::{MavMessage, PARAM_REQUEST_LIST_DATA};
+    use mavlink::tlog::index::{IndexedTlogReader, TlogIndex};
+    use mavlink::tlog::TlogWriter;
+    use mavlink::{MAVLinkV2MessageRaw, MessageData};
+
+    const HEARTBEAT_ID: u32 = 0;
+    const PARAM_REQUEST_LIST_ID: u32 = PARAM_REQUEST_LIST_DATA::ID;
+
+    /// A heartbeat every second and a parameter request every other second
+    fn log() -> Vec<u8> {
+        let mut request = MAVLinkV2MessageRaw::new();
+        request.serialize_message(
+            COMMON_MSG_HEADER,
+            &MavMessage::PARAM_REQUEST_LIST(PARAM_REQUEST_LIST_DATA {
+                target_system: 1,
+                target_component: 1,
+            }),
+        );
+
+        let mut writer = TlogWriter::new(Vec::new());
+        for second in 0..10 {
+            let timestamp = 1_000_000 * second;
+            writer.write_frame_at(timestamp, HEARTBEAT_V2).unwrap();
+            if second % 2 == 1 {
+                writer
+                    .write_frame_at(timestamp + 1, request.raw_bytes())
+                    .unwrap();
+            }
+        }
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_tlog_index() {
+        let mut reader = IndexedTlogReader::new(Cursor::new(log())).unwrap();
+        let index = reader.index();
+        assert_eq!(index.len(), 15);
+        assert_eq!(index.count(HEARTBEAT_ID), 10);
+        assert_eq!(index.count(PARAM_REQUEST_LIST_ID), 5);
+        assert_eq!(
+            index.counts().into_iter().collect::<Vec<_>>(),
+            [(HEARTBEAT_ID, 10), (PARAM_REQUEST_LIST_ID, 5)]
+        );
+        assert_eq!(index.time_range(), Some((0, 9_000_001)));
+        assert_eq!(index.entries()[1].offset, 8 + HEARTBEAT_V2.len() as u64);
+
+        reader.seek_to_time(4_500_000);
+        let (timestamp, frame) = reader.read_record().unwrap();
+        assert_eq!(timestamp, 5_000_000);
+        assert_eq!(frame.raw_bytes(), HEARTBEAT_V2);
+
+        reader.select([PARAM_REQUEST_LIST_ID]);
+        let timestamps: Vec<_> = reader.by_ref().map(|record| record.unwrap().0).collect();
+        assert_eq!(timestamps, [5_000_001, 7_000_001, 9_000_001]);
+
+        reader.select_all();
+        reader.seek_to_record(0);
+        assert_eq!(reader.count(), 15);
+    }
+
+    #[test]
+    fn test_tlog_index_persistence() {
+        let index = TlogIndex::build(Cursor::new(log())).unwrap();
+        let mut saved = Vec::new();
+        index.save(&mut saved).unwrap();
+        assert_eq!(TlogIndex::load(saved.as_slice()).unwrap(), index);
+        assert!(TlogIndex::load(&saved[1..]).is_err());
+
+        let dir = std::env::temp_dir();
+        let log_path = dir.join("mavlink_index_test.tlog");
+        let index_path = dir.join("mavlink_index_test.tlog.idx");
+        std::fs::write(&log_path, log()).unwrap();
+        std::fs::remove_file(&index_path).ok();
+
+        let reader = IndexedTlogReader::open_with_index(&log_path, &index_path).unwrap();
+        assert_eq!(reader.index(), &index);
+        let saved = TlogIndex::load(std::fs::File::open(&index_path).unwrap()).unwrap();
+        assert_eq!(saved, index);
+
+        // a grown log is indexed again
+        let mut grown = log();
+        grown.extend_from_slice(&log());
+        std::fs::write(&log_path, &grown).unwrap();
+        let reader = IndexedTlogReader::open_with_index(&log_path, &index_path).unwrap();
+        assert_eq!(reader.index().len(), 30);
+        assert_eq!(reader.count(), 30);
+
+        std::fs::remove_file(&log_path).ok();
+        std::fs::remove_file(&index_path).ok();
+    }
+}
+
+#[cfg(all(feature = "std", feature = "common"))]
+mod test_tlog_recorder {
+    use std::sync::mpsc;
+    use std::thread;
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use crate::test_tlog::{now_us, records};
+    use mavlink::common::{MavMessage, PARAM_REQUEST_LIST_DATA};
+    use mavlink::tlog::TlogRecorder;
+    use mavlink::{MavConnection, MavHeader};
+
+    /// Test that the traffic of a connection is recorded in both directions
+    #[test]
+    fn test_tlog_recorder() {
+        let vehicle = mavlink::connect::<MavMessage>("mem:test_tlog_recorder").unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let request = vehicle.recv_raw().unwrap();
+            tx.send(request.raw_bytes().to_vec()).unwrap();
+            vehicle
+                .send(
+                    &COMMON_MSG_HEADER,
+                    &MavMessage::HEARTBEAT(get_heartbeat_msg()),
+                )
+                .unwrap();
+        });
+
+        let connection = mavlink::connect::<MavMessage>("mem:test_tlog_recorder").unwrap();
+        let recorder = TlogRecorder::new(connection, Vec::new());
+        let start = now_us();
+
+        let request = MavMessage::PARAM_REQUEST_LIST(PARAM_REQUEST_LIST_DATA {
+            target_system: 1,
+            target_component: 1,
+        });
+        recorder.send(&MavHeader::default(), &request).unwrap();
+        let (header, msg) = recorder.recv().unwrap();
+        assert_eq!(header.system_id, COMMON_MSG_HEADER.system_id);
+        assert_eq!(msg, MavMessage::HEARTBEAT(get_heartbeat_msg()));
+
+        let (_, log) = recorder.into_parts();
+        let records = records(&log);
+        assert_eq!(records.len(), 2);
+        assert!(records
+            .iter()
+            .all(|(timestamp, _)| (start..=now_us()).contains(timestamp)));
+        // the recorded request is exactly what the vehicle received
+        assert_eq!(records[0].1, rx.recv().unwrap());
+        assert_eq!(records[1].1[4], header.sequence);
+        assert_eq!(records[1].1[5], header.system_id);
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "common"))]
+mod test_async_tlog {
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use crate::test_tlog::records;
+    use mavlink::common::MavMessage;
+
+    /// Test writing a session with an async `fileout` connection
+    #[tokio::test]
+    async fn test_async_fileout_connection() {
+        let path = std::env::temp_dir().join("mavlink_async_fileout_test.tlog");
+        let writer = mavlink::connect_async::<MavMessage>(&format!("fileout:{}", path.display()))
+            .await
+            .unwrap();
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+        writer.send(&COMMON_MSG_HEADER, &heartbeat).await.unwrap();
+        writer.send(&COMMON_MSG_HEADER, &heartbeat).await.unwrap();
+        assert!(writer.recv().await.is_err());
+        drop(writer);
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let records = records(&data);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].1[4], 1);
+    }
+}
@@ -2,8 +2,11 @@ mod test_shared;
 
 #[cfg(all(feature = "std", feature = "udp", feature = "common"))]
 mod test_udp_connections {
+    use std::io;
     use std::thread;
+    use std::time::{Duration, Instant};
 
+    use mavlink::error::MessageReadError;
     use mavlink::{MavConnection, MessageData};
 
     /// Test whether we can send a message via UDP and receive it OK
@@ -88,4 +91,28 @@ mod test_udp_connections {
         }
         assert_eq!(recv_count, RECEIVE_CHECK_COUNT);
     }
+
+    /// Test whether recv_timeout gives up without data and still receives messages
+    #[test]
+    fn test_udp_recv_timeout() {
+        let server = mavlink::connect::<mavlink::common::MavMessage>("udpin:0.0.0.0:14563")
+            .expect("Couldn't create server");
+
+        let start = Instant::now();
+        let err = server.recv_timeout(Duration::from_millis(50)).unwrap_err();
+        assert!(matches!(
+            err,
+            MessageReadError::Io(e)
+                if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+        ));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        let client = mavlink::connect::<mavlink::common::MavMessage>("udpout:127.0.0.1:14563")
+            .expect("Couldn't create client");
+        let msg = mavlink::common::MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        client.send_default(&msg).unwrap();
+
+        let (_header, received) = server.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(received, msg);
+    }
 }
@@ -0,0 +1,138 @@
+mod test_shared;
+
+#[cfg(all(feature = "std", feature = "udp", feature = "common"))]
+mod test_udp_server {
+    use std::net::UdpSocket;
+    use std::thread;
+    use std::time::Duration;
+
+    use mavlink::common::MavMessage;
+    use mavlink::{
+        Connectable, MavConnection, MavHeader, MavlinkVersion, UdpConfig, UdpMode,
+        UdpServerConnection,
+    };
+
+    fn header(system_id: u8) -> MavHeader {
+        MavHeader {
+            system_id,
+            component_id: 1,
+            sequence: 0,
+        }
+    }
+
+    /// Test that the server replies to multiple clients and tells them apart
+    #[test]
+    fn test_udp_server_clients() {
+        let server = UdpServerConnection::bind("127.0.0.1:14575").expect("Couldn't bind server");
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let client_a =
+            mavlink::connect::<MavMessage>("udpout:127.0.0.1:14575").expect("Couldn't connect");
+        let client_b =
+            mavlink::connect::<MavMessage>("udpout:127.0.0.1:14575").expect("Couldn't connect");
+
+        client_a.send(&header(1), &heartbeat).unwrap();
+        let (peer_a, header_a, _) = server.recv_from::<MavMessage>().unwrap();
+        assert_eq!(header_a.system_id, 1);
+        client_b.send(&header(2), &heartbeat).unwrap();
+        let (peer_b, header_b, _) = server.recv_from::<MavMessage>().unwrap();
+        assert_eq!(header_b.system_id, 2);
+        assert_ne!(peer_a, peer_b);
+        assert_eq!(server.peers().len(), 2);
+
+        // messages are sent to all clients
+        MavConnection::<MavMessage>::send(&server, &header(255), &heartbeat).unwrap();
+        assert_eq!(client_a.recv().unwrap().0.system_id, 255);
+        assert_eq!(client_b.recv().unwrap().0.system_id, 255);
+
+        // or just one of them
+        server.send_to(peer_b, &header(254), &heartbeat).unwrap();
+        assert_eq!(client_b.recv().unwrap().0.system_id, 254);
+        assert!(client_a.try_recv().is_err());
+    }
+
+    /// Test that clients which stopped sending are forgotten
+    #[test]
+    fn test_udp_server_peer_timeout() {
+        let server = UdpConfig::new("127.0.0.1:14576".to_owned(), UdpMode::Udpserver)
+            .peer_timeout(Duration::from_millis(100))
+            .connect::<MavMessage>()
+            .expect("Couldn't bind server");
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let client =
+            mavlink::connect::<MavMessage>("udpout:127.0.0.1:14576").expect("Couldn't connect");
+        client.send(&header(1), &heartbeat).unwrap();
+        server.recv().unwrap();
+        assert!(server.send(&header(255), &heartbeat).unwrap() > 0);
+
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(server.send(&header(255), &heartbeat).unwrap(), 0);
+    }
+
+    /// Test that data buffered for a client is dropped when the client times out
+    #[test]
+    fn test_udp_server_peer_timeout_partial_frame() {
+        let mut server = UdpServerConnection::bind("127.0.0.1:0").expect("Couldn't bind server");
+        server.set_peer_timeout(Duration::from_millis(100));
+        let address = server.local_addr().unwrap();
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+        let mut frame = Vec::new();
+        mavlink::write_versioned_msg(&mut frame, MavlinkVersion::V2, header(1), &heartbeat)
+            .unwrap();
+
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client.send_to(&frame[..5], address).unwrap();
+        let result = MavConnection::<MavMessage>::recv_timeout(&server, Duration::from_millis(50));
+        assert!(result.is_err());
+
+        thread::sleep(Duration::from_millis(200));
+        assert!(server.peers().is_empty());
+        client.send_to(&frame, address).unwrap();
+        let (peer, received, _) = server.recv_from::<MavMessage>().unwrap();
+        assert_eq!(peer, client.local_addr().unwrap());
+        assert_eq!(received.system_id, 1);
+        // the partial frame was not taken for the start of the new one
+        let stats = MavConnection::<MavMessage>::link_stats(&server);
+        assert_eq!(stats.read.crc_errors, 0);
+    }
+}
+
+#[cfg(all(feature = "tokio-1", feature = "udp", feature = "common"))]
+mod test_async_udp_server {
+    use mavlink::common::MavMessage;
+    use mavlink::{AsyncMavConnection, AsyncUdpServerConnection, MavHeader};
+
+    /// Test that the async server replies to multiple clients and tells them apart
+    #[tokio::test]
+    async fn test_async_udp_server_clients() {
+        let server = AsyncUdpServerConnection::bind("127.0.0.1:14577")
+            .await
+            .expect("Couldn't bind server");
+        let heartbeat = MavMessage::HEARTBEAT(crate::test_shared::get_heartbeat_msg());
+
+        let mut clients = Vec::new();
+        for system_id in 1..=2 {
+            let client = mavlink::connect_async::<MavMessage>("udpout:127.0.0.1:14577")
+                .await
+                .expect("Couldn't connect");
+            let header = MavHeader {
+                system_id,
+                ..Default::default()
+            };
+            client.send(&header, &heartbeat).await.unwrap();
+            let (_, received, _) = server.recv_from::<MavMessage>().await.unwrap();
+            assert_eq!(received.system_id, system_id);
+            clients.push(client);
+        }
+        assert_eq!(server.peers().len(), 2);
+
+        AsyncMavConnection::<MavMessage>::send_default(&server, &heartbeat)
+            .await
+            .unwrap();
+        for client in &clients {
+            let (header, _) = client.recv().await.unwrap();
+            assert_eq!(header.system_id, MavHeader::default().system_id);
+        }
+    }
+}
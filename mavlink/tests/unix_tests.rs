@@ -0,0 +1,157 @@
+mod test_shared;
+
+#[cfg(all(feature = "unix", unix, feature = "common"))]
+mod test_unix {
+    use std::io;
+    use std::path::PathBuf;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::MavMessage;
+    use mavlink::error::MessageReadError;
+    use mavlink::{Connectable, MavConnection, UnixConfig, UnixMode};
+
+    pub fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mavlink-{}-{name}.sock", std::process::id()))
+    }
+
+    #[test]
+    pub fn test_unix_stream() {
+        let path = socket_path("stream");
+        let address = format!("unixin:{}", path.display());
+        let server = thread::spawn(move || {
+            let vehicle = mavlink::connect::<MavMessage>(&address).unwrap();
+            let (header, msg) = vehicle.recv().unwrap();
+            assert_eq!(header.system_id, COMMON_MSG_HEADER.system_id);
+            vehicle.send(&COMMON_MSG_HEADER, &msg).unwrap();
+        });
+
+        let address = format!("unix:{}", path.display());
+        let gcs = loop {
+            match mavlink::connect::<MavMessage>(&address) {
+                Ok(gcs) => break gcs,
+                Err(_) => thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+        gcs.send(&COMMON_MSG_HEADER, &heartbeat).unwrap();
+        let (_, msg) = gcs.recv().unwrap();
+        assert_eq!(msg, heartbeat);
+
+        server.join().unwrap();
+        let err = gcs.recv().unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+    }
+
+    #[test]
+    pub fn test_unix_datagram() {
+        let vehicle_path = socket_path("datagram-vehicle");
+        let gcs_path = socket_path("datagram-gcs");
+        let vehicle =
+            mavlink::connect::<MavMessage>(&format!("unixgram:{}", vehicle_path.display()))
+                .unwrap();
+        let gcs = UnixConfig::new(gcs_path, UnixMode::UnixGram)
+            .peer(vehicle_path)
+            .connect::<MavMessage>()
+            .unwrap();
+
+        let err = vehicle.try_recv().unwrap_err();
+        assert!(matches!(err, MessageReadError::Io(e) if e.kind() == io::ErrorKind::WouldBlock));
+        // the vehicle does not know where to send to yet
+        assert_eq!(
+            vehicle
+                .send_default(&MavMessage::HEARTBEAT(get_heartbeat_msg()))
+                .unwrap(),
+            0
+        );
+
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+        gcs.send(&COMMON_MSG_HEADER, &heartbeat).unwrap();
+        let (_, msg) = vehicle.recv().unwrap();
+        assert_eq!(msg, heartbeat);
+
+        // replies go to the last sender
+        vehicle.send(&COMMON_MSG_HEADER, &heartbeat).unwrap();
+        let (_, msg) = gcs.recv().unwrap();
+        assert_eq!(msg, heartbeat);
+    }
+
+    #[test]
+    pub fn test_unix_stale_socket() {
+        let path = socket_path("stale");
+        let config = UnixConfig::new(path, UnixMode::UnixGram);
+        let first = config.connect::<MavMessage>().unwrap();
+        drop(first);
+        // the socket file is still there, but nobody is bound to it anymore
+        config.connect::<MavMessage>().unwrap();
+    }
+
+    #[test]
+    pub fn test_unix_socket_in_use() {
+        let path = socket_path("in-use");
+        let config = UnixConfig::new(path.clone(), UnixMode::UnixGram);
+        let _first = config.connect::<MavMessage>().unwrap();
+        // a socket that is still bound is not taken over
+        let err = config.connect::<MavMessage>().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+        assert!(path.exists());
+    }
+}
+
+#[cfg(all(feature = "unix", unix, feature = "tokio-1", feature = "common"))]
+mod test_async_unix {
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::MavMessage;
+    use mavlink::{AsyncConnectable, UnixConfig, UnixMode};
+
+    #[tokio::test]
+    pub async fn test_async_unix_datagram() {
+        let vehicle_path = crate::test_unix::socket_path("async-vehicle");
+        let gcs_path = crate::test_unix::socket_path("async-gcs");
+        let vehicle =
+            mavlink::connect_async::<MavMessage>(&format!("unixgram:{}", vehicle_path.display()))
+                .await
+                .unwrap();
+        let gcs = UnixConfig::new(gcs_path, UnixMode::UnixGram)
+            .peer(vehicle_path)
+            .connect_async::<MavMessage>()
+            .await
+            .unwrap();
+
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+        gcs.send(&COMMON_MSG_HEADER, &heartbeat).await.unwrap();
+        let (_, msg) = vehicle.recv().await.unwrap();
+        assert_eq!(msg, heartbeat);
+
+        vehicle.send(&COMMON_MSG_HEADER, &heartbeat).await.unwrap();
+        let (_, msg) = gcs.recv().await.unwrap();
+        assert_eq!(msg, heartbeat);
+    }
+
+    #[tokio::test]
+    pub async fn test_async_unix_stream() {
+        let path = crate::test_unix::socket_path("async-stream");
+        let address = format!("unixin:{}", path.display());
+        let server = tokio::spawn(async move {
+            let vehicle = mavlink::connect_async::<MavMessage>(&address)
+                .await
+                .unwrap();
+            let (_, msg) = vehicle.recv().await.unwrap();
+            vehicle.send(&COMMON_MSG_HEADER, &msg).await.unwrap();
+        });
+
+        let address = format!("unix:{}", path.display());
+        let gcs = loop {
+            match mavlink::connect_async::<MavMessage>(&address).await {
+                Ok(gcs) => break gcs,
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+            }
+        };
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+        gcs.send(&COMMON_MSG_HEADER, &heartbeat).await.unwrap();
+        let (_, msg) = gcs.recv().await.unwrap();
+        assert_eq!(msg, heartbeat);
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,62 @@
+mod test_shared;
+
+#[cfg(all(feature = "websocket", feature = "common"))]
+mod test_websocket {
+    use std::io;
+
+    use crate::test_shared::{get_heartbeat_msg, COMMON_MSG_HEADER};
+    use mavlink::common::MavMessage;
+    use mavlink::{AsyncMavConnection, AsyncWebSocketServerConnection};
+
+    #[tokio::test]
+    pub async fn test_websocket_client_server() {
+        let server = AsyncWebSocketServerConnection::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let address = format!("ws://{}/mavlink", server.local_addr());
+        let gcs = mavlink::connect_async::<MavMessage>(&address)
+            .await
+            .unwrap();
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+
+        for _ in 0..3 {
+            gcs.send(&COMMON_MSG_HEADER, &heartbeat).await.unwrap();
+        }
+        for sequence in 0..3 {
+            let (header, msg) = AsyncMavConnection::<MavMessage>::recv(&server)
+                .await
+                .unwrap();
+            assert_eq!(header.sequence, sequence);
+            assert_eq!(msg, heartbeat);
+        }
+        assert_eq!(server.peers().await.len(), 1);
+        let stats = AsyncMavConnection::<MavMessage>::link_stats(&server);
+        assert_eq!(stats.read.frames_received, 3);
+
+        let sent = AsyncMavConnection::<MavMessage>::send(&server, &COMMON_MSG_HEADER, &heartbeat)
+            .await
+            .unwrap();
+        assert!(sent > 0);
+        let (header, msg) = gcs.recv().await.unwrap();
+        assert_eq!(header.system_id, COMMON_MSG_HEADER.system_id);
+        assert_eq!(msg, heartbeat);
+
+        drop(server);
+        assert!(gcs.recv().await.is_err());
+    }
+
+    #[tokio::test]
+    pub async fn test_websocket_server_without_clients() {
+        let server = mavlink::connect_async::<MavMessage>("wsserver:127.0.0.1:0")
+            .await
+            .unwrap();
+        let heartbeat = MavMessage::HEARTBEAT(get_heartbeat_msg());
+        assert_eq!(server.send_default(&heartbeat).await.unwrap(), 0);
+    }
+
+    #[test]
+    pub fn test_websocket_is_async_only() {
+        let result = mavlink::connect::<MavMessage>("ws://127.0.0.1:5761");
+        assert!(matches!(result, Err(e) if e.kind() == io::ErrorKind::Unsupported));
+    }
+}